@@ -0,0 +1,25 @@
+-- full-text index over the contents of text-like files. The rowid of each entry is the id of the file it belongs to
+begin;
+
+create virtual table FileContents using fts5(contents, tokenize = 'porter unicode61');
+
+-- fts tables can't reference other tables, so entries need to be removed by hand when their file is deleted
+create trigger trg_file_contents_delete
+after delete on FileRecords
+begin
+    delete from FileContents where rowid = old.id;
+end;
+
+-- make sure every existing file is queued for content indexing on the next startup
+delete
+from metadata
+where name = 'contentsIndexed';
+
+update
+    metadata
+set
+    value = 10
+where
+    name = 'version';
+
+commit;
@@ -0,0 +1,28 @@
+-- searches saved under a name, so they can be browsed like a folder whose contents are always up to date
+begin;
+
+create table SavedSearches (
+    id integer primary key,
+    title text not null unique,
+    -- text that file names must contain. Empty if file names aren't searched
+    search text not null default ''
+);
+
+-- the tags and attributes files must have to be in the saved search
+create table SavedSearchCriteria (
+    savedSearchId integer not null references SavedSearches(id) on delete cascade,
+    type text not null check (type in ('tag', 'attribute')),
+    -- the tag title, or the raw attribute search (e.g. `fileType.eq;image`)
+    value text not null
+);
+
+create index idx_saved_search_criteria_search on SavedSearchCriteria(savedSearchId);
+
+update
+    metadata
+set
+    value = 11
+where
+    name = 'version';
+
+commit;
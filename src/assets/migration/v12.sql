@@ -0,0 +1,25 @@
+-- camera details parsed from the EXIF data of images and the track info of videos. Files without any EXIF data don't have a row
+begin;
+
+create table FileExif (
+    fileId integer primary key references FileRecords(id) on delete cascade,
+    cameraMake text,
+    cameraModel text,
+    lens text,
+    iso integer,
+    -- in millimeters
+    focalLength real,
+    width integer,
+    height integer,
+    -- the EXIF orientation value, from 1 to 8
+    orientation integer
+);
+
+update
+    metadata
+set
+    value = 12
+where
+    name = 'version';
+
+commit;
@@ -0,0 +1,18 @@
+-- gps coordinates parsed from the EXIF data of images and the track info of videos
+begin;
+
+alter table FileExif add column latitude real;
+alter table FileExif add column longitude real;
+-- in meters above sea level
+alter table FileExif add column altitude real;
+
+create index idx_file_exif_location on FileExif(latitude, longitude);
+
+update
+    metadata
+set
+    value = 13
+where
+    name = 'version';
+
+commit;
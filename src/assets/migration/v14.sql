@@ -0,0 +1,15 @@
+-- tags can be nested under a parent tag, e.g. `cat` under `animal`
+begin;
+
+alter table Tags add column parentId integer references Tags(id) on delete set null;
+
+create index idx_tags_parent_id on Tags(parentId);
+
+update
+    metadata
+set
+    value = 14
+where
+    name = 'version';
+
+commit;
@@ -0,0 +1,15 @@
+-- optional display details for tags
+begin;
+
+-- hex color, e.g. #ff8800
+alter table Tags add column color varchar;
+alter table Tags add column description varchar;
+
+update
+    metadata
+set
+    value = 15
+where
+    name = 'version';
+
+commit;
@@ -0,0 +1,20 @@
+-- other names a tag goes by, e.g. `pic` and `photo` for `picture`
+begin;
+
+create table TagAliases
+(
+    id    integer primary key autoincrement,
+    tagId integer not null references Tags (id) on delete cascade,
+    alias varchar not null unique collate nocase
+);
+
+create index idx_tag_aliases_tag_id on TagAliases (tagId);
+
+update
+    metadata
+set
+    value = 16
+where
+    name = 'version';
+
+commit;
@@ -0,0 +1,30 @@
+-- rules that automatically tag files when they're uploaded or moved
+begin;
+
+create table AutoTagRules
+(
+    id      integer primary key autoincrement,
+    name    varchar not null unique collate nocase,
+    enabled integer not null default 1
+);
+
+-- the conditions a file must match for the rule to apply, and the tags the rule adds
+create table AutoTagRuleCriteria
+(
+    ruleId integer not null references AutoTagRules (id) on delete cascade,
+    type   text    not null check (type in
+                                   ('fileType', 'extension', 'namePattern', 'folderPath', 'minSize', 'maxSize',
+                                    'exif', 'tag')),
+    value  text    not null
+);
+
+create index idx_auto_tag_rule_criteria_rule on AutoTagRuleCriteria (ruleId);
+
+update
+    metadata
+set
+    value = 17
+where
+    name = 'version';
+
+commit;
@@ -0,0 +1,30 @@
+-- lets files and folders opt out of inheriting tags from the folders above them
+begin;
+
+create table TagInheritanceBlocks
+(
+    id       integer primary key autoincrement,
+    fileId   integer references FileRecords (id) on delete cascade,
+    folderId integer references Folders (id) on delete cascade,
+    -- the tag that isn't inherited. null blocks every tag
+    tagId    integer references Tags (id) on delete cascade default null,
+    -- make sure that either a file or a folder is blocking
+    check ((fileId is not null) != (folderId is not null))
+);
+
+create unique index idx_tag_inheritance_blocks_unique_file on TagInheritanceBlocks (fileId, coalesce(tagId, 0))
+where
+    fileId is not null;
+
+create unique index idx_tag_inheritance_blocks_unique_folder on TagInheritanceBlocks (folderId, coalesce(tagId, 0))
+where
+    folderId is not null;
+
+update
+    metadata
+set
+    value = 18
+where
+    name = 'version';
+
+commit;
@@ -0,0 +1,48 @@
+-- user-defined descriptions and key/value properties for files and folders
+begin;
+
+create table ItemDescriptions (
+    id integer primary key,
+    fileId integer references FileRecords(id) on delete cascade,
+    folderId integer references Folders(id) on delete cascade,
+    description text not null,
+    -- make sure that either a file or a folder was described
+    check ((fileId is not null) != (folderId is not null))
+);
+
+-- a file or folder can only ever have 1 description
+create unique index idx_item_descriptions_unique_file on ItemDescriptions(fileId)
+where
+    fileId is not null;
+
+create unique index idx_item_descriptions_unique_folder on ItemDescriptions(folderId)
+where
+    folderId is not null;
+
+create table ItemProperties (
+    id integer primary key,
+    fileId integer references FileRecords(id) on delete cascade,
+    folderId integer references Folders(id) on delete cascade,
+    key text not null,
+    value text not null,
+    -- make sure that either a file or a folder has the property
+    check ((fileId is not null) != (folderId is not null))
+);
+
+-- keys are unique per item
+create unique index idx_item_properties_unique_file on ItemProperties(fileId, key)
+where
+    fileId is not null;
+
+create unique index idx_item_properties_unique_folder on ItemProperties(folderId, key)
+where
+    folderId is not null;
+
+update
+    metadata
+set
+    value = 7
+where
+    name = 'version';
+
+commit;
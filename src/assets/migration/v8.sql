@@ -0,0 +1,28 @@
+-- track when a file was uploaded and last modified separately from when it was created
+begin;
+
+alter table
+    FileRecords
+add
+    column dateUploaded datetime;
+
+alter table
+    FileRecords
+add
+    column dateModified datetime;
+
+-- existing files only ever had the one date, so that's the best guess we have for both
+update
+    FileRecords
+set
+    dateUploaded = dateCreated,
+    dateModified = dateCreated;
+
+update
+    metadata
+set
+    value = 8
+where
+    name = 'version';
+
+commit;
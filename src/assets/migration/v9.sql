@@ -0,0 +1,34 @@
+-- starred files and folders, and download statistics for files
+begin;
+
+create table Favorites (
+    id integer primary key,
+    fileId integer references FileRecords(id) on delete cascade,
+    folderId integer references Folders(id) on delete cascade,
+    -- make sure that either a file or a folder was starred
+    check ((fileId is not null) != (folderId is not null))
+);
+
+create unique index idx_favorites_unique_file on Favorites(fileId)
+where
+    fileId is not null;
+
+create unique index idx_favorites_unique_folder on Favorites(folderId)
+where
+    folderId is not null;
+
+-- only files that have been downloaded at least once will have a row here
+create table FileAccessStats (
+    fileId integer primary key references FileRecords(id) on delete cascade,
+    downloadCount integer not null default 0,
+    lastAccessed datetime
+);
+
+update
+    metadata
+set
+    value = 9
+where
+    name = 'version';
+
+commit;
@@ -0,0 +1,4 @@
+insert
+    or ignore into Favorites(folderId)
+values
+    (?1)
@@ -0,0 +1,11 @@
+select
+    f.id,
+    fav.id is not null,
+    s.downloadCount,
+    s.lastAccessed
+from
+    FileRecords f
+    left join Favorites fav on f.id = fav.fileId
+    left join FileAccessStats s on f.id = s.fileId
+where
+    f.id in ({})
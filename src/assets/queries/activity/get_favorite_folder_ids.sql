@@ -0,0 +1,6 @@
+select
+    folderId
+from
+    Favorites
+where
+    folderId in ({})
@@ -0,0 +1,8 @@
+insert into
+    FileAccessStats(fileId, downloadCount, lastAccessed)
+values
+    (?1, 1, ?2) on conflict(fileId) do
+update
+set
+    downloadCount = downloadCount + 1,
+    lastAccessed = excluded.lastAccessed
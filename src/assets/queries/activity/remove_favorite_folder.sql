@@ -0,0 +1,4 @@
+delete from
+    Favorites
+where
+    folderId = ?1
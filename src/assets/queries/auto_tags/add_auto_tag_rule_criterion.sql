@@ -0,0 +1,2 @@
+insert into AutoTagRuleCriteria(ruleId, type, value)
+values (?1, ?2, ?3)
\ No newline at end of file
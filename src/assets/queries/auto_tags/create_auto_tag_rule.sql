@@ -0,0 +1,2 @@
+insert into AutoTagRules(name, enabled)
+values (?1, ?2)
\ No newline at end of file
@@ -0,0 +1,4 @@
+delete from
+    AutoTagRules
+where
+    id = ?1
\ No newline at end of file
@@ -0,0 +1,4 @@
+delete from
+    AutoTagRuleCriteria
+where
+    ruleId = ?1
\ No newline at end of file
@@ -0,0 +1,8 @@
+select
+    id,
+    name,
+    enabled
+from
+    AutoTagRules
+order by
+    lower(name)
\ No newline at end of file
@@ -0,0 +1,8 @@
+select
+    id,
+    name,
+    enabled
+from
+    AutoTagRules
+where
+    id = ?1
\ No newline at end of file
@@ -0,0 +1,8 @@
+select
+    id,
+    name,
+    enabled
+from
+    AutoTagRules
+where
+    lower(name) = lower(?1)
\ No newline at end of file
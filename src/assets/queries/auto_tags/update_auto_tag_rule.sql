@@ -0,0 +1,7 @@
+update
+    AutoTagRules
+set
+    name = ?1,
+    enabled = ?2
+where
+    id = ?3
\ No newline at end of file
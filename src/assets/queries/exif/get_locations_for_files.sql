@@ -0,0 +1,8 @@
+select fileId,
+       latitude,
+       longitude,
+       altitude
+from FileExif
+where latitude is not null
+  and longitude is not null
+  and fileId in ({})
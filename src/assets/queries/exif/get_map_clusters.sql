@@ -0,0 +1,11 @@
+-- groups located files into a grid with cells ?1 degrees wide, so nearby files are returned as a single point
+select avg(latitude),
+       avg(longitude),
+       count(fileId),
+       min(fileId)
+from FileExif
+where latitude is not null
+  and longitude is not null
+  and {}
+group by cast((latitude + 90) / ?1 as integer), cast((longitude + 180) / ?1 as integer)
+order by count(fileId) desc
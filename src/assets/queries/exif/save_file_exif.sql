@@ -0,0 +1,2 @@
+insert or replace into FileExif (fileId, cameraMake, cameraModel, lens, iso, focalLength, width, height, orientation, latitude, longitude, altitude)
+values (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
@@ -0,0 +1,6 @@
+select
+    count(*)
+from
+    FileRecords f
+where
+    {}
\ No newline at end of file
@@ -1,2 +1,2 @@
-insert into FileRecords(name, fileSize, dateCreated, type)
-values (?1, ?2, ?3, ?4)
\ No newline at end of file
+insert into FileRecords(name, fileSize, dateCreated, type, dateUploaded, dateModified)
+values (?1, ?2, ?3, ?4, ?5, ?6)
\ No newline at end of file
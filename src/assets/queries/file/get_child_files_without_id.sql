@@ -5,7 +5,9 @@ select
     f.fileSize,
     f.dateCreated,
     f.type,
-    NULL
+    NULL,
+    f.dateUploaded,
+    f.dateModified
 from
     FileRecords f
 where
@@ -0,0 +1,17 @@
+select
+    f.id,
+    f.name,
+    f.fileSize,
+    f.dateCreated,
+    f.type,
+    ff.folderId,
+    f.dateUploaded,
+    f.dateModified
+from
+    FileRecords f
+    join Favorites fav on f.id = fav.fileId
+    left join folder_files ff on f.id = ff.fileId
+    left join FileAccessStats s on f.id = s.fileId
+order by
+    {0} {1},
+    f.id desc
@@ -0,0 +1,20 @@
+select
+    f.id,
+    f.name,
+    f.fileSize,
+    f.dateCreated,
+    f.type,
+    ff.folderId,
+    f.dateUploaded,
+    f.dateModified
+from
+    FileRecords f
+    left join folder_files ff on f.id = ff.fileId
+    left join FileAccessStats s on f.id = s.fileId
+where
+    {0} is not null
+order by
+    {0} {1},
+    f.id desc
+limit
+    ?1 offset ?2
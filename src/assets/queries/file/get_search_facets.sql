@@ -0,0 +1,64 @@
+with
+    matches as (
+        select
+            f.id,
+            f.type,
+            f.fileSize,
+            f.dateCreated
+        from
+            FileRecords f
+            left join folder_files ff on ff.fileId = f.id
+        where
+            {0}
+    )
+select
+    'fileType',
+    type,
+    count(*)
+from
+    matches
+group by
+    type
+union all
+select
+    'fileSize',
+    case
+        when fileSize < {1} then 'tiny'
+        when fileSize < {2} then 'small'
+        when fileSize < {3} then 'medium'
+        when fileSize < {4} then 'large'
+        else 'extraLarge'
+    end as bucket,
+    count(*)
+from
+    matches
+group by
+    bucket
+union all
+select
+    'year',
+    strftime('%Y', dateCreated) as year,
+    count(*)
+from
+    matches
+where
+    strftime('%Y', dateCreated) is not null
+group by
+    year
+union all
+select
+    'tag',
+    t.title,
+    count(distinct ti.fileId)
+from
+    TaggedItems ti
+    join Tags t on t.id = ti.tagId
+where
+    ti.fileId in (
+        select
+            id
+        from
+            matches
+    )
+group by
+    t.title
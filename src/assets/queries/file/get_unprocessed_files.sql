@@ -0,0 +1,19 @@
+select
+    f.id,
+    f.name,
+    f.fileSize,
+    f.dateCreated,
+    f.type,
+    ff.folderId,
+    f.dateUploaded,
+    f.dateModified
+from
+    FileRecords f
+    left join folder_files ff on f.id = ff.fileId
+where
+    {}
+order by
+    f.dateUploaded asc,
+    f.id asc
+limit
+    :limit offset :offset
\ No newline at end of file
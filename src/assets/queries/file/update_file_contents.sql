@@ -0,0 +1,3 @@
+update FileRecords
+set fileSize = ?1, type = ?2, dateModified = ?3
+where id = ?4
@@ -0,0 +1,4 @@
+delete from
+    FileContents
+where
+    rowid = ?1
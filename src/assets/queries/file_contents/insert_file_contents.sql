@@ -0,0 +1,4 @@
+insert into
+    FileContents(rowid, contents)
+values
+    (?1, ?2)
@@ -4,7 +4,9 @@ select
     f.fileSize,
     f.dateCreated,
     f.type,
-    ff.folderId
+    ff.folderId,
+    f.dateUploaded,
+    f.dateModified
 from
     FileRecords f
     left join folder_files ff on ff.fileId = f.id
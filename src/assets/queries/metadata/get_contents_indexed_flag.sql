@@ -0,0 +1 @@
+select value from metadata where name = 'contentsIndexed'
\ No newline at end of file
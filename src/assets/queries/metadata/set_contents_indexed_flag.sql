@@ -0,0 +1,2 @@
+insert into metadata(name, value)
+values ('contentsIndexed', 1)
\ No newline at end of file
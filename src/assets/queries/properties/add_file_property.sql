@@ -0,0 +1,4 @@
+insert into
+    ItemProperties(fileId, key, value)
+values
+    (?1, ?2, ?3)
\ No newline at end of file
@@ -0,0 +1,4 @@
+insert into
+    ItemDescriptions(folderId, description)
+values
+    (?1, ?2)
\ No newline at end of file
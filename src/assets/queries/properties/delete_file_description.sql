@@ -0,0 +1,4 @@
+delete from
+    ItemDescriptions
+where
+    fileId = ?1
\ No newline at end of file
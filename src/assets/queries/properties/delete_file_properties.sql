@@ -0,0 +1,4 @@
+delete from
+    ItemProperties
+where
+    fileId = ?1
\ No newline at end of file
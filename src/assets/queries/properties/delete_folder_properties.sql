@@ -0,0 +1,4 @@
+delete from
+    ItemProperties
+where
+    folderId = ?1
\ No newline at end of file
@@ -0,0 +1,7 @@
+select
+    fileId,
+    description
+from
+    ItemDescriptions
+where
+    fileId in ({})
\ No newline at end of file
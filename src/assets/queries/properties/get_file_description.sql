@@ -0,0 +1,6 @@
+select
+    description
+from
+    ItemDescriptions
+where
+    fileId = ?1
\ No newline at end of file
@@ -0,0 +1,6 @@
+select
+    description
+from
+    ItemDescriptions
+where
+    folderId = ?1
\ No newline at end of file
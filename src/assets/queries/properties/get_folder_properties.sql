@@ -0,0 +1,7 @@
+select
+    key,
+    value
+from
+    ItemProperties
+where
+    folderId = ?1
\ No newline at end of file
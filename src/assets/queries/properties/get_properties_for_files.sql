@@ -0,0 +1,8 @@
+select
+    fileId,
+    key,
+    value
+from
+    ItemProperties
+where
+    fileId in ({})
\ No newline at end of file
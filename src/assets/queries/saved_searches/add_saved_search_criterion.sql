@@ -0,0 +1,2 @@
+insert into SavedSearchCriteria(savedSearchId, type, value)
+values (?1, ?2, ?3)
\ No newline at end of file
@@ -0,0 +1,2 @@
+insert into SavedSearches(title, search)
+values (?1, ?2)
\ No newline at end of file
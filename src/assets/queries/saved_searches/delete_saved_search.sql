@@ -0,0 +1,4 @@
+delete from
+    SavedSearches
+where
+    id = ?1
\ No newline at end of file
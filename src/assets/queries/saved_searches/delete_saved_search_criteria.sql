@@ -0,0 +1,4 @@
+delete from
+    SavedSearchCriteria
+where
+    savedSearchId = ?1
\ No newline at end of file
@@ -0,0 +1,8 @@
+select
+    id,
+    title,
+    search
+from
+    SavedSearches
+order by
+    lower(title)
\ No newline at end of file
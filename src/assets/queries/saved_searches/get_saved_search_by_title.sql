@@ -0,0 +1,8 @@
+select
+    id,
+    title,
+    search
+from
+    SavedSearches
+where
+    lower(title) = lower(?1)
\ No newline at end of file
@@ -0,0 +1,9 @@
+select
+    type,
+    value
+from
+    SavedSearchCriteria
+where
+    savedSearchId = ?1
+order by
+    rowid
\ No newline at end of file
@@ -0,0 +1,7 @@
+update
+    SavedSearches
+set
+    title = ?1,
+    search = ?2
+where
+    id = ?3
\ No newline at end of file
@@ -0,0 +1,2 @@
+insert into TagInheritanceBlocks(fileId, tagId)
+values (?1, ?2)
\ No newline at end of file
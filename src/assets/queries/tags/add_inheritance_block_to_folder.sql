@@ -0,0 +1,2 @@
+insert into TagInheritanceBlocks(folderId, tagId)
+values (?1, ?2)
\ No newline at end of file
@@ -0,0 +1,6 @@
+-- the merged tag's title becomes an alias of the tag it was merged into, so it can still be used to tag and search
+insert
+    or ignore into TagAliases (tagId, alias)
+select ?2, title
+from Tags
+where id = ?1
@@ -0,0 +1,4 @@
+insert
+    or ignore into TagAliases (tagId, alias)
+values
+    (?1, ?2)
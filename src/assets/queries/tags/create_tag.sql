@@ -1,2 +1,2 @@
-insert into Tags(title)
-values (?1)
+insert into Tags(title, parentId)
+values (?1, ?2)
\ No newline at end of file
@@ -0,0 +1,8 @@
+-- items that already have the tag being merged into would break the unique indexes
+delete
+from TaggedItems
+where tagId = ?1
+  and exists (select 1
+              from TaggedItems target
+              where target.tagId = ?2
+                and (target.fileId = TaggedItems.fileId or target.folderId = TaggedItems.folderId))
\ No newline at end of file
@@ -0,0 +1,10 @@
+-- explicit tags from the tag being merged win over implicit tags already on the item
+delete
+from TaggedItems
+where tagId = ?2
+  and implicitFromId is not null
+  and exists (select 1
+              from TaggedItems source
+              where source.tagId = ?1
+                and source.implicitFromId is null
+                and (source.fileId = TaggedItems.fileId or source.folderId = TaggedItems.folderId))
\ No newline at end of file
@@ -0,0 +1,4 @@
+delete from
+    TagInheritanceBlocks
+where
+    fileId = ?1
\ No newline at end of file
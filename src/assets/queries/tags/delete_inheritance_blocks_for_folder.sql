@@ -0,0 +1,4 @@
+delete from
+    TagInheritanceBlocks
+where
+    folderId = ?1
\ No newline at end of file
@@ -0,0 +1,3 @@
+delete
+from TagAliases
+where tagId = ?1
@@ -0,0 +1,3 @@
+select tagId, alias
+from TagAliases
+order by lower(alias)
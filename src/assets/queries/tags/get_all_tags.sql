@@ -0,0 +1,12 @@
+select t.id,
+       t.title,
+       t.parentId,
+       t.color,
+       t.description,
+       coalesce(sum(ti.fileId is not null and ti.implicitFromId is null), 0)   as fileCount,
+       coalesce(sum(ti.folderId is not null and ti.implicitFromId is null), 0) as folderCount,
+       coalesce(sum(ti.implicitFromId is not null), 0)                         as implicitCount
+from Tags t
+         left join TaggedItems ti on ti.tagId = t.id
+group by t.id
+order by lower(t.title), t.id
\ No newline at end of file
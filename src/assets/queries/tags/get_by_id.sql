@@ -1,3 +1,3 @@
-select id, title
+select id, title, parentId, color, description
 from Tags
-where id = ?1;
+where id = ?1;
\ No newline at end of file
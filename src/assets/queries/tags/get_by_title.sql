@@ -1,3 +1,8 @@
-select id, title
-from Tags
-where lower(title) = lower(?1)
+select t.id, t.title, t.parentId, t.color, t.description
+from Tags t
+         left join TagAliases a on a.tagId = t.id and a.alias = ?1
+where lower(t.title) = lower(?1)
+   or a.id is not null
+-- a tag with the title wins over a tag with the alias
+order by lower(t.title) = lower(?1) desc
+limit 1
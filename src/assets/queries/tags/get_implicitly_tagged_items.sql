@@ -0,0 +1,4 @@
+select fileId, folderId
+from TaggedItems
+where tagId = ?1
+  and implicitFromId is not null
@@ -0,0 +1,10 @@
+-- a null title means every tag is blocked
+select
+    t.title
+from
+    TagInheritanceBlocks b
+    left join Tags t on t.id = b.tagId
+where
+    b.folderId = ?1
+order by
+    lower(t.title)
\ No newline at end of file
@@ -0,0 +1,4 @@
+select alias
+from TagAliases
+where tagId = ?1
+order by lower(alias)
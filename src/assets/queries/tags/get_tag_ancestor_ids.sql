@@ -0,0 +1,13 @@
+with recursive ancestors(id) as (
+    select parentId
+    from Tags
+    where id = ?1
+      and parentId is not null
+    union
+    select t.parentId
+    from Tags t
+             join ancestors a on t.id = a.id
+    where t.parentId is not null
+)
+select id
+from ancestors
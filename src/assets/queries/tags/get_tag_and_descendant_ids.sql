@@ -0,0 +1,12 @@
+with recursive descendants(id) as (
+    select id
+    from Tags
+    where lower(title) = lower(?1)
+       or id in (select tagId from TagAliases where alias = ?1)
+    union
+    select t.id
+    from Tags t
+             join descendants d on t.parentId = d.id
+)
+select id
+from descendants
@@ -0,0 +1,5 @@
+select id
+from Tags
+where instr(title, ':') > 0
+  and lower(trim(substr(title, 1, instr(title, ':') - 1))) = lower(trim(?1))
+  and trim(substr(title, instr(title, ':') + 1)) != ''
@@ -0,0 +1,15 @@
+-- a tag nested under the tag being merged is moved up to the merged tag's parent first, so that moving the merged tag's children can't make a cycle
+update Tags
+set parentId = (select parentId from Tags where id = ?1)
+where id = ?2
+  and ?1 in (with recursive ancestors(id) as (select parentId
+                                              from Tags
+                                              where id = ?2
+                                                and parentId is not null
+                                              union
+                                              select t.parentId
+                                              from Tags t
+                                                       join ancestors a on t.id = a.id
+                                              where t.parentId is not null)
+             select id
+             from ancestors)
\ No newline at end of file
@@ -0,0 +1,4 @@
+update Tags
+set parentId = ?2
+where parentId = ?1
+  and id != ?2
\ No newline at end of file
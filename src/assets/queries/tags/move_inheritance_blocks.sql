@@ -0,0 +1,4 @@
+update
+    or ignore TagInheritanceBlocks
+set tagId = ?2
+where tagId = ?1
\ No newline at end of file
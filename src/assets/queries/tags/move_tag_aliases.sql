@@ -0,0 +1,4 @@
+update
+    or ignore TagAliases
+set tagId = ?2
+where tagId = ?1
@@ -0,0 +1,3 @@
+update TaggedItems
+set tagId = ?2
+where tagId = ?1
\ No newline at end of file
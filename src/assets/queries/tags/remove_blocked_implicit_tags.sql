@@ -0,0 +1,27 @@
+-- every folder at or under a blocking folder, along with the blocking folder and the tag it blocks
+with recursive blocked(rootId, tagId, folderId) as (select folderId, tagId, folderId
+                                                    from TagInheritanceBlocks
+                                                    where folderId is not null
+                                                    union
+                                                    select blocked.rootId, blocked.tagId, f.id
+                                                    from Folders f
+                                                             join blocked on f.parentId = blocked.folderId)
+delete
+from TaggedItems
+where implicitFromId is not null
+  -- only the passed files and folders are checked
+  and (fileId in (select value from rarray(:fileIds)) or folderId in (select value from rarray(:folderIds)))
+  and (
+    -- files that block the tag don't inherit it from anywhere
+    exists (select 1
+            from TagInheritanceBlocks b
+            where b.fileId = TaggedItems.fileId
+              and (b.tagId is null or b.tagId = TaggedItems.tagId))
+        -- items at or under a blocking folder don't inherit the tag from folders above the blocking folder
+        or exists (select 1
+                   from blocked b
+                   where (b.tagId is null or b.tagId = TaggedItems.tagId)
+                     and b.folderId = coalesce(TaggedItems.folderId,
+                                               (select ff.folderId from Folder_Files ff where ff.fileId = TaggedItems.fileId))
+                     and TaggedItems.implicitFromId not in (select b2.folderId from blocked b2 where b2.rootId = b.rootId))
+    )
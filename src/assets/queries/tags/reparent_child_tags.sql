@@ -0,0 +1,3 @@
+update Tags
+set parentId = (select parentId from Tags where id = ?1)
+where parentId = ?1
\ No newline at end of file
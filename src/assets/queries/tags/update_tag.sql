@@ -1,3 +1,6 @@
 update Tags
-set title = ?1
-where id = ?2
+set title       = ?1,
+    parentId    = ?2,
+    color       = ?3,
+    description = ?4
+where id = ?5
\ No newline at end of file
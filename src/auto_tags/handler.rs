@@ -0,0 +1,129 @@
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use rocket::State;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+
+use super::service;
+use super::{
+    AutoTagRuleApi, AutoTagRuleError, AutoTagRuleResponse, DeleteAutoTagRuleResponse,
+    ListAutoTagRulesResponse,
+};
+use crate::guard::HeaderAuth;
+use crate::model::guard::auth::ValidateResult;
+use crate::model::response::BasicMessage;
+use crate::util::update_last_request_time;
+
+/// lists all auto tag rules, ordered by name
+#[get("/")]
+pub fn get_auto_tag_rules(
+    auth: HeaderAuth,
+    last_request_time: &State<Arc<Mutex<Instant>>>,
+) -> ListAutoTagRulesResponse {
+    match auth.validate() {
+        ValidateResult::Ok => { /*no op*/ }
+        ValidateResult::NoPasswordSet => return ListAutoTagRulesResponse::Unauthorized("No password has been set. You can set a username and password by making a POST to `/api/password`".to_string()),
+        ValidateResult::Invalid => return ListAutoTagRulesResponse::Unauthorized("Bad Credentials".to_string())
+    };
+    update_last_request_time(last_request_time);
+    match service::get_all_auto_tag_rules() {
+        Ok(rules) => ListAutoTagRulesResponse::Success(Json::from(rules)),
+        Err(_) => ListAutoTagRulesResponse::GenericError(BasicMessage::new(
+            "Failed to retrieve auto tag rules. Check server logs for details",
+        )),
+    }
+}
+
+/// creates a rule. It only applies to files uploaded or moved from now on;
+/// make a POST to `/autotags/apply` to apply it to existing files
+#[post("/", data = "<rule>")]
+pub fn create_auto_tag_rule(
+    rule: Json<AutoTagRuleApi>,
+    auth: HeaderAuth,
+    last_request_time: &State<Arc<Mutex<Instant>>>,
+) -> AutoTagRuleResponse {
+    match auth.validate() {
+        ValidateResult::Ok => { /*no op*/ }
+        ValidateResult::NoPasswordSet => return AutoTagRuleResponse::Unauthorized("No password has been set. You can set a username and password by making a POST to `/api/password`".to_string()),
+        ValidateResult::Invalid => return AutoTagRuleResponse::Unauthorized("Bad Credentials".to_string())
+    };
+    update_last_request_time(last_request_time);
+    match service::create_auto_tag_rule(rule.into_inner()) {
+        Ok(rule) => AutoTagRuleResponse::Created(Json::from(rule)),
+        Err(e) => auto_tag_rule_error_response(e),
+    }
+}
+
+#[put("/", data = "<rule>")]
+pub fn update_auto_tag_rule(
+    rule: Json<AutoTagRuleApi>,
+    auth: HeaderAuth,
+    last_request_time: &State<Arc<Mutex<Instant>>>,
+) -> AutoTagRuleResponse {
+    match auth.validate() {
+        ValidateResult::Ok => { /*no op*/ }
+        ValidateResult::NoPasswordSet => return AutoTagRuleResponse::Unauthorized("No password has been set. You can set a username and password by making a POST to `/api/password`".to_string()),
+        ValidateResult::Invalid => return AutoTagRuleResponse::Unauthorized("Bad Credentials".to_string())
+    };
+    update_last_request_time(last_request_time);
+    match service::update_auto_tag_rule(rule.into_inner()) {
+        Ok(rule) => AutoTagRuleResponse::Success(Json::from(rule)),
+        Err(e) => auto_tag_rule_error_response(e),
+    }
+}
+
+/// deletes the rule. Tags it already added to files are kept
+#[delete("/<id>")]
+pub fn delete_auto_tag_rule(
+    id: u32,
+    auth: HeaderAuth,
+    last_request_time: &State<Arc<Mutex<Instant>>>,
+) -> DeleteAutoTagRuleResponse {
+    match auth.validate() {
+        ValidateResult::Ok => { /*no op*/ }
+        ValidateResult::NoPasswordSet => return DeleteAutoTagRuleResponse::Unauthorized("No password has been set. You can set a username and password by making a POST to `/api/password`".to_string()),
+        ValidateResult::Invalid => return DeleteAutoTagRuleResponse::Unauthorized("Bad Credentials".to_string())
+    };
+    update_last_request_time(last_request_time);
+    match service::delete_auto_tag_rule(id) {
+        Ok(()) => DeleteAutoTagRuleResponse::Success(()),
+        Err(AutoTagRuleError::NotFound) => DeleteAutoTagRuleResponse::NotFound(BasicMessage::new(
+            "The auto tag rule with the passed id could not be found.",
+        )),
+        Err(_) => DeleteAutoTagRuleResponse::GenericError(BasicMessage::new(
+            "Failed to delete auto tag rule. Check server logs for details",
+        )),
+    }
+}
+
+/// queues re-applying every enabled rule to every existing file
+#[post("/apply")]
+pub fn apply_auto_tag_rules(
+    auth: HeaderAuth,
+    last_request_time: &State<Arc<Mutex<Instant>>>,
+) -> Status {
+    match auth.validate() {
+        ValidateResult::Ok => { /*no op*/ }
+        ValidateResult::NoPasswordSet => return Status::Unauthorized,
+        ValidateResult::Invalid => return Status::Unauthorized,
+    };
+    update_last_request_time(last_request_time);
+    service::queue_apply_rules_to_all_files();
+    Status::Accepted
+}
+
+fn auto_tag_rule_error_response(e: AutoTagRuleError) -> AutoTagRuleResponse {
+    match e {
+        AutoTagRuleError::NotFound => AutoTagRuleResponse::NotFound(BasicMessage::new(
+            "The auto tag rule with the passed id could not be found.",
+        )),
+        AutoTagRuleError::AlreadyExists => AutoTagRuleResponse::BadRequest(BasicMessage::new(
+            "An auto tag rule with that name already exists.",
+        )),
+        AutoTagRuleError::Invalid(e) => AutoTagRuleResponse::BadRequest(BasicMessage::new(&e)),
+        AutoTagRuleError::DbError => AutoTagRuleResponse::GenericError(BasicMessage::new(
+            "Failed to save auto tag rule. Check server logs for details",
+        )),
+    }
+}
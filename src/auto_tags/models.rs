@@ -0,0 +1,119 @@
+use rocket::serde::json::Json;
+use rocket::serde::{Deserialize, Serialize};
+
+use crate::model::file_types::FileTypes;
+use crate::model::response::BasicMessage;
+
+/// a rule that adds explicit tags to files matching its conditions when they're uploaded or moved.
+/// This is the same whether it's a request or a response
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(crate = "rocket::serde")]
+pub struct AutoTagRuleApi {
+    /// will be None if new
+    #[serde(default)]
+    pub id: Option<u32>,
+    pub name: String,
+    /// disabled rules are kept, but never applied
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// every condition that's set must match for the rule to apply
+    #[serde(default)]
+    pub conditions: AutoTagConditions,
+    /// titles of the tags added to matching files. Tags that don't exist yet are created
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// the conditions a file must match for an [AutoTagRuleApi] to apply. Empty conditions are ignored
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[serde(crate = "rocket::serde")]
+pub struct AutoTagConditions {
+    /// the file must be one of these types
+    #[serde(default, rename = "fileTypes")]
+    pub file_types: Vec<FileTypes>,
+    /// the file must have one of these extensions, without the leading `.`. Case-insensitive
+    #[serde(default)]
+    pub extensions: Vec<String>,
+    /// a regular expression the whole file name must contain a match for
+    #[serde(default, rename = "namePattern")]
+    pub name_pattern: Option<String>,
+    /// the path of a folder the file must be in, either directly or in one of its subfolders
+    #[serde(default, rename = "folderPath")]
+    pub folder_path: Option<String>,
+    /// the smallest the file can be, in bytes
+    #[serde(default, rename = "minSize")]
+    pub min_size: Option<u64>,
+    /// the largest the file can be, in bytes
+    #[serde(default, rename = "maxSize")]
+    pub max_size: Option<u64>,
+    /// EXIF and location attribute searches the file must match, in the same format as the `attributes`
+    /// file search parameter (e.g. `cameraModel.eq;Pixel 7`)
+    #[serde(default)]
+    pub exif: Vec<String>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl AutoTagConditions {
+    pub fn is_empty(&self) -> bool {
+        self.file_types.is_empty()
+            && self.extensions.is_empty()
+            && self.name_pattern.is_none()
+            && self.folder_path.is_none()
+            && self.min_size.is_none()
+            && self.max_size.is_none()
+            && self.exif.is_empty()
+    }
+}
+
+#[derive(PartialEq, Debug)]
+pub enum AutoTagRuleError {
+    /// no rule with the id exists
+    NotFound,
+    /// a different rule already has the name
+    AlreadyExists,
+    /// the name is blank, the rule has no conditions or tags, or a condition couldn't be parsed
+    Invalid(String),
+    /// an error with the database
+    DbError,
+}
+
+#[derive(Responder, Debug)]
+pub enum AutoTagRuleResponse {
+    #[response(status = 200)]
+    Success(Json<AutoTagRuleApi>),
+    #[response(status = 201)]
+    Created(Json<AutoTagRuleApi>),
+    #[response(status = 400, content_type = "json")]
+    BadRequest(Json<BasicMessage>),
+    #[response(status = 401)]
+    Unauthorized(String),
+    #[response(status = 404, content_type = "json")]
+    NotFound(Json<BasicMessage>),
+    #[response(status = 500, content_type = "json")]
+    GenericError(Json<BasicMessage>),
+}
+
+#[derive(Responder, Debug)]
+pub enum ListAutoTagRulesResponse {
+    #[response(status = 200)]
+    Success(Json<Vec<AutoTagRuleApi>>),
+    #[response(status = 401)]
+    Unauthorized(String),
+    #[response(status = 500, content_type = "json")]
+    GenericError(Json<BasicMessage>),
+}
+
+#[derive(Responder, Debug)]
+pub enum DeleteAutoTagRuleResponse {
+    #[response(status = 204)]
+    Success(()),
+    #[response(status = 401)]
+    Unauthorized(String),
+    #[response(status = 404, content_type = "json")]
+    NotFound(Json<BasicMessage>),
+    #[response(status = 500, content_type = "json")]
+    GenericError(Json<BasicMessage>),
+}
@@ -0,0 +1,165 @@
+use rusqlite::{Connection, params};
+
+use super::AutoTagRuleApi;
+use crate::model::file_types::FileTypes;
+
+const FILE_TYPE_CRITERION: &str = "fileType";
+const EXTENSION_CRITERION: &str = "extension";
+const NAME_PATTERN_CRITERION: &str = "namePattern";
+const FOLDER_PATH_CRITERION: &str = "folderPath";
+const MIN_SIZE_CRITERION: &str = "minSize";
+const MAX_SIZE_CRITERION: &str = "maxSize";
+const EXIF_CRITERION: &str = "exif";
+const TAG_CRITERION: &str = "tag";
+
+/// creates a rule along with its conditions and tags, returning its new id.
+/// This does not check if a rule with the same name exists, so the caller must check that themselves
+pub fn create_auto_tag_rule(
+    rule: &AutoTagRuleApi,
+    con: &Connection,
+) -> Result<u32, rusqlite::Error> {
+    let mut pst = con.prepare(include_str!(
+        "../assets/queries/auto_tags/create_auto_tag_rule.sql"
+    ))?;
+    let id = pst.insert(params![rule.name, rule.enabled])? as u32;
+    add_criteria(id, rule, con)?;
+    Ok(id)
+}
+
+/// retrieves the rule with the passed `id`, including its conditions and tags
+pub fn get_auto_tag_rule(id: u32, con: &Connection) -> Result<AutoTagRuleApi, rusqlite::Error> {
+    let mut pst = con.prepare(include_str!(
+        "../assets/queries/auto_tags/get_auto_tag_rule.sql"
+    ))?;
+    let rule = pst.query_row([id], map_auto_tag_rule)?;
+    with_criteria(rule, con)
+}
+
+/// searches for a rule that case-insensitively matches the passed name.
+///
+/// if `None` is returned, that means there was no match
+pub fn get_auto_tag_rule_by_name(
+    name: &str,
+    con: &Connection,
+) -> Result<Option<AutoTagRuleApi>, rusqlite::Error> {
+    let mut pst = con.prepare(include_str!(
+        "../assets/queries/auto_tags/get_auto_tag_rule_by_name.sql"
+    ))?;
+    match pst.query_row([name], map_auto_tag_rule) {
+        Ok(rule) => Ok(Some(rule)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// retrieves every rule ordered by name, including their conditions and tags
+pub fn get_all_auto_tag_rules(con: &Connection) -> Result<Vec<AutoTagRuleApi>, rusqlite::Error> {
+    let mut pst = con.prepare(include_str!(
+        "../assets/queries/auto_tags/get_all_auto_tag_rules.sql"
+    ))?;
+    let rules = pst
+        .query_map([], map_auto_tag_rule)?
+        .collect::<Result<Vec<AutoTagRuleApi>, rusqlite::Error>>()?;
+    rules.into_iter().map(|it| with_criteria(it, con)).collect()
+}
+
+/// replaces the name, enabled flag, conditions, and tags of the rule. Checking to make sure it exists
+/// needs to be done on the caller's end
+pub fn update_auto_tag_rule(
+    rule: &AutoTagRuleApi,
+    con: &Connection,
+) -> Result<(), rusqlite::Error> {
+    let id = rule.id.unwrap_or(0);
+    let mut pst = con.prepare(include_str!(
+        "../assets/queries/auto_tags/update_auto_tag_rule.sql"
+    ))?;
+    pst.execute(params![rule.name, rule.enabled, id])?;
+    delete_criteria(id, con)?;
+    add_criteria(id, rule, con)
+}
+
+/// deletes the rule and its criteria, returning `true` if it existed
+pub fn delete_auto_tag_rule(id: u32, con: &Connection) -> Result<bool, rusqlite::Error> {
+    delete_criteria(id, con)?;
+    let mut pst = con.prepare(include_str!(
+        "../assets/queries/auto_tags/delete_auto_tag_rule.sql"
+    ))?;
+    Ok(pst.execute([id])? > 0)
+}
+
+fn add_criteria(id: u32, rule: &AutoTagRuleApi, con: &Connection) -> Result<(), rusqlite::Error> {
+    let mut pst = con.prepare(include_str!(
+        "../assets/queries/auto_tags/add_auto_tag_rule_criterion.sql"
+    ))?;
+    let conditions = &rule.conditions;
+    for file_type in conditions.file_types.iter() {
+        pst.execute(params![id, FILE_TYPE_CRITERION, file_type.to_string()])?;
+    }
+    for extension in conditions.extensions.iter() {
+        pst.execute(params![id, EXTENSION_CRITERION, extension])?;
+    }
+    if let Some(pattern) = &conditions.name_pattern {
+        pst.execute(params![id, NAME_PATTERN_CRITERION, pattern])?;
+    }
+    if let Some(path) = &conditions.folder_path {
+        pst.execute(params![id, FOLDER_PATH_CRITERION, path])?;
+    }
+    if let Some(size) = conditions.min_size {
+        pst.execute(params![id, MIN_SIZE_CRITERION, size.to_string()])?;
+    }
+    if let Some(size) = conditions.max_size {
+        pst.execute(params![id, MAX_SIZE_CRITERION, size.to_string()])?;
+    }
+    for exif in conditions.exif.iter() {
+        pst.execute(params![id, EXIF_CRITERION, exif])?;
+    }
+    for tag in rule.tags.iter() {
+        pst.execute(params![id, TAG_CRITERION, tag])?;
+    }
+    Ok(())
+}
+
+fn delete_criteria(id: u32, con: &Connection) -> Result<(), rusqlite::Error> {
+    let mut pst = con.prepare(include_str!(
+        "../assets/queries/auto_tags/delete_auto_tag_rule_criteria.sql"
+    ))?;
+    pst.execute([id])?;
+    Ok(())
+}
+
+/// fills out the conditions and tags of the passed rule
+fn with_criteria(
+    mut rule: AutoTagRuleApi,
+    con: &Connection,
+) -> Result<AutoTagRuleApi, rusqlite::Error> {
+    let mut pst = con.prepare(include_str!(
+        "../assets/queries/auto_tags/get_auto_tag_rule_criteria.sql"
+    ))?;
+    let mut rows = pst.query([rule.id])?;
+    while let Some(row) = rows.next()? {
+        let criterion_type: String = row.get(0)?;
+        let value: String = row.get(1)?;
+        let conditions = &mut rule.conditions;
+        match criterion_type.as_str() {
+            FILE_TYPE_CRITERION => conditions.file_types.push(FileTypes::from(value.as_str())),
+            EXTENSION_CRITERION => conditions.extensions.push(value),
+            NAME_PATTERN_CRITERION => conditions.name_pattern = Some(value),
+            FOLDER_PATH_CRITERION => conditions.folder_path = Some(value),
+            MIN_SIZE_CRITERION => conditions.min_size = value.parse().ok(),
+            MAX_SIZE_CRITERION => conditions.max_size = value.parse().ok(),
+            EXIF_CRITERION => conditions.exif.push(value),
+            _ => rule.tags.push(value),
+        }
+    }
+    Ok(rule)
+}
+
+fn map_auto_tag_rule(row: &rusqlite::Row) -> Result<AutoTagRuleApi, rusqlite::Error> {
+    Ok(AutoTagRuleApi {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        enabled: row.get(2)?,
+        conditions: Default::default(),
+        tags: Vec::new(),
+    })
+}
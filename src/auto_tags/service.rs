@@ -0,0 +1,402 @@
+use std::backtrace::Backtrace;
+
+use regex::Regex;
+use rusqlite::Connection;
+
+use super::repository;
+use super::{AutoTagConditions, AutoTagRuleApi, AutoTagRuleError};
+use crate::model::repository::FileRecord;
+use crate::model::request::attributes::{AttributeSearch, AttributeTypes, ParseError};
+use crate::model::request::search_query::SearchQuery;
+use crate::queue;
+use crate::repository::{file_repository, folder_repository, open_connection};
+use crate::tags::service as tag_service;
+
+/// the queue that folders to apply rules to are published on
+const AUTO_TAG_QUEUE: &str = "auto_tags";
+/// the message published to apply rules to every file instead of a single folder
+const ALL_FILES_MESSAGE: &str = "all";
+
+/// a rule whose conditions have been parsed so that it can be checked against many files
+struct CompiledRule {
+    rule: AutoTagRuleApi,
+    name_pattern: Option<Regex>,
+    exif: Option<SearchQuery>,
+}
+
+/// saves a new rule, returning it with its id
+pub fn create_auto_tag_rule(rule: AutoTagRuleApi) -> Result<AutoTagRuleApi, AutoTagRuleError> {
+    let rule = validate(rule)?;
+    let con = open_connection();
+    let res = check_name_is_free(&rule, &con)
+        .and_then(|_| repository::create_auto_tag_rule(&rule, &con).map_err(log_db_error));
+    con.close().unwrap();
+    let id = res?;
+    Ok(AutoTagRuleApi {
+        id: Some(id),
+        ..rule
+    })
+}
+
+/// retrieves the rule with the passed id
+pub fn get_auto_tag_rule(id: u32) -> Result<AutoTagRuleApi, AutoTagRuleError> {
+    let con = open_connection();
+    let res = repository::get_auto_tag_rule(id, &con);
+    con.close().unwrap();
+    match res {
+        Ok(rule) => Ok(rule),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Err(AutoTagRuleError::NotFound),
+        Err(e) => Err(log_db_error(e)),
+    }
+}
+
+/// lists all rules, ordered by name
+pub fn get_all_auto_tag_rules() -> Result<Vec<AutoTagRuleApi>, AutoTagRuleError> {
+    let con = open_connection();
+    let res = repository::get_all_auto_tag_rules(&con);
+    con.close().unwrap();
+    res.map_err(log_db_error)
+}
+
+/// replaces everything about the rule with the id in `rule`
+pub fn update_auto_tag_rule(rule: AutoTagRuleApi) -> Result<AutoTagRuleApi, AutoTagRuleError> {
+    let rule = validate(rule)?;
+    get_auto_tag_rule(rule.id.unwrap_or(0))?;
+    let con = open_connection();
+    let res = check_name_is_free(&rule, &con)
+        .and_then(|_| repository::update_auto_tag_rule(&rule, &con).map_err(log_db_error));
+    con.close().unwrap();
+    res.map(|_| rule)
+}
+
+pub fn delete_auto_tag_rule(id: u32) -> Result<(), AutoTagRuleError> {
+    let con = open_connection();
+    let res = repository::delete_auto_tag_rule(id, &con);
+    con.close().unwrap();
+    match res {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(AutoTagRuleError::NotFound),
+        Err(e) => Err(log_db_error(e)),
+    }
+}
+
+/// adds the tags of every enabled rule the file with the passed id matches to the file.
+/// Tags that were added by a rule the file no longer matches are left alone
+pub fn apply_rules_to_file(file_id: u32) -> Result<(), AutoTagRuleError> {
+    let rules = get_enabled_rules()?;
+    apply_rules(file_id, &rules)
+}
+
+/// applies every enabled rule to the files in the folder with the passed id and all of its subfolders
+pub fn apply_rules_to_folder(folder_id: u32) -> Result<(), AutoTagRuleError> {
+    let rules = get_enabled_rules()?;
+    if rules.is_empty() {
+        return Ok(());
+    }
+    let con = open_connection();
+    let file_ids = folder_repository::get_all_child_folder_ids(&[folder_id], &con)
+        .and_then(|mut folder_ids| {
+            folder_ids.push(folder_id);
+            folder_repository::get_child_files(&folder_ids, &con)
+        })
+        .map(|files| {
+            files
+                .into_iter()
+                .filter_map(|it| it.id)
+                .collect::<Vec<u32>>()
+        });
+    con.close().unwrap();
+    for file_id in file_ids.map_err(log_db_error)? {
+        apply_rules(file_id, &rules)?;
+    }
+    Ok(())
+}
+
+/// queues applying every enabled rule to the files in the folder with the passed id and all of its subfolders,
+/// see [apply_rules_to_folder]
+pub fn queue_apply_rules_to_folder(folder_id: u32) {
+    queue::publish_message(AUTO_TAG_QUEUE, &folder_id.to_string());
+}
+
+/// queues applying every enabled rule to every file on the server, see [apply_rules_to_all_files]
+pub fn queue_apply_rules_to_all_files() {
+    queue::publish_message(AUTO_TAG_QUEUE, ALL_FILES_MESSAGE);
+}
+
+/// handles a message published by [queue_apply_rules_to_folder] or [queue_apply_rules_to_all_files]
+///
+/// ## Returns
+/// * `true` if the message was handled, even if the rules couldn't be applied
+/// * `false` if a database error occurred and the message should be re-queued
+pub async fn process_auto_tag_message(message_data: String) -> bool {
+    if message_data == ALL_FILES_MESSAGE {
+        apply_rules_to_all_files();
+        return true;
+    }
+    let folder_id: u32 = match message_data.parse() {
+        Ok(id) => id,
+        Err(e) => {
+            log::error!(
+                "Failed to parse {message_data} as a u32! Exception is {e:?}\n{}",
+                Backtrace::force_capture()
+            );
+            // we can't re-queue this or else we'll keep getting errors
+            return true;
+        }
+    };
+    match apply_rules_to_folder(folder_id) {
+        Ok(()) => true,
+        // already logged
+        Err(AutoTagRuleError::DbError) => false,
+        Err(e) => {
+            log::error!(
+                "Failed to apply auto tag rules to folder {folder_id}. Error is {e:?}\n{}",
+                Backtrace::force_capture()
+            );
+            true
+        }
+    }
+}
+
+/// applies every enabled rule to every file on the server. Meant to be run in the background after rules change,
+/// since it checks every file
+pub fn apply_rules_to_all_files() {
+    let Ok(rules) = get_enabled_rules() else {
+        return;
+    };
+    let con = open_connection();
+    let file_ids = file_repository::get_all_file_ids(&con);
+    con.close().unwrap();
+    let file_ids = match file_ids {
+        Ok(ids) => ids,
+        Err(e) => {
+            log_db_error(e);
+            return;
+        }
+    };
+    log::info!(
+        "Applying {} auto tag rules to {} files...",
+        rules.len(),
+        file_ids.len()
+    );
+    let failed = file_ids
+        .into_iter()
+        .filter(|id| apply_rules(*id, &rules).is_err())
+        .count();
+    if failed > 0 {
+        log::error!("Failed to apply auto tag rules to {failed} files. Check logs for details");
+    } else {
+        log::info!("Finished applying auto tag rules");
+    }
+}
+
+fn get_enabled_rules() -> Result<Vec<CompiledRule>, AutoTagRuleError> {
+    Ok(get_all_auto_tag_rules()?
+        .into_iter()
+        .filter(|rule| rule.enabled)
+        .filter_map(|rule| compile(rule).ok())
+        .collect())
+}
+
+fn apply_rules(file_id: u32, rules: &[CompiledRule]) -> Result<(), AutoTagRuleError> {
+    if rules.is_empty() {
+        return Ok(());
+    }
+    let con = open_connection();
+    let res = matching_tags(file_id, rules, &con);
+    con.close().unwrap();
+    let tags = res.map_err(log_db_error)?;
+    if tags.is_empty() {
+        return Ok(());
+    }
+    tag_service::add_tags_to_file(file_id, &tags).map_err(|e| {
+        log::error!(
+            "Failed to add auto tags to file {file_id}! Error is {e:?}\n{}",
+            Backtrace::force_capture()
+        );
+        AutoTagRuleError::DbError
+    })
+}
+
+/// retrieves the tags of every rule the file matches
+fn matching_tags(
+    file_id: u32,
+    rules: &[CompiledRule],
+    con: &Connection,
+) -> Result<Vec<String>, rusqlite::Error> {
+    let file = file_repository::get_file(file_id, con)?;
+    let path = file_repository::get_file_path(file_id, con)?;
+    // the path includes the file name, and files in the root folder have no folder path
+    let folder_path = path.rsplit_once('/').map(|(it, _)| it).unwrap_or("");
+    let mut tags: Vec<String> = Vec::new();
+    for rule in rules {
+        if matches(rule, &file, folder_path, con)? {
+            tags.extend(rule.rule.tags.iter().cloned());
+        }
+    }
+    Ok(tags)
+}
+
+/// checks if the file matches every condition of the rule. The conditions that don't need the database are checked first
+fn matches(
+    rule: &CompiledRule,
+    file: &FileRecord,
+    folder_path: &str,
+    con: &Connection,
+) -> Result<bool, rusqlite::Error> {
+    let conditions = &rule.rule.conditions;
+    let extension = file
+        .name
+        .rsplit_once('.')
+        .map(|(_, it)| it.to_lowercase())
+        .unwrap_or_default();
+    let matches_file = (conditions.file_types.is_empty()
+        || conditions.file_types.contains(&file.file_type))
+        && (conditions.extensions.is_empty() || conditions.extensions.contains(&extension))
+        && rule
+            .name_pattern
+            .as_ref()
+            .is_none_or(|pattern| pattern.is_match(&file.name))
+        && conditions.folder_path.as_deref().is_none_or(|path| {
+            folder_path == path || folder_path.starts_with(format!("{path}/").as_str())
+        })
+        && conditions.min_size.is_none_or(|size| file.size >= size)
+        && conditions.max_size.is_none_or(|size| file.size <= size);
+    if !matches_file {
+        return Ok(false);
+    }
+    match &rule.exif {
+        Some(query) => file_repository::file_matches_query(file.id.unwrap(), query, con),
+        None => Ok(true),
+    }
+}
+
+/// trims the name, tags, and conditions and makes sure the rule can be applied
+fn validate(mut rule: AutoTagRuleApi) -> Result<AutoTagRuleApi, AutoTagRuleError> {
+    rule.name = rule.name.trim().to_string();
+    if rule.name.is_empty() {
+        return Err(AutoTagRuleError::Invalid(
+            "Auto tag rule name cannot be empty".to_string(),
+        ));
+    }
+    let mut tags: Vec<String> = Vec::new();
+    for tag in rule.tags.iter().map(|it| it.trim()) {
+        if !tag.is_empty() && !tags.iter().any(|it| it.eq_ignore_ascii_case(tag)) {
+            tags.push(tag.to_string());
+        }
+    }
+    if tags.is_empty() {
+        return Err(AutoTagRuleError::Invalid(
+            "At least one tag is required.".to_string(),
+        ));
+    }
+    rule.tags = tags;
+    rule.conditions = normalize_conditions(rule.conditions);
+    if rule.conditions.is_empty() {
+        return Err(AutoTagRuleError::Invalid(
+            "At least one condition is required.".to_string(),
+        ));
+    }
+    if let (Some(min), Some(max)) = (rule.conditions.min_size, rule.conditions.max_size)
+        && min > max
+    {
+        return Err(AutoTagRuleError::Invalid(
+            "minSize cannot be larger than maxSize.".to_string(),
+        ));
+    }
+    compile(rule).map(|it| it.rule)
+}
+
+/// trims the conditions, dropping any that are blank
+fn normalize_conditions(conditions: AutoTagConditions) -> AutoTagConditions {
+    let mut file_types = conditions.file_types;
+    file_types.sort();
+    file_types.dedup();
+    let mut extensions: Vec<String> = Vec::new();
+    for extension in conditions.extensions {
+        let extension = extension.trim().trim_start_matches('.').to_lowercase();
+        if !extension.is_empty() && !extensions.contains(&extension) {
+            extensions.push(extension);
+        }
+    }
+    let folder_path = conditions
+        .folder_path
+        .map(|it| it.trim().trim_matches('/').to_string())
+        .filter(|it| !it.is_empty());
+    AutoTagConditions {
+        file_types,
+        extensions,
+        name_pattern: conditions.name_pattern.filter(|it| !it.is_empty()),
+        folder_path,
+        min_size: conditions.min_size,
+        max_size: conditions.max_size,
+        exif: conditions
+            .exif
+            .into_iter()
+            .map(|it| it.trim().to_string())
+            .filter(|it| !it.is_empty())
+            .collect(),
+    }
+}
+
+fn compile(rule: AutoTagRuleApi) -> Result<CompiledRule, AutoTagRuleError> {
+    let name_pattern = match &rule.conditions.name_pattern {
+        Some(pattern) => Some(Regex::new(pattern).map_err(|e| {
+            AutoTagRuleError::Invalid(format!("namePattern is not a valid regex: {e}"))
+        })?),
+        None => None,
+    };
+    let exif = if rule.conditions.exif.is_empty() {
+        None
+    } else {
+        Some(parse_exif(rule.conditions.exif.clone())?)
+    };
+    Ok(CompiledRule {
+        rule,
+        name_pattern,
+        exif,
+    })
+}
+
+/// parses the exif conditions into a query that matches files with all of them
+fn parse_exif(exif: Vec<String>) -> Result<SearchQuery, AutoTagRuleError> {
+    let attributes = AttributeSearch::try_from(exif).map_err(|e| {
+        AutoTagRuleError::Invalid(match e {
+            ParseError::BadEqualityOperator(message)
+            | ParseError::MissingValue(message)
+            | ParseError::BadValue(message)
+            | ParseError::InvalidSearch(message)
+            | ParseError::InvalidQuery(message) => message,
+        })
+    })?;
+    let mut queries: Vec<SearchQuery> = Vec::new();
+    for attribute in attributes.attributes {
+        if !matches!(
+            attribute,
+            AttributeTypes::Exif(_) | AttributeTypes::Location(_)
+        ) {
+            return Err(AutoTagRuleError::Invalid(
+                "exif conditions can only search EXIF fields or location.".to_string(),
+            ));
+        }
+        queries.push(SearchQuery::Attribute(attribute));
+    }
+    Ok(SearchQuery::And(queries))
+}
+
+/// makes sure no other rule has the same name
+fn check_name_is_free(rule: &AutoTagRuleApi, con: &Connection) -> Result<(), AutoTagRuleError> {
+    match repository::get_auto_tag_rule_by_name(&rule.name, con) {
+        Ok(Some(existing)) if existing.id != rule.id => Err(AutoTagRuleError::AlreadyExists),
+        Ok(_) => Ok(()),
+        Err(e) => Err(log_db_error(e)),
+    }
+}
+
+fn log_db_error(e: rusqlite::Error) -> AutoTagRuleError {
+    log::error!(
+        "Failed to access auto tag rules in the database. Exception is {e:?}\n{}",
+        Backtrace::force_capture()
+    );
+    AutoTagRuleError::DbError
+}
@@ -0,0 +1,131 @@
+use rocket::http::{Header, Status};
+
+use crate::auto_tags::AutoTagRuleApi;
+use crate::model::response::BasicMessage;
+use crate::tags::service as tag_service;
+use crate::test::*;
+
+#[test]
+fn requires_auth() {
+    init_db_folder();
+    let client = client();
+    let res = client.get(uri!("/autotags")).dispatch();
+    assert_eq!(res.status(), Status::Unauthorized);
+    let res = client.post(uri!("/autotags/apply")).dispatch();
+    assert_eq!(res.status(), Status::Unauthorized);
+    cleanup();
+}
+
+#[test]
+fn create_list_update_and_delete() {
+    set_password();
+    let client = client();
+    let res = client
+        .post(uri!("/autotags"))
+        .header(Header::new("Authorization", AUTH))
+        .body(r#"{"name":"screenshots","conditions":{"fileTypes":["Image"],"namePattern":"^Screenshot"},"tags":["screenshot"]}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Created);
+    let created: AutoTagRuleApi = res.into_json().unwrap();
+    assert_eq!(Some(1), created.id);
+    assert!(created.enabled);
+    let res = client
+        .get(uri!("/autotags"))
+        .header(Header::new("Authorization", AUTH))
+        .dispatch();
+    assert_eq!(
+        vec![created],
+        res.into_json::<Vec<AutoTagRuleApi>>().unwrap()
+    );
+    let res = client
+        .put(uri!("/autotags"))
+        .header(Header::new("Authorization", AUTH))
+        .body(r#"{"id":1,"name":"screenshots","enabled":false,"conditions":{"extensions":["png"]},"tags":["screenshot"]}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    assert!(!res.into_json::<AutoTagRuleApi>().unwrap().enabled);
+    let res = client
+        .delete(uri!("/autotags/1"))
+        .header(Header::new("Authorization", AUTH))
+        .dispatch();
+    assert_eq!(res.status(), Status::NoContent);
+    let res = client
+        .delete(uri!("/autotags/1"))
+        .header(Header::new("Authorization", AUTH))
+        .dispatch();
+    assert_eq!(res.status(), Status::NotFound);
+    cleanup();
+}
+
+#[test]
+fn create_with_bad_conditions() {
+    set_password();
+    let client = client();
+    let res = client
+        .post(uri!("/autotags"))
+        .header(Header::new("Authorization", AUTH))
+        .body(r#"{"name":"bad","conditions":{"namePattern":"("},"tags":["tag"]}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::BadRequest);
+    let message: BasicMessage = res.into_json().unwrap();
+    assert!(
+        message
+            .message
+            .starts_with("namePattern is not a valid regex")
+    );
+    cleanup();
+}
+
+#[test]
+fn apply_is_accepted() {
+    set_password();
+    let client = client();
+    let res = client
+        .post(uri!("/autotags/apply"))
+        .header(Header::new("Authorization", AUTH))
+        .dispatch();
+    assert_eq!(res.status(), Status::Accepted);
+    cleanup();
+}
+
+#[test]
+fn uploading_file_applies_rules() {
+    set_password();
+    remove_files();
+    let client = client();
+    client
+        .post(uri!("/autotags"))
+        .header(Header::new("Authorization", AUTH))
+        .body(r#"{"name":"screenshots","conditions":{"namePattern":"^Screenshot"},"tags":["screenshot"]}"#)
+        .dispatch();
+    let body = "--BOUNDARY\r\n\
+Content-Disposition: form-data; name=\"file\"; filename=\"Screenshot_1.txt\"\r\n\
+Content-Type: text/plain\r\n\
+\r\n\
+aGk=\r\n\
+--BOUNDARY\r\n\
+Content-Disposition: form-data; name=\"extension\"\r\n\
+\r\n\
+txt\r\n\
+--BOUNDARY\r\n\
+Content-Disposition: form-data; name=\"folderId\"\r\n\
+\r\n\
+0\r\n\
+--BOUNDARY--";
+    let res = client
+        .post("/files")
+        .header(Header::new("Authorization", AUTH))
+        .header(Header::new(
+            "Content-Type",
+            "multipart/form-data; boundary=BOUNDARY",
+        ))
+        .body(body)
+        .dispatch();
+    assert_eq!(res.status(), Status::Created);
+    let tags = tag_service::get_tags_on_file(1).unwrap();
+    assert_eq!(
+        vec!["screenshot".to_string()],
+        tags.into_iter().map(|it| it.title).collect::<Vec<String>>()
+    );
+    cleanup();
+}
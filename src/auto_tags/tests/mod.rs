@@ -0,0 +1,2 @@
+mod handler;
+mod service;
@@ -0,0 +1,396 @@
+use std::collections::BTreeMap;
+
+use crate::auto_tags::service::*;
+use crate::auto_tags::{AutoTagConditions, AutoTagRuleApi, AutoTagRuleError};
+use crate::exif::ExifData;
+use crate::model::api::FileApi;
+use crate::model::file_types::FileTypes;
+use crate::model::repository::FileRecord;
+use crate::model::request::folder_requests::UpdateFolderRequest;
+use crate::repository::open_connection;
+use crate::service::{file_service, folder_service};
+use crate::tags::service as tag_service;
+use crate::test::*;
+
+fn rule(name: &str, conditions: AutoTagConditions, tags: Vec<&str>) -> AutoTagRuleApi {
+    AutoTagRuleApi {
+        id: None,
+        name: name.to_string(),
+        enabled: true,
+        conditions,
+        tags: tags.into_iter().map(String::from).collect(),
+    }
+}
+
+fn tag_titles(file_id: u32) -> Vec<String> {
+    let mut titles: Vec<String> = tag_service::get_tags_on_file(file_id)
+        .unwrap()
+        .into_iter()
+        .map(|it| it.title)
+        .collect();
+    titles.sort();
+    titles
+}
+
+fn screenshot_rule() -> AutoTagRuleApi {
+    rule(
+        "screenshots",
+        AutoTagConditions {
+            file_types: vec![FileTypes::Image],
+            name_pattern: Some("^Screenshot".to_string()),
+            ..Default::default()
+        },
+        vec!["screenshot"],
+    )
+}
+
+#[test]
+fn create_and_get_auto_tag_rule() {
+    init_db_folder();
+    let created = create_auto_tag_rule(rule(
+        " photos ",
+        AutoTagConditions {
+            extensions: vec![" .JPG ".to_string(), "jpg".to_string(), "png".to_string()],
+            folder_path: Some("/Camera/".to_string()),
+            ..Default::default()
+        },
+        vec![" photo ", "PHOTO", ""],
+    ))
+    .unwrap();
+    assert_eq!(Some(1), created.id);
+    assert_eq!("photos", created.name);
+    assert_eq!(vec!["photo".to_string()], created.tags);
+    assert_eq!(
+        vec!["jpg".to_string(), "png".to_string()],
+        created.conditions.extensions
+    );
+    assert_eq!(Some("Camera".to_string()), created.conditions.folder_path);
+    assert_eq!(Ok(created), get_auto_tag_rule(1));
+    cleanup();
+}
+
+#[test]
+fn create_rejects_invalid_rules() {
+    init_db_folder();
+    let conditions = AutoTagConditions {
+        extensions: vec!["png".to_string()],
+        ..Default::default()
+    };
+    assert!(matches!(
+        create_auto_tag_rule(rule(" ", conditions.clone(), vec!["tag"])),
+        Err(AutoTagRuleError::Invalid(_))
+    ));
+    assert!(matches!(
+        create_auto_tag_rule(rule("no tags", conditions.clone(), vec![" "])),
+        Err(AutoTagRuleError::Invalid(_))
+    ));
+    assert!(matches!(
+        create_auto_tag_rule(rule("no conditions", Default::default(), vec!["tag"])),
+        Err(AutoTagRuleError::Invalid(_))
+    ));
+    let bad_conditions = [
+        AutoTagConditions {
+            name_pattern: Some("(".to_string()),
+            ..Default::default()
+        },
+        AutoTagConditions {
+            min_size: Some(10),
+            max_size: Some(5),
+            ..Default::default()
+        },
+        AutoTagConditions {
+            exif: vec!["cameraModel.eq".to_string()],
+            ..Default::default()
+        },
+        AutoTagConditions {
+            exif: vec!["fileType.eq;image".to_string()],
+            ..Default::default()
+        },
+    ];
+    for conditions in bad_conditions {
+        assert!(matches!(
+            create_auto_tag_rule(rule("bad", conditions, vec!["tag"])),
+            Err(AutoTagRuleError::Invalid(_))
+        ));
+    }
+    create_auto_tag_rule(rule("pngs", conditions.clone(), vec!["png"])).unwrap();
+    assert_eq!(
+        Err(AutoTagRuleError::AlreadyExists),
+        create_auto_tag_rule(rule("PNGS", conditions, vec!["png"]))
+    );
+    cleanup();
+}
+
+#[test]
+fn update_replaces_conditions_and_tags() {
+    init_db_folder();
+    create_auto_tag_rule(screenshot_rule()).unwrap();
+    create_auto_tag_rule(rule(
+        "videos",
+        AutoTagConditions {
+            file_types: vec![FileTypes::Video],
+            ..Default::default()
+        },
+        vec!["video"],
+    ))
+    .unwrap();
+    let mut updated = rule(
+        "big files",
+        AutoTagConditions {
+            min_size: Some(1024),
+            exif: vec!["cameraModel.eq;Pixel 7".to_string()],
+            ..Default::default()
+        },
+        vec!["big"],
+    );
+    updated.id = Some(1);
+    updated.enabled = false;
+    update_auto_tag_rule(updated.clone()).unwrap();
+    assert_eq!(Ok(updated.clone()), get_auto_tag_rule(1));
+    updated.name = "videos".to_string();
+    assert_eq!(
+        Err(AutoTagRuleError::AlreadyExists),
+        update_auto_tag_rule(updated.clone())
+    );
+    updated.id = Some(3);
+    assert_eq!(
+        Err(AutoTagRuleError::NotFound),
+        update_auto_tag_rule(updated)
+    );
+    cleanup();
+}
+
+#[test]
+fn delete_removes_auto_tag_rule() {
+    init_db_folder();
+    create_auto_tag_rule(screenshot_rule()).unwrap();
+    assert_eq!(Ok(()), delete_auto_tag_rule(1));
+    assert_eq!(Err(AutoTagRuleError::NotFound), get_auto_tag_rule(1));
+    assert_eq!(Err(AutoTagRuleError::NotFound), delete_auto_tag_rule(1));
+    assert_eq!(Ok(vec![]), get_all_auto_tag_rules());
+    cleanup();
+}
+
+#[test]
+fn apply_rules_to_file_adds_tags_when_all_conditions_match() {
+    init_db_folder();
+    create_auto_tag_rule(screenshot_rule()).unwrap();
+    create_file_db_entry("Screenshot_1.png", None);
+    create_file_db_entry("Screenshot_2.txt", None);
+    create_file_db_entry("photo.png", None);
+    for id in 1..=3 {
+        apply_rules_to_file(id).unwrap();
+    }
+    assert_eq!(vec!["screenshot".to_string()], tag_titles(1));
+    assert!(tag_titles(2).is_empty());
+    assert!(tag_titles(3).is_empty());
+    cleanup();
+}
+
+#[rocket::async_test]
+async fn processing_all_files_message_tags_existing_files() {
+    init_db_folder();
+    create_file_db_entry("Screenshot_1.png", None);
+    create_file_db_entry("notes.txt", None);
+    create_auto_tag_rule(screenshot_rule()).unwrap();
+    assert!(process_auto_tag_message("all".to_string()).await);
+    assert_eq!(vec!["screenshot".to_string()], tag_titles(1));
+    assert!(tag_titles(2).is_empty());
+    cleanup();
+}
+
+#[rocket::async_test]
+async fn processing_invalid_message_is_not_requeued() {
+    init_db_folder();
+    assert!(process_auto_tag_message("not a folder".to_string()).await);
+    cleanup();
+}
+
+#[test]
+fn apply_rules_to_file_checks_extension_folder_and_size() {
+    init_db_folder();
+    create_auto_tag_rule(rule(
+        "raw photos",
+        AutoTagConditions {
+            extensions: vec!["DNG".to_string()],
+            folder_path: Some("Camera".to_string()),
+            min_size: Some(100),
+            max_size: Some(1000),
+            ..Default::default()
+        },
+        vec!["raw"],
+    ))
+    .unwrap();
+    create_folder_db_entry("Camera", None); // id 1
+    create_folder_db_entry("2024", Some(1)); // id 2
+    create_folder_db_entry("Cameras", None); // id 3
+    for (name, parent_id, size) in [
+        ("a.dng", Some(2), 500),
+        ("b.DNG", Some(1), 100),
+        ("c.dng", Some(3), 500),
+        ("d.dng", Some(1), 5000),
+        ("e.jpg", Some(1), 500),
+    ] {
+        FileRecord {
+            id: None,
+            name: name.to_string(),
+            parent_id,
+            create_date: now(),
+            size,
+            file_type: FileTypes::Image,
+            upload_date: None,
+            modified_date: None,
+        }
+        .save_to_db();
+    }
+    for id in 1..=5 {
+        apply_rules_to_file(id).unwrap();
+    }
+    assert_eq!(vec!["raw".to_string()], tag_titles(1));
+    assert_eq!(vec!["raw".to_string()], tag_titles(2));
+    assert!(tag_titles(3).is_empty());
+    assert!(tag_titles(4).is_empty());
+    assert!(tag_titles(5).is_empty());
+    cleanup();
+}
+
+#[test]
+fn apply_rules_to_file_checks_exif() {
+    init_db_folder();
+    create_auto_tag_rule(rule(
+        "pixel",
+        AutoTagConditions {
+            exif: vec!["cameraModel.eq;pixel 7".to_string()],
+            ..Default::default()
+        },
+        vec!["pixel"],
+    ))
+    .unwrap();
+    create_file_db_entry("a.jpg", None);
+    create_file_db_entry("b.jpg", None);
+    let con = open_connection();
+    let exif = ExifData {
+        camera_model: Some("Pixel 7".to_string()),
+        ..Default::default()
+    };
+    crate::exif::repository::save_file_exif(1, &exif, &con).unwrap();
+    con.close().unwrap();
+    apply_rules_to_file(1).unwrap();
+    apply_rules_to_file(2).unwrap();
+    assert_eq!(vec!["pixel".to_string()], tag_titles(1));
+    assert!(tag_titles(2).is_empty());
+    cleanup();
+}
+
+#[test]
+fn apply_rules_to_file_skips_disabled_rules_and_keeps_existing_tags() {
+    init_db_folder();
+    let mut disabled = rule(
+        "all images",
+        AutoTagConditions {
+            file_types: vec![FileTypes::Image],
+            ..Default::default()
+        },
+        vec!["image"],
+    );
+    disabled.enabled = false;
+    create_auto_tag_rule(disabled).unwrap();
+    create_auto_tag_rule(screenshot_rule()).unwrap();
+    create_file_db_entry("Screenshot.png", None);
+    create_tag_file("screenshot", 1);
+    create_tag_file("keep", 1);
+    apply_rules_to_file(1).unwrap();
+    assert_eq!(
+        vec!["keep".to_string(), "screenshot".to_string()],
+        tag_titles(1)
+    );
+    cleanup();
+}
+
+#[test]
+fn moving_file_applies_rules() {
+    init_db_folder();
+    create_auto_tag_rule(rule(
+        "family",
+        AutoTagConditions {
+            folder_path: Some("Family".to_string()),
+            ..Default::default()
+        },
+        vec!["family"],
+    ))
+    .unwrap();
+    create_folder_db_entry("Family", None);
+    create_folder_disk("Family");
+    create_file_db_entry("test.txt", None);
+    create_file_disk("test.txt", "test");
+    file_service::update_file(FileApi {
+        id: 1,
+        folder_id: Some(1),
+        name: "test.txt".to_string(),
+        tags: vec![],
+        size: Some(0),
+        date_created: Some(now()),
+        file_type: None,
+        description: None,
+        properties: Some(BTreeMap::new()),
+        date_uploaded: None,
+        date_modified: None,
+        favorite: false,
+        download_count: 0,
+        last_accessed: None,
+        location: None,
+        relevance: None,
+    })
+    .unwrap();
+    assert_eq!(vec!["family".to_string()], tag_titles(1));
+    cleanup();
+}
+
+#[rocket::async_test]
+async fn moving_folder_applies_rules_to_files_under_it() {
+    init_db_folder();
+    create_auto_tag_rule(rule(
+        "family",
+        AutoTagConditions {
+            folder_path: Some("Family".to_string()),
+            ..Default::default()
+        },
+        vec!["family"],
+    ))
+    .unwrap();
+    create_folder_db_entry("Family", None); // id 1
+    create_folder_db_entry("Trip", None); // id 2
+    create_folder_db_entry("Day 1", Some(2)); // id 3
+    create_folder_disk("Family");
+    create_folder_disk("Trip");
+    create_folder_disk("Trip/Day 1");
+    create_file_db_entry("test.txt", Some(3));
+    create_file_disk("Trip/Day 1/test.txt", "test");
+    folder_service::update_folder(&UpdateFolderRequest {
+        id: 2,
+        name: "Trip".to_string(),
+        parent_id: Some(1),
+        tags: vec![],
+        description: None,
+        properties: Some(BTreeMap::new()),
+    })
+    .unwrap();
+    // the folder is queued, so the consumer is what applies the rules
+    assert!(process_auto_tag_message("2".to_string()).await);
+    assert_eq!(vec!["family".to_string()], tag_titles(1));
+    cleanup();
+}
+
+#[test]
+fn apply_rules_to_all_files_tags_existing_files() {
+    init_db_folder();
+    create_file_db_entry("Screenshot_1.png", None);
+    create_file_db_entry("Screenshot_2.png", None);
+    create_file_db_entry("notes.txt", None);
+    create_auto_tag_rule(screenshot_rule()).unwrap();
+    apply_rules_to_all_files();
+    assert_eq!(vec!["screenshot".to_string()], tag_titles(1));
+    assert_eq!(vec!["screenshot".to_string()], tag_titles(2));
+    assert!(tag_titles(3).is_empty());
+    cleanup();
+}
@@ -27,6 +27,42 @@ pub mod config {
         pub sleep_time_millis: u32,
     }
 
+    /// config properties for encrypting stored files at rest
+    #[derive(Deserialize, Clone, Debug, Default)]
+    pub struct EncryptionConfig {
+        /// whether newly stored files should be encrypted. Existing files can be converted with the `encrypt-store` and `decrypt-store` commands
+        #[serde(default)]
+        pub enabled: bool,
+        pub passphrase: Option<String>,
+        /// path to a file whose contents are used as the secret instead of a passphrase
+        #[serde(rename = "keyFile")]
+        pub key_file: Option<String>,
+    }
+
+    /// config properties for where the contents of files and folders are kept
+    #[derive(Deserialize, Clone, Debug)]
+    pub struct StorageConfig {
+        /// either `local` (the default) or `s3`
+        #[serde(default = "default_storage_backend")]
+        pub backend: String,
+        /// connection details for an S3-compatible service, required when `backend` is `s3`
+        #[serde(rename = "S3")]
+        pub s3: Option<crate::storage::S3Options>,
+    }
+
+    fn default_storage_backend() -> String {
+        "local".to_string()
+    }
+
+    impl Default for StorageConfig {
+        fn default() -> Self {
+            Self {
+                backend: default_storage_backend(),
+                s3: None,
+            }
+        }
+    }
+
     /// config properties for the whole of this application
     #[derive(Deserialize, Clone, Debug)]
     pub struct FileServerConfig {
@@ -36,6 +72,10 @@ pub mod config {
         pub queue_jobs: QueueJobsConfig,
         #[serde(rename = "Database")]
         pub database: DbConfig,
+        #[serde(rename = "Encryption", default)]
+        pub encryption: EncryptionConfig,
+        #[serde(rename = "Storage", default)]
+        pub storage: StorageConfig,
     }
 
     /// Parses the config file located at ./FileServer.toml, if it exists.
@@ -81,6 +121,8 @@ pub mod config {
         database: DbConfig {
             location: "./db.sqlite".to_string(),
         },
+        encryption: EncryptionConfig::default(),
+        storage: StorageConfig::default(),
     });
 }
 
@@ -0,0 +1,20 @@
+pub mod repository;
+pub mod service;
+
+#[cfg(test)]
+mod tests;
+
+use crate::repository::{metadata_repository, open_connection};
+
+/// queues every text-like file for content indexing if that hasn't been done for this database yet
+pub fn load_all_file_contents() {
+    let con = open_connection();
+    let indexed_flag = metadata_repository::get_contents_indexed_flag(&con);
+    if let Ok(false) = indexed_flag {
+        log::info!("Content index flag not set, queuing all text-like files for indexing");
+        service::mass_index_contents();
+        // Ignore result - if setting the flag fails, we'll just re-index next time
+        let _ = metadata_repository::set_contents_indexed_flag(&con);
+    }
+    con.close().unwrap();
+}
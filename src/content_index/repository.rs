@@ -0,0 +1,24 @@
+use rusqlite::Connection;
+
+/// replaces the indexed contents of the file with the passed id
+pub fn index_file_contents(
+    file_id: u32,
+    contents: &str,
+    con: &Connection,
+) -> Result<(), rusqlite::Error> {
+    remove_file_contents(file_id, con)?;
+    let mut pst = con.prepare(include_str!(
+        "../assets/queries/file_contents/insert_file_contents.sql"
+    ))?;
+    pst.execute(rusqlite::params![file_id, contents])?;
+    Ok(())
+}
+
+/// removes the file with the passed id from the content index. Does nothing if the file was never indexed
+pub fn remove_file_contents(file_id: u32, con: &Connection) -> Result<(), rusqlite::Error> {
+    let mut pst = con.prepare(include_str!(
+        "../assets/queries/file_contents/delete_file_contents.sql"
+    ))?;
+    pst.execute([file_id])?;
+    Ok(())
+}
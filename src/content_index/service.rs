@@ -0,0 +1,283 @@
+use std::backtrace::Backtrace;
+use std::io::Read;
+use std::path::Path;
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::content_index::repository::{index_file_contents, remove_file_contents};
+use crate::model::file_types::FileTypes;
+use crate::repository::{file_repository, open_connection};
+use crate::storage::{self, models::StorageError};
+
+/// only this many bytes from the start of each file are indexed, so that huge log files don't bloat the database
+const MAX_INDEXED_BYTES: u64 = 1024 * 1024;
+
+/// pdf and docx files have to be read whole to get their text out, so bigger ones are skipped to keep memory use down
+const MAX_DOCUMENT_BYTES: u64 = 50 * 1024 * 1024;
+
+/// matches rtf control words (e.g. `\par`, `\fs24`) and control symbols (e.g. `\'e9`)
+static RTF_CONTROL_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\\'[0-9a-fA-F]{2}|\\[a-zA-Z]+-?\d* ?|\\[^a-zA-Z]").unwrap());
+
+/// matches the runs of text (`<w:t>`), tabs, breaks, and paragraph ends in a docx's `word/document.xml`
+static DOCX_TEXT_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"<w:t(?:\s[^>]*)?>([^<]*)</w:t>|<w:tab\s*/>|<w:br\s*/>|</w:p>").unwrap()
+});
+
+/// the formats that need their text pulled out of a binary file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DocumentFormat {
+    Pdf,
+    Docx,
+}
+
+impl DocumentFormat {
+    fn from_file_name(file_name: &str) -> Option<Self> {
+        let extension = Path::new(file_name).extension()?.to_str()?;
+        if extension.eq_ignore_ascii_case("pdf") {
+            Some(Self::Pdf)
+        } else if extension.eq_ignore_ascii_case("docx") {
+            Some(Self::Docx)
+        } else {
+            None
+        }
+    }
+}
+
+/// checks if files of the passed type can have their contents indexed
+pub fn is_indexable(file_type: FileTypes) -> bool {
+    matches!(
+        file_type,
+        FileTypes::Text | FileTypes::Code | FileTypes::Configuration | FileTypes::Document
+    )
+}
+
+/// Indexes the contents of the file whose id is `message_data`, so that it can be found by the words inside of it.
+/// If the file was deleted or is no longer a text-like file, it's removed from the index instead.
+///
+/// ## Returns
+/// * `true` if the message was handled, even if the file couldn't be indexed
+/// * `false` if a database error occurred and the message should be re-queued
+pub async fn process_single_file_contents(message_data: String) -> bool {
+    let id: u32 = match message_data.parse() {
+        Ok(id) => id,
+        Err(e) => {
+            log::error!(
+                "Failed to parse {message_data} as a u32! Exception is {e:?}\n{}",
+                Backtrace::force_capture()
+            );
+            // we can't re-queue this or else we'll keep getting errors
+            return true;
+        }
+    };
+    let con = open_connection();
+    let file = match file_repository::get_file(id, &con) {
+        Ok(f) => f,
+        Err(rusqlite::Error::QueryReturnedNoRows) => {
+            // the file was deleted, so its contents shouldn't be searchable anymore
+            let res = remove_file_contents(id, &con);
+            con.close().unwrap();
+            return res.is_ok();
+        }
+        Err(e) => {
+            log::error!(
+                "Failed to retrieve file id {id} for content indexing. Exception is {e:?}\n{}",
+                Backtrace::force_capture()
+            );
+            con.close().unwrap();
+            return false;
+        }
+    };
+    let contents = if is_indexable(file.file_type) {
+        read_file_text(id, &file.name, &con)
+    } else {
+        // the file might have been renamed to a type that isn't indexed
+        None
+    };
+    let res = match contents {
+        Some(text) => index_file_contents(id, &text, &con),
+        None => remove_file_contents(id, &con),
+    };
+    con.close().unwrap();
+    match res {
+        Ok(()) => true,
+        Err(e) => {
+            log::error!(
+                "Failed to update content index for file id {id}. Exception is {e:?}\n{}",
+                Backtrace::force_capture()
+            );
+            // Database error, might be temporary, re-queue
+            false
+        }
+    }
+}
+
+/// reads the stored file and extracts its plain text. Only the first [MAX_INDEXED_BYTES] of text are kept
+fn read_file_text(id: u32, name: &str, con: &rusqlite::Connection) -> Option<String> {
+    let path = file_repository::get_file_path(id, con).ok()?;
+    // documents can't be parsed from only part of the file
+    let is_document = DocumentFormat::from_file_name(name).is_some();
+    let max_bytes = if is_document {
+        MAX_DOCUMENT_BYTES
+    } else {
+        MAX_INDEXED_BYTES
+    };
+    let mut bytes = Vec::new();
+    let read_res = storage::open(&path)
+        .and_then(|file| Ok(file.take(max_bytes + 1).read_to_end(&mut bytes)?));
+    match read_res {
+        Ok(_) if is_document && bytes.len() as u64 > max_bytes => {
+            log::debug!("File id {id} is too big to extract text from, skipping content indexing");
+            None
+        }
+        Ok(_) => {
+            bytes.truncate(max_bytes as usize);
+            extract_text(&bytes, name).map(truncate_text)
+        }
+        Err(StorageError::NotFound) => {
+            log::debug!("File id {id} not found in storage, skipping content indexing");
+            None
+        }
+        Err(e) => {
+            log::error!(
+                "Failed to read file id {id} for content indexing. Exception is {e:?}\n{}",
+                Backtrace::force_capture()
+            );
+            None
+        }
+    }
+}
+
+/// extracts the plain text out of the passed file contents.
+///
+/// pdf and docx files have their text pulled out of them. Returns `None` for other binary formats,
+/// which would only fill the index with garbage
+pub fn extract_text(bytes: &[u8], file_name: &str) -> Option<String> {
+    let text = match DocumentFormat::from_file_name(file_name) {
+        Some(DocumentFormat::Pdf) => extract_pdf_text(bytes, file_name)?,
+        Some(DocumentFormat::Docx) => extract_docx_text(bytes, file_name)?,
+        None if bytes.contains(&0) => return None,
+        None => {
+            let text = String::from_utf8_lossy(bytes);
+            let is_rtf = Path::new(file_name)
+                .extension()
+                .is_some_and(|it| it.eq_ignore_ascii_case("rtf"));
+            if is_rtf {
+                RTF_CONTROL_REGEX
+                    .replace_all(&text, "")
+                    .replace(['{', '}'], "")
+            } else {
+                text.into_owned()
+            }
+        }
+    };
+    if text.trim().is_empty() {
+        None
+    } else {
+        Some(text)
+    }
+}
+
+fn extract_pdf_text(bytes: &[u8], file_name: &str) -> Option<String> {
+    // the pdf parser can panic on malformed files, which shouldn't take down the queue consumer
+    match std::panic::catch_unwind(|| pdf_extract::extract_text_from_mem(bytes)) {
+        Ok(Ok(text)) => Some(text),
+        Ok(Err(e)) => {
+            log::warn!("Failed to extract text from pdf {file_name}: {e:?}");
+            None
+        }
+        Err(_) => {
+            log::warn!("Failed to extract text from pdf {file_name}: the pdf parser panicked");
+            None
+        }
+    }
+}
+
+fn extract_docx_text(bytes: &[u8], file_name: &str) -> Option<String> {
+    let mut xml = String::new();
+    let res = zip::ZipArchive::new(std::io::Cursor::new(bytes)).and_then(|mut archive| {
+        let entry = archive.by_name("word/document.xml")?;
+        // the zip is small enough to index, but its document could decompress into far more than that
+        if entry.size() > MAX_DOCUMENT_BYTES {
+            return Err(document_too_large());
+        }
+        entry
+            .take(MAX_DOCUMENT_BYTES + 1)
+            .read_to_string(&mut xml)?;
+        if xml.len() as u64 > MAX_DOCUMENT_BYTES {
+            return Err(document_too_large());
+        }
+        Ok(())
+    });
+    if let Err(e) = res {
+        log::warn!("Failed to extract text from docx {file_name}: {e:?}");
+        return None;
+    }
+    let mut text = String::new();
+    for captures in DOCX_TEXT_REGEX.captures_iter(&xml) {
+        match captures.get(1) {
+            Some(run) => text.push_str(&unescape_xml(run.as_str())),
+            None if captures[0].starts_with("<w:tab") => text.push('\t'),
+            None => text.push('\n'),
+        }
+    }
+    Some(text)
+}
+
+fn document_too_large() -> zip::result::ZipError {
+    std::io::Error::other(format!(
+        "word/document.xml is larger than {MAX_DOCUMENT_BYTES} bytes"
+    ))
+    .into()
+}
+
+/// replaces the predefined xml entities with the characters they stand for
+fn unescape_xml(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// cuts the text down to at most [MAX_INDEXED_BYTES], without splitting a character
+fn truncate_text(mut text: String) -> String {
+    let mut end = (MAX_INDEXED_BYTES as usize).min(text.len());
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    text.truncate(end);
+    text
+}
+
+/// Queues all text-like files in the database for content indexing.
+/// This function should be called on startup if the content index flag is not set.
+pub fn mass_index_contents() {
+    use crate::queue::publish_message;
+
+    let con = open_connection();
+    let all_files = file_repository::get_all_files(&con);
+    con.close().unwrap();
+
+    let all_files = match all_files {
+        Ok(files) => files,
+        Err(e) => {
+            log::error!(
+                "Failed to retrieve files for mass content indexing: {e:?}\n{}",
+                Backtrace::force_capture()
+            );
+            return;
+        }
+    };
+
+    let mut queued_count = 0;
+    for file in all_files {
+        if let Some(id) = file.id.filter(|_| is_indexable(file.file_type)) {
+            publish_message("content_index", &id.to_string());
+            queued_count += 1;
+        }
+    }
+
+    log::info!("Queued {queued_count} files for content indexing");
+}
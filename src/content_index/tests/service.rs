@@ -0,0 +1,207 @@
+use std::io::Write;
+
+use crate::content_index::service::{extract_text, process_single_file_contents};
+use crate::model::api::FileApi;
+use crate::model::request::search_query::SearchQuery;
+use crate::repository::{file_repository, open_connection};
+use crate::service::file_service::file_dir;
+use crate::test::{cleanup, create_file_db_entry, create_file_disk, init_db_folder};
+use rocket::tokio;
+
+fn search(criteria: &str) -> Vec<u32> {
+    let con = open_connection();
+    let query = SearchQuery::Content(criteria.to_string());
+    let res =
+        file_repository::search_files_by_query(&query, Default::default(), 50, 0, &con).unwrap();
+    con.close().unwrap();
+    res.into_iter().map(|it| FileApi::from(it).id).collect()
+}
+
+#[tokio::test]
+async fn indexes_text_file_contents() {
+    init_db_folder();
+    create_file_db_entry("notes.txt", None);
+    create_file_disk("notes.txt", "the quick brown fox");
+    assert!(process_single_file_contents("1".to_string()).await);
+    assert_eq!(vec![1], search("quick fox"));
+    // stemming means searching for different forms of the same word works
+    assert_eq!(vec![1], search("foxes"));
+    assert!(search("slow").is_empty());
+    cleanup();
+}
+
+#[tokio::test]
+async fn reindexing_replaces_old_contents() {
+    init_db_folder();
+    create_file_db_entry("notes.txt", None);
+    create_file_disk("notes.txt", "old contents");
+    assert!(process_single_file_contents("1".to_string()).await);
+    create_file_disk("notes.txt", "new contents");
+    assert!(process_single_file_contents("1".to_string()).await);
+    assert!(search("old").is_empty());
+    assert_eq!(vec![1], search("new"));
+    cleanup();
+}
+
+#[tokio::test]
+async fn deleted_files_are_removed_from_the_index() {
+    init_db_folder();
+    create_file_db_entry("notes.txt", None);
+    create_file_disk("notes.txt", "contents");
+    assert!(process_single_file_contents("1".to_string()).await);
+    let con = open_connection();
+    file_repository::delete_file(1, &con).unwrap();
+    con.close().unwrap();
+    assert!(search("contents").is_empty());
+    // the queued message for the deleted file shouldn't be re-queued
+    assert!(process_single_file_contents("1".to_string()).await);
+    cleanup();
+}
+
+#[tokio::test]
+async fn does_not_index_other_file_types() {
+    init_db_folder();
+    create_file_db_entry("image.png", None);
+    create_file_disk("image.png", "not really an image");
+    assert!(process_single_file_contents("1".to_string()).await);
+    assert!(search("image").is_empty());
+    cleanup();
+}
+
+#[tokio::test]
+async fn ignores_bad_message() {
+    init_db_folder();
+    assert!(process_single_file_contents("not a number".to_string()).await);
+    cleanup();
+}
+
+/// builds a single page pdf showing the passed text
+fn pdf(text: &str) -> Vec<u8> {
+    let stream = format!("BT /F1 12 Tf 72 712 Td ({text}) Tj ET");
+    let objects = [
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        "<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_string(),
+        "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 612 792] /Contents 4 0 R /Resources << /Font << /F1 5 0 R >> >> >>".to_string(),
+        format!("<< /Length {} >>\nstream\n{stream}\nendstream", stream.len()),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>".to_string(),
+    ];
+    let mut pdf = "%PDF-1.4\n".to_string();
+    let mut offsets = Vec::new();
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.push_str(&format!("{} 0 obj\n{object}\nendobj\n", i + 1));
+    }
+    let xref_offset = pdf.len();
+    pdf.push_str(&format!(
+        "xref\n0 {}\n0000000000 65535 f \n",
+        objects.len() + 1
+    ));
+    for offset in offsets {
+        pdf.push_str(&format!("{offset:010} 00000 n \n"));
+    }
+    pdf.push_str(&format!(
+        "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref_offset}\n%%EOF\n",
+        objects.len() + 1
+    ));
+    pdf.into_bytes()
+}
+
+/// builds a docx whose document has the passed paragraphs
+fn docx(paragraphs: &[&str]) -> Vec<u8> {
+    let body: String = paragraphs
+        .iter()
+        .map(|it| format!(r#"<w:p><w:r><w:t xml:space="preserve">{it}</w:t></w:r></w:p>"#))
+        .collect();
+    let xml = format!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body>{body}</w:body></w:document>"#
+    );
+    let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    writer
+        .start_file(
+            "word/document.xml",
+            zip::write::SimpleFileOptions::default(),
+        )
+        .unwrap();
+    writer.write_all(xml.as_bytes()).unwrap();
+    writer.finish().unwrap().into_inner()
+}
+
+#[test]
+fn extract_text_skips_binary_contents() {
+    assert_eq!(None, extract_text(b"\x7fELF\0\x01\x02", "program.bin"));
+}
+
+#[test]
+fn extract_text_reads_pdf() {
+    let text = extract_text(&pdf("quarterly tax report"), "report.pdf").unwrap();
+    assert!(text.contains("quarterly tax report"));
+    // malformed pdfs are skipped
+    assert_eq!(None, extract_text(b"%PDF-1.7\0\x01\x02", "document.pdf"));
+}
+
+#[test]
+fn extract_text_reads_docx() {
+    let text = extract_text(
+        &docx(&["Dear landlord,", "rent &amp; deposit"]),
+        "letter.DOCX",
+    )
+    .unwrap();
+    assert_eq!("Dear landlord,\nrent & deposit\n", text);
+    assert_eq!(None, extract_text(b"PK\x03\x04\0\0", "letter.docx"));
+}
+
+#[test]
+fn extract_text_skips_docx_that_decompresses_too_large() {
+    // compresses to well under the size limit, but decompresses to more than it
+    let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    writer
+        .start_file(
+            "word/document.xml",
+            zip::write::SimpleFileOptions::default(),
+        )
+        .unwrap();
+    let chunk = vec![b' '; 1024 * 1024];
+    for _ in 0..51 {
+        writer.write_all(&chunk).unwrap();
+    }
+    let bytes = writer.finish().unwrap().into_inner();
+    assert!(bytes.len() < 1024 * 1024);
+    assert_eq!(None, extract_text(&bytes, "bomb.docx"));
+}
+
+#[tokio::test]
+async fn indexes_document_contents() {
+    init_db_folder();
+    create_file_db_entry("report.pdf", None);
+    create_file_db_entry("letter.docx", None);
+    std::fs::write(format!("{}/report.pdf", file_dir()), pdf("quarterly taxes")).unwrap();
+    std::fs::write(
+        format!("{}/letter.docx", file_dir()),
+        docx(&["rent is due"]),
+    )
+    .unwrap();
+    assert!(process_single_file_contents("1".to_string()).await);
+    assert!(process_single_file_contents("2".to_string()).await);
+    assert_eq!(vec![1], search("quarterly"));
+    assert_eq!(vec![2], search("rent"));
+    cleanup();
+}
+
+#[test]
+fn extract_text_strips_rtf_formatting() {
+    let text = extract_text(
+        br"{\rtf1\ansi{\fonttbl\f0 Arial;}\f0\fs24 Hello \b world\b0\par}",
+        "doc.rtf",
+    )
+    .unwrap();
+    assert!(!text.contains('\\'));
+    assert!(text.contains("Hello world"));
+}
+
+#[test]
+fn search_treats_query_syntax_literally() {
+    init_db_folder();
+    // unbalanced quotes and operators would be a syntax error if they were passed to fts as-is
+    assert!(search("\"unbalanced OR").is_empty());
+    cleanup();
+}
@@ -116,6 +116,54 @@ pub fn migrate_db(con: &Connection, table_version: u64) -> Result<()> {
         log_migration_version(6);
         migrate_v6(con)?;
     }
+    if table_version < 7 {
+        log_migration_version(7);
+        migrate_v7(con)?;
+    }
+    if table_version < 8 {
+        log_migration_version(8);
+        migrate_v8(con)?;
+    }
+    if table_version < 9 {
+        log_migration_version(9);
+        migrate_v9(con)?;
+    }
+    if table_version < 10 {
+        log_migration_version(10);
+        migrate_v10(con)?;
+    }
+    if table_version < 11 {
+        log_migration_version(11);
+        migrate_v11(con)?;
+    }
+    if table_version < 12 {
+        log_migration_version(12);
+        migrate_v12(con)?;
+    }
+    if table_version < 13 {
+        log_migration_version(13);
+        migrate_v13(con)?;
+    }
+    if table_version < 14 {
+        log_migration_version(14);
+        migrate_v14(con)?;
+    }
+    if table_version < 15 {
+        log_migration_version(15);
+        migrate_v15(con)?;
+    }
+    if table_version < 16 {
+        log_migration_version(16);
+        migrate_v16(con)?;
+    }
+    if table_version < 17 {
+        log_migration_version(17);
+        migrate_v17(con)?;
+    }
+    if table_version < 18 {
+        log_migration_version(18);
+        migrate_v18(con)?;
+    }
     Ok(())
 }
 
@@ -146,3 +194,51 @@ fn migrate_v5(con: &Connection) -> Result<()> {
 fn migrate_v6(con: &Connection) -> Result<()> {
     con.execute_batch(include_str!("./assets/migration/v6.sql"))
 }
+
+fn migrate_v7(con: &Connection) -> Result<()> {
+    con.execute_batch(include_str!("./assets/migration/v7.sql"))
+}
+
+fn migrate_v8(con: &Connection) -> Result<()> {
+    con.execute_batch(include_str!("./assets/migration/v8.sql"))
+}
+
+fn migrate_v9(con: &Connection) -> Result<()> {
+    con.execute_batch(include_str!("./assets/migration/v9.sql"))
+}
+
+fn migrate_v10(con: &Connection) -> Result<()> {
+    con.execute_batch(include_str!("./assets/migration/v10.sql"))
+}
+
+fn migrate_v11(con: &Connection) -> Result<()> {
+    con.execute_batch(include_str!("./assets/migration/v11.sql"))
+}
+
+fn migrate_v12(con: &Connection) -> Result<()> {
+    con.execute_batch(include_str!("./assets/migration/v12.sql"))
+}
+
+fn migrate_v13(con: &Connection) -> Result<()> {
+    con.execute_batch(include_str!("./assets/migration/v13.sql"))
+}
+
+fn migrate_v14(con: &Connection) -> Result<()> {
+    con.execute_batch(include_str!("./assets/migration/v14.sql"))
+}
+
+fn migrate_v15(con: &Connection) -> Result<()> {
+    con.execute_batch(include_str!("./assets/migration/v15.sql"))
+}
+
+fn migrate_v16(con: &Connection) -> Result<()> {
+    con.execute_batch(include_str!("./assets/migration/v16.sql"))
+}
+
+fn migrate_v17(con: &Connection) -> Result<()> {
+    con.execute_batch(include_str!("./assets/migration/v17.sql"))
+}
+
+fn migrate_v18(con: &Connection) -> Result<()> {
+    con.execute_batch(include_str!("./assets/migration/v18.sql"))
+}
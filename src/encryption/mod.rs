@@ -0,0 +1,16 @@
+pub mod models;
+mod service;
+
+pub use service::{
+    DecryptReader, decrypt_contents, decrypt_file, encrypt_contents, encrypt_file_in_place,
+    is_enabled, is_encrypted, migrate_store, plaintext_size,
+};
+
+#[cfg(not(test))]
+pub use service::check_config;
+
+#[cfg(test)]
+pub use service::set_test_secret;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,18 @@
+/// represents error states that can arise from encrypting or decrypting files on the disk
+#[derive(Debug)]
+pub enum EncryptionError {
+    /// the file is encrypted, but no passphrase or key file has been configured to decrypt it
+    NotConfigured,
+    /// the key could not be derived from the configured secret
+    KeyDerivation,
+    /// the file could not be encrypted, or it could not be decrypted with the configured secret (wrong key or tampered file)
+    Crypto,
+    /// a generic error reading from or writing to the file system
+    Io(std::io::Error),
+}
+
+impl From<std::io::Error> for EncryptionError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
@@ -0,0 +1,460 @@
+use std::backtrace::Backtrace;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::XChaCha20Poly1305;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::stream::{DecryptorBE32, EncryptorBE32};
+use chacha20poly1305::aead::{KeyInit, OsRng};
+use once_cell::sync::Lazy;
+use sha2::{Digest, Sha256};
+
+use super::models::EncryptionError;
+use crate::previews::preview_dir;
+use crate::storage;
+use crate::storage::models::{LocalFile, StorageError};
+
+/// marks the start of every encrypted file, so that encrypted and plain files can live side by side while a store is being migrated
+const MAGIC: &[u8; 8] = b"FSCRYPT1";
+const SALT_LEN: usize = 16;
+/// XChaCha20's 24 byte nonce, minus the 5 bytes the stream construction uses for its chunk counter and last chunk flag
+const NONCE_LEN: usize = 19;
+const HEADER_LEN: usize = MAGIC.len() + SALT_LEN + NONCE_LEN;
+/// files are encrypted in chunks of this size so that large files never need to be held in memory all at once
+const CHUNK_LEN: usize = 64 * 1024;
+const TAG_LEN: usize = 16;
+
+/// salt used to derive the key for every file encrypted by this process. It's stored in each file's header,
+/// so that files can always be decrypted with nothing but the passphrase
+static WRITE_SALT: Lazy<[u8; SALT_LEN]> = Lazy::new(|| {
+    let mut salt = [0; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    salt
+});
+
+/// keys that have already been derived, keyed by a hash of the secret and the salt.
+/// Key derivation is intentionally slow, so it should only happen once per salt
+#[allow(clippy::type_complexity)]
+static KEY_CACHE: Lazy<Mutex<HashMap<([u8; 32], [u8; SALT_LEN]), [u8; 32]>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// the passphrase or key file contents from the config file. A key file takes priority over a passphrase
+#[cfg(not(test))]
+static SECRET: Lazy<Option<Vec<u8>>> = Lazy::new(|| {
+    let config = &crate::config::FILE_SERVER_CONFIG.encryption;
+    if let Some(key_file) = &config.key_file {
+        match fs::read(key_file) {
+            Ok(contents) if !contents.is_empty() => Some(contents),
+            Ok(_) => panic!("Encryption key file {key_file} is empty"),
+            Err(e) => panic!("Failed to read encryption key file {key_file}: {e:?}"),
+        }
+    } else {
+        config
+            .passphrase
+            .as_ref()
+            .filter(|it| !it.is_empty())
+            .map(|it| it.as_bytes().to_vec())
+    }
+});
+
+#[cfg(not(test))]
+fn secret() -> Option<Vec<u8>> {
+    SECRET.clone()
+}
+
+/// whether newly written files should be encrypted.
+///
+/// Files that are already encrypted can still be read as long as a passphrase or key file is configured, even if this returns false
+#[cfg(not(test))]
+pub fn is_enabled() -> bool {
+    crate::config::FILE_SERVER_CONFIG.encryption.enabled && SECRET.is_some()
+}
+
+/// makes sure that a passphrase or key file is configured if encryption is enabled.
+///
+/// panics:
+/// panics if encryption is enabled without a secret, or if the key file can't be read
+#[cfg(not(test))]
+pub fn check_config() {
+    if crate::config::FILE_SERVER_CONFIG.encryption.enabled && SECRET.is_none() {
+        panic!("Encryption is enabled, but neither a passphrase nor a key file is configured");
+    }
+}
+
+#[cfg(test)]
+thread_local! {
+    static TEST_SECRET: std::cell::RefCell<Option<Vec<u8>>> = const { std::cell::RefCell::new(None) };
+}
+
+#[cfg(test)]
+fn secret() -> Option<Vec<u8>> {
+    TEST_SECRET.with_borrow(|it| it.clone())
+}
+
+#[cfg(test)]
+pub fn is_enabled() -> bool {
+    secret().is_some()
+}
+
+/// sets the passphrase for the current test. Encryption is enabled for as long as a passphrase is set
+#[cfg(test)]
+pub fn set_test_secret(secret: Option<&str>) {
+    TEST_SECRET.set(secret.map(|it| it.as_bytes().to_vec()));
+}
+
+#[cfg(not(test))]
+fn argon2_params() -> Params {
+    Params::default()
+}
+
+/// the default params make each test take several seconds in debug builds
+#[cfg(test)]
+fn argon2_params() -> Params {
+    Params::new(Params::MIN_M_COST, 1, 1, None).unwrap()
+}
+
+/// checks if the file at the passed path starts with the encrypted file header
+pub fn is_encrypted(path: &Path) -> std::io::Result<bool> {
+    let mut file = File::open(path)?;
+    let header = read_chunk(&mut file, MAGIC.len())?;
+    Ok(header == MAGIC)
+}
+
+/// encrypts the file at the passed path, replacing its contents.
+///
+/// Returns `false` without touching the file if it is already encrypted
+pub fn encrypt_file_in_place(path: &Path) -> Result<bool, EncryptionError> {
+    if is_encrypted(path)? {
+        return Ok(false);
+    }
+    convert_in_place(path, encrypt_stream)?;
+    Ok(true)
+}
+
+/// decrypts the file at the passed path, replacing its contents.
+///
+/// Returns `false` without touching the file if it isn't encrypted
+fn decrypt_file_in_place(path: &Path) -> Result<bool, EncryptionError> {
+    if !is_encrypted(path)? {
+        return Ok(false);
+    }
+    convert_in_place(path, decrypt_stream)?;
+    Ok(true)
+}
+
+/// decrypts the file at `source`, writing its plain contents to `destination`
+pub fn decrypt_file(source: &Path, destination: &Path) -> Result<(), EncryptionError> {
+    write_converted(source, destination, decrypt_stream)
+}
+
+/// encrypts the passed file contents
+pub fn encrypt_contents(contents: &[u8]) -> Result<Vec<u8>, EncryptionError> {
+    let mut encrypted = Vec::with_capacity(contents.len() + HEADER_LEN + TAG_LEN);
+    encrypt_stream(&mut &contents[..], &mut encrypted)?;
+    Ok(encrypted)
+}
+
+/// decrypts the passed file contents. Contents that aren't encrypted are returned as-is
+pub fn decrypt_contents(contents: Vec<u8>) -> Result<Vec<u8>, EncryptionError> {
+    if !contents.starts_with(MAGIC) {
+        return Ok(contents);
+    }
+    let mut decrypted = Vec::with_capacity(contents.len());
+    decrypt_stream(&mut contents.as_slice(), &mut decrypted)?;
+    Ok(decrypted)
+}
+
+/// returns the size of the plain contents of the file at the passed path, without decrypting it
+pub fn plaintext_size(path: &Path) -> std::io::Result<u64> {
+    let len = fs::metadata(path)?.len();
+    if !is_encrypted(path)? {
+        return Ok(len);
+    }
+    let body = len.saturating_sub(HEADER_LEN as u64);
+    let chunks = body.div_ceil((CHUNK_LEN + TAG_LEN) as u64).max(1);
+    Ok(body.saturating_sub(chunks * TAG_LEN as u64))
+}
+
+/// encrypts (or decrypts, if `encrypt` is false) every stored file and preview that isn't already in the desired state.
+/// Files already in the desired state are skipped, so an interrupted migration can safely be run again.
+///
+/// This must not be run while the server is running.
+///
+/// Returns the number of files that were converted
+pub fn migrate_store(encrypt: bool) -> Result<usize, StorageError> {
+    if secret().is_none() {
+        return Err(EncryptionError::NotConfigured.into());
+    }
+    let mut converted = 0;
+    for entry in storage::backend().list("")? {
+        if entry.is_dir {
+            continue;
+        }
+        match migrate_stored_file(&entry.path, encrypt) {
+            Ok(true) => {
+                log::info!("Migrated {}", entry.path);
+                converted += 1;
+            }
+            Ok(false) => {}
+            Err(e) => {
+                log::error!(
+                    "Failed to migrate {}. Nested exception is {e:?}\n{}",
+                    entry.path,
+                    Backtrace::force_capture()
+                );
+                return Err(e);
+            }
+        }
+    }
+    // previews are always kept on the local disk
+    let preview_dir = PathBuf::from(preview_dir());
+    if preview_dir.exists() {
+        converted += migrate_dir(&preview_dir, encrypt)?;
+    }
+    Ok(converted)
+}
+
+// ==== private functions ==== \
+
+/// converts the file stored at `path`, returning `false` if it was already in the desired state
+fn migrate_stored_file(path: &str, encrypt: bool) -> Result<bool, StorageError> {
+    let fetched = storage::backend().fetch(path)?;
+    if is_encrypted(fetched.path())? == encrypt {
+        return Ok(false);
+    }
+    let converted = LocalFile::temporary(None)?;
+    if encrypt {
+        write_converted(fetched.path(), converted.path(), encrypt_stream)?;
+    } else {
+        write_converted(fetched.path(), converted.path(), decrypt_stream)?;
+    }
+    storage::backend().store(converted.path(), path)?;
+    Ok(true)
+}
+
+fn migrate_dir(dir: &Path, encrypt: bool) -> Result<usize, EncryptionError> {
+    let mut converted = 0;
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            converted += migrate_dir(&path, encrypt)?;
+            continue;
+        }
+        let res = if encrypt {
+            encrypt_file_in_place(&path)
+        } else {
+            decrypt_file_in_place(&path)
+        };
+        match res {
+            Ok(true) => {
+                log::info!("Migrated {}", path.display());
+                converted += 1;
+            }
+            Ok(false) => {}
+            Err(e) => {
+                log::error!(
+                    "Failed to migrate {}. Nested exception is {e:?}\n{}",
+                    path.display(),
+                    Backtrace::force_capture()
+                );
+                return Err(e);
+            }
+        }
+    }
+    Ok(converted)
+}
+
+/// derives the key for the passed salt from the configured secret
+fn derive_key(salt: &[u8; SALT_LEN]) -> Result<[u8; 32], EncryptionError> {
+    let secret = secret().ok_or(EncryptionError::NotConfigured)?;
+    let cache_key = (Sha256::digest(&secret).into(), *salt);
+    let mut cache = KEY_CACHE.lock().unwrap();
+    if let Some(key) = cache.get(&cache_key) {
+        return Ok(*key);
+    }
+    let mut key = [0; 32];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, argon2_params())
+        .hash_password_into(&secret, salt, &mut key)
+        .map_err(|e| {
+            log::error!(
+                "Failed to derive encryption key. Nested exception is {e:?}\n{}",
+                Backtrace::force_capture()
+            );
+            EncryptionError::KeyDerivation
+        })?;
+    cache.insert(cache_key, key);
+    Ok(key)
+}
+
+/// writes the header followed by the encrypted contents of `reader` into `writer`
+fn encrypt_stream(reader: &mut impl Read, writer: &mut impl Write) -> Result<(), EncryptionError> {
+    let salt = *WRITE_SALT;
+    let key = derive_key(&salt)?;
+    let mut nonce = [0; NONCE_LEN];
+    OsRng.fill_bytes(&mut nonce);
+    writer.write_all(MAGIC)?;
+    writer.write_all(&salt)?;
+    writer.write_all(&nonce)?;
+    let cipher = XChaCha20Poly1305::new(&key.into());
+    let mut encryptor: EncryptorBE32<XChaCha20Poly1305> =
+        EncryptorBE32::from_aead(cipher, &nonce.into());
+    let mut current = read_chunk(reader, CHUNK_LEN)?;
+    loop {
+        // the last chunk is flagged so that truncated files fail to decrypt
+        let next = if current.len() == CHUNK_LEN {
+            read_chunk(reader, CHUNK_LEN)?
+        } else {
+            Vec::new()
+        };
+        if next.is_empty() {
+            let encrypted = encryptor
+                .encrypt_last(current.as_slice())
+                .map_err(|_| EncryptionError::Crypto)?;
+            writer.write_all(&encrypted)?;
+            return Ok(());
+        }
+        let encrypted = encryptor
+            .encrypt_next(current.as_slice())
+            .map_err(|_| EncryptionError::Crypto)?;
+        writer.write_all(&encrypted)?;
+        current = next;
+    }
+}
+
+/// reads the header and encrypted contents from `reader`, and writes the decrypted contents into `writer`
+fn decrypt_stream(reader: &mut impl Read, writer: &mut impl Write) -> Result<(), EncryptionError> {
+    let mut reader = DecryptReader::new(reader)?;
+    while let Some(decrypted) = reader.next_chunk()? {
+        writer.write_all(&decrypted)?;
+    }
+    Ok(())
+}
+
+/// decrypts an encrypted file one chunk at a time as it's read, so that its plain contents never have to be held in memory or written to the disk all at once
+pub struct DecryptReader<R: Read> {
+    reader: R,
+    /// `None` once the last chunk has been decrypted
+    decryptor: Option<DecryptorBE32<XChaCha20Poly1305>>,
+    /// the next encrypted chunk
+    current: Vec<u8>,
+    /// the decrypted chunk being read, and how much of it has been read already
+    decrypted: Vec<u8>,
+    position: usize,
+}
+
+impl<R: Read> DecryptReader<R> {
+    /// reads the header from the passed reader, failing if it isn't encrypted or the key can't be derived
+    pub fn new(mut reader: R) -> Result<Self, EncryptionError> {
+        let header = read_chunk(&mut reader, HEADER_LEN)?;
+        if header.len() != HEADER_LEN || !header.starts_with(MAGIC) {
+            return Err(EncryptionError::Crypto);
+        }
+        let (salt, nonce) = header[MAGIC.len()..].split_at(SALT_LEN);
+        let key = derive_key(salt.try_into().unwrap())?;
+        let nonce: [u8; NONCE_LEN] = nonce.try_into().unwrap();
+        let cipher = XChaCha20Poly1305::new(&key.into());
+        let decryptor: DecryptorBE32<XChaCha20Poly1305> =
+            DecryptorBE32::from_aead(cipher, &nonce.into());
+        let current = read_chunk(&mut reader, CHUNK_LEN + TAG_LEN)?;
+        Ok(Self {
+            reader,
+            decryptor: Some(decryptor),
+            current,
+            decrypted: Vec::new(),
+            position: 0,
+        })
+    }
+
+    /// decrypts the next chunk, returning `None` once every chunk has been decrypted
+    fn next_chunk(&mut self) -> Result<Option<Vec<u8>>, EncryptionError> {
+        let Some(mut decryptor) = self.decryptor.take() else {
+            return Ok(None);
+        };
+        let next = if self.current.len() == CHUNK_LEN + TAG_LEN {
+            read_chunk(&mut self.reader, CHUNK_LEN + TAG_LEN)?
+        } else {
+            Vec::new()
+        };
+        let current = std::mem::replace(&mut self.current, next);
+        if self.current.is_empty() {
+            let decrypted = decryptor
+                .decrypt_last(current.as_slice())
+                .map_err(|_| EncryptionError::Crypto)?;
+            return Ok(Some(decrypted));
+        }
+        let decrypted = decryptor
+            .decrypt_next(current.as_slice())
+            .map_err(|_| EncryptionError::Crypto)?;
+        self.decryptor = Some(decryptor);
+        Ok(Some(decrypted))
+    }
+}
+
+impl<R: Read> Read for DecryptReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        while self.position == self.decrypted.len() {
+            match self.next_chunk() {
+                Ok(Some(decrypted)) => {
+                    self.decrypted = decrypted;
+                    self.position = 0;
+                }
+                Ok(None) => return Ok(0),
+                Err(EncryptionError::Io(e)) => return Err(e),
+                Err(e) => {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!("{e:?}"),
+                    ));
+                }
+            }
+        }
+        let len = buf.len().min(self.decrypted.len() - self.position);
+        buf[..len].copy_from_slice(&self.decrypted[self.position..self.position + len]);
+        self.position += len;
+        Ok(len)
+    }
+}
+
+/// reads up to `len` bytes from `reader`. Fewer bytes are only returned if the end of the reader was reached
+fn read_chunk(reader: &mut impl Read, len: usize) -> std::io::Result<Vec<u8>> {
+    let mut chunk = Vec::with_capacity(len);
+    reader.by_ref().take(len as u64).read_to_end(&mut chunk)?;
+    Ok(chunk)
+}
+
+/// runs `convert` on the contents of `source`, writing the output to `destination`
+fn write_converted<F>(source: &Path, destination: &Path, convert: F) -> Result<(), EncryptionError>
+where
+    F: FnOnce(&mut BufReader<File>, &mut BufWriter<File>) -> Result<(), EncryptionError>,
+{
+    let mut input = BufReader::new(File::open(source)?);
+    let mut output = BufWriter::new(File::create(destination)?);
+    let res = convert(&mut input, &mut output).and_then(|_| {
+        let file = output.into_inner().map_err(|e| e.into_error())?;
+        Ok(file.sync_all()?)
+    });
+    if res.is_err() {
+        fs::remove_file(destination).unwrap_or(());
+    }
+    res
+}
+
+/// replaces the contents of the file at `path` with the output of `convert`.
+/// The output is written next to the file first, so the original is never left half-converted
+fn convert_in_place<F>(path: &Path, convert: F) -> Result<(), EncryptionError>
+where
+    F: FnOnce(&mut BufReader<File>, &mut BufWriter<File>) -> Result<(), EncryptionError>,
+{
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    let working_path = path.with_file_name(format!(".{file_name}.{}.tmp", nanoid::nanoid!()));
+    write_converted(path, &working_path, convert)?;
+    if let Err(e) = fs::rename(&working_path, path) {
+        fs::remove_file(&working_path).unwrap_or(());
+        return Err(e.into());
+    }
+    Ok(())
+}
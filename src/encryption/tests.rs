@@ -0,0 +1,209 @@
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use rocket::futures::StreamExt;
+
+use crate::encryption::models::EncryptionError;
+use crate::encryption::{
+    DecryptReader, decrypt_contents, decrypt_file, encrypt_contents, encrypt_file_in_place,
+    is_encrypted, migrate_store, plaintext_size, set_test_secret,
+};
+use crate::service::file_service::file_dir;
+use crate::storage;
+use crate::storage::models::StorageError;
+use crate::test::{cleanup, create_file_disk, create_folder_disk};
+
+fn disk_path(name: &str) -> PathBuf {
+    PathBuf::from(file_dir()).join(name)
+}
+
+fn read(path: &Path) -> Result<Vec<u8>, EncryptionError> {
+    decrypt_contents(fs::read(path).unwrap())
+}
+
+/// contents spanning several encryption chunks, with a partial chunk at the end
+fn large_contents() -> Vec<u8> {
+    (0..200_000u32).map(|it| (it % 251) as u8).collect()
+}
+
+#[test]
+fn encrypted_files_can_be_read_back() {
+    set_test_secret(Some("passphrase"));
+    fs::create_dir_all(file_dir()).unwrap();
+    let path = disk_path("large.bin");
+    let contents = large_contents();
+    fs::write(&path, &contents).unwrap();
+    assert!(encrypt_file_in_place(&path).unwrap());
+    assert!(is_encrypted(&path).unwrap());
+    assert_ne!(contents, fs::read(&path).unwrap());
+    assert_eq!(contents, read(&path).unwrap());
+    assert_eq!(contents.len() as u64, plaintext_size(&path).unwrap());
+    let decrypted_path = disk_path("decrypted.bin");
+    decrypt_file(&path, &decrypted_path).unwrap();
+    assert_eq!(contents, fs::read(&decrypted_path).unwrap());
+    cleanup();
+}
+
+#[test]
+fn encrypting_twice_does_nothing() {
+    set_test_secret(Some("passphrase"));
+    create_file_disk("test.txt", "hello");
+    let path = disk_path("test.txt");
+    assert!(encrypt_file_in_place(&path).unwrap());
+    let encrypted = fs::read(&path).unwrap();
+    assert!(!encrypt_file_in_place(&path).unwrap());
+    assert_eq!(encrypted, fs::read(&path).unwrap());
+    cleanup();
+}
+
+#[test]
+fn encrypt_contents_handles_chunk_boundaries() {
+    set_test_secret(Some("passphrase"));
+    fs::create_dir_all(file_dir()).unwrap();
+    let path = disk_path("boundary.bin");
+    for len in [0, 1, 64 * 1024, 64 * 1024 + 1, 128 * 1024] {
+        let contents = vec![7u8; len];
+        fs::write(&path, encrypt_contents(&contents).unwrap()).unwrap();
+        assert!(is_encrypted(&path).unwrap());
+        assert_eq!(contents, read(&path).unwrap(), "length {len}");
+        assert_eq!(len as u64, plaintext_size(&path).unwrap(), "length {len}");
+    }
+    cleanup();
+}
+
+#[test]
+fn decrypt_reader_reads_across_chunks() {
+    set_test_secret(Some("passphrase"));
+    let contents = large_contents();
+    let encrypted = encrypt_contents(&contents).unwrap();
+    let mut reader = DecryptReader::new(encrypted.as_slice()).unwrap();
+    // reads that don't line up with the encryption chunks still return everything in order
+    let mut decrypted = Vec::new();
+    let mut buf = [0; 1000];
+    loop {
+        let len = reader.read(&mut buf).unwrap();
+        if len == 0 {
+            break;
+        }
+        decrypted.extend_from_slice(&buf[..len]);
+    }
+    assert_eq!(contents, decrypted);
+    // tampered contents fail part of the way through instead of returning the wrong bytes
+    let mut tampered = encrypted.clone();
+    let last = tampered.len() - 1;
+    tampered[last] ^= 1;
+    let mut reader = DecryptReader::new(tampered.as_slice()).unwrap();
+    let res = reader.read_to_end(&mut Vec::new());
+    assert_eq!(std::io::ErrorKind::InvalidData, res.unwrap_err().kind());
+}
+
+#[test]
+fn decrypt_contents_passes_through_plain_contents() {
+    assert_eq!(
+        b"hello".to_vec(),
+        decrypt_contents(b"hello".to_vec()).unwrap()
+    );
+}
+
+#[test]
+fn wrong_passphrase_fails_to_decrypt() {
+    set_test_secret(Some("passphrase"));
+    create_file_disk("test.txt", "hello");
+    let path = disk_path("test.txt");
+    encrypt_file_in_place(&path).unwrap();
+    set_test_secret(Some("not the passphrase"));
+    let res = read(&path);
+    assert!(matches!(res, Err(EncryptionError::Crypto)));
+    cleanup();
+}
+
+#[test]
+fn tampered_files_fail_to_decrypt() {
+    set_test_secret(Some("passphrase"));
+    fs::create_dir_all(file_dir()).unwrap();
+    let path = disk_path("large.bin");
+    fs::write(&path, encrypt_contents(&large_contents()).unwrap()).unwrap();
+    let mut encrypted = fs::read(&path).unwrap();
+    let last = encrypted.len() - 1;
+    encrypted[last] ^= 1;
+    fs::write(&path, &encrypted).unwrap();
+    assert!(matches!(read(&path), Err(EncryptionError::Crypto)));
+    // dropping the last chunk entirely must also be detected
+    encrypted.truncate(encrypted.len() - 100);
+    fs::write(&path, &encrypted).unwrap();
+    assert!(matches!(read(&path), Err(EncryptionError::Crypto)));
+    cleanup();
+}
+
+#[rocket::async_test]
+async fn streaming_tampered_files_fails() {
+    set_test_secret(Some("passphrase"));
+    fs::create_dir_all(file_dir()).unwrap();
+    let path = disk_path("large.bin");
+    let contents = large_contents();
+    let encrypted = encrypt_contents(&contents).unwrap();
+    // a bad first chunk fails before anything is streamed, so that a proper error response can be sent instead
+    let mut tampered = encrypted.clone();
+    tampered[100] ^= 1;
+    fs::write(&path, &tampered).unwrap();
+    let opened = storage::open("large.bin").unwrap();
+    assert!(matches!(
+        storage::stream_contents(opened),
+        Err(StorageError::Io(_))
+    ));
+    // a bad later chunk is streamed as an error after the chunks before it
+    let mut tampered = encrypted;
+    let last = tampered.len() - 1;
+    tampered[last] ^= 1;
+    fs::write(&path, &tampered).unwrap();
+    let stream = storage::stream_contents(storage::open("large.bin").unwrap()).unwrap();
+    assert_eq!(Some(contents.len() as u64), stream.size);
+    let chunks: Vec<std::io::Result<Vec<u8>>> = stream.chunks.collect().await;
+    assert!(chunks.last().unwrap().is_err());
+    assert!(chunks[..chunks.len() - 1].iter().all(|it| it.is_ok()));
+    cleanup();
+}
+
+#[test]
+fn encrypted_files_need_a_passphrase() {
+    set_test_secret(Some("passphrase"));
+    create_file_disk("test.txt", "hello");
+    let path = disk_path("test.txt");
+    encrypt_file_in_place(&path).unwrap();
+    set_test_secret(None);
+    assert!(matches!(read(&path), Err(EncryptionError::NotConfigured)));
+    cleanup();
+}
+
+#[test]
+fn migrate_store_converts_nested_files() {
+    set_test_secret(Some("passphrase"));
+    create_folder_disk("top/bottom");
+    create_file_disk("root.txt", "root");
+    create_file_disk("top/bottom/nested.txt", "nested");
+    assert_eq!(2, migrate_store(true).unwrap());
+    assert!(is_encrypted(&disk_path("root.txt")).unwrap());
+    assert!(is_encrypted(&disk_path("top/bottom/nested.txt")).unwrap());
+    // already encrypted files are skipped
+    assert_eq!(0, migrate_store(true).unwrap());
+    assert_eq!(2, migrate_store(false).unwrap());
+    assert_eq!("root", fs::read_to_string(disk_path("root.txt")).unwrap());
+    assert_eq!(
+        "nested",
+        fs::read_to_string(disk_path("top/bottom/nested.txt")).unwrap()
+    );
+    // no leftover working files
+    assert_eq!(2, fs::read_dir(file_dir()).unwrap().count());
+    cleanup();
+}
+
+#[test]
+fn migrate_store_requires_a_passphrase() {
+    create_file_disk("test.txt", "hello");
+    assert!(matches!(
+        migrate_store(true),
+        Err(StorageError::Encryption(EncryptionError::NotConfigured))
+    ));
+    cleanup();
+}
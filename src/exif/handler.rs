@@ -3,10 +3,13 @@ use std::{
     time::Instant,
 };
 
-use rocket::{State, http::Status};
+use rocket::{State, http::Status, serde::json::Json};
 
+use super::{GetMapClustersError, GetMapClustersResponse};
 use crate::{
-    guard::HeaderAuth, model::guard::auth::ValidateResult, util::update_last_request_time,
+    guard::HeaderAuth,
+    model::{guard::auth::ValidateResult, response::BasicMessage},
+    util::update_last_request_time,
 };
 
 #[get("/regen")]
@@ -24,3 +27,30 @@ pub fn regenerate_exif(auth: HeaderAuth, last_request_time: &State<Arc<Mutex<Ins
 
     Status::Accepted
 }
+
+/// groups files with gps coordinates into clusters for a map view.
+///
+/// `zoom` is from 0 (the whole world) to 20, and `bounds` limits the clusters to an area in the form `<south>,<west>,<north>,<east>`
+#[get("/map?<zoom>&<bounds>")]
+pub fn get_map_clusters(
+    zoom: Option<u8>,
+    bounds: Option<&str>,
+    auth: HeaderAuth,
+    last_request_time: &State<Arc<Mutex<Instant>>>,
+) -> GetMapClustersResponse {
+    match auth.validate() {
+        ValidateResult::Ok => { /*no op*/ }
+        ValidateResult::NoPasswordSet => return GetMapClustersResponse::Unauthorized("No password has been set. You can set a username and password by making a POST to `/api/password`".to_string()),
+        ValidateResult::Invalid => return GetMapClustersResponse::Unauthorized("Bad Credentials".to_string())
+    };
+    update_last_request_time(last_request_time);
+    match super::service::get_map_clusters(zoom.unwrap_or_default(), bounds) {
+        Ok(clusters) => GetMapClustersResponse::Success(Json::from(clusters)),
+        Err(GetMapClustersError::BadRequest(e)) => {
+            GetMapClustersResponse::BadRequest(BasicMessage::new(&e))
+        }
+        Err(GetMapClustersError::DbError) => GetMapClustersResponse::GenericError(
+            BasicMessage::new("Failed to retrieve map points. Check server logs for details"),
+        ),
+    }
+}
@@ -1,10 +1,13 @@
 pub mod handler;
+pub mod models;
 pub mod repository;
 pub mod service;
 
 #[cfg(test)]
 mod tests;
 
+pub use models::*;
+
 #[cfg(test)]
 pub use service::process_single_file_exif;
 
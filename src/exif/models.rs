@@ -0,0 +1,148 @@
+use std::hash::{Hash, Hasher};
+
+use chrono::NaiveDateTime;
+use rocket::serde::json::Json;
+use rocket::serde::{Deserialize, Serialize};
+
+use crate::model::response::BasicMessage;
+
+/// the details pulled from the EXIF data of an image, or the track info of a video.
+/// Any field can be missing, since cameras and editing software don't all write the same tags
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ExifData {
+    /// when the photo or video was taken
+    pub date_taken: Option<NaiveDateTime>,
+    pub camera_make: Option<String>,
+    pub camera_model: Option<String>,
+    pub lens: Option<String>,
+    pub iso: Option<u32>,
+    /// in millimeters
+    pub focal_length: Option<f64>,
+    /// in pixels
+    pub width: Option<u32>,
+    /// in pixels
+    pub height: Option<u32>,
+    /// the EXIF orientation value, from 1 to 8
+    pub orientation: Option<u32>,
+    /// where the photo or video was taken
+    pub location: Option<FileLocation>,
+}
+
+impl ExifData {
+    /// whether there's anything to store besides the date taken, which is stored as the file's create date
+    pub fn has_details(&self) -> bool {
+        self.camera_make.is_some()
+            || self.camera_model.is_some()
+            || self.lens.is_some()
+            || self.iso.is_some()
+            || self.focal_length.is_some()
+            || self.width.is_some()
+            || self.height.is_some()
+            || self.orientation.is_some()
+            || self.location.is_some()
+    }
+}
+
+/// gps coordinates of where a file was taken, in decimal degrees
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(crate = "rocket::serde")]
+pub struct FileLocation {
+    /// positive is north
+    pub latitude: f64,
+    /// positive is east
+    pub longitude: f64,
+    /// in meters above sea level
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub altitude: Option<f64>,
+}
+
+// coordinates are never NaN, so they can be compared and hashed by their bits. This lets FileApi stay hashable
+impl Eq for FileLocation {}
+
+impl Hash for FileLocation {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.latitude.to_bits().hash(state);
+        self.longitude.to_bits().hash(state);
+        self.altitude.map(f64::to_bits).hash(state);
+    }
+}
+
+/// the area of a map to retrieve points for, in decimal degrees
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MapBounds {
+    pub south: f64,
+    pub west: f64,
+    pub north: f64,
+    pub east: f64,
+}
+
+impl TryFrom<&str> for MapBounds {
+    type Error = String;
+
+    /// parses bounds in the form `<south>,<west>,<north>,<east>`.
+    /// West can be larger than east for areas that cross the 180th meridian
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let parts: Vec<f64> = value
+            .split(',')
+            .map(|it| it.trim().parse::<f64>())
+            .collect::<Result<Vec<f64>, _>>()
+            .map_err(|_| format!("Invalid bounds {value}. Bounds must be 4 numbers"))?;
+        let [south, west, north, east] = parts[..] else {
+            return Err(format!(
+                "Invalid bounds {value}. Bounds must be in the form south,west,north,east"
+            ));
+        };
+        let valid_latitude = |it: f64| (-90.0..=90.0).contains(&it);
+        let valid_longitude = |it: f64| (-180.0..=180.0).contains(&it);
+        if !valid_latitude(south) || !valid_latitude(north) || south > north {
+            return Err(format!(
+                "Invalid bounds {value}. Latitudes must be between -90 and 90, and south can't be above north"
+            ));
+        }
+        if !valid_longitude(west) || !valid_longitude(east) {
+            return Err(format!(
+                "Invalid bounds {value}. Longitudes must be between -180 and 180"
+            ));
+        }
+        Ok(Self {
+            south,
+            west,
+            north,
+            east,
+        })
+    }
+}
+
+/// a group of files that are close to each other on a map
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(crate = "rocket::serde")]
+pub struct MapCluster {
+    /// the average latitude of the files in the cluster
+    pub latitude: f64,
+    /// the average longitude of the files in the cluster
+    pub longitude: f64,
+    /// how many files are in the cluster
+    pub count: u32,
+    /// one of the files in the cluster, so that a preview can be shown for it
+    #[serde(rename = "fileId")]
+    pub file_id: u32,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum GetMapClustersError {
+    /// zoom or bounds were invalid
+    BadRequest(String),
+    DbError,
+}
+
+#[derive(Responder, Debug)]
+pub enum GetMapClustersResponse {
+    #[response(status = 200)]
+    Success(Json<Vec<MapCluster>>),
+    #[response(status = 400, content_type = "json")]
+    BadRequest(Json<BasicMessage>),
+    #[response(status = 401)]
+    Unauthorized(String),
+    #[response(status = 500, content_type = "json")]
+    GenericError(Json<BasicMessage>),
+}
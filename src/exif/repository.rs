@@ -2,6 +2,12 @@ use chrono::NaiveDateTime;
 use rusqlite::Connection;
 use std::backtrace::Backtrace;
 
+use std::collections::HashMap;
+
+use itertools::Itertools;
+
+use super::{ExifData, FileLocation, MapBounds, MapCluster};
+
 /// Updates the creation date of a file in the database.
 ///
 /// ## Parameters
@@ -35,6 +41,139 @@ pub fn update_file_create_date(
     }
 }
 
+/// Stores the camera details of a file, replacing any that were stored before.
+///
+/// ## Parameters
+/// * `file_id` - The ID of the file the details belong to
+/// * `exif` - The details to store. The date taken is not stored here, since it's the file's creation date
+/// * `con` - The database connection
+pub fn save_file_exif(
+    file_id: u32,
+    exif: &ExifData,
+    con: &Connection,
+) -> Result<(), rusqlite::Error> {
+    let mut stmt = con.prepare(include_str!("../assets/queries/exif/save_file_exif.sql"))?;
+    let res = stmt.execute(rusqlite::params![
+        file_id,
+        exif.camera_make,
+        exif.camera_model,
+        exif.lens,
+        exif.iso,
+        exif.focal_length,
+        exif.width,
+        exif.height,
+        exif.orientation,
+        exif.location.map(|it| it.latitude),
+        exif.location.map(|it| it.longitude),
+        exif.location.and_then(|it| it.altitude)
+    ]);
+    if let Err(e) = res {
+        log::error!(
+            "Failed to save EXIF data for file id {file_id}: {e:?}\n{}",
+            Backtrace::force_capture()
+        );
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// Retrieves the gps coordinates of the passed files. Files without coordinates won't be in the returned map
+pub fn get_locations_for_files(
+    file_ids: &[u32],
+    con: &Connection,
+) -> Result<HashMap<u32, FileLocation>, rusqlite::Error> {
+    let in_clause = file_ids.iter().map(|it| format!("'{it}'")).join(",");
+    let query = format!(
+        include_str!("../assets/queries/exif/get_locations_for_files.sql"),
+        in_clause
+    );
+    let mut stmt = con.prepare(query.as_str())?;
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get(0)?,
+            FileLocation {
+                latitude: row.get(1)?,
+                longitude: row.get(2)?,
+                altitude: row.get(3)?,
+            },
+        ))
+    })?;
+    rows.collect()
+}
+
+/// Groups every file with gps coordinates into a grid, returning one point per grid cell with files in it.
+///
+/// ## Parameters
+/// * `cell_size` - How wide each grid cell is, in degrees
+/// * `bounds` - If passed, only files inside the bounds are included
+/// * `con` - The database connection
+pub fn get_map_clusters(
+    cell_size: f64,
+    bounds: Option<MapBounds>,
+    con: &Connection,
+) -> Result<Vec<MapCluster>, rusqlite::Error> {
+    let bounds_clause = bounds
+        .map(location_bounds_clause)
+        .unwrap_or("1".to_string());
+    let query = format!(
+        include_str!("../assets/queries/exif/get_map_clusters.sql"),
+        bounds_clause
+    );
+    let mut stmt = con.prepare(query.as_str())?;
+    let rows = stmt.query_map([cell_size], |row| {
+        Ok(MapCluster {
+            latitude: row.get(0)?,
+            longitude: row.get(1)?,
+            count: row.get(2)?,
+            file_id: row.get(3)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// creates a sql condition on the `latitude` and `longitude` columns of FileExif that matches coordinates inside the `bounds`
+pub fn location_bounds_clause(bounds: MapBounds) -> String {
+    // bounds are validated numbers, so they're safe to put directly in the query
+    let MapBounds {
+        south,
+        west,
+        north,
+        east,
+    } = bounds;
+    if west <= east {
+        format!("latitude between {south} and {north} and longitude between {west} and {east}")
+    } else {
+        // the bounds cross the 180th meridian
+        format!(
+            "latitude between {south} and {north} and (longitude >= {west} or longitude <= {east})"
+        )
+    }
+}
+
+/// creates a sql condition on the `latitude` and `longitude` columns of FileExif that matches coordinates within `kilometers`
+/// of the passed point, or further away than that if `outside` is true.
+///
+/// Distances are approximated by treating the area around the point as flat, which is close enough for the distances a photo search uses.
+/// Longitudes are compared the short way around, so points on the other side of the 180th meridian are still nearby
+pub fn location_radius_clause(
+    latitude: f64,
+    longitude: f64,
+    kilometers: f64,
+    outside: bool,
+) -> String {
+    const KILOMETERS_PER_DEGREE: f64 = 111.32;
+    // degrees of longitude get shorter the further they are from the equator
+    let longitude_scale = latitude.to_radians().cos().powi(2);
+    let max_distance = (kilometers / KILOMETERS_PER_DEGREE).powi(2);
+    let op = if outside { ">" } else { "<=" };
+    // the gap between 179.9 and -179.9 is 0.2 degrees, not 359.8
+    let longitude_distance =
+        format!("min(abs(longitude - {longitude}), 360 - abs(longitude - {longitude}))");
+    format!(
+        "latitude is not null and longitude is not null and ((latitude - {latitude}) * (latitude - {latitude}) + {longitude_distance} * {longitude_distance} * {longitude_scale}) {op} {max_distance}"
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -57,6 +196,8 @@ mod tests {
             create_date: old_date,
             size: 100,
             file_type: FileTypes::Image,
+            upload_date: None,
+            modified_date: None,
         }
         .save_to_db();
 
@@ -97,4 +238,170 @@ mod tests {
 
         cleanup();
     }
+
+    #[test]
+    fn save_file_exif_replaces_existing_data() {
+        init_db_folder();
+        crate::test::create_file_db_entry("test.png", None);
+        let con = open_connection();
+        let first = ExifData {
+            camera_model: Some("Pixel 7".to_string()),
+            iso: Some(100),
+            ..Default::default()
+        };
+        let second = ExifData {
+            camera_model: Some("Pixel 8".to_string()),
+            width: Some(4000),
+            ..Default::default()
+        };
+        save_file_exif(1, &first, &con).unwrap();
+        save_file_exif(1, &second, &con).unwrap();
+
+        let stored: (String, Option<u32>, Option<u32>) = con
+            .query_row(
+                "select cameraModel, iso, width from FileExif where fileId = 1",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        con.close().unwrap();
+
+        assert_eq!(("Pixel 8".to_string(), None, Some(4000)), stored);
+        cleanup();
+    }
+
+    #[test]
+    fn deleting_file_deletes_exif_data() {
+        init_db_folder();
+        crate::test::create_file_db_entry("test.png", None);
+        let con = open_connection();
+        let exif = ExifData {
+            iso: Some(100),
+            ..Default::default()
+        };
+        save_file_exif(1, &exif, &con).unwrap();
+        crate::repository::file_repository::delete_file(1, &con).unwrap();
+
+        let count: u32 = con
+            .query_row("select count(*) from FileExif", [], |row| row.get(0))
+            .unwrap();
+        con.close().unwrap();
+
+        assert_eq!(0, count);
+        cleanup();
+    }
+
+    fn save_location(file_id: u32, latitude: f64, longitude: f64, con: &Connection) {
+        let exif = ExifData {
+            location: Some(FileLocation {
+                latitude,
+                longitude,
+                altitude: None,
+            }),
+            ..Default::default()
+        };
+        save_file_exif(file_id, &exif, con).unwrap();
+    }
+
+    #[test]
+    fn get_locations_for_files_skips_files_without_location() {
+        init_db_folder();
+        crate::test::create_file_db_entry("a.png", None);
+        crate::test::create_file_db_entry("b.png", None);
+        crate::test::create_file_db_entry("c.png", None);
+        let con = open_connection();
+        save_location(1, 40.5, -74.25, &con);
+        let no_location = ExifData {
+            iso: Some(100),
+            ..Default::default()
+        };
+        save_file_exif(2, &no_location, &con).unwrap();
+
+        let res = get_locations_for_files(&[1, 2, 3], &con).unwrap();
+        con.close().unwrap();
+
+        assert_eq!(1, res.len());
+        assert_eq!(
+            Some(&FileLocation {
+                latitude: 40.5,
+                longitude: -74.25,
+                altitude: None
+            }),
+            res.get(&1)
+        );
+        cleanup();
+    }
+
+    #[test]
+    fn get_map_clusters_groups_nearby_files() {
+        init_db_folder();
+        crate::test::create_file_db_entry("a.png", None);
+        crate::test::create_file_db_entry("b.png", None);
+        crate::test::create_file_db_entry("c.png", None);
+        let con = open_connection();
+        save_location(1, 10.1, 10.1, &con);
+        save_location(2, 10.3, 10.3, &con);
+        save_location(3, -30.0, 100.0, &con);
+
+        let mut res = get_map_clusters(1.0, None, &con).unwrap();
+        con.close().unwrap();
+        res.sort_by_key(|it| it.file_id);
+
+        assert_eq!(2, res.len());
+        assert_eq!((2, 1), (res[0].count, res[0].file_id));
+        assert!((res[0].latitude - 10.2).abs() < 0.0001);
+        assert_eq!((1, 3), (res[1].count, res[1].file_id));
+        cleanup();
+    }
+
+    #[test]
+    fn get_map_clusters_only_includes_files_in_bounds() {
+        init_db_folder();
+        crate::test::create_file_db_entry("a.png", None);
+        crate::test::create_file_db_entry("b.png", None);
+        let con = open_connection();
+        save_location(1, 10.0, 179.5, &con);
+        save_location(2, 10.0, 0.0, &con);
+        // crosses the 180th meridian
+        let bounds = MapBounds::try_from("0,170,20,-170").unwrap();
+
+        let res = get_map_clusters(1.0, Some(bounds), &con).unwrap();
+        con.close().unwrap();
+
+        assert_eq!(1, res.len());
+        assert_eq!(1, res[0].file_id);
+        cleanup();
+    }
+
+    #[test]
+    fn location_radius_clause_crosses_180th_meridian() {
+        init_db_folder();
+        crate::test::create_file_db_entry("a.png", None);
+        crate::test::create_file_db_entry("b.png", None);
+        crate::test::create_file_db_entry("c.png", None);
+        let con = open_connection();
+        // ~16km east of the point, across the meridian
+        save_location(1, -16.5, -179.95, &con);
+        save_location(2, -16.5, 179.85, &con);
+        save_location(3, -16.5, 170.0, &con);
+        let search = |outside: bool| -> Vec<u32> {
+            let clause = location_radius_clause(-16.5, 179.9, 20.0, outside);
+            con.prepare(&format!(
+                "select fileId from FileExif where {clause} order by fileId"
+            ))
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .map(Result::unwrap)
+            .collect()
+        };
+
+        let near = search(false);
+        let far = search(true);
+        con.close().unwrap();
+
+        assert_eq!(vec![1, 2], near);
+        assert_eq!(vec![3], far);
+        cleanup();
+    }
 }
@@ -1,21 +1,25 @@
-use crate::exif::repository::update_file_create_date;
+use crate::auto_tags::service as auto_tag_service;
+use crate::exif::repository::{self, save_file_exif, update_file_create_date};
+use crate::exif::{ExifData, FileLocation, GetMapClustersError, MapBounds, MapCluster};
 use crate::model::file_types::FileTypes;
 use crate::repository::{file_repository, open_connection};
-use crate::service::file_service::{file_dir, get_file_path};
-use chrono::NaiveDateTime;
-use nom_exif::{Exif, ExifIter, ExifTag, MediaParser, MediaSource, TrackInfo};
+use crate::service::file_service::get_file_path;
+use crate::storage;
+use nom_exif::{
+    EntryValue, Exif, ExifIter, ExifTag, GPSInfo, LatLng, MediaParser, MediaSource, TrackInfo,
+    TrackInfoTag,
+};
 use std::backtrace::Backtrace;
-use std::path::PathBuf;
 
-/// Attempts to parse EXIF data from a file and extract the original creation date.
+/// Attempts to parse the EXIF data of an image, or the track info of a video.
 ///
 /// ## Parameters
 /// * `file_path` - The path to the file to parse
 ///
 /// ## Returns
-/// * `Some(NaiveDateTime)` if EXIF data was successfully parsed and contains a creation date
-/// * `None` if EXIF parsing failed or no creation date was found
-pub fn parse_exif_date(file_path: &str) -> Option<NaiveDateTime> {
+/// * `Some(ExifData)` if the file has EXIF data or track info that could be parsed. Any of its fields can still be missing
+/// * `None` if the file has no EXIF data or parsing failed
+pub fn parse_exif(file_path: &str) -> Option<ExifData> {
     let mut parser = MediaParser::new();
     let ms = match MediaSource::file_path(file_path) {
         Ok(src) => src,
@@ -31,22 +35,100 @@ pub fn parse_exif_date(file_path: &str) -> Option<NaiveDateTime> {
             }
         };
         let exif: Exif = iter.into();
-        exif.get(ExifTag::DateTimeOriginal)
-            .and_then(|it| it.as_time())
-            .map(|it| it.naive_local())
+        Some(ExifData {
+            date_taken: exif
+                .get(ExifTag::DateTimeOriginal)
+                .and_then(|it| it.as_time())
+                .map(|it| it.naive_local()),
+            camera_make: exif_text(exif.get(ExifTag::Make)),
+            camera_model: exif_text(exif.get(ExifTag::Model)),
+            lens: exif_text(exif.get(ExifTag::LensModel)),
+            iso: exif_number(exif.get(ExifTag::ISOSpeedRatings)),
+            focal_length: exif
+                .get(ExifTag::FocalLength)
+                .and_then(|it| it.as_urational())
+                .map(|it| it.as_float())
+                .filter(|it| it.is_finite()),
+            // the exif sub-ifd has the size of the full image, but some files only have the ifd0 size
+            width: exif_number(exif.get(ExifTag::ExifImageWidth))
+                .or_else(|| exif_number(exif.get(ExifTag::ImageWidth))),
+            height: exif_number(exif.get(ExifTag::ExifImageHeight))
+                .or_else(|| exif_number(exif.get(ExifTag::ImageHeight))),
+            orientation: exif_number(exif.get(ExifTag::Orientation)),
+            location: exif
+                .get_gps_info()
+                .ok()
+                .flatten()
+                .and_then(|it| gps_location(&it)),
+        })
     } else if ms.has_track() {
         let data: TrackInfo = match parser.parse(ms) {
             Ok(td) => td,
             Err(_) => return None,
         };
-        data.get(nom_exif::TrackInfoTag::CreateDate)
-            .and_then(|it| it.as_time())
-            .map(|it| it.naive_local())
+        Some(ExifData {
+            date_taken: data
+                .get(TrackInfoTag::CreateDate)
+                .and_then(|it| it.as_time())
+                .map(|it| it.naive_local()),
+            camera_make: exif_text(data.get(TrackInfoTag::Make)),
+            camera_model: exif_text(data.get(TrackInfoTag::Model)),
+            width: exif_number(data.get(TrackInfoTag::ImageWidth)),
+            height: exif_number(data.get(TrackInfoTag::ImageHeight)),
+            location: data.get_gps_info().and_then(gps_location),
+            ..Default::default()
+        })
     } else {
         None
     }
 }
 
+/// converts gps info from degrees, minutes, and seconds into decimal degrees.
+/// Returns `None` if the coordinates are out of range, which happens when the camera writes empty values
+fn gps_location(gps: &GPSInfo) -> Option<FileLocation> {
+    let to_decimal = |it: &LatLng, negative: bool| {
+        let degrees = it.0.as_float() + it.1.as_float() / 60.0 + it.2.as_float() / 3600.0;
+        if negative { -degrees } else { degrees }
+    };
+    let latitude = to_decimal(&gps.latitude, gps.latitude_ref.eq_ignore_ascii_case(&'S'));
+    let longitude = to_decimal(&gps.longitude, gps.longitude_ref.eq_ignore_ascii_case(&'W'));
+    // NaN (from a 0 denominator) isn't contained in either range either
+    if !(-90.0..=90.0).contains(&latitude) || !(-180.0..=180.0).contains(&longitude) {
+        return None;
+    }
+    // altitude ref 1 means below sea level
+    let altitude = Some(gps.altitude.as_float())
+        .filter(|it| it.is_finite())
+        .map(|it| if gps.altitude_ref == 1 { -it } else { it });
+    Some(FileLocation {
+        latitude,
+        longitude,
+        altitude,
+    })
+}
+
+/// pulls the text out of an EXIF value, ignoring the padding some cameras add
+fn exif_text(value: Option<&EntryValue>) -> Option<String> {
+    value
+        .and_then(|it| it.as_str())
+        .map(|it| it.trim_matches(|c: char| c == '\0' || c.is_whitespace()))
+        .filter(|it| !it.is_empty())
+        .map(|it| it.to_string())
+}
+
+/// pulls a whole number out of an EXIF value, since the same tag can be stored with different sizes
+fn exif_number(value: Option<&EntryValue>) -> Option<u32> {
+    match value? {
+        EntryValue::U8(it) => Some(u32::from(*it)),
+        EntryValue::U16(it) => Some(u32::from(*it)),
+        EntryValue::U32(it) => Some(*it),
+        EntryValue::U64(it) => u32::try_from(*it).ok(),
+        EntryValue::U16Array(it) => it.first().map(|it| u32::from(*it)),
+        EntryValue::U32Array(it) => it.first().copied(),
+        _ => None,
+    }
+}
+
 /// Processes a single file to extract EXIF data and update its creation date in the database.
 ///
 /// ## Parameters
@@ -94,10 +176,7 @@ pub async fn process_single_file_exif(message_data: String) -> bool {
 
     // Get file path on disk
     let path = match get_file_path(id) {
-        Ok(p) => {
-            let path = PathBuf::from(file_dir()).join(p);
-            path.to_string_lossy().into_owned()
-        }
+        Ok(p) => p,
         Err(_) => {
             log::debug!("File id {id} not found on disk, skipping EXIF processing");
             con.close().unwrap();
@@ -106,16 +185,43 @@ pub async fn process_single_file_exif(message_data: String) -> bool {
         }
     };
 
-    // Parse EXIF date or use current date as fallback
-    let create_date =
-        parse_exif_date(&path).unwrap_or_else(|| chrono::offset::Local::now().naive_local());
+    // Parse EXIF data or use current date as fallback. The parser reads from the path, so encrypted or remotely stored files need a local copy
+    let local_file = match storage::local_copy(&path) {
+        Ok(f) => f,
+        Err(e) => {
+            log::error!(
+                "Failed to get a local copy of file id {id} for EXIF processing. Exception is {e:?}\n{}",
+                Backtrace::force_capture()
+            );
+            con.close().unwrap();
+            return true;
+        }
+    };
+    let exif = parse_exif(&local_file.path().to_string_lossy()).unwrap_or_default();
+    let create_date = exif
+        .date_taken
+        .unwrap_or_else(|| chrono::offset::Local::now().naive_local());
 
-    // Update file record in database with the extracted date
-    let update_result = update_file_create_date(id, create_date, &con);
+    // Update file record in database with the extracted date and camera details.
+    // The details are saved even when the file has none, so that it isn't listed as missing EXIF processing
+    let update_result = update_file_create_date(id, create_date, &con)
+        .and_then(|_| save_file_exif(id, &exif, &con));
     con.close().unwrap();
 
     match update_result {
-        Ok(_) => true,
+        Ok(_) => {
+            // EXIF details aren't known until now, so auto tag rules that check them couldn't match when the file was uploaded.
+            // Failing to auto tag shouldn't re-queue the file
+            if exif.has_details()
+                && let Err(e) = auto_tag_service::apply_rules_to_file(id)
+            {
+                log::error!(
+                    "Failed to apply auto tag rules to file {id} after processing its EXIF data. Error is {e:?}\n{}",
+                    Backtrace::force_capture()
+                );
+            }
+            true
+        }
         Err(_) => {
             // Database error, might be temporary, re-queue
             false
@@ -159,3 +265,95 @@ pub fn mass_exif_process() {
 
     log::info!("Queued {queued_count} files for EXIF processing");
 }
+
+/// the most zoomed in level that points can be clustered for
+const MAX_MAP_ZOOM: u8 = 20;
+
+/// Groups the files with gps coordinates into clusters for a map view.
+///
+/// ## Parameters
+/// * `zoom` - How zoomed in the map is, from 0 (the whole world) to 20. Each level halves the size of the clusters
+/// * `bounds` - If passed, only files inside these bounds are included. Must be `<south>,<west>,<north>,<east>`
+pub fn get_map_clusters(
+    zoom: u8,
+    bounds: Option<&str>,
+) -> Result<Vec<MapCluster>, GetMapClustersError> {
+    if zoom > MAX_MAP_ZOOM {
+        return Err(GetMapClustersError::BadRequest(format!(
+            "Invalid zoom {zoom}. Zoom must be between 0 and {MAX_MAP_ZOOM}"
+        )));
+    }
+    let bounds = bounds
+        .map(MapBounds::try_from)
+        .transpose()
+        .map_err(GetMapClustersError::BadRequest)?;
+    // like web map tiles, zoom 0 shows the whole world and each level doubles the scale. Splitting each tile into 8 cells keeps points from overlapping
+    let cell_size = 360.0 / f64::from(1u32 << zoom) / 8.0;
+    let con = open_connection();
+    let res = repository::get_map_clusters(cell_size, bounds, &con);
+    con.close().unwrap();
+    res.map_err(|e| {
+        log::error!(
+            "Failed to retrieve map clusters from the database: {e:?}\n{}",
+            Backtrace::force_capture()
+        );
+        GetMapClustersError::DbError
+    })
+}
+
+#[cfg(test)]
+mod gps_location_tests {
+    use nom_exif::{GPSInfo, LatLng, URational};
+
+    use super::gps_location;
+
+    fn lat_lng(degrees: u32, minutes: u32, seconds: u32) -> LatLng {
+        LatLng(
+            URational::from((degrees, 1)),
+            URational::from((minutes, 1)),
+            URational::from((seconds, 1)),
+        )
+    }
+
+    #[test]
+    fn converts_to_decimal_degrees() {
+        let gps = GPSInfo {
+            latitude_ref: 'S',
+            latitude: lat_lng(33, 52, 12),
+            longitude_ref: 'E',
+            longitude: lat_lng(151, 12, 36),
+            altitude_ref: 1,
+            altitude: URational::from((25, 2)),
+            ..Default::default()
+        };
+        let location = gps_location(&gps).unwrap();
+        assert!((location.latitude + 33.87).abs() < 0.0001);
+        assert!((location.longitude - 151.21).abs() < 0.0001);
+        assert_eq!(Some(-12.5), location.altitude);
+    }
+
+    #[test]
+    fn skips_out_of_range_coordinates() {
+        let gps = GPSInfo {
+            latitude_ref: 'N',
+            latitude: lat_lng(95, 0, 0),
+            longitude_ref: 'W',
+            longitude: lat_lng(10, 0, 0),
+            ..Default::default()
+        };
+        assert_eq!(None, gps_location(&gps));
+    }
+
+    #[test]
+    fn skips_missing_altitude() {
+        let gps = GPSInfo {
+            latitude_ref: 'N',
+            latitude: lat_lng(10, 0, 0),
+            longitude_ref: 'W',
+            longitude: lat_lng(10, 0, 0),
+            altitude: URational::from((0, 0)),
+            ..Default::default()
+        };
+        assert_eq!(None, gps_location(&gps).unwrap().altitude);
+    }
+}
@@ -1,8 +1,12 @@
 use rocket::http::Status;
 use rocket::local::blocking::Client;
 
+use crate::exif::{ExifData, FileLocation, MapCluster};
+use crate::repository::open_connection;
 use crate::rocket;
-use crate::test::{cleanup, init_db_folder, remove_files};
+use crate::test::{
+    AUTH, cleanup, create_file_db_entry, init_db_folder, remove_files, set_password,
+};
 
 fn client() -> Client {
     Client::tracked(rocket()).unwrap()
@@ -47,3 +51,70 @@ fn should_reject_if_no_password_set() {
     assert_eq!(res.status(), Status::Unauthorized);
     cleanup();
 }
+
+#[test]
+fn map_should_require_auth() {
+    init_db_folder();
+    let client = client();
+    let res = client.get("/exif/map?zoom=3").dispatch();
+    assert_eq!(res.status(), Status::Unauthorized);
+    cleanup();
+}
+
+#[test]
+fn map_should_reject_bad_bounds() {
+    set_password();
+    let client = client();
+    let res = client
+        .get("/exif/map?zoom=3&bounds=1,2,3")
+        .header(rocket::http::Header::new("Authorization", AUTH))
+        .dispatch();
+    assert_eq!(res.status(), Status::BadRequest);
+    cleanup();
+}
+
+#[test]
+fn map_should_reject_bad_zoom() {
+    set_password();
+    let client = client();
+    let res = client
+        .get("/exif/map?zoom=99")
+        .header(rocket::http::Header::new("Authorization", AUTH))
+        .dispatch();
+    assert_eq!(res.status(), Status::BadRequest);
+    cleanup();
+}
+
+#[test]
+fn map_should_return_clusters() {
+    set_password();
+    create_file_db_entry("a.png", None);
+    let con = open_connection();
+    let exif = ExifData {
+        location: Some(FileLocation {
+            latitude: 10.0,
+            longitude: 20.0,
+            altitude: None,
+        }),
+        ..Default::default()
+    };
+    crate::exif::repository::save_file_exif(1, &exif, &con).unwrap();
+    con.close().unwrap();
+    let client = client();
+    let res = client
+        .get("/exif/map?zoom=3")
+        .header(rocket::http::Header::new("Authorization", AUTH))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let clusters: Vec<MapCluster> = res.into_json().unwrap();
+    assert_eq!(
+        vec![MapCluster {
+            latitude: 10.0,
+            longitude: 20.0,
+            count: 1,
+            file_id: 1
+        }],
+        clusters
+    );
+    cleanup();
+}
@@ -1,6 +1,7 @@
 use crate::exif::process_single_file_exif;
 use crate::model::file_types::FileTypes;
 use crate::model::repository::FileRecord;
+use crate::model::request::file_requests::UnprocessedKind;
 use crate::repository::{file_repository, open_connection};
 use crate::service::file_service::file_dir;
 use crate::test::{cleanup, create_file_disk, init_db_folder};
@@ -24,6 +25,8 @@ async fn successfully_parsing_exif_data_stores_in_db() {
         create_date: old_date,
         size: 100,
         file_type: FileTypes::Image,
+        upload_date: None,
+        modified_date: None,
     }
     .save_to_db();
 
@@ -73,6 +76,8 @@ async fn failing_to_parse_exif_stores_current_date() {
         create_date: old_date,
         size: file_content.len() as u64,
         file_type: FileTypes::Image,
+        upload_date: None,
+        modified_date: None,
     }
     .save_to_db();
 
@@ -122,6 +127,8 @@ async fn missing_file_from_filesystem_is_silently_ignored() {
         create_date: old_date,
         size: 100,
         file_type: FileTypes::Image,
+        upload_date: None,
+        modified_date: None,
     }
     .save_to_db();
 
@@ -165,3 +172,45 @@ async fn invalid_file_id_is_handled() {
 
     cleanup();
 }
+
+#[tokio::test]
+async fn image_without_exif_is_marked_processed() {
+    init_db_folder();
+    // a 1x1 png, which has no EXIF data
+    let png: [u8; 69] = [
+        0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x48, 0x44,
+        0x52, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x08, 0x02, 0x00, 0x00, 0x00, 0x90,
+        0x77, 0x53, 0xde, 0x00, 0x00, 0x00, 0x0c, 0x49, 0x44, 0x41, 0x54, 0x78, 0x9c, 0x63, 0xf8,
+        0xcf, 0xc0, 0x00, 0x00, 0x03, 0x01, 0x01, 0x00, 0xc9, 0xfe, 0x92, 0xef, 0x00, 0x00, 0x00,
+        0x00, 0x49, 0x45, 0x4e, 0x44, 0xae, 0x42, 0x60, 0x82,
+    ];
+    std::fs::write(format!("{}/screenshot.png", file_dir()), png).unwrap();
+    let file_id = FileRecord {
+        id: None,
+        name: "screenshot.png".to_string(),
+        parent_id: None,
+        create_date: chrono::offset::Local::now().naive_local(),
+        size: png.len() as u64,
+        file_type: FileTypes::Image,
+        upload_date: None,
+        modified_date: None,
+    }
+    .save_to_db()
+    .id
+    .unwrap();
+
+    let con = open_connection();
+    let (before, _) =
+        file_repository::get_unprocessed_files(&[UnprocessedKind::Exif], &[], 50, 0, &con).unwrap();
+    assert_eq!(1, before.len());
+
+    assert!(process_single_file_exif(file_id.to_string()).await);
+
+    let (after, total) =
+        file_repository::get_unprocessed_files(&[UnprocessedKind::Exif], &[], 50, 0, &con).unwrap();
+    con.close().unwrap();
+    assert!(after.is_empty());
+    assert_eq!(0, total);
+
+    cleanup();
+}
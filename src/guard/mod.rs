@@ -87,6 +87,24 @@ impl<'a> FromRequest<'a> for HeaderAuth {
     }
 }
 
+/// the value of the `If-Match` header, used for optimistic concurrency when modifying a resource.
+///
+/// Use `Option<IfMatch>` in handlers where the header isn't required by rocket itself
+#[derive(Debug)]
+pub struct IfMatch(pub String);
+
+#[async_trait]
+impl<'a> FromRequest<'a> for IfMatch {
+    type Error = ();
+
+    async fn from_request(request: &'a Request<'_>) -> Outcome<Self, Self::Error> {
+        match request.headers().get_one("If-Match") {
+            Some(value) if !value.trim().is_empty() => Outcome::Success(IfMatch(value.to_string())),
+            _ => Outcome::Error((Status::PreconditionRequired, ())),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -2,28 +2,37 @@ use std::sync::{Arc, Mutex};
 use std::time::Instant;
 
 use rocket::State;
+use rocket::data::{ByteUnit, Data, Limits};
 use rocket::form::{Form, Strict};
-use rocket::http::Status;
+use rocket::http::{Header, Status};
 use rocket::serde::json::Json;
 
-use crate::guard::HeaderAuth;
+use crate::guard::{HeaderAuth, IfMatch};
 use crate::model::api::FileApi;
 use crate::model::error::file_errors::{
-    CreateFileError, DeleteFileError, GetFileError, GetPreviewError, SearchFileError,
-    UpdateFileError,
+    CreateFileError, DeleteFileError, FavoriteFileError, GetFileError, GetPreviewError,
+    ListFilesError, SearchFileError, UpdateFileContentsError, UpdateFileError,
 };
+use crate::model::error::tag_errors::TagRelationError;
 use crate::model::guard::auth::ValidateResult;
 use crate::model::request::attributes::{self, AttributeSearch};
-use crate::model::request::file_requests::CreateFileRequest;
-use crate::model::response::BasicMessage;
+use crate::model::request::file_requests::{
+    CreateFileRequest, FileSort, PageRequest, SearchFilesParams, SearchFilesRequest,
+    UnprocessedKind,
+};
+use crate::model::request::search_query::SearchQuery;
 use crate::model::response::file_responses::{
-    CreateFileResponse, DeleteFileResponse, DownloadFileResponse, GetFileResponse,
-    SearchFileResponse, UpdateFileResponse,
+    CreateFileResponse, DeleteFileResponse, DownloadFileResponse, FavoriteFileResponse,
+    GetFileResponse, ListFilesResponse, PagedFilesResponse, SearchFileResponse, StreamedContents,
+    UpdateFileContentsResponse, UpdateFileResponse,
 };
+use crate::model::response::tag_responses::TagInheritanceResponse;
+use crate::model::response::{BasicMessage, TagInheritanceApi};
 use crate::previews;
 use crate::previews::models::GetPreviewResponse;
 use crate::service::file_service::save_file;
 use crate::service::{file_service, search_service};
+use crate::tags::service as tag_service;
 use crate::util::update_last_request_time;
 
 /// accepts a file via request body and stores it off
@@ -41,7 +50,7 @@ pub async fn upload_file(
     }
     update_last_request_time(last_request_time);
     match save_file(&mut file_input.into_inner(), force.unwrap_or(false)).await {
-        Ok(f) => CreateFileResponse::Success(Json::from(f)),
+        Ok(f) => CreateFileResponse::Success(Json::from(Box::new(f))),
         Err(e) => match e {
             CreateFileError::FailWriteDisk => {
                 CreateFileResponse::Failure(BasicMessage::new("Failed to save file to disk!"))
@@ -55,6 +64,11 @@ pub async fn upload_file(
             CreateFileError::AlreadyExists => {
                 CreateFileResponse::AlreadyExists(BasicMessage::new("That file already exists"))
             }
+            CreateFileError::InvalidDateModified => {
+                CreateFileResponse::BadRequest(BasicMessage::new(
+                    "dateModified must be milliseconds since the unix epoch or in yyyy-MM-ddTHH:mm:ss format",
+                ))
+            }
         },
     }
 }
@@ -72,7 +86,13 @@ pub fn get_file(
     }
     update_last_request_time(last_request_time);
     match file_service::get_file_metadata(id) {
-        Ok(file) => GetFileResponse::Success(Json::from(file)),
+        Ok(file) => match file_service::get_file_etag(id) {
+            Some(etag) => GetFileResponse::SuccessWithETag(
+                Json::from(Box::new(file)),
+                Header::new("ETag", etag),
+            ),
+            None => GetFileResponse::Success(Json::from(Box::new(file))),
+        },
         Err(GetFileError::NotFound) => GetFileResponse::FileNotFound(BasicMessage::new(
             "The file with the passed id could not be found.",
         )),
@@ -83,11 +103,39 @@ pub fn get_file(
     }
 }
 
-#[get("/metadata?<search>&<tags>&<attributes>")]
+/// searches for files, returning a page of results. The total number of matching files is in the `X-Total-Count` header
+#[get("/metadata?<request..>")]
 pub fn search_files(
-    search: Option<String>,
-    tags: Option<Vec<String>>,
-    attributes: Vec<String>,
+    request: SearchFilesRequest,
+    auth: HeaderAuth,
+    last_request_time: &State<Arc<Mutex<Instant>>>,
+) -> SearchFileResponse {
+    match auth.validate() {
+        ValidateResult::Ok => { /*no op*/ }
+        ValidateResult::NoPasswordSet => return SearchFileResponse::Unauthorized("No password has been set. You can set a username and password by making a POST to `/api/password`".to_string()),
+        ValidateResult::Invalid => return SearchFileResponse::Unauthorized("Bad Credentials".to_string())
+    }
+    update_last_request_time(last_request_time);
+    let params = match search_files_params(request) {
+        Ok(params) => params,
+        Err(e) => return e,
+    };
+    match search_service::search_files(params) {
+        Ok((files, total)) => SearchFileResponse::Success(
+            Json::from(files),
+            Header::new("X-Total-Count", total.to_string()),
+        ),
+        Err(SearchFileError::DbError) => SearchFileResponse::GenericError(BasicMessage::new(
+            "Failed to search files. Check server logs for details",
+        )),
+    }
+}
+
+/// counts how many files matching the search have each file type, tag, size, and creation year.
+/// Takes the same query parameters as [search_files], but counts every matching file instead of a page of them
+#[get("/metadata/facets?<request..>")]
+pub fn search_file_facets(
+    request: SearchFilesRequest,
     auth: HeaderAuth,
     last_request_time: &State<Arc<Mutex<Instant>>>,
 ) -> SearchFileResponse {
@@ -97,10 +145,69 @@ pub fn search_files(
         ValidateResult::Invalid => return SearchFileResponse::Unauthorized("Bad Credentials".to_string())
     }
     update_last_request_time(last_request_time);
-    let search = search.unwrap_or("".to_string());
-    let tags = tags.unwrap_or_default();
-    let attributes = AttributeSearch::try_from(attributes);
-    let attributes = attributes.map_err(|e| match e {
+    let params = match search_files_params(request) {
+        Ok(params) => params,
+        Err(e) => return e,
+    };
+    match search_service::search_facets(params) {
+        Ok(facets) => SearchFileResponse::Facets(Json::from(facets)),
+        Err(SearchFileError::DbError) => SearchFileResponse::GenericError(BasicMessage::new(
+            "Failed to count search facets. Check server logs for details",
+        )),
+    }
+}
+
+/// validates the query parameters of a file search, returning the bad request response to send if they're invalid
+fn search_files_params(
+    request: SearchFilesRequest,
+) -> Result<SearchFilesParams, SearchFileResponse> {
+    let sort = request
+        .search_sort()
+        .map_err(|e| SearchFileResponse::BadRequest(BasicMessage::new(e.as_str())))?;
+    let page = request
+        .limit_offset()
+        .map_err(|e| SearchFileResponse::BadRequest(BasicMessage::new(e.as_str())))?;
+    let search = request.search.unwrap_or("".to_string());
+    let tags = request.tags.unwrap_or_default();
+    let content = request.content.unwrap_or_default();
+    let attributes =
+        AttributeSearch::try_from(request.attributes).map_err(search_parse_error_response)?;
+    let query = match request.q.filter(|it| !it.trim().is_empty()) {
+        Some(q) => Some(SearchQuery::try_from(q.as_str()).map_err(search_parse_error_response)?),
+        None => None,
+    };
+    if search.is_empty()
+        && tags.is_empty()
+        && attributes.is_empty()
+        && content.trim().is_empty()
+        && query.is_none()
+    {
+        return Err(SearchFileResponse::BadRequest(BasicMessage::new(
+            "Search string, query, content, attributes, or tags are required.",
+        )));
+    }
+    // the folder only narrows down the search, so it isn't enough on its own
+    let folder = request.folderId.map(|id| SearchQuery::Folder {
+        id,
+        recursive: request.recursive.unwrap_or(false),
+    });
+    let query = match (query, folder) {
+        (Some(query), Some(folder)) => Some(SearchQuery::And(vec![query, folder])),
+        (query, folder) => query.or(folder),
+    };
+    Ok(SearchFilesParams {
+        title: search,
+        tags,
+        attributes,
+        content,
+        query,
+        sort,
+        page,
+    })
+}
+
+fn search_parse_error_response(e: attributes::ParseError) -> SearchFileResponse {
+    match e {
         attributes::ParseError::BadEqualityOperator(er) => {
             SearchFileResponse::BadRequest(BasicMessage::new(er.as_str()))
         }
@@ -113,22 +220,206 @@ pub fn search_files(
         attributes::ParseError::InvalidSearch(er) => {
             SearchFileResponse::BadRequest(BasicMessage::new(er.as_str()))
         }
-    });
-    if let Err(e) = attributes {
-        return e;
+        attributes::ParseError::InvalidQuery(er) => {
+            SearchFileResponse::BadRequest(BasicMessage::new(er.as_str()))
+        }
     }
-    let attributes = attributes.unwrap();
-    if search.is_empty() && tags.is_empty() && attributes.is_empty() {
-        return SearchFileResponse::BadRequest(BasicMessage::new(
-            "Search string, attributes, or tags are required.",
-        ));
+}
+
+/// lists a page of files, most recent first. Defaults to ordering by upload date
+#[get("/recent?<sort>&<paging..>")]
+pub fn get_recent_files(
+    sort: Option<String>,
+    paging: PageRequest,
+    auth: HeaderAuth,
+    last_request_time: &State<Arc<Mutex<Instant>>>,
+) -> ListFilesResponse {
+    match auth.validate() {
+        ValidateResult::Ok => { /*no op*/ }
+        ValidateResult::NoPasswordSet => return ListFilesResponse::Unauthorized("No password has been set. You can set a username and password by making a POST to `/api/password`".to_string()),
+        ValidateResult::Invalid => return ListFilesResponse::Unauthorized("Bad Credentials".to_string())
+    };
+    update_last_request_time(last_request_time);
+    let sort = match sort.as_deref().map(FileSort::try_from) {
+        Some(Ok(sort)) => sort,
+        Some(Err(e)) => return ListFilesResponse::BadRequest(BasicMessage::new(e.as_str())),
+        None => FileSort::DateUploaded,
+    };
+    let (limit, offset) = match paging.limit_offset() {
+        Ok(it) => it,
+        Err(e) => return ListFilesResponse::BadRequest(BasicMessage::new(e.as_str())),
+    };
+    match file_service::get_recent_files(sort, limit, offset) {
+        Ok(files) => ListFilesResponse::Success(Json::from(files)),
+        Err(ListFilesError::DbError) => ListFilesResponse::GenericError(BasicMessage::new(
+            "Failed to retrieve recent files. Check server logs for details",
+        )),
     }
-    match search_service::search_files(&search, tags, attributes) {
-        Ok(files) => {
-            SearchFileResponse::Success(Json::from(files.into_iter().collect::<Vec<FileApi>>()))
+}
+
+/// lists a page of files that still need to be worked through, oldest uploads first.
+/// `missing` can be any of `tags`, `exif`, and `preview`, and files missing any of them are listed. Defaults to all of them.
+/// The total number of those files is in the `X-Total-Count` header
+#[get("/unprocessed?<missing>&<paging..>")]
+pub fn get_unprocessed_files(
+    missing: Vec<String>,
+    paging: PageRequest,
+    auth: HeaderAuth,
+    last_request_time: &State<Arc<Mutex<Instant>>>,
+) -> PagedFilesResponse {
+    match auth.validate() {
+        ValidateResult::Ok => { /*no op*/ }
+        ValidateResult::NoPasswordSet => return PagedFilesResponse::Unauthorized("No password has been set. You can set a username and password by making a POST to `/api/password`".to_string()),
+        ValidateResult::Invalid => return PagedFilesResponse::Unauthorized("Bad Credentials".to_string())
+    };
+    update_last_request_time(last_request_time);
+    let kinds = if missing.is_empty() {
+        vec![
+            UnprocessedKind::Tags,
+            UnprocessedKind::Exif,
+            UnprocessedKind::Preview,
+        ]
+    } else {
+        match missing
+            .iter()
+            .map(|it| UnprocessedKind::try_from(it.as_str()))
+            .collect::<Result<Vec<UnprocessedKind>, String>>()
+        {
+            Ok(kinds) => kinds,
+            Err(e) => return PagedFilesResponse::BadRequest(BasicMessage::new(e.as_str())),
         }
-        Err(SearchFileError::DbError) => SearchFileResponse::GenericError(BasicMessage::new(
-            "Failed to search files. Check server logs for details",
+    };
+    let (limit, offset) = match paging.limit_offset() {
+        Ok(it) => it,
+        Err(e) => return PagedFilesResponse::BadRequest(BasicMessage::new(e.as_str())),
+    };
+    match file_service::get_unprocessed_files(&kinds, limit, offset) {
+        Ok((files, total)) => PagedFilesResponse::Success(
+            Json::from(files),
+            Header::new("X-Total-Count", total.to_string()),
+        ),
+        Err(ListFilesError::DbError) => PagedFilesResponse::GenericError(BasicMessage::new(
+            "Failed to retrieve unprocessed files. Check server logs for details",
+        )),
+    }
+}
+
+/// lists all starred files. Defaults to ordering by name
+#[get("/favorites?<sort>")]
+pub fn get_favorite_files(
+    sort: Option<String>,
+    auth: HeaderAuth,
+    last_request_time: &State<Arc<Mutex<Instant>>>,
+) -> ListFilesResponse {
+    match auth.validate() {
+        ValidateResult::Ok => { /*no op*/ }
+        ValidateResult::NoPasswordSet => return ListFilesResponse::Unauthorized("No password has been set. You can set a username and password by making a POST to `/api/password`".to_string()),
+        ValidateResult::Invalid => return ListFilesResponse::Unauthorized("Bad Credentials".to_string())
+    };
+    update_last_request_time(last_request_time);
+    let sort = match sort.as_deref().map(FileSort::try_from) {
+        Some(Ok(sort)) => sort,
+        Some(Err(e)) => return ListFilesResponse::BadRequest(BasicMessage::new(e.as_str())),
+        None => FileSort::Name,
+    };
+    match file_service::get_favorite_files(sort) {
+        Ok(files) => ListFilesResponse::Success(Json::from(files)),
+        Err(ListFilesError::DbError) => ListFilesResponse::GenericError(BasicMessage::new(
+            "Failed to retrieve favorite files. Check server logs for details",
+        )),
+    }
+}
+
+/// stars the file with the passed id
+#[put("/favorite/<id>")]
+pub fn add_favorite_file(
+    id: u32,
+    auth: HeaderAuth,
+    last_request_time: &State<Arc<Mutex<Instant>>>,
+) -> FavoriteFileResponse {
+    match auth.validate() {
+        ValidateResult::Ok => { /*no op*/ }
+        ValidateResult::NoPasswordSet => return FavoriteFileResponse::Unauthorized("No password has been set. You can set a username and password by making a POST to `/api/password`".to_string()),
+        ValidateResult::Invalid => return FavoriteFileResponse::Unauthorized("Bad Credentials".to_string())
+    };
+    update_last_request_time(last_request_time);
+    set_file_favorite(id, true)
+}
+
+/// un-stars the file with the passed id
+#[delete("/favorite/<id>")]
+pub fn remove_favorite_file(
+    id: u32,
+    auth: HeaderAuth,
+    last_request_time: &State<Arc<Mutex<Instant>>>,
+) -> FavoriteFileResponse {
+    match auth.validate() {
+        ValidateResult::Ok => { /*no op*/ }
+        ValidateResult::NoPasswordSet => return FavoriteFileResponse::Unauthorized("No password has been set. You can set a username and password by making a POST to `/api/password`".to_string()),
+        ValidateResult::Invalid => return FavoriteFileResponse::Unauthorized("Bad Credentials".to_string())
+    };
+    update_last_request_time(last_request_time);
+    set_file_favorite(id, false)
+}
+
+fn set_file_favorite(id: u32, favorite: bool) -> FavoriteFileResponse {
+    match file_service::set_file_favorite(id, favorite) {
+        Ok(()) => FavoriteFileResponse::Success(()),
+        Err(FavoriteFileError::NotFound) => FavoriteFileResponse::NotFound(BasicMessage::new(
+            "The file with the passed id could not be found.",
+        )),
+        Err(FavoriteFileError::DbError) => FavoriteFileResponse::GenericError(BasicMessage::new(
+            "Failed to update favorite file. Check server logs for details",
+        )),
+    }
+}
+
+/// retrieves the tags the file doesn't inherit from its ancestor folders
+#[get("/inheritance/<id>")]
+pub fn get_file_tag_inheritance(
+    id: u32,
+    auth: HeaderAuth,
+    last_request_time: &State<Arc<Mutex<Instant>>>,
+) -> TagInheritanceResponse {
+    match auth.validate() {
+        ValidateResult::Ok => { /*no op*/ }
+        ValidateResult::NoPasswordSet => return TagInheritanceResponse::Unauthorized("No password has been set. You can set a username and password by making a POST to `/api/password`".to_string()),
+        ValidateResult::Invalid => return TagInheritanceResponse::Unauthorized("Bad Credentials".to_string())
+    };
+    update_last_request_time(last_request_time);
+    file_tag_inheritance_response(tag_service::get_file_tag_inheritance(id))
+}
+
+/// replaces the tags the file doesn't inherit from its ancestor folders
+#[put("/inheritance/<id>", data = "<inheritance>")]
+pub fn update_file_tag_inheritance(
+    id: u32,
+    inheritance: Json<TagInheritanceApi>,
+    auth: HeaderAuth,
+    last_request_time: &State<Arc<Mutex<Instant>>>,
+) -> TagInheritanceResponse {
+    match auth.validate() {
+        ValidateResult::Ok => { /*no op*/ }
+        ValidateResult::NoPasswordSet => return TagInheritanceResponse::Unauthorized("No password has been set. You can set a username and password by making a POST to `/api/password`".to_string()),
+        ValidateResult::Invalid => return TagInheritanceResponse::Unauthorized("Bad Credentials".to_string())
+    };
+    update_last_request_time(last_request_time);
+    file_tag_inheritance_response(tag_service::set_file_tag_inheritance(
+        id,
+        inheritance.into_inner(),
+    ))
+}
+
+fn file_tag_inheritance_response(
+    res: Result<TagInheritanceApi, TagRelationError>,
+) -> TagInheritanceResponse {
+    match res {
+        Ok(inheritance) => TagInheritanceResponse::Success(Json::from(inheritance)),
+        Err(TagRelationError::DbError) => TagInheritanceResponse::TagDbError(BasicMessage::new(
+            "Failed to process tag inheritance. Check server logs for details",
+        )),
+        Err(_) => TagInheritanceResponse::NotFound(BasicMessage::new(
+            "The file with the passed id could not be found.",
         )),
     }
 }
@@ -146,13 +437,25 @@ pub fn download_file(
     }
     update_last_request_time(last_request_time);
     match file_service::get_file_contents(id) {
-        Ok(f) => DownloadFileResponse::Success(f),
+        Ok(f) => {
+            file_service::record_file_download(id);
+            match file_service::get_file_etag(id) {
+                Some(etag) => DownloadFileResponse::SuccessWithETag(
+                    StreamedContents(f),
+                    Header::new("ETag", etag),
+                ),
+                None => DownloadFileResponse::Success(StreamedContents(f)),
+            }
+        }
         Err(GetFileError::NotFound) => DownloadFileResponse::FileNotFound(BasicMessage::new(
             "The file with the passed id could not be found.",
         )),
         Err(GetFileError::DbFailure) => DownloadFileResponse::FileDbError(BasicMessage::new(
             "Failed to retrieve the file info from the database. Check the server logs for details",
         )),
+        Err(GetFileError::FileSystemError) => DownloadFileResponse::FileDbError(BasicMessage::new(
+            "Failed to read the file from the disk. Check the server logs for details",
+        )),
         Err(_) => panic!("Download file: We should never get here"),
     }
 }
@@ -196,7 +499,7 @@ pub fn update_file(
     };
     update_last_request_time(last_request_time);
     match file_service::update_file(data.into_inner()) {
-        Ok(f) => UpdateFileResponse::Success(Json::from(f)),
+        Ok(f) => UpdateFileResponse::Success(Json::from(Box::new(f))),
         Err(UpdateFileError::NotFound) => UpdateFileResponse::NotFound(BasicMessage::new(
             "The file with the passed id could not be found.",
         )),
@@ -215,6 +518,64 @@ pub fn update_file(
     }
 }
 
+/// replaces the contents of a text, code, or configuration file in place.
+/// The `If-Match` header must contain the hash of the contents being replaced, so concurrent edits aren't overwritten
+#[put("/content/<id>", data = "<contents>")]
+pub async fn update_file_contents(
+    id: u32,
+    contents: Data<'_>,
+    if_match: Option<IfMatch>,
+    limits: &Limits,
+    auth: HeaderAuth,
+    last_request_time: &State<Arc<Mutex<Instant>>>,
+) -> UpdateFileContentsResponse {
+    match auth.validate() {
+        ValidateResult::Ok => { /*no op*/ }
+        ValidateResult::NoPasswordSet => return UpdateFileContentsResponse::Unauthorized("No password has been set. You can set a username and password by making a POST to `/api/password`".to_string()),
+        ValidateResult::Invalid => return UpdateFileContentsResponse::Unauthorized("Bad Credentials".to_string())
+    };
+    update_last_request_time(last_request_time);
+    let Some(IfMatch(expected_hash)) = if_match else {
+        return UpdateFileContentsResponse::PreconditionRequired(BasicMessage::new(
+            "The If-Match header must contain the hash of the file contents being replaced",
+        ));
+    };
+    let limit = limits.get("file").unwrap_or(ByteUnit::Gibibyte(1));
+    let contents = match contents.open(limit).into_bytes().await {
+        Ok(c) if c.is_complete() => c.into_inner(),
+        Ok(_) => {
+            return UpdateFileContentsResponse::TooLarge(BasicMessage::new(
+                "File contents are too large",
+            ));
+        }
+        Err(_) => {
+            return UpdateFileContentsResponse::BadRequest(BasicMessage::new(
+                "Failed to read file contents from request",
+            ));
+        }
+    };
+    match file_service::update_file_contents(id, &contents, &expected_hash) {
+        Ok(file) => {
+            let etag = format!("\"{}\"", file_service::hash_file_contents(&contents));
+            UpdateFileContentsResponse::Success(Json::from(file), Header::new("ETag", etag))
+        }
+        Err(UpdateFileContentsError::NotFound) => UpdateFileContentsResponse::NotFound(
+            BasicMessage::new("The file with the passed id could not be found."),
+        ),
+        Err(UpdateFileContentsError::UnsupportedType) => UpdateFileContentsResponse::BadRequest(
+            BasicMessage::new("Only text, code, and configuration files can be edited"),
+        ),
+        Err(UpdateFileContentsError::HashMismatch) => {
+            UpdateFileContentsResponse::PreconditionFailed(BasicMessage::new(
+                "The file has been modified since it was last retrieved",
+            ))
+        }
+        Err(_) => UpdateFileContentsResponse::GenericError(BasicMessage::new(
+            "Failed to update the file contents. Check the server logs for details",
+        )),
+    }
+}
+
 #[get("/preview/<id>")]
 pub async fn get_file_preview(
     id: u32,
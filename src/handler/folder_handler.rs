@@ -7,17 +7,23 @@ use rocket::serde::json::{self, Json};
 
 use crate::guard::HeaderAuth;
 use crate::model::error::folder_errors::{
-    CreateFolderError, DeleteFolderError, DownloadFolderError, GetFolderError, UpdateFolderError,
+    CreateFolderError, DeleteFolderError, DownloadFolderError, FavoriteFolderError, GetFolderError,
+    UpdateFolderError,
 };
 use crate::model::guard::auth::ValidateResult;
 use crate::model::request::folder_requests::{CreateFolderRequest, UpdateFolderRequest};
 
-use crate::model::response::BasicMessage;
+use crate::model::error::tag_errors::TagRelationError;
+use crate::model::response::file_responses::StreamedContents;
 use crate::model::response::folder_responses::{
-    CreateFolderResponse, DeleteFolderResponse, DownloadFolderResponse, GetFolderResponse,
-    GetMultiPreviewResponse, UpdateFolderResponse,
+    CreateFolderResponse, DeleteFolderResponse, DownloadFolderResponse, FavoriteFolderResponse,
+    GetFavoriteFoldersResponse, GetFolderResponse, GetMultiPreviewResponse, SearchFolderResponse,
+    UpdateFolderResponse,
 };
-use crate::service::folder_service;
+use crate::model::response::tag_responses::TagInheritanceResponse;
+use crate::model::response::{BasicMessage, TagInheritanceApi};
+use crate::service::{folder_service, search_service};
+use crate::tags::service as tag_service;
 use crate::util::update_last_request_time;
 
 #[get("/metadata/<id>")]
@@ -57,7 +63,7 @@ pub fn download_folder(
     };
     update_last_request_time(last_request_time);
     match folder_service::download_folder(id) {
-        Ok(f) => DownloadFolderResponse::Success(f),
+        Ok(f) => DownloadFolderResponse::Success(StreamedContents(f)),
         Err(DownloadFolderError::NotFound) => DownloadFolderResponse::FolderNotFound(
             BasicMessage::new("No folder with that id found"),
         ),
@@ -205,3 +211,146 @@ pub async fn get_child_file_previews(
         )),
     }
 }
+
+/// lists all starred folders
+#[get("/favorites")]
+pub fn get_favorite_folders(
+    auth: HeaderAuth,
+    last_request_time: &State<Arc<Mutex<Instant>>>,
+) -> GetFavoriteFoldersResponse {
+    match auth.validate() {
+        ValidateResult::Ok => { /*no op*/ }
+        ValidateResult::NoPasswordSet => return GetFavoriteFoldersResponse::Unauthorized("No password has been set. You can set a username and password by making a POST to `/api/password`".to_string()),
+        ValidateResult::Invalid => return GetFavoriteFoldersResponse::Unauthorized("Bad Credentials".to_string())
+    };
+    update_last_request_time(last_request_time);
+    match folder_service::get_favorite_folders() {
+        Ok(folders) => GetFavoriteFoldersResponse::Success(Json::from(folders)),
+        Err(_) => GetFavoriteFoldersResponse::FolderDbError(BasicMessage::new(
+            "Failed to pull favorite folders from database. Check server logs for details",
+        )),
+    }
+}
+
+/// stars the folder with the passed id
+#[put("/favorite/<id>")]
+pub fn add_favorite_folder(
+    id: u32,
+    auth: HeaderAuth,
+    last_request_time: &State<Arc<Mutex<Instant>>>,
+) -> FavoriteFolderResponse {
+    match auth.validate() {
+        ValidateResult::Ok => { /*no op*/ }
+        ValidateResult::NoPasswordSet => return FavoriteFolderResponse::Unauthorized("No password has been set. You can set a username and password by making a POST to `/api/password`".to_string()),
+        ValidateResult::Invalid => return FavoriteFolderResponse::Unauthorized("Bad Credentials".to_string())
+    };
+    update_last_request_time(last_request_time);
+    set_folder_favorite(id, true)
+}
+
+/// un-stars the folder with the passed id
+#[delete("/favorite/<id>")]
+pub fn remove_favorite_folder(
+    id: u32,
+    auth: HeaderAuth,
+    last_request_time: &State<Arc<Mutex<Instant>>>,
+) -> FavoriteFolderResponse {
+    match auth.validate() {
+        ValidateResult::Ok => { /*no op*/ }
+        ValidateResult::NoPasswordSet => return FavoriteFolderResponse::Unauthorized("No password has been set. You can set a username and password by making a POST to `/api/password`".to_string()),
+        ValidateResult::Invalid => return FavoriteFolderResponse::Unauthorized("Bad Credentials".to_string())
+    };
+    update_last_request_time(last_request_time);
+    set_folder_favorite(id, false)
+}
+
+fn set_folder_favorite(id: u32, favorite: bool) -> FavoriteFolderResponse {
+    match folder_service::set_folder_favorite(id, favorite) {
+        Ok(()) => FavoriteFolderResponse::Success(()),
+        Err(FavoriteFolderError::NotFound) => FavoriteFolderResponse::FolderNotFound(
+            BasicMessage::new("The folder with the passed id could not be found."),
+        ),
+        Err(FavoriteFolderError::DbFailure) => FavoriteFolderResponse::FolderDbError(
+            BasicMessage::new("Failed to update favorite folder. Check server logs for details"),
+        ),
+    }
+}
+
+/// retrieves the tags the folder doesn't inherit from its ancestor folders
+#[get("/inheritance/<id>")]
+pub fn get_folder_tag_inheritance(
+    id: u32,
+    auth: HeaderAuth,
+    last_request_time: &State<Arc<Mutex<Instant>>>,
+) -> TagInheritanceResponse {
+    match auth.validate() {
+        ValidateResult::Ok => { /*no op*/ }
+        ValidateResult::NoPasswordSet => return TagInheritanceResponse::Unauthorized("No password has been set. You can set a username and password by making a POST to `/api/password`".to_string()),
+        ValidateResult::Invalid => return TagInheritanceResponse::Unauthorized("Bad Credentials".to_string())
+    };
+    update_last_request_time(last_request_time);
+    folder_tag_inheritance_response(tag_service::get_folder_tag_inheritance(id))
+}
+
+/// replaces the tags the folder doesn't inherit from its ancestor folders
+#[put("/inheritance/<id>", data = "<inheritance>")]
+pub fn update_folder_tag_inheritance(
+    id: u32,
+    inheritance: Json<TagInheritanceApi>,
+    auth: HeaderAuth,
+    last_request_time: &State<Arc<Mutex<Instant>>>,
+) -> TagInheritanceResponse {
+    match auth.validate() {
+        ValidateResult::Ok => { /*no op*/ }
+        ValidateResult::NoPasswordSet => return TagInheritanceResponse::Unauthorized("No password has been set. You can set a username and password by making a POST to `/api/password`".to_string()),
+        ValidateResult::Invalid => return TagInheritanceResponse::Unauthorized("Bad Credentials".to_string())
+    };
+    update_last_request_time(last_request_time);
+    folder_tag_inheritance_response(tag_service::set_folder_tag_inheritance(
+        id,
+        inheritance.into_inner(),
+    ))
+}
+
+fn folder_tag_inheritance_response(
+    res: Result<TagInheritanceApi, TagRelationError>,
+) -> TagInheritanceResponse {
+    match res {
+        Ok(inheritance) => TagInheritanceResponse::Success(Json::from(inheritance)),
+        Err(TagRelationError::DbError) => TagInheritanceResponse::TagDbError(BasicMessage::new(
+            "Failed to process tag inheritance. Check server logs for details",
+        )),
+        Err(_) => TagInheritanceResponse::NotFound(BasicMessage::new(
+            "The folder with the passed id could not be found.",
+        )),
+    }
+}
+
+/// searches for folders by name and tags, including tags inherited from a parent folder
+#[get("/metadata?<search>&<tags>")]
+pub fn search_folders(
+    search: Option<String>,
+    tags: Option<Vec<String>>,
+    auth: HeaderAuth,
+    last_request_time: &State<Arc<Mutex<Instant>>>,
+) -> SearchFolderResponse {
+    match auth.validate() {
+        ValidateResult::Ok => { /*no op*/ }
+        ValidateResult::NoPasswordSet => return SearchFolderResponse::Unauthorized("No password has been set. You can set a username and password by making a POST to `/api/password`".to_string()),
+        ValidateResult::Invalid => return SearchFolderResponse::Unauthorized("Bad Credentials".to_string())
+    };
+    update_last_request_time(last_request_time);
+    let search = search.unwrap_or_default();
+    let tags = tags.unwrap_or_default();
+    if search.trim().is_empty() && tags.is_empty() {
+        return SearchFolderResponse::BadRequest(BasicMessage::new(
+            "Search string or tags are required.",
+        ));
+    }
+    match search_service::search_folders(search.trim(), tags) {
+        Ok(folders) => SearchFolderResponse::Success(Json::from(folders)),
+        Err(_) => SearchFolderResponse::GenericError(BasicMessage::new(
+            "Failed to search folders. Check server logs for details",
+        )),
+    }
+}
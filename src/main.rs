@@ -11,6 +11,9 @@ use db_migrations::generate_all_file_types_and_sizes;
 use handler::{api_handler::*, file_handler::*, folder_handler::*};
 use tags::handler::*;
 
+use crate::auto_tags::service::process_auto_tag_message;
+use crate::content_index::load_all_file_contents;
+use crate::content_index::service::process_single_file_contents;
 use crate::exif::load_all_exif_data;
 use crate::handler::api_handler::update_password;
 use crate::previews::generate_preview;
@@ -18,10 +21,13 @@ use crate::queue::file_preview_consumer;
 use crate::repository::initialize_db;
 
 use crate::exif::service::process_single_file_exif;
-use crate::queue::exif_consumer;
+use crate::queue::{auto_tags_consumer, content_index_consumer, exif_consumer};
 
+mod auto_tags;
 mod config;
+mod content_index;
 mod db_migrations;
+mod encryption;
 mod exif;
 mod guard;
 mod handler;
@@ -29,7 +35,9 @@ mod model;
 mod previews;
 mod queue;
 mod repository;
+mod saved_searches;
 mod service;
+mod storage;
 mod tags;
 mod util;
 
@@ -68,19 +76,57 @@ fn init_log() -> Result<(), fern::InitError> {
     Ok(())
 }
 
+/// runs the command passed as the first command line argument, if any, and exits. Supported commands are:
+/// - `encrypt-store`: encrypts all stored files and previews that aren't encrypted yet
+/// - `decrypt-store`: decrypts all stored files and previews that are encrypted
+#[cfg(not(test))]
+fn run_command() {
+    let Some(command) = std::env::args().nth(1) else {
+        return;
+    };
+    let encrypt = match command.as_str() {
+        "encrypt-store" => true,
+        "decrypt-store" => false,
+        _ => {
+            eprintln!(
+                "Unknown command {command}. Supported commands are encrypt-store and decrypt-store"
+            );
+            std::process::exit(2);
+        }
+    };
+    match encryption::migrate_store(encrypt) {
+        Ok(count) => {
+            println!("Finished {command}. {count} files were converted");
+            std::process::exit(0);
+        }
+        Err(e) => {
+            eprintln!("Failed to run {command}: {e}. Check output.log for details");
+            std::process::exit(1);
+        }
+    }
+}
+
 #[launch]
 pub fn rocket() -> Rocket<Build> {
     #[cfg(not(test))]
     init_log().unwrap();
+    #[cfg(not(test))]
+    run_command();
+    #[cfg(not(test))]
+    encryption::check_config();
+    // decrypted copies left behind by a previous run are removed before anything else can make new ones
+    fs::remove_dir_all(Path::new(temp_dir().as_str())).unwrap_or(());
+    fs::create_dir(Path::new(temp_dir().as_str())).unwrap();
     initialize_db().unwrap();
     generate_all_file_types_and_sizes();
     load_all_exif_data();
-    fs::remove_dir_all(Path::new(temp_dir().as_str())).unwrap_or(());
-    fs::create_dir(Path::new(temp_dir().as_str())).unwrap();
+    load_all_file_contents();
     // keep track of when the last request was made. This will let us wait for the server to be free before processing file previews
     let last_request_time: Arc<Mutex<Instant>> = Arc::new(Mutex::new(Instant::now()));
     file_preview_consumer(&last_request_time, generate_preview);
     exif_consumer(&last_request_time, process_single_file_exif);
+    content_index_consumer(&last_request_time, process_single_file_contents);
+    auto_tags_consumer(&last_request_time, process_auto_tag_message);
     // ik this isn't the right place for this, but it's a single line to prevent us from losing the directory
     // rocket needs this even during tests because it's configured in rocket.toml, and I can't change that value per test
     fs::write("./.file_server_temp/.gitkeep", "").unwrap();
@@ -103,9 +149,18 @@ pub fn rocket() -> Rocket<Build> {
                 delete_file,
                 download_file,
                 update_file,
+                update_file_contents,
                 search_files,
+                search_file_facets,
                 get_file_preview,
-                regenerate_previews
+                regenerate_previews,
+                get_recent_files,
+                get_unprocessed_files,
+                get_favorite_files,
+                add_favorite_file,
+                remove_favorite_file,
+                get_file_tag_inheritance,
+                update_file_tag_inheritance
             ],
         )
         .mount(
@@ -116,15 +171,61 @@ pub fn rocket() -> Rocket<Build> {
                 create_folder,
                 update_folder,
                 delete_folder,
-                get_child_file_previews
+                get_child_file_previews,
+                get_favorite_folders,
+                add_favorite_folder,
+                remove_favorite_folder,
+                get_folder_tag_inheritance,
+                update_folder_tag_inheritance,
+                search_folders
             ],
         )
         .mount(
             "/tags",
-            routes![get_tag, create_tag, update_tag, delete_tag],
+            routes![
+                get_tag,
+                get_all_tags,
+                get_tag_namespaces,
+                create_tag,
+                update_tag,
+                merge_tag,
+                delete_tag
+            ],
+        )
+        .mount(
+            "/searches",
+            routes![
+                saved_searches::handler::get_saved_searches,
+                saved_searches::handler::get_saved_search_folder,
+                saved_searches::handler::create_saved_search,
+                saved_searches::handler::update_saved_search,
+                saved_searches::handler::delete_saved_search
+            ],
+        )
+        .mount(
+            "/autotags",
+            routes![
+                auto_tags::handler::get_auto_tag_rules,
+                auto_tags::handler::create_auto_tag_rule,
+                auto_tags::handler::update_auto_tag_rule,
+                auto_tags::handler::delete_auto_tag_rule,
+                auto_tags::handler::apply_auto_tag_rules
+            ],
+        )
+        .mount(
+            "/previews",
+            routes![
+                previews::handler::get_folder_previews,
+                previews::handler::get_saved_search_previews
+            ],
+        )
+        .mount(
+            "/exif",
+            routes![
+                exif::handler::regenerate_exif,
+                exif::handler::get_map_clusters
+            ],
         )
-        .mount("/previews", routes![previews::handler::get_folder_previews])
-        .mount("/exif", routes![exif::handler::regenerate_exif])
         .manage(last_request_time)
 }
 
@@ -1,9 +1,13 @@
+use std::collections::BTreeMap;
+use std::ops::AddAssign;
+
 use chrono::NaiveDateTime;
 use regex::Regex;
 use rocket::serde::{Deserialize, Serialize};
 
+use crate::exif::FileLocation;
 use crate::model::file_types::FileTypes;
-use crate::model::repository::FileRecord;
+use crate::model::repository::{FileActivity, FileRecord, ItemDetails};
 use crate::model::response::TaggedItemApi;
 
 #[derive(Deserialize, Serialize, Debug, Hash, Clone, Eq)]
@@ -23,6 +27,36 @@ pub struct FileApi {
     pub date_created: Option<NaiveDateTime>,
     #[serde(rename = "fileType", skip_serializing_if = "Option::is_none")]
     pub file_type: Option<FileTypes>,
+    #[serde(rename = "dateUploaded", skip_serializing_if = "Option::is_none")]
+    pub date_uploaded: Option<NaiveDateTime>,
+    #[serde(rename = "dateModified", skip_serializing_if = "Option::is_none")]
+    pub date_modified: Option<NaiveDateTime>,
+    /// free-text notes about the file. Left unchanged by file updates when missing, and removed when blank
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// arbitrary user-defined key/value pairs, searchable via `property:<key>` attributes.
+    /// Left unchanged by file updates when missing, otherwise replaces all of the file's properties
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub properties: Option<BTreeMap<String, String>>,
+    /// whether the file has been starred. Not modifiable through file updates
+    #[serde(default)]
+    pub favorite: bool,
+    /// how many times the file has been downloaded
+    #[serde(rename = "downloadCount", default)]
+    pub download_count: u32,
+    /// the last time the file was downloaded
+    #[serde(
+        rename = "lastAccessed",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub last_accessed: Option<NaiveDateTime>,
+    /// where the file was taken, parsed from its EXIF data. Not modifiable through file updates
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<FileLocation>,
+    /// how closely the name matches the title search, from 0 to 100. Only set on search results when there was a title search
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub relevance: Option<u32>,
 }
 
 impl FileApi {
@@ -59,6 +93,15 @@ impl FileApi {
             size: None,
             date_created: None,
             file_type: None,
+            description: None,
+            properties: Some(BTreeMap::new()),
+            date_uploaded: None,
+            date_modified: None,
+            favorite: false,
+            download_count: 0,
+            last_accessed: None,
+            location: None,
+            relevance: None,
         }
     }
 }
@@ -74,10 +117,50 @@ impl From<FileRecord> for FileApi {
             size: Some(value.size),
             date_created: Some(value.create_date),
             file_type: Some(value.file_type),
+            date_uploaded: value.upload_date,
+            date_modified: value.modified_date,
+            favorite: false,
+            download_count: 0,
+            last_accessed: None,
+            description: None,
+            properties: Some(BTreeMap::new()),
+            location: None,
+            relevance: None,
         }
     }
 }
 
+impl AddAssign<ItemDetails> for FileApi {
+    fn add_assign(&mut self, rhs: ItemDetails) {
+        self.description = rhs.description;
+        self.properties = Some(rhs.properties);
+    }
+}
+
+impl AddAssign<FileActivity> for FileApi {
+    fn add_assign(&mut self, rhs: FileActivity) {
+        self.favorite = rhs.favorite;
+        self.download_count = rhs.download_count;
+        self.last_accessed = rhs.last_accessed;
+    }
+}
+
+/// how many files matching a search fall into each group, so clients can show filters with counts.
+/// Groups without any matching files are left out
+#[derive(Deserialize, Serialize, Debug, Default, Clone, PartialEq, Eq)]
+#[serde(crate = "rocket::serde")]
+pub struct SearchFacets {
+    #[serde(rename = "fileTypes")]
+    pub file_types: BTreeMap<FileTypes, u32>,
+    /// keyed by tag title, including tags inherited from a parent folder
+    pub tags: BTreeMap<String, u32>,
+    /// keyed by the file size alias, see [crate::model::request::attributes::FileSizes]
+    #[serde(rename = "fileSizes")]
+    pub file_sizes: BTreeMap<String, u32>,
+    /// keyed by the year the file was created
+    pub years: BTreeMap<String, u32>,
+}
+
 #[cfg(test)]
 mod update_file_request_tests {
     use crate::model::api::FileApi;
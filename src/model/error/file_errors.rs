@@ -6,6 +6,8 @@ pub enum CreateFileError {
     FailWriteDb,
     ParentFolderNotFound,
     AlreadyExists,
+    /// the client passed a modified date in an unrecognized format
+    InvalidDateModified,
 }
 
 #[derive(PartialEq, Debug)]
@@ -14,6 +16,8 @@ pub enum GetFileError {
     DbFailure,
     /// failed to retrieve tags for file
     TagError,
+    /// failed to read or decrypt the file on the disk
+    FileSystemError,
 }
 
 #[derive(PartialEq, Debug)]
@@ -59,6 +63,34 @@ pub enum UpdateFileError {
     TagError,
 }
 
+#[derive(PartialEq, Debug)]
+pub enum UpdateFileContentsError {
+    /// file not found in the db
+    NotFound,
+    /// the file isn't a type that can be edited as text
+    UnsupportedType,
+    /// the file's current contents don't match the hash the client last saw
+    HashMismatch,
+    /// Generic database error
+    DbError,
+    /// Generic filesystem error
+    FileSystemError,
+}
+
+#[derive(PartialEq, Debug)]
+pub enum FavoriteFileError {
+    /// file not found in the db
+    NotFound,
+    /// Generic database error
+    DbError,
+}
+
+/// represents error states that can arise from listing files outside of a search, such as recent files
+#[derive(PartialEq, Debug)]
+pub enum ListFilesError {
+    DbError,
+}
+
 #[derive(PartialEq, Debug)]
 pub enum SearchFileError {
     DbError,
@@ -6,6 +6,13 @@ pub enum GetFolderError {
     TagError,
 }
 
+#[derive(PartialEq, Debug)]
+pub enum FavoriteFolderError {
+    /// folder doesn't exist in the database
+    NotFound,
+    DbFailure,
+}
+
 #[derive(PartialEq, Debug)]
 pub enum DownloadFolderError {
     /// folder doesn't exist in the database
@@ -2,6 +2,12 @@
 pub enum CreateTagError {
     /// an error with the database
     DbError,
+    /// no tag with the passed parent id exists
+    ParentNotFound,
+    /// the color isn't a hex color
+    InvalidColor,
+    /// an alias is already the title or an alias of another tag
+    AliasAlreadyExists,
 }
 
 #[derive(PartialEq, Debug)]
@@ -20,6 +26,26 @@ pub enum UpdateTagError {
     TagNotFound,
     /// a tag with the selected name already exists, and is not the tag being updated
     NewNameAlreadyExists,
+    /// no tag with the passed parent id exists
+    ParentNotFound,
+    /// the parent is the tag itself or is nested under it
+    CircularParent,
+    /// the color isn't a hex color
+    InvalidColor,
+    /// an alias is already the title or an alias of another tag
+    AliasAlreadyExists,
+}
+
+#[derive(PartialEq, Debug)]
+pub enum MergeTagError {
+    /// an error with the database
+    DbError,
+    /// the tag being merged doesn't exist
+    TagNotFound,
+    /// the tag being merged into doesn't exist
+    TargetNotFound,
+    /// a tag can't be merged into itself
+    SameTag,
 }
 
 #[derive(PartialEq, Debug)]
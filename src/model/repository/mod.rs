@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use chrono::NaiveDateTime;
 use rocket::serde::Serialize;
 
@@ -16,10 +18,14 @@ pub struct FileRecord {
     pub name: String,
     /// will be None if in the root folder
     pub parent_id: Option<u32>,
-    /// the date the file was uploaded to the server
+    /// the date the file was originally created. This is the upload date unless EXIF data says otherwise
     pub create_date: NaiveDateTime,
     pub size: u64,
     pub file_type: FileTypes,
+    /// the date the file was uploaded to the server. Defaults to the current time when the file is created
+    pub upload_date: Option<NaiveDateTime>,
+    /// the date the file's contents were last modified. Defaults to the upload date when the file is created
+    pub modified_date: Option<NaiveDateTime>,
 }
 
 #[derive(Debug, PartialEq, Eq, Hash)]
@@ -32,6 +38,45 @@ pub struct Folder {
     pub parent_id: Option<u32>,
 }
 
+/// user-defined information attached to a file or folder
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ItemDetails {
+    /// free-text notes about the item
+    pub description: Option<String>,
+    /// arbitrary user-defined key/value pairs. Keys are unique per item
+    pub properties: BTreeMap<String, String>,
+}
+
+impl ItemDetails {
+    /// applies the description and properties sent in an update request. Missing values leave the existing ones
+    /// unchanged, and blank descriptions are treated as removing the description
+    pub fn updated_with(
+        self,
+        description: Option<String>,
+        properties: Option<BTreeMap<String, String>>,
+    ) -> Self {
+        Self {
+            description: match description {
+                Some(d) if d.trim().is_empty() => None,
+                Some(d) => Some(d),
+                None => self.description,
+            },
+            properties: properties.unwrap_or(self.properties),
+        }
+    }
+}
+
+/// how a file has been used, tracked separately from the file record itself
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct FileActivity {
+    /// whether the file has been starred
+    pub favorite: bool,
+    /// how many times the file has been downloaded
+    pub download_count: u32,
+    /// the last time the file was downloaded, `None` if it never has been
+    pub last_accessed: Option<NaiveDateTime>,
+}
+
 impl From<&FileApi> for FileRecord {
     fn from(value: &FileApi) -> Self {
         let create_date = value
@@ -49,6 +94,8 @@ impl From<&FileApi> for FileRecord {
             // will be 0 if a size needs to be set
             size: value.size.unwrap_or_default(),
             file_type: value.file_type.unwrap_or(FileTypes::Unknown),
+            upload_date: value.date_uploaded,
+            modified_date: value.date_modified,
         }
     }
 }
@@ -1,8 +1,10 @@
-use std::{fmt::Display, str::FromStr};
+use std::fmt::Display;
 
+use chrono::{Datelike, Days, Local, Months, NaiveDate, Weekday};
 use once_cell::sync::Lazy;
 use regex::Regex;
 
+use crate::exif::MapBounds;
 use crate::model::file_types::FileTypes;
 
 /// represents equality operators for searching (e.g. ==, >, and <)
@@ -12,6 +14,8 @@ pub enum EqualityOperator {
     Gt,
     Lt,
     Neq,
+    /// matches values from the first value up to and including the second, passed as `<start>,<end>`
+    Between,
 }
 
 impl TryFrom<&str> for EqualityOperator {
@@ -25,8 +29,9 @@ impl TryFrom<&str> for EqualityOperator {
             "lt" => Ok(Self::Lt),
             "gt" => Ok(Self::Gt),
             "neq" => Ok(Self::Neq),
+            "between" => Ok(Self::Between),
             _ => Err(ParseError::BadEqualityOperator(format!(
-                "{value} is not a valid equality operator. Valid ops are `eq`, `lt`, and `gt`"
+                "{value} is not a valid equality operator. Valid ops are `eq`, `neq`, `lt`, `gt`, and `between`"
             ))),
         }
     }
@@ -39,6 +44,7 @@ impl Display for EqualityOperator {
             EqualityOperator::Gt => f.write_str("gt"),
             EqualityOperator::Lt => f.write_str("lt"),
             EqualityOperator::Neq => f.write_str("neq"),
+            EqualityOperator::Between => f.write_str("between"),
         }
     }
 }
@@ -51,6 +57,8 @@ impl From<EqualityOperator> for &str {
             EqualityOperator::Lt => "<",
             EqualityOperator::Gt => ">",
             EqualityOperator::Neq => "<>",
+            // ranges aren't a single comparison, so they're handled by the repository
+            EqualityOperator::Between => "between",
         }
     }
 }
@@ -112,12 +120,22 @@ pub enum AttributeTypes {
     Named(NamedComparisonAttribute),
     /// attributes with values that are aliased to a specific name (e.g. 1Gb being [FileSizes::ExtraLarge])
     Aliased(AliasedAttribute),
+    /// user-defined details on a file, such as its description or a custom property
+    Detail(DetailAttribute),
+    /// camera details parsed from a file's EXIF data
+    Exif(ExifAttribute),
+    /// where a file was taken, parsed from its EXIF data
+    Location(LocationAttribute),
+    /// how many tags a file has
+    TagCount(TagCountAttribute),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FullComparisonTypes {
     FileSize,
     DateCreated,
+    DateUploaded,
+    DateModified,
 }
 
 /// used to force compile-time handling of all aliased attributes. Not useful right now, but if we ever get another field to search on
@@ -131,7 +149,11 @@ pub enum AliasedComparisonTypes {
 pub struct FullComparisonAttribute {
     pub field: FullComparisonTypes,
     pub operator: EqualityOperator,
-    /// might be annoying that it's a string here, but we just need to be sure we validate the value when parsing. Not like we have to deal with it being a string outside of tests
+    /// might be annoying that it's a string here, but we just need to be sure we validate the value when parsing. Not like we have to deal with it being a string outside of tests.
+    ///
+    /// This is either a single value, or a range in the form `<start>,<end>` where start is inclusive and end is exclusive.
+    /// For a range, `lt` matches values before the start, `gt` matches values at or after the end,
+    /// `eq` and `between` match values in the range, and `neq` matches values outside of it
     pub value: String,
 }
 
@@ -156,14 +178,98 @@ pub struct AliasedAttribute {
     pub operator: EqualityOperator,
 }
 
+/// user-defined details that can be searched on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DetailTypes {
+    Description,
+    /// a custom property with the contained key
+    Property(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DetailAttribute {
+    pub field: DetailTypes,
+    pub value: String,
+    pub operator: EqualityOperator,
+}
+
+/// camera details parsed from EXIF data that can be searched on (see [crate::exif::ExifData])
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExifTypes {
+    CameraMake,
+    CameraModel,
+    Lens,
+    Iso,
+    FocalLength,
+    Width,
+    Height,
+    Orientation,
+}
+
+impl ExifTypes {
+    /// whether the field is text, which can only be searched with `eq` and `neq`
+    pub fn is_text(&self) -> bool {
+        matches!(self, Self::CameraMake | Self::CameraModel | Self::Lens)
+    }
+}
+
+impl Display for ExifTypes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::CameraMake => write!(f, "cameraMake"),
+            Self::CameraModel => write!(f, "cameraModel"),
+            Self::Lens => write!(f, "lens"),
+            Self::Iso => write!(f, "iso"),
+            Self::FocalLength => write!(f, "focalLength"),
+            Self::Width => write!(f, "width"),
+            Self::Height => write!(f, "height"),
+            Self::Orientation => write!(f, "orientation"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExifAttribute {
+    pub field: ExifTypes,
+    /// for [EqualityOperator::Between], this is `<min>,<max>`, and both are included
+    pub value: String,
+    pub operator: EqualityOperator,
+}
+
+/// searches on the gps coordinates of a file.
+///
+/// - `between` takes bounds in the form `<south>,<west>,<north>,<east>` (see [crate::exif::MapBounds])
+/// - `lt` takes `<latitude>,<longitude>,<kilometers>`, and matches files within that distance of the point
+/// - `gt` takes the same value as `lt`, and matches files further away than that distance
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocationAttribute {
+    pub operator: EqualityOperator,
+    /// validated when parsing, so this can be parsed again without checking
+    pub value: String,
+}
+
+/// searches on how many tags a file has, e.g. `tagCount.eq;0` for files without any tags
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagCountAttribute {
+    /// only count tags added directly to the file (`explicitTagCount`), ignoring tags inherited from a parent folder
+    pub explicit_only: bool,
+    /// a validated whole number. For [EqualityOperator::Between], this is `<min>,<max>`, and both are included
+    pub value: String,
+    pub operator: EqualityOperator,
+}
+
 /// represents an attribute search feature.
 ///
 /// There are multiple attribute search types.
-/// - size and date are `full comparison attributes`, where we can use every
+/// - size and dates are `full comparison attributes`, where we can use every
 ///   instance of the [EqualityOperator] to search on them
 /// - file type is a `named attributed`, where the list of allowed search values are determined by a specific list.
 /// - size can also be an `aliased attribute`, where specific values have titles (see [FileSizes])
-#[derive(Debug)]
+/// - description and custom properties are `detail attributes`, which search user-defined values on the file
+/// - camera details are `exif attributes`, which search details parsed from the file's EXIF data
+/// - location is a `location attribute`, which searches the gps coordinates parsed from the file's EXIF data
+/// - tag counts are `tag count attributes`, which search how many tags the file has
+#[derive(Debug, Default)]
 pub struct AttributeSearch {
     pub attributes: Vec<AttributeTypes>,
 }
@@ -180,9 +286,12 @@ impl TryFrom<Vec<String>> for AttributeSearch {
     type Error = ParseError;
     /// attempts to parse the entire vec into an AttributeSearch
     /// format for param:
-    /// - full comparison: `<field>.<op>;<value>`
+    /// - full comparison: `<field>.<op>;<value>`, or `<field>.between;<start>,<end>`
     /// - named attribute: `<field>.eq;<value>`
     /// - aliased attribute: `<field>.eq;<value>`
+    /// - detail attribute: `description.<op>;<value>` or `property:<key>.<op>;<value>`
+    /// - exif attribute: `<field>.<op>;<value>`, e.g. `cameraModel.eq;Pixel 7` or `width.gt;3000`
+    /// - location attribute: `location.between;<south>,<west>,<north>,<east>` or `location.lt;<latitude>,<longitude>,<kilometers>`
     fn try_from(value: Vec<String>) -> Result<Self, Self::Error> {
         let mut attributes: Vec<AttributeTypes> = Vec::new();
         for val in value {
@@ -203,6 +312,8 @@ pub enum ParseError {
     BadValue(String),
     /// no search is allowed for that field name
     InvalidSearch(String),
+    /// a boolean search query (see [crate::model::request::search_query::SearchQuery]) is malformed
+    InvalidQuery(String),
 }
 
 // we don't care about the error message when dealing with equality for error messages
@@ -214,6 +325,7 @@ impl PartialEq<ParseError> for ParseError {
                 | (Self::MissingValue(_), Self::MissingValue(_))
                 | (Self::BadValue(_), Self::BadValue(_))
                 | (Self::InvalidSearch(_), Self::InvalidSearch(_))
+                | (Self::InvalidQuery(_), Self::InvalidQuery(_))
         )
     }
 }
@@ -245,13 +357,18 @@ fn validate_format(attr_string: &str) -> Result<(), ParseError> {
 
 /// parses and validates the passed `attr_string` into a valid [AttributeTypes] instance
 ///
-/// - fileSize and dateCreated are [FullComparisonAttribute]s, where we can use every instance of the [EqualityOperator] to search on them
+/// - fileSize, dateCreated, dateUploaded, and dateModified are [FullComparisonAttribute]s, where we can use every instance of the [EqualityOperator] to search on them
 /// - fileType is a [NamedComparisonAttribute]s, where the list of allowed search values are determined by a specific list.
 /// - fileSize can also be an [AliasedAttribute]s, where specific values have titles (see [FileSizes])
-fn parse_attribute(attr_string: String) -> Result<AttributeTypes, ParseError> {
+/// - description and `property:<key>` are [DetailAttribute]s, which search user-defined details on the file
+/// - cameraMake, cameraModel, lens, iso, focalLength, width, height, and orientation are [ExifAttribute]s, which search details parsed from EXIF data
+/// - location is a [LocationAttribute], which searches gps coordinates parsed from EXIF data
+/// - tagCount and explicitTagCount are [TagCountAttribute]s, which search how many tags the file has
+pub fn parse_attribute(attr_string: String) -> Result<AttributeTypes, ParseError> {
     let attr_string = attr_string.as_str();
     validate_format(attr_string)?;
-    let field_name = parse_field(attr_string).to_ascii_lowercase();
+    let field = parse_field(attr_string);
+    let field_name = field.to_ascii_lowercase();
     let op = parse_operator(attr_string)?;
     let value = parse_value(attr_string);
 
@@ -259,9 +376,26 @@ fn parse_attribute(attr_string: String) -> Result<AttributeTypes, ParseError> {
     if field_name == *"filesize" {
         parse_file_size(op, value)
     } else if field_name == *"datecreated" {
-        parse_date_created(op, value)
+        parse_date(FullComparisonTypes::DateCreated, op, value)
+    } else if field_name == *"dateuploaded" {
+        parse_date(FullComparisonTypes::DateUploaded, op, value)
+    } else if field_name == *"datemodified" {
+        parse_date(FullComparisonTypes::DateModified, op, value)
     } else if field_name == *"filetype" {
         parse_file_type(op, value)
+    } else if field_name == *"description" {
+        parse_description(op, value)
+    } else if field_name.starts_with("property:") {
+        // property keys are user-defined, so we need to keep the original casing
+        parse_property(&field["property:".len()..], op, value)
+    } else if let Some(exif_field) = parse_exif_field(&field_name) {
+        parse_exif(exif_field, op, value)
+    } else if field_name == *"location" {
+        parse_location(op, value)
+    } else if field_name == *"tagcount" {
+        parse_tag_count(false, op, value)
+    } else if field_name == *"explicittagcount" {
+        parse_tag_count(true, op, value)
     } else {
         Err(ParseError::InvalidSearch(format!(
             "{attr_string} searches an invalid search term"
@@ -269,44 +403,199 @@ fn parse_attribute(attr_string: String) -> Result<AttributeTypes, ParseError> {
     }
 }
 
-/// parses a date from `value` as a `yyyy-MM-dd` format as a [FullComparisonAttribute]
-fn parse_date_created(
+/// a date search value, after relative dates have been resolved
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DateValue {
+    /// a single day, searched as the period from the start of that day to the start of the next
+    Day(NaiveDate),
+    /// every day from the start (inclusive) to the end (exclusive)
+    Period(NaiveDate, NaiveDate),
+}
+
+impl DateValue {
+    fn start(&self) -> NaiveDate {
+        match self {
+            Self::Day(day) => *day,
+            Self::Period(start, _) => *start,
+        }
+    }
+
+    fn end(&self) -> NaiveDate {
+        match self {
+            Self::Day(day) => *day + Days::new(1),
+            Self::Period(_, end) => *end,
+        }
+    }
+}
+
+/// parses a date search on the passed date `field` as a [FullComparisonAttribute]. `value` can be:
+/// - a `yyyy-MM-dd` date
+/// - a day relative to today: `today`, or `-<n><unit>` where unit is `d`, `w`, `m`, or `y` (e.g. `-30d` for 30 days ago)
+/// - a period: `yyyy`, `yyyy-MM`, `this_week`, `this_month`, or `this_year`
+///
+/// Every value is turned into a period, with single days covering the whole day. `eq` matches dates within the period,
+/// `neq` matches dates outside of it, `lt` matches dates before it, and `gt` matches dates after it.
+/// `between` takes 2 of these separated by a comma, and includes both of them (e.g. `2021,2022-06` is the start of 2021 through the end of June 2022)
+fn parse_date(
+    field: FullComparisonTypes,
     operator: EqualityOperator,
     value: &str,
 ) -> Result<AttributeTypes, ParseError> {
+    let today = Local::now().date_naive();
+    let value = if operator == EqualityOperator::Between {
+        let (start, end) = split_range(value)?;
+        let (start, end) = (resolve_date(start, today)?, resolve_date(end, today)?);
+        if start.start() >= end.end() {
+            return Err(ParseError::BadValue(format!(
+                "{value} is not a valid date range: the start must not be after the end"
+            )));
+        }
+        format!("{},{}", start.start(), end.end())
+    } else {
+        // stored dates include the time, so single days are searched as a period too
+        let value = resolve_date(value, today)?;
+        format!("{},{}", value.start(), value.end())
+    };
+    Ok(AttributeTypes::FullComp(FullComparisonAttribute {
+        field,
+        operator,
+        value,
+    }))
+}
+
+/// resolves the passed date expression (see [parse_date]) relative to `today`
+fn resolve_date(value: &str, today: NaiveDate) -> Result<DateValue, ParseError> {
     // large number of dates being passed probably won't happen, but just in case someone decides to do something stupid, we don't want to lag the search on low-powered raspi
-    static FORMAT: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[0-9]{4}(-[0-9]{2}){2}$").unwrap());
-    if !FORMAT.is_match(value) {
-        Err(ParseError::BadValue(format!(
-            "{value} is not a valid yyyy-MM-dd date format"
-        )))
+    static DAY: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[0-9]{4}(-[0-9]{2}){2}$").unwrap());
+    static MONTH: Lazy<Regex> = Lazy::new(|| Regex::new(r"^([0-9]{4})-([0-9]{2})$").unwrap());
+    static YEAR: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[0-9]{4}$").unwrap());
+    static RELATIVE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^-([0-9]{1,5})([dwmy])$").unwrap());
+    let bad_value = || {
+        ParseError::BadValue(format!(
+            "{value} is not a valid date. Dates can be yyyy-MM-dd, yyyy-MM, yyyy, today, this_week, this_month, this_year, or relative like -30d"
+        ))
+    };
+    let lower = value.trim().to_ascii_lowercase();
+    let year_start = |year: i32| NaiveDate::from_ymd_opt(year, 1, 1).ok_or_else(bad_value);
+    let month_start = |date: NaiveDate| date.with_day(1).ok_or_else(bad_value);
+    if DAY.is_match(&lower) {
+        NaiveDate::parse_from_str(&lower, "%Y-%m-%d")
+            .map(DateValue::Day)
+            .map_err(|_| bad_value())
+    } else if let Some(captures) = MONTH.captures(&lower) {
+        let start = NaiveDate::from_ymd_opt(
+            captures[1].parse().map_err(|_| bad_value())?,
+            captures[2].parse().map_err(|_| bad_value())?,
+            1,
+        )
+        .ok_or_else(bad_value)?;
+        Ok(DateValue::Period(start, start + Months::new(1)))
+    } else if YEAR.is_match(&lower) {
+        let year: i32 = lower.parse().map_err(|_| bad_value())?;
+        Ok(DateValue::Period(year_start(year)?, year_start(year + 1)?))
+    } else if let Some(captures) = RELATIVE.captures(&lower) {
+        let amount: u32 = captures[1].parse().map_err(|_| bad_value())?;
+        let day = match &captures[2] {
+            "d" => today.checked_sub_days(Days::new(amount.into())),
+            "w" => today.checked_sub_days(Days::new(u64::from(amount) * 7)),
+            "m" => today.checked_sub_months(Months::new(amount)),
+            _ => today.checked_sub_months(Months::new(amount.saturating_mul(12))),
+        };
+        day.map(DateValue::Day).ok_or_else(bad_value)
     } else {
-        Ok(AttributeTypes::FullComp(FullComparisonAttribute {
-            field: FullComparisonTypes::DateCreated,
-            operator,
-            value: value.to_string(),
-        }))
+        match lower.as_str() {
+            "today" => Ok(DateValue::Day(today)),
+            "this_week" => {
+                let start = today.week(Weekday::Mon).first_day();
+                Ok(DateValue::Period(start, start + Days::new(7)))
+            }
+            "this_month" => {
+                let start = month_start(today)?;
+                Ok(DateValue::Period(start, start + Months::new(1)))
+            }
+            "this_year" => Ok(DateValue::Period(
+                year_start(today.year())?,
+                year_start(today.year() + 1)?,
+            )),
+            _ => Err(bad_value()),
+        }
     }
 }
 
-/// parses an attribute search for either a [FullComparisonAttribute] or an [AliasedAttribute]
+/// parses an attribute search for either a [FullComparisonAttribute] or an [AliasedAttribute].
+///
+/// Byte sizes can be a whole number of bytes, or a number with a unit (`B`, `KB`, `MB`, `GB`, or `TB`, e.g. `1.5GB`).
+/// Units are powers of 1024 to match [FileSizes], so `KiB`, `MiB`, etc. are accepted too.
+/// `between` takes 2 byte sizes separated by a comma, and includes both of them
 fn parse_file_size(operator: EqualityOperator, value: &str) -> Result<AttributeTypes, ParseError> {
-    if FileSizes::try_from(value).is_ok() {
+    if operator == EqualityOperator::Between {
+        let (min, max) = split_range(value)?;
+        let (min, max) = (parse_byte_size(min)?, parse_byte_size(max)?);
+        if min > max {
+            return Err(ParseError::BadValue(format!(
+                "{value} is not a valid size range: the start must not be larger than the end"
+            )));
+        }
+        Ok(AttributeTypes::FullComp(FullComparisonAttribute {
+            field: FullComparisonTypes::FileSize,
+            operator,
+            value: format!("{min},{}", max.saturating_add(1)),
+        }))
+    } else if FileSizes::try_from(value).is_ok() {
         Ok(AttributeTypes::Aliased(AliasedAttribute {
             field: AliasedComparisonTypes::FileSize,
             value: value.to_string(),
             operator,
         }))
-    } else if usize::from_str(value).is_ok() {
+    } else {
         Ok(AttributeTypes::FullComp(FullComparisonAttribute {
             field: FullComparisonTypes::FileSize,
             operator,
-            value: value.to_string(),
+            value: parse_byte_size(value)?.to_string(),
         }))
+    }
+}
+
+/// converts a human-readable byte size such as `10MB` into a number of bytes
+fn parse_byte_size(value: &str) -> Result<u64, ParseError> {
+    static FORMAT: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"^(?i)([0-9]{1,20}(\.[0-9]{1,10})?)\s*(b|[kmgt]i?b)?$").unwrap());
+    let bad_value = || ParseError::BadValue(format!("{value} is not a valid byte size for files"));
+    let captures = FORMAT.captures(value.trim()).ok_or_else(bad_value)?;
+    let multiplier: u64 = match captures
+        .get(3)
+        .map(|it| it.as_str().to_ascii_lowercase().replace('i', ""))
+        .as_deref()
+    {
+        Some("kb") => 1024,
+        Some("mb") => 1024 * 1024,
+        Some("gb") => 1024 * 1024 * 1024,
+        Some("tb") => 1024 * 1024 * 1024 * 1024,
+        _ => 1,
+    };
+    let number = &captures[1];
+    // whole numbers are kept exact so that large byte counts don't lose precision
+    if let Ok(whole) = number.parse::<u64>() {
+        whole.checked_mul(multiplier).ok_or_else(bad_value)
     } else {
-        Err(ParseError::BadValue(format!(
-            "{value} is not a valid byte size for files"
-        )))
+        let bytes = number.parse::<f64>().map_err(|_| bad_value())? * multiplier as f64;
+        if bytes < u64::MAX as f64 {
+            Ok(bytes.round() as u64)
+        } else {
+            Err(bad_value())
+        }
+    }
+}
+
+/// splits the value of a `between` search into its start and end
+fn split_range(value: &str) -> Result<(&str, &str), ParseError> {
+    match value.split_once(',') {
+        Some((start, end)) if !start.trim().is_empty() && !end.trim().is_empty() => {
+            Ok((start.trim(), end.trim()))
+        }
+        _ => Err(ParseError::MissingValue(format!(
+            "{value} is not a valid range: `between` requires a start and end separated by a comma"
+        ))),
     }
 }
 
@@ -332,6 +621,191 @@ fn parse_file_type(operator: EqualityOperator, value: &str) -> Result<AttributeT
     }
 }
 
+/// parses an attribute search for a [DetailAttribute] on the description of a file.
+///
+/// Descriptions are free text, so `eq` checks if the description contains `value`, and `neq` checks that it doesn't
+fn parse_description(
+    operator: EqualityOperator,
+    value: &str,
+) -> Result<AttributeTypes, ParseError> {
+    if operator != EqualityOperator::Eq && operator != EqualityOperator::Neq {
+        Err(ParseError::BadEqualityOperator(format!(
+            "{operator} is not a valid equality operator for description"
+        )))
+    } else if value.is_empty() {
+        Err(ParseError::MissingValue(
+            "description search requires a value".to_string(),
+        ))
+    } else {
+        Ok(AttributeTypes::Detail(DetailAttribute {
+            field: DetailTypes::Description,
+            value: value.to_string(),
+            operator,
+        }))
+    }
+}
+
+/// parses an attribute search for a [DetailAttribute] on the custom property with the passed `key`
+fn parse_property(
+    key: &str,
+    operator: EqualityOperator,
+    value: &str,
+) -> Result<AttributeTypes, ParseError> {
+    if key.is_empty() {
+        Err(ParseError::InvalidSearch(
+            "property searches must include a key, e.g. `property:camera.eq;value`".to_string(),
+        ))
+    } else if operator == EqualityOperator::Between {
+        Err(ParseError::BadEqualityOperator(
+            "between is not a valid equality operator for properties".to_string(),
+        ))
+    } else {
+        Ok(AttributeTypes::Detail(DetailAttribute {
+            field: DetailTypes::Property(key.to_string()),
+            value: value.to_string(),
+            operator,
+        }))
+    }
+}
+
+/// determines which [ExifTypes] the lowercase `field_name` refers to, if any
+fn parse_exif_field(field_name: &str) -> Option<ExifTypes> {
+    match field_name {
+        "cameramake" => Some(ExifTypes::CameraMake),
+        "cameramodel" => Some(ExifTypes::CameraModel),
+        "lens" => Some(ExifTypes::Lens),
+        "iso" => Some(ExifTypes::Iso),
+        "focallength" => Some(ExifTypes::FocalLength),
+        "width" => Some(ExifTypes::Width),
+        "height" => Some(ExifTypes::Height),
+        "orientation" => Some(ExifTypes::Orientation),
+        _ => None,
+    }
+}
+
+/// parses an attribute search for an [ExifAttribute].
+///
+/// Text fields (see [ExifTypes::is_text]) only allow `eq` and `neq`, and are compared ignoring case.
+/// Number fields allow every operator, and `between` takes 2 numbers separated by a comma
+fn parse_exif(
+    field: ExifTypes,
+    operator: EqualityOperator,
+    value: &str,
+) -> Result<AttributeTypes, ParseError> {
+    let parse_number = |number: &str| match number.trim().parse::<f64>() {
+        Ok(number) if number.is_finite() => Ok(number),
+        _ => Err(ParseError::BadValue(format!(
+            "{number} is not a valid number for {field}"
+        ))),
+    };
+    let value = if field.is_text() {
+        if operator != EqualityOperator::Eq && operator != EqualityOperator::Neq {
+            return Err(ParseError::BadEqualityOperator(format!(
+                "{operator} is not a valid equality operator for {field}"
+            )));
+        }
+        if value.trim().is_empty() {
+            return Err(ParseError::MissingValue(format!(
+                "{field} search requires a value"
+            )));
+        }
+        value.to_string()
+    } else if operator == EqualityOperator::Between {
+        let (min, max) = split_range(value)?;
+        if parse_number(min)? > parse_number(max)? {
+            return Err(ParseError::BadValue(format!(
+                "{value} is not a valid range: the start must not be larger than the end"
+            )));
+        }
+        format!("{min},{max}")
+    } else {
+        parse_number(value)?;
+        value.trim().to_string()
+    };
+    Ok(AttributeTypes::Exif(ExifAttribute {
+        field,
+        value,
+        operator,
+    }))
+}
+
+/// parses an attribute search for a [TagCountAttribute]. Every operator is allowed
+fn parse_tag_count(
+    explicit_only: bool,
+    operator: EqualityOperator,
+    value: &str,
+) -> Result<AttributeTypes, ParseError> {
+    let parse_count = |count: &str| {
+        count
+            .trim()
+            .parse::<u32>()
+            .map_err(|_| ParseError::BadValue(format!("{count} is not a valid number of tags")))
+    };
+    let value = if operator == EqualityOperator::Between {
+        let (min, max) = split_range(value)?;
+        let (min, max) = (parse_count(min)?, parse_count(max)?);
+        if min > max {
+            return Err(ParseError::BadValue(format!(
+                "{value} is not a valid range: the start must not be larger than the end"
+            )));
+        }
+        format!("{min},{max}")
+    } else {
+        parse_count(value)?.to_string()
+    };
+    Ok(AttributeTypes::TagCount(TagCountAttribute {
+        explicit_only,
+        value,
+        operator,
+    }))
+}
+
+/// parses an attribute search for a [LocationAttribute]
+fn parse_location(operator: EqualityOperator, value: &str) -> Result<AttributeTypes, ParseError> {
+    match operator {
+        EqualityOperator::Between => {
+            MapBounds::try_from(value).map_err(ParseError::BadValue)?;
+        }
+        EqualityOperator::Lt | EqualityOperator::Gt => {
+            parse_radius(value)?;
+        }
+        _ => {
+            return Err(ParseError::BadEqualityOperator(format!(
+                "{operator} is not a valid equality operator for location. Use `between` for an area, or `lt` and `gt` for a distance"
+            )));
+        }
+    }
+    Ok(AttributeTypes::Location(LocationAttribute {
+        operator,
+        value: value.to_string(),
+    }))
+}
+
+/// parses the value of a distance search on a location, in the form `<latitude>,<longitude>,<kilometers>`
+pub fn parse_radius(value: &str) -> Result<(f64, f64, f64), ParseError> {
+    let bad_value = || {
+        ParseError::BadValue(format!(
+            "{value} is not a valid distance search. Must be <latitude>,<longitude>,<kilometers>"
+        ))
+    };
+    let parts = value
+        .split(',')
+        .map(|it| it.trim().parse::<f64>())
+        .collect::<Result<Vec<f64>, _>>()
+        .map_err(|_| bad_value())?;
+    match parts[..] {
+        [latitude, longitude, kilometers]
+            if (-90.0..=90.0).contains(&latitude)
+                && (-180.0..=180.0).contains(&longitude)
+                && kilometers.is_finite()
+                && kilometers > 0.0 =>
+        {
+            Ok((latitude, longitude, kilometers))
+        }
+        _ => Err(bad_value()),
+    }
+}
+
 /// returns the field name part of the passed `attr_string`.
 /// This does not do any validation, and assumes that the str has been validated beforehand
 fn parse_field(attr_string: &str) -> &str {
@@ -375,6 +849,10 @@ mod parse_operator_tests {
         assert_eq!(EqualityOperator::Eq, parse_operator("test.eq;5").unwrap());
         assert_eq!(EqualityOperator::Lt, parse_operator("test.lt;5").unwrap());
         assert_eq!(EqualityOperator::Gt, parse_operator("test.gt;5").unwrap());
+        assert_eq!(
+            EqualityOperator::Between,
+            parse_operator("test.between;5,6").unwrap()
+        );
     }
 }
 
@@ -436,6 +914,210 @@ mod parse_file_size_tests {
     fn full_comp_requires_positive_numeric_byte_value() {
         assert!(parse_file_size(EqualityOperator::Gt, "-1").is_err());
     }
+
+    #[test]
+    fn converts_units_to_bytes() {
+        for (value, bytes) in [
+            ("10MB", 10 * 1024 * 1024),
+            ("10 mib", 10 * 1024 * 1024),
+            ("1.5GB", 3 * 512 * 1024 * 1024),
+            ("2kb", 2048),
+            ("12b", 12),
+        ] {
+            assert_eq!(
+                AttributeTypes::FullComp(FullComparisonAttribute {
+                    field: FullComparisonTypes::FileSize,
+                    operator: EqualityOperator::Lt,
+                    value: u64::to_string(&bytes),
+                }),
+                parse_file_size(EqualityOperator::Lt, value).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_units() {
+        assert_eq!(
+            Err(ParseError::BadValue("".to_string())),
+            parse_file_size(EqualityOperator::Lt, "10XB")
+        );
+    }
+
+    #[test]
+    fn days_become_ranges() {
+        let today = Local::now().date_naive();
+        assert_eq!(
+            AttributeTypes::FullComp(FullComparisonAttribute {
+                field: FullComparisonTypes::DateCreated,
+                operator: EqualityOperator::Eq,
+                value: format!("{today},{}", today + Days::new(1))
+            }),
+            parse_attribute("dateCreated.eq;today".to_string()).unwrap()
+        );
+        assert_eq!(
+            AttributeTypes::FullComp(FullComparisonAttribute {
+                field: FullComparisonTypes::DateCreated,
+                operator: EqualityOperator::Gt,
+                value: "2021-03-04,2021-03-05".to_string()
+            }),
+            parse_attribute("dateCreated.gt;2021-03-04".to_string()).unwrap()
+        );
+    }
+
+    #[test]
+    fn between_includes_both_ends() {
+        assert_eq!(
+            AttributeTypes::FullComp(FullComparisonAttribute {
+                field: FullComparisonTypes::FileSize,
+                operator: EqualityOperator::Between,
+                value: "1024,2049".to_string(),
+            }),
+            parse_attribute("fileSize.between;1KB,2KB".to_string()).unwrap()
+        );
+    }
+
+    #[test]
+    fn between_rejects_bad_ranges() {
+        assert_eq!(
+            Err(ParseError::BadValue("".to_string())),
+            parse_file_size(EqualityOperator::Between, "2KB,1KB")
+        );
+        assert_eq!(
+            Err(ParseError::MissingValue("".to_string())),
+            parse_file_size(EqualityOperator::Between, "2KB")
+        );
+        assert_eq!(
+            Err(ParseError::BadValue("".to_string())),
+            parse_file_size(EqualityOperator::Between, "small,large")
+        );
+    }
+}
+
+#[cfg(test)]
+mod parse_date_tests {
+    use super::*;
+
+    #[test]
+    fn parses_all_date_fields() {
+        for (name, field) in [
+            ("dateCreated", FullComparisonTypes::DateCreated),
+            ("dateUploaded", FullComparisonTypes::DateUploaded),
+            ("dateModified", FullComparisonTypes::DateModified),
+        ] {
+            assert_eq!(
+                AttributeTypes::FullComp(FullComparisonAttribute {
+                    field,
+                    operator: EqualityOperator::Lt,
+                    value: "2024-12-30,2024-12-31".to_string()
+                }),
+                parse_attribute(format!("{name}.lt;2024-12-30")).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_bad_date_format() {
+        assert_eq!(
+            Err(ParseError::BadValue("".to_string())),
+            parse_attribute("dateModified.lt;12-30-2024".to_string())
+        );
+        assert_eq!(
+            Err(ParseError::BadValue("".to_string())),
+            parse_attribute("dateModified.lt;2024-13-01".to_string())
+        );
+    }
+
+    #[test]
+    fn periods_become_ranges() {
+        assert_eq!(
+            AttributeTypes::FullComp(FullComparisonAttribute {
+                field: FullComparisonTypes::DateCreated,
+                operator: EqualityOperator::Eq,
+                value: "2021-01-01,2022-01-01".to_string()
+            }),
+            parse_attribute("dateCreated.eq;2021".to_string()).unwrap()
+        );
+        assert_eq!(
+            AttributeTypes::FullComp(FullComparisonAttribute {
+                field: FullComparisonTypes::DateCreated,
+                operator: EqualityOperator::Gt,
+                value: "2021-12-01,2022-01-01".to_string()
+            }),
+            parse_attribute("dateCreated.gt;2021-12".to_string()).unwrap()
+        );
+    }
+
+    #[test]
+    fn between_includes_both_ends() {
+        assert_eq!(
+            AttributeTypes::FullComp(FullComparisonAttribute {
+                field: FullComparisonTypes::DateUploaded,
+                operator: EqualityOperator::Between,
+                value: "2021-01-01,2022-07-01".to_string()
+            }),
+            parse_attribute("dateUploaded.between;2021,2022-06".to_string()).unwrap()
+        );
+        assert_eq!(
+            AttributeTypes::FullComp(FullComparisonAttribute {
+                field: FullComparisonTypes::DateUploaded,
+                operator: EqualityOperator::Between,
+                value: "2021-03-04,2021-03-06".to_string()
+            }),
+            parse_attribute("dateUploaded.between;2021-03-04,2021-03-05".to_string()).unwrap()
+        );
+    }
+
+    #[test]
+    fn between_rejects_bad_ranges() {
+        assert_eq!(
+            Err(ParseError::BadValue("".to_string())),
+            parse_attribute("dateCreated.between;2022,2021".to_string())
+        );
+        assert_eq!(
+            Err(ParseError::MissingValue("".to_string())),
+            parse_attribute("dateCreated.between;2022".to_string())
+        );
+    }
+
+    #[test]
+    fn resolves_relative_dates() {
+        let today = NaiveDate::from_ymd_opt(2024, 3, 31).unwrap();
+        let day = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        assert_eq!(
+            DateValue::Day(day(2024, 3, 1)),
+            resolve_date("-30d", today).unwrap()
+        );
+        assert_eq!(
+            DateValue::Day(day(2024, 3, 17)),
+            resolve_date("-2w", today).unwrap()
+        );
+        assert_eq!(
+            DateValue::Day(day(2024, 2, 29)),
+            resolve_date("-1m", today).unwrap()
+        );
+        assert_eq!(
+            DateValue::Day(day(2023, 3, 31)),
+            resolve_date("-1y", today).unwrap()
+        );
+        assert_eq!(DateValue::Day(today), resolve_date("Today", today).unwrap());
+        // 2024-03-31 is a sunday
+        assert_eq!(
+            DateValue::Period(day(2024, 3, 25), day(2024, 4, 1)),
+            resolve_date("this_week", today).unwrap()
+        );
+        assert_eq!(
+            DateValue::Period(day(2024, 3, 1), day(2024, 4, 1)),
+            resolve_date("this_month", today).unwrap()
+        );
+        assert_eq!(
+            DateValue::Period(day(2024, 1, 1), day(2025, 1, 1)),
+            resolve_date("this_year", today).unwrap()
+        );
+        assert_eq!(
+            Err(ParseError::BadValue("".to_string())),
+            resolve_date("last_tuesday", today)
+        );
+    }
 }
 
 #[cfg(test)]
@@ -461,6 +1143,165 @@ mod parse_file_type {
     }
 }
 
+#[cfg(test)]
+mod parse_detail_tests {
+    use super::*;
+
+    #[test]
+    fn description_accepts_eq_or_neq() {
+        assert!(parse_attribute("description.eq;vacation".to_string()).is_ok());
+        assert!(parse_attribute("description.neq;vacation".to_string()).is_ok());
+    }
+
+    #[test]
+    fn description_rejects_lt_or_gt() {
+        assert_eq!(
+            Err(ParseError::BadEqualityOperator("".to_string())),
+            parse_attribute("description.lt;vacation".to_string())
+        );
+        assert_eq!(
+            Err(ParseError::BadEqualityOperator("".to_string())),
+            parse_attribute("description.gt;vacation".to_string())
+        );
+    }
+
+    #[test]
+    fn property_keeps_key_casing() {
+        assert_eq!(
+            AttributeTypes::Detail(DetailAttribute {
+                field: DetailTypes::Property("cameraModel".to_string()),
+                value: "Pixel 7".to_string(),
+                operator: EqualityOperator::Eq,
+            }),
+            parse_attribute("property:cameraModel.eq;Pixel 7".to_string()).unwrap()
+        );
+    }
+
+    #[test]
+    fn property_rejects_between() {
+        assert_eq!(
+            Err(ParseError::BadEqualityOperator("".to_string())),
+            parse_attribute("property:iso.between;100,400".to_string())
+        );
+    }
+
+    #[test]
+    fn property_requires_key() {
+        assert_eq!(
+            Err(ParseError::InvalidSearch("".to_string())),
+            parse_attribute("property:.eq;value".to_string())
+        );
+    }
+}
+
+#[cfg(test)]
+mod parse_exif_tests {
+    use super::*;
+
+    #[test]
+    fn parses_text_fields() {
+        assert_eq!(
+            AttributeTypes::Exif(ExifAttribute {
+                field: ExifTypes::CameraModel,
+                value: "Pixel 7".to_string(),
+                operator: EqualityOperator::Eq,
+            }),
+            parse_attribute("cameraModel.eq;Pixel 7".to_string()).unwrap()
+        );
+        assert_eq!(
+            Err(ParseError::BadEqualityOperator("".to_string())),
+            parse_attribute("lens.gt;50mm".to_string())
+        );
+        assert_eq!(
+            Err(ParseError::MissingValue("".to_string())),
+            parse_attribute("cameraMake.eq;".to_string())
+        );
+    }
+
+    #[test]
+    fn parses_number_fields() {
+        for (attr, field) in [
+            ("iso", ExifTypes::Iso),
+            ("focalLength", ExifTypes::FocalLength),
+            ("width", ExifTypes::Width),
+            ("height", ExifTypes::Height),
+            ("orientation", ExifTypes::Orientation),
+        ] {
+            assert_eq!(
+                AttributeTypes::Exif(ExifAttribute {
+                    field,
+                    value: "3000".to_string(),
+                    operator: EqualityOperator::Gt,
+                }),
+                parse_attribute(format!("{attr}.gt;3000")).unwrap()
+            );
+        }
+        assert_eq!(
+            Err(ParseError::BadValue("".to_string())),
+            parse_attribute("iso.gt;high".to_string())
+        );
+    }
+
+    #[test]
+    fn number_fields_accept_between() {
+        assert_eq!(
+            AttributeTypes::Exif(ExifAttribute {
+                field: ExifTypes::FocalLength,
+                value: "24,70.5".to_string(),
+                operator: EqualityOperator::Between,
+            }),
+            parse_attribute("focalLength.between;24,70.5".to_string()).unwrap()
+        );
+        assert_eq!(
+            Err(ParseError::BadValue("".to_string())),
+            parse_attribute("iso.between;800,100".to_string())
+        );
+    }
+}
+
+#[cfg(test)]
+mod parse_location_tests {
+    use super::*;
+
+    #[test]
+    fn accepts_bounds_and_distances() {
+        assert_eq!(
+            AttributeTypes::Location(LocationAttribute {
+                operator: EqualityOperator::Between,
+                value: "40,-75,41.5,-73".to_string(),
+            }),
+            parse_attribute("location.between;40,-75,41.5,-73".to_string()).unwrap()
+        );
+        assert_eq!(
+            AttributeTypes::Location(LocationAttribute {
+                operator: EqualityOperator::Lt,
+                value: "48.85,2.35,10".to_string(),
+            }),
+            parse_attribute("location.lt;48.85,2.35,10".to_string()).unwrap()
+        );
+    }
+
+    #[test]
+    fn rejects_bad_values() {
+        assert_eq!(
+            Err(ParseError::BadValue("".to_string())),
+            parse_attribute("location.between;41,-75,40,-73".to_string())
+        );
+        assert_eq!(
+            Err(ParseError::BadValue("".to_string())),
+            parse_attribute("location.lt;95,2.35,10".to_string())
+        );
+        assert_eq!(
+            Err(ParseError::BadValue("".to_string())),
+            parse_attribute("location.gt;48.85,2.35".to_string())
+        );
+        assert_eq!(
+            Err(ParseError::BadEqualityOperator("".to_string())),
+            parse_attribute("location.eq;48.85,2.35,10".to_string())
+        );
+    }
+}
+
 #[cfg(test)]
 mod quality_operator_into_tests {
     use crate::model::request::attributes::EqualityOperator;
@@ -477,3 +1318,44 @@ mod quality_operator_into_tests {
         assert_eq!("<>", neq);
     }
 }
+
+#[cfg(test)]
+mod parse_tag_count_tests {
+    use super::*;
+
+    #[test]
+    fn parses_including_and_excluding_inherited_tags() {
+        assert_eq!(
+            AttributeTypes::TagCount(TagCountAttribute {
+                explicit_only: false,
+                value: "0".to_string(),
+                operator: EqualityOperator::Eq,
+            }),
+            parse_attribute("tagCount.eq;0".to_string()).unwrap()
+        );
+        assert_eq!(
+            AttributeTypes::TagCount(TagCountAttribute {
+                explicit_only: true,
+                value: "1,3".to_string(),
+                operator: EqualityOperator::Between,
+            }),
+            parse_attribute("explicitTagCount.between;1, 3".to_string()).unwrap()
+        );
+    }
+
+    #[test]
+    fn rejects_bad_values() {
+        for attribute in [
+            "tagCount.eq;-1",
+            "tagCount.gt;1.5",
+            "tagCount.eq;",
+            "explicitTagCount.between;3,1",
+        ] {
+            assert_eq!(
+                Err(ParseError::BadValue("".to_string())),
+                parse_attribute(attribute.to_string()),
+                "{attribute} should not parse"
+            );
+        }
+    }
+}
@@ -1,7 +1,12 @@
 use core::option::Option;
 
+use chrono::{DateTime, Local, NaiveDateTime};
 use rocket::fs::TempFile;
 
+use crate::model::error::file_errors::CreateFileError;
+use crate::model::request::attributes::AttributeSearch;
+use crate::model::request::search_query::SearchQuery;
+
 #[derive(FromForm)]
 #[allow(non_snake_case)] // cannot serde rename the field, and it's better to have camel case for the api
 pub struct CreateFileRequest<'a> {
@@ -17,6 +22,9 @@ pub struct CreateFileRequest<'a> {
     /// intellij http scratch pad (even directly imported from curl), or java.
     /// I don't want to pursue this anymore, and this works
     folderId: Option<String>,
+    /// when the file was last modified on the client, so it isn't lost on upload.
+    /// Either milliseconds since the unix epoch (e.g. javascript's `File.lastModified`) or `yyyy-MM-ddTHH:mm:ss`
+    dateModified: Option<String>,
 }
 
 impl CreateFileRequest<'_> {
@@ -27,4 +35,386 @@ impl CreateFileRequest<'_> {
         }
         .unwrap()
     }
+
+    /// parses the client-provided modified date, returning `None` if the client didn't send one
+    pub fn date_modified(&self) -> Result<Option<NaiveDateTime>, CreateFileError> {
+        match &self.dateModified {
+            Some(date) => parse_date_modified(date)
+                .map(Some)
+                .ok_or(CreateFileError::InvalidDateModified),
+            None => Ok(None),
+        }
+    }
+}
+
+fn parse_date_modified(date: &str) -> Option<NaiveDateTime> {
+    if let Ok(millis) = date.parse::<i64>() {
+        DateTime::from_timestamp_millis(millis).map(|it| it.with_timezone(&Local).naive_local())
+    } else {
+        NaiveDateTime::parse_from_str(date, "%Y-%m-%dT%H:%M:%S").ok()
+    }
+}
+
+/// how to order listings of files, such as favorites and recent files
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum FileSort {
+    /// alphabetically, ignoring case
+    Name,
+    /// most recently uploaded first
+    DateUploaded,
+    /// most recently downloaded first
+    LastAccessed,
+    /// most downloaded first
+    DownloadCount,
+}
+
+impl TryFrom<&str> for FileSort {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.to_lowercase().as_str() {
+            "name" => Ok(Self::Name),
+            "dateuploaded" => Ok(Self::DateUploaded),
+            "lastaccessed" => Ok(Self::LastAccessed),
+            "downloadcount" => Ok(Self::DownloadCount),
+            _ => Err(format!(
+                "Invalid sort {value}. Must be one of name, dateUploaded, lastAccessed, downloadCount"
+            )),
+        }
+    }
+}
+
+/// processing a file can be missing, used to list files that still need to be worked through
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum UnprocessedKind {
+    /// files without any tags, including tags inherited from a parent folder
+    Tags,
+    /// images and videos without any stored EXIF data
+    Exif,
+    /// images and videos without a generated preview
+    Preview,
+}
+
+impl TryFrom<&str> for UnprocessedKind {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.to_lowercase().as_str() {
+            "tags" => Ok(Self::Tags),
+            "exif" => Ok(Self::Exif),
+            "preview" => Ok(Self::Preview),
+            _ => Err(format!(
+                "Invalid missing value {value}. Must be one of tags, exif, preview"
+            )),
+        }
+    }
+}
+
+/// the field search results are ordered by
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum SearchSortField {
+    /// how closely the file name matches the title search, see [crate::repository::title_search::title_score].
+    /// Files that match equally well, or every file if the search has no title, are ordered by name
+    #[default]
+    Relevance,
+    /// alphabetically, ignoring case
+    Name,
+    Size,
+    DateCreated,
+    /// grouped by [crate::model::file_types::FileTypes]
+    Type,
+}
+
+impl TryFrom<&str> for SearchSortField {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.to_lowercase().as_str() {
+            "relevance" => Ok(Self::Relevance),
+            "name" => Ok(Self::Name),
+            "size" => Ok(Self::Size),
+            "datecreated" => Ok(Self::DateCreated),
+            "type" => Ok(Self::Type),
+            _ => Err(format!(
+                "Invalid sort {value}. Must be one of relevance, name, size, dateCreated, type"
+            )),
+        }
+    }
+}
+
+/// how to order search results. Defaults to the most relevant first
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct SearchSort {
+    pub field: SearchSortField,
+    pub descending: bool,
+}
+
+impl Default for SearchSort {
+    fn default() -> Self {
+        Self {
+            field: SearchSortField::Relevance,
+            descending: true,
+        }
+    }
+}
+
+/// everything a file search needs: the criteria files must all match, how to order them, and which page to return.
+/// Defaults to no criteria, ordered by relevance, on the first page of [DEFAULT_PAGE_SIZE] files
+#[derive(Debug)]
+pub struct SearchFilesParams {
+    /// text that must be in the file name
+    pub title: String,
+    /// titles or aliases of tags the file must have
+    pub tags: Vec<String>,
+    /// attribute searches the file must match, see [AttributeSearch]
+    pub attributes: AttributeSearch,
+    /// words that must be in the file contents
+    pub content: String,
+    /// a boolean search query the file must also match
+    pub query: Option<SearchQuery>,
+    /// how to order the results
+    pub sort: SearchSort,
+    /// the `(limit, offset)` of the page of results to return
+    pub page: (u32, u32),
+}
+
+impl Default for SearchFilesParams {
+    fn default() -> Self {
+        Self {
+            title: String::new(),
+            tags: Vec::new(),
+            attributes: AttributeSearch::default(),
+            content: String::new(),
+            query: None,
+            sort: SearchSort::default(),
+            page: (DEFAULT_PAGE_SIZE, 0),
+        }
+    }
+}
+
+/// query parameters for searching files
+#[derive(FromForm, Debug, Default)]
+#[allow(non_snake_case)] // cannot serde rename the field, and it's better to have camel case for the api
+pub struct SearchFilesRequest {
+    /// text that must be in the file name
+    pub search: Option<String>,
+    /// titles of tags the file must have
+    pub tags: Option<Vec<String>>,
+    /// attribute searches, see [crate::model::request::attributes::AttributeSearch]
+    pub attributes: Vec<String>,
+    /// words that must be in the file contents
+    pub content: Option<String>,
+    /// a boolean search query, see [crate::model::request::search_query::SearchQuery]
+    pub q: Option<String>,
+    /// only search files inside this folder. 0 is the root folder
+    pub folderId: Option<u32>,
+    /// also search the subfolders of [Self::folderId], defaults to `false`
+    pub recursive: Option<bool>,
+    /// the field to sort by, defaults to relevance
+    pub sort: Option<String>,
+    /// `asc` or `desc`, defaults to `desc` for relevance and `asc` for everything else
+    pub order: Option<String>,
+    /// 1-based page number, see [PageRequest]
+    page: Option<u32>,
+    /// how many results are in a page, see [PageRequest]
+    pageSize: Option<u32>,
+}
+
+impl SearchFilesRequest {
+    /// parses the requested `sort` and `order` into a [SearchSort]
+    pub fn search_sort(&self) -> Result<SearchSort, String> {
+        let field = match self.sort.as_deref() {
+            Some(sort) => SearchSortField::try_from(sort)?,
+            None => SearchSortField::Relevance,
+        };
+        let descending = match self.order.as_deref().map(str::to_lowercase).as_deref() {
+            None => field == SearchSortField::Relevance,
+            Some("asc") => false,
+            Some("desc") => true,
+            Some(_) => return Err("order must be either asc or desc".to_string()),
+        };
+        Ok(SearchSort { field, descending })
+    }
+
+    /// converts the requested page into a `(limit, offset)` pair for querying the database
+    pub fn limit_offset(&self) -> Result<(u32, u32), String> {
+        PageRequest {
+            page: self.page,
+            pageSize: self.pageSize,
+        }
+        .limit_offset()
+    }
+}
+
+/// query parameters for requesting a single page of results
+#[derive(FromForm, Debug, Default)]
+#[allow(non_snake_case)] // cannot serde rename the field, and it's better to have camel case for the api
+pub struct PageRequest {
+    /// 1-based page number, defaults to the first page
+    page: Option<u32>,
+    /// how many results are in a page, defaults to [DEFAULT_PAGE_SIZE]
+    pageSize: Option<u32>,
+}
+
+pub const DEFAULT_PAGE_SIZE: u32 = 50;
+pub const MAX_PAGE_SIZE: u32 = 500;
+
+impl PageRequest {
+    /// converts the requested page into a `(limit, offset)` pair for querying the database
+    pub fn limit_offset(&self) -> Result<(u32, u32), String> {
+        let page = self.page.unwrap_or(1);
+        let page_size = self.pageSize.unwrap_or(DEFAULT_PAGE_SIZE);
+        if page == 0 {
+            return Err("page must be 1 or greater".to_string());
+        }
+        if page_size == 0 || page_size > MAX_PAGE_SIZE {
+            return Err(format!("pageSize must be between 1 and {MAX_PAGE_SIZE}"));
+        }
+        Ok((page_size, (page - 1) * page_size))
+    }
+}
+
+#[cfg(test)]
+mod parse_date_modified_tests {
+    use chrono::{NaiveDate, NaiveTime};
+
+    use super::*;
+
+    #[test]
+    fn parses_iso_date() {
+        let expected = NaiveDateTime::new(
+            NaiveDate::from_ymd_opt(2024, 12, 30).unwrap(),
+            NaiveTime::from_hms_opt(13, 5, 0).unwrap(),
+        );
+        assert_eq!(Some(expected), parse_date_modified("2024-12-30T13:05:00"));
+    }
+
+    #[test]
+    fn parses_epoch_millis() {
+        let expected = DateTime::from_timestamp(1735563900, 0)
+            .unwrap()
+            .with_timezone(&Local)
+            .naive_local();
+        assert_eq!(Some(expected), parse_date_modified("1735563900000"));
+    }
+
+    #[test]
+    fn rejects_bad_format() {
+        assert_eq!(None, parse_date_modified("12/30/2024"));
+    }
+}
+
+#[cfg(test)]
+mod file_sort_tests {
+    use super::*;
+
+    #[test]
+    fn parses_ignoring_case() {
+        assert_eq!(
+            Ok(FileSort::DateUploaded),
+            FileSort::try_from("dateUploaded")
+        );
+        assert_eq!(
+            Ok(FileSort::LastAccessed),
+            FileSort::try_from("LASTACCESSED")
+        );
+        assert_eq!(
+            Ok(FileSort::DownloadCount),
+            FileSort::try_from("downloadcount")
+        );
+        assert_eq!(Ok(FileSort::Name), FileSort::try_from("Name"));
+    }
+
+    #[test]
+    fn rejects_unknown_sort() {
+        assert!(FileSort::try_from("size").is_err());
+    }
+}
+
+#[cfg(test)]
+mod page_request_tests {
+    use super::*;
+
+    #[test]
+    fn defaults_to_first_page() {
+        assert_eq!(
+            Ok((DEFAULT_PAGE_SIZE, 0)),
+            PageRequest::default().limit_offset()
+        );
+    }
+
+    #[test]
+    fn calculates_offset() {
+        let req = PageRequest {
+            page: Some(3),
+            pageSize: Some(10),
+        };
+        assert_eq!(Ok((10, 20)), req.limit_offset());
+    }
+
+    #[test]
+    fn rejects_page_0() {
+        let req = PageRequest {
+            page: Some(0),
+            pageSize: None,
+        };
+        assert!(req.limit_offset().is_err());
+    }
+
+    #[test]
+    fn rejects_bad_page_size() {
+        let req = PageRequest {
+            page: None,
+            pageSize: Some(MAX_PAGE_SIZE + 1),
+        };
+        assert!(req.limit_offset().is_err());
+        let req = PageRequest {
+            page: None,
+            pageSize: Some(0),
+        };
+        assert!(req.limit_offset().is_err());
+    }
+}
+
+#[cfg(test)]
+mod search_sort_tests {
+    use super::*;
+
+    #[test]
+    fn defaults_to_most_relevant_first() {
+        assert_eq!(
+            Ok(SearchSort::default()),
+            SearchFilesRequest::default().search_sort()
+        );
+    }
+
+    #[test]
+    fn parses_field_and_order_ignoring_case() {
+        let req = SearchFilesRequest {
+            sort: Some("DATECREATED".to_string()),
+            order: Some("Desc".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            Ok(SearchSort {
+                field: SearchSortField::DateCreated,
+                descending: true,
+            }),
+            req.search_sort()
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_sort_and_order() {
+        let req = SearchFilesRequest {
+            sort: Some("downloadCount".to_string()),
+            ..Default::default()
+        };
+        assert!(req.search_sort().is_err());
+        let req = SearchFilesRequest {
+            order: Some("up".to_string()),
+            ..Default::default()
+        };
+        assert!(req.search_sort().is_err());
+    }
 }
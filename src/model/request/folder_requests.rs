@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use rocket::serde::{Deserialize, Serialize};
 
 use crate::model::response::TaggedItemApi;
@@ -21,4 +23,10 @@ pub struct UpdateFolderRequest {
     #[serde(rename = "parentId")]
     pub parent_id: Option<u32>,
     pub tags: Vec<TaggedItemApi>,
+    /// replaces the folder's description. Blank descriptions remove it, and missing descriptions leave it unchanged
+    #[serde(default)]
+    pub description: Option<String>,
+    /// replaces all of the folder's custom properties. Missing properties leave them unchanged
+    #[serde(default)]
+    pub properties: Option<BTreeMap<String, String>>,
 }
@@ -9,6 +9,8 @@ use crate::guard::HeaderAuth;
 pub mod attributes;
 pub mod file_requests;
 pub mod folder_requests;
+pub mod search_query;
+pub mod tag_requests;
 
 /// Because `Auth` is used as a request guard, we can't use it for creating login credentials.
 /// This allows us to accept one in a post body.
@@ -0,0 +1,439 @@
+use crate::model::request::attributes::{AttributeTypes, ParseError, parse_attribute};
+
+/// a boolean search query over files, e.g. `(tag:cats OR tag:dogs) AND type:image AND NOT tag:blurry`.
+///
+/// Syntax:
+/// - `AND`, `OR`, and `NOT` combine terms, and must be upper case. `NOT` binds tightest, then `AND`, then `OR`
+/// - terms next to each other without an operator are `AND`ed together
+/// - parentheses group terms
+/// - `tag:<title>` matches files with the tag, including tags inherited from a parent folder
+/// - `namespace:<namespace>` matches files with any tag in the namespace, e.g. `namespace:person` matches files tagged `person:alice`
+/// - `type:<fileType>` is short for the `fileType.eq;<fileType>` attribute
+/// - `content:<word>` matches files whose indexed contents contain the word
+/// - `<field>.<op>;<value>` is any attribute search supported by [AttributeTypes]
+/// - anything else (or `title:<value>`) matches files whose name fuzzily matches the value, see [crate::repository::title_search::title_score]
+///
+/// Values containing spaces or parentheses can be wrapped in double quotes, e.g. `tag:"red car"`. A quoted term is always
+/// treated as part of the title, so `"OR"` searches for the word instead of being an operator
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SearchQuery {
+    And(Vec<SearchQuery>),
+    Or(Vec<SearchQuery>),
+    Not(Box<SearchQuery>),
+    Title(String),
+    Tag(String),
+    /// files with any tag in the namespace, see [crate::tags::Tag::namespace]
+    Namespace(String),
+    Content(String),
+    Attribute(AttributeTypes),
+    /// files directly inside the folder with the id, or anywhere under it if `recursive`.
+    /// This isn't part of the query syntax, and is instead added from the `folderId` search parameter
+    Folder {
+        id: u32,
+        recursive: bool,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    LeftParen,
+    RightParen,
+    And,
+    Or,
+    Not,
+    /// a search term, and whether it started with a quote
+    Term(String, bool),
+}
+
+impl TryFrom<&str> for SearchQuery {
+    type Error = ParseError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let tokens = tokenize(value)?;
+        if tokens.is_empty() {
+            return Err(ParseError::InvalidQuery(
+                "search query is empty".to_string(),
+            ));
+        }
+        let mut parser = Parser {
+            tokens,
+            position: 0,
+            depth: 0,
+        };
+        let query = parser.parse_or()?;
+        if let Some(token) = parser.peek() {
+            return Err(ParseError::InvalidQuery(format!(
+                "unexpected {} in search query",
+                describe(token)
+            )));
+        }
+        Ok(query)
+    }
+}
+
+impl SearchQuery {
+    /// the title searches files are ranked by. Titles inside a `NOT` are left out, since they can't match anything
+    pub fn title_terms(&self) -> Vec<&str> {
+        match self {
+            SearchQuery::And(queries) | SearchQuery::Or(queries) => {
+                queries.iter().flat_map(|it| it.title_terms()).collect()
+            }
+            SearchQuery::Title(title) => vec![title.as_str()],
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// splits the raw query into tokens, handling quoted values
+fn tokenize(query: &str) -> Result<Vec<Token>, ParseError> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    // whether the current term has had any quotes in it, so that `""` is still a term
+    let mut has_quotes = false;
+    let mut starts_with_quote = false;
+    let flush = |current: &mut String,
+                 has_quotes: &mut bool,
+                 starts_with_quote: &mut bool,
+                 tokens: &mut Vec<Token>| {
+        if current.is_empty() && !*has_quotes {
+            return;
+        }
+        let token = match current.as_str() {
+            "AND" if !*has_quotes => Token::And,
+            "OR" if !*has_quotes => Token::Or,
+            "NOT" if !*has_quotes => Token::Not,
+            _ => Token::Term(std::mem::take(current), *starts_with_quote),
+        };
+        current.clear();
+        *has_quotes = false;
+        *starts_with_quote = false;
+        tokens.push(token);
+    };
+    for c in query.chars() {
+        match c {
+            '"' => {
+                if current.is_empty() && !has_quotes {
+                    starts_with_quote = true;
+                }
+                in_quotes = !in_quotes;
+                has_quotes = true;
+            }
+            _ if in_quotes => current.push(c),
+            '(' | ')' => {
+                flush(
+                    &mut current,
+                    &mut has_quotes,
+                    &mut starts_with_quote,
+                    &mut tokens,
+                );
+                tokens.push(if c == '(' {
+                    Token::LeftParen
+                } else {
+                    Token::RightParen
+                });
+            }
+            _ if c.is_whitespace() => flush(
+                &mut current,
+                &mut has_quotes,
+                &mut starts_with_quote,
+                &mut tokens,
+            ),
+            _ => current.push(c),
+        }
+    }
+    if in_quotes {
+        return Err(ParseError::InvalidQuery(
+            "search query has an unclosed quote".to_string(),
+        ));
+    }
+    flush(
+        &mut current,
+        &mut has_quotes,
+        &mut starts_with_quote,
+        &mut tokens,
+    );
+    Ok(tokens)
+}
+
+fn describe(token: &Token) -> String {
+    match token {
+        Token::LeftParen => "`(`".to_string(),
+        Token::RightParen => "`)`".to_string(),
+        Token::And => "`AND`".to_string(),
+        Token::Or => "`OR`".to_string(),
+        Token::Not => "`NOT`".to_string(),
+        Token::Term(term, _) => format!("`{term}`"),
+    }
+}
+
+/// how deeply groups and `NOT`s can be nested in a query, so that deeply nested queries can't overflow the stack
+const MAX_DEPTH: usize = 64;
+
+/// recursive descent parser over the tokens of a query
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+    /// how many groups and `NOT`s are around the token being parsed
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    /// runs `parse` one level deeper, failing if that goes past [MAX_DEPTH]
+    fn nested(
+        &mut self,
+        parse: fn(&mut Self) -> Result<SearchQuery, ParseError>,
+    ) -> Result<SearchQuery, ParseError> {
+        if self.depth == MAX_DEPTH {
+            return Err(ParseError::InvalidQuery(format!(
+                "search query can't be nested more than {MAX_DEPTH} levels deep"
+            )));
+        }
+        self.depth += 1;
+        let res = parse(self);
+        self.depth -= 1;
+        res
+    }
+
+    fn parse_or(&mut self) -> Result<SearchQuery, ParseError> {
+        let mut terms = vec![self.parse_and()?];
+        while self.peek() == Some(&Token::Or) {
+            self.next();
+            terms.push(self.parse_and()?);
+        }
+        Ok(if terms.len() == 1 {
+            terms.remove(0)
+        } else {
+            SearchQuery::Or(terms)
+        })
+    }
+
+    fn parse_and(&mut self) -> Result<SearchQuery, ParseError> {
+        let mut terms = vec![self.parse_not()?];
+        loop {
+            match self.peek() {
+                Some(Token::And) => {
+                    self.next();
+                    terms.push(self.parse_not()?);
+                }
+                // terms without an operator between them are implicitly ANDed
+                Some(Token::Term(..) | Token::LeftParen | Token::Not) => {
+                    terms.push(self.parse_not()?)
+                }
+                _ => break,
+            }
+        }
+        Ok(if terms.len() == 1 {
+            terms.remove(0)
+        } else {
+            SearchQuery::And(terms)
+        })
+    }
+
+    fn parse_not(&mut self) -> Result<SearchQuery, ParseError> {
+        if self.peek() == Some(&Token::Not) {
+            self.next();
+            Ok(SearchQuery::Not(Box::new(self.nested(Self::parse_not)?)))
+        } else {
+            self.parse_primary()
+        }
+    }
+
+    fn parse_primary(&mut self) -> Result<SearchQuery, ParseError> {
+        match self.next() {
+            Some(Token::LeftParen) => {
+                let query = self.nested(Self::parse_or)?;
+                match self.next() {
+                    Some(Token::RightParen) => Ok(query),
+                    _ => Err(ParseError::InvalidQuery(
+                        "search query is missing a closing `)`".to_string(),
+                    )),
+                }
+            }
+            Some(Token::Term(term, quoted)) => parse_term(term, quoted),
+            Some(token) => Err(ParseError::InvalidQuery(format!(
+                "expected a search term but found {}",
+                describe(&token)
+            ))),
+            None => Err(ParseError::InvalidQuery(
+                "search query ends before a search term".to_string(),
+            )),
+        }
+    }
+}
+
+/// converts a single term into the matching [SearchQuery]
+fn parse_term(term: String, quoted: bool) -> Result<SearchQuery, ParseError> {
+    if quoted {
+        return Ok(SearchQuery::Title(term));
+    }
+    let (prefix, value) = match term.split_once(':') {
+        Some((prefix, value)) => (prefix.to_ascii_lowercase(), value),
+        None => (String::new(), term.as_str()),
+    };
+    let require_value = |kind: &str| {
+        if value.is_empty() {
+            Err(ParseError::MissingValue(format!(
+                "`{term}` is missing a {kind} to search for"
+            )))
+        } else {
+            Ok(value.to_string())
+        }
+    };
+    match prefix.as_str() {
+        "tag" => Ok(SearchQuery::Tag(require_value("tag")?)),
+        "namespace" => Ok(SearchQuery::Namespace(require_value("namespace")?)),
+        "type" => parse_attribute(format!("fileType.eq;{}", require_value("file type")?))
+            .map(SearchQuery::Attribute),
+        "content" => Ok(SearchQuery::Content(require_value("word")?)),
+        "title" => Ok(SearchQuery::Title(require_value("title")?)),
+        // property searches have a colon in their field name, so they need to be checked before falling back to the title
+        _ if term.contains('.') && term.contains(';') => {
+            parse_attribute(term).map(SearchQuery::Attribute)
+        }
+        _ => Ok(SearchQuery::Title(term)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::request::attributes::{
+        EqualityOperator, FullComparisonAttribute, FullComparisonTypes, NamedAttributes,
+        NamedComparisonAttribute,
+    };
+
+    fn image() -> SearchQuery {
+        SearchQuery::Attribute(AttributeTypes::Named(NamedComparisonAttribute {
+            field: NamedAttributes::FileType,
+            value: "image".to_string(),
+            operator: EqualityOperator::Eq,
+        }))
+    }
+
+    fn tag(title: &str) -> SearchQuery {
+        SearchQuery::Tag(title.to_string())
+    }
+
+    #[test]
+    fn parses_grouping_and_operators() {
+        let query =
+            SearchQuery::try_from("(tag:cats OR tag:dogs) AND type:image AND NOT tag:blurry")
+                .unwrap();
+        assert_eq!(
+            SearchQuery::And(vec![
+                SearchQuery::Or(vec![tag("cats"), tag("dogs")]),
+                image(),
+                SearchQuery::Not(Box::new(tag("blurry"))),
+            ]),
+            query
+        );
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        let query = SearchQuery::try_from("tag:a tag:b OR tag:c").unwrap();
+        assert_eq!(
+            SearchQuery::Or(vec![SearchQuery::And(vec![tag("a"), tag("b")]), tag("c")]),
+            query
+        );
+    }
+
+    #[test]
+    fn parses_attributes_and_titles() {
+        let query = SearchQuery::try_from("report.pdf dateCreated.gt;2024-01-01").unwrap();
+        assert_eq!(
+            SearchQuery::And(vec![
+                SearchQuery::Title("report.pdf".to_string()),
+                SearchQuery::Attribute(AttributeTypes::FullComp(FullComparisonAttribute {
+                    field: FullComparisonTypes::DateCreated,
+                    operator: EqualityOperator::Gt,
+                    value: "2024-01-01,2024-01-02".to_string(),
+                })),
+            ]),
+            query
+        );
+    }
+
+    #[test]
+    fn handles_quoted_values() {
+        let query = SearchQuery::try_from(r#"tag:"red car" "OR" (content:"engine")"#).unwrap();
+        assert_eq!(
+            SearchQuery::And(vec![
+                tag("red car"),
+                SearchQuery::Title("OR".to_string()),
+                SearchQuery::Content("engine".to_string()),
+            ]),
+            query
+        );
+    }
+
+    #[test]
+    fn parses_namespaces() {
+        let query = SearchQuery::try_from("namespace:person NOT tag:person:alice").unwrap();
+        assert_eq!(
+            SearchQuery::And(vec![
+                SearchQuery::Namespace("person".to_string()),
+                SearchQuery::Not(Box::new(tag("person:alice"))),
+            ]),
+            query
+        );
+        assert!(SearchQuery::try_from("namespace:").is_err());
+    }
+
+    #[test]
+    fn rejects_malformed_queries() {
+        for query in [
+            "",
+            "(tag:a",
+            "tag:a)",
+            "tag:a OR",
+            "NOT",
+            "\"unclosed",
+            "tag:",
+            "type:nonsense",
+        ] {
+            assert!(
+                SearchQuery::try_from(query).is_err(),
+                "{query} should not parse"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_deeply_nested_queries() {
+        let groups = format!("{}a{}", "(".repeat(100_000), ")".repeat(100_000));
+        assert!(matches!(
+            SearchQuery::try_from(groups.as_str()),
+            Err(ParseError::InvalidQuery(_))
+        ));
+        let negations = format!("{}a", "NOT ".repeat(100_000));
+        assert!(matches!(
+            SearchQuery::try_from(negations.as_str()),
+            Err(ParseError::InvalidQuery(_))
+        ));
+        // queries right at the limit still parse
+        let groups = format!("{}a{}", "(".repeat(MAX_DEPTH), ")".repeat(MAX_DEPTH));
+        assert_eq!(
+            SearchQuery::Title("a".to_string()),
+            SearchQuery::try_from(groups.as_str()).unwrap()
+        );
+    }
+
+    #[test]
+    fn title_terms_skips_negated_titles() {
+        let query =
+            SearchQuery::try_from("(receipt OR invoice) AND NOT draft AND tag:tax").unwrap();
+        assert_eq!(vec!["receipt", "invoice"], query.title_terms());
+    }
+}
@@ -0,0 +1,44 @@
+use rocket::serde::{Deserialize, Deserializer, Serialize};
+
+/// merges the tag in the request path into the tag with the id `target_id`
+#[derive(Deserialize, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct MergeTagRequest {
+    #[serde(rename = "targetId")]
+    pub target_id: u32,
+}
+
+/// updates the tag with the id `id`. Every field other than the id and title can be left out to keep its current value
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+#[serde(crate = "rocket::serde")]
+pub struct UpdateTagRequest {
+    pub id: u32,
+    pub title: String,
+    /// the id of the tag to nest this one under. `null` un-nests the tag, and a missing parent leaves it unchanged
+    #[serde(
+        default,
+        rename = "parentId",
+        deserialize_with = "nullable",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub parent_id: Option<Option<u32>>,
+    /// replaces the tag's color. A blank color removes it, and a missing color leaves it unchanged
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    /// replaces the tag's description. A blank description removes it, and a missing description leaves it unchanged
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// replaces all of the tag's aliases. Missing aliases leave them unchanged
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aliases: Option<Vec<String>>,
+}
+
+/// deserializes a field that is `null` into `Some(None)`, so that it can be told apart from a missing field.
+/// Must be used alongside `#[serde(default)]` so that missing fields are `None`
+fn nullable<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
@@ -1,9 +1,17 @@
-use std::fs::File;
+use std::io::Cursor;
+use std::pin::Pin;
+use std::task::{Context, Poll, ready};
 
+use rocket::futures::Stream;
+use rocket::http::Header;
+use rocket::response::{self, Responder};
 use rocket::serde::json::Json;
+use rocket::tokio::io::{AsyncRead, ReadBuf};
+use rocket::{Request, Response};
 
-use crate::model::api::FileApi;
+use crate::model::api::{FileApi, SearchFacets};
 use crate::model::response::BasicMessage;
+use crate::storage::models::ContentsStream;
 
 type NoContent = ();
 
@@ -14,11 +22,66 @@ pub enum GetFileResponse {
     #[response(status = 500, content_type = "json")]
     FileDbError(Json<BasicMessage>),
     #[response(status = 200, content_type = "json")]
-    Success(Json<FileApi>),
+    Success(Json<Box<FileApi>>),
+    /// for files that can be edited. The header is the ETag of the contents
+    #[response(status = 200, content_type = "json")]
+    SuccessWithETag(Json<Box<FileApi>>, Header<'static>),
     #[response(status = 401)]
     Unauthorized(String),
 }
 
+/// streams the plain contents of a stored file as the response body, see [`crate::storage::stream_contents`].
+///
+/// If the size of the contents is known it's sent as the `Content-Length`, so that a stream that fails part of the way through
+/// aborts the connection instead of looking like a complete body to the client
+pub struct StreamedContents(pub ContentsStream);
+
+impl<'r> Responder<'r, 'static> for StreamedContents {
+    fn respond_to(self, _: &'r Request<'_>) -> response::Result<'static> {
+        let ContentsStream { size, chunks } = self.0;
+        let mut response = Response::build();
+        if let Some(size) = size {
+            response.raw_header("Content-Length", size.to_string());
+        }
+        response
+            .streamed_body(ChunkReader {
+                chunks,
+                current: Cursor::new(Vec::new()),
+            })
+            .ok()
+    }
+}
+
+/// reads the chunks of a [ContentsStream] in order, failing with the stream's error instead of ending early
+struct ChunkReader {
+    chunks: Pin<Box<dyn Stream<Item = std::io::Result<Vec<u8>>> + Send>>,
+    current: Cursor<Vec<u8>>,
+}
+
+impl AsyncRead for ChunkReader {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        loop {
+            let position = self.current.position() as usize;
+            let remaining = &self.current.get_ref()[position..];
+            if !remaining.is_empty() {
+                let len = remaining.len().min(buf.remaining());
+                buf.put_slice(&remaining[..len]);
+                self.current.set_position((position + len) as u64);
+                return Poll::Ready(Ok(()));
+            }
+            match ready!(self.chunks.as_mut().poll_next(cx)) {
+                Some(Ok(chunk)) => self.current = Cursor::new(chunk),
+                Some(Err(e)) => return Poll::Ready(Err(e)),
+                None => return Poll::Ready(Ok(())),
+            }
+        }
+    }
+}
+
 #[derive(Responder)]
 pub enum DownloadFileResponse {
     #[response(status = 404, content_type = "json")]
@@ -26,7 +89,10 @@ pub enum DownloadFileResponse {
     #[response(status = 500, content_type = "json")]
     FileDbError(Json<BasicMessage>),
     #[response(status = 200, content_type = "application/octet-stream")]
-    Success(File),
+    Success(StreamedContents),
+    /// for files that can be edited. The header is the ETag of the contents
+    #[response(status = 200, content_type = "application/octet-stream")]
+    SuccessWithETag(StreamedContents, Header<'static>),
     #[response(status = 401)]
     Unauthorized(String),
 }
@@ -34,7 +100,7 @@ pub enum DownloadFileResponse {
 #[derive(Responder)]
 pub enum CreateFileResponse {
     #[response(status = 201)]
-    Success(Json<FileApi>),
+    Success(Json<Box<FileApi>>),
     #[response(status = 401)]
     Unauthorized(String),
     #[response(status = 500, content_type = "json")]
@@ -43,6 +109,8 @@ pub enum CreateFileResponse {
     NotFound(Json<BasicMessage>),
     #[response(status = 400, content_type = "json")]
     AlreadyExists(Json<BasicMessage>),
+    #[response(status = 400, content_type = "json")]
+    BadRequest(Json<BasicMessage>),
 }
 
 #[derive(Responder)]
@@ -60,7 +128,7 @@ pub enum DeleteFileResponse {
 #[derive(Responder)]
 pub enum UpdateFileResponse {
     #[response(status = 200)]
-    Success(Json<FileApi>),
+    Success(Json<Box<FileApi>>),
     #[response(status = 401)]
     Unauthorized(String),
     #[response(status = 400, content_type = "json")]
@@ -71,8 +139,45 @@ pub enum UpdateFileResponse {
     GenericError(Json<BasicMessage>),
 }
 
+#[derive(Responder)]
+#[allow(clippy::large_enum_variant)]
+pub enum UpdateFileContentsResponse {
+    /// the header is the ETag of the new contents
+    #[response(status = 200)]
+    Success(Json<FileApi>, Header<'static>),
+    #[response(status = 401)]
+    Unauthorized(String),
+    #[response(status = 400, content_type = "json")]
+    BadRequest(Json<BasicMessage>),
+    #[response(status = 404, content_type = "json")]
+    NotFound(Json<BasicMessage>),
+    #[response(status = 412, content_type = "json")]
+    PreconditionFailed(Json<BasicMessage>),
+    #[response(status = 413, content_type = "json")]
+    TooLarge(Json<BasicMessage>),
+    #[response(status = 428, content_type = "json")]
+    PreconditionRequired(Json<BasicMessage>),
+    #[response(status = 500, content_type = "json")]
+    GenericError(Json<BasicMessage>),
+}
+
 #[derive(Responder, Debug)]
 pub enum SearchFileResponse {
+    /// the header is the total number of files matching the search, across all pages
+    #[response(status = 200)]
+    Success(Json<Vec<FileApi>>, Header<'static>),
+    #[response(status = 200)]
+    Facets(Json<SearchFacets>),
+    #[response(status = 400, content_type = "json")]
+    BadRequest(Json<BasicMessage>),
+    #[response(status = 401)]
+    Unauthorized(String),
+    #[response(status = 500, content_type = "json")]
+    GenericError(Json<BasicMessage>),
+}
+
+#[derive(Responder, Debug)]
+pub enum ListFilesResponse {
     #[response(status = 200)]
     Success(Json<Vec<FileApi>>),
     #[response(status = 400, content_type = "json")]
@@ -82,3 +187,28 @@ pub enum SearchFileResponse {
     #[response(status = 500, content_type = "json")]
     GenericError(Json<BasicMessage>),
 }
+
+#[derive(Responder)]
+pub enum PagedFilesResponse {
+    /// the header is the total number of files across all pages
+    #[response(status = 200)]
+    Success(Json<Vec<FileApi>>, Header<'static>),
+    #[response(status = 400, content_type = "json")]
+    BadRequest(Json<BasicMessage>),
+    #[response(status = 401)]
+    Unauthorized(String),
+    #[response(status = 500, content_type = "json")]
+    GenericError(Json<BasicMessage>),
+}
+
+#[derive(Responder)]
+pub enum FavoriteFileResponse {
+    #[response(status = 204)]
+    Success(NoContent),
+    #[response(status = 401)]
+    Unauthorized(String),
+    #[response(status = 404, content_type = "json")]
+    NotFound(Json<BasicMessage>),
+    #[response(status = 500, content_type = "json")]
+    GenericError(Json<BasicMessage>),
+}
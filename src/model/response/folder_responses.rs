@@ -1,12 +1,13 @@
-use std::collections::HashMap;
-use std::fs::File;
+use std::collections::{BTreeMap, HashMap};
 use std::ops::AddAssign;
 
 use rocket::serde::{Deserialize, Serialize, json::Json};
 
 use crate::model::api::FileApi;
-use crate::model::repository::Folder;
+use crate::model::repository::{Folder, ItemDetails};
+use crate::model::response::file_responses::StreamedContents;
 use crate::model::response::{BasicMessage, TaggedItemApi};
+use crate::saved_searches::models::SavedSearchApi;
 
 type NoContent = ();
 
@@ -21,6 +22,22 @@ pub struct FolderResponse {
     pub folders: Vec<FolderResponse>,
     pub files: Vec<FileApi>,
     pub tags: Vec<TaggedItemApi>,
+    /// free-text notes about the folder
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// arbitrary user-defined key/value pairs
+    #[serde(default)]
+    pub properties: BTreeMap<String, String>,
+    /// whether the folder has been starred. Not modifiable through folder updates
+    #[serde(default)]
+    pub favorite: bool,
+    /// the saved searches, which are browsed like folders through `GET /searches/<id>`. Only the root folder lists them
+    #[serde(
+        rename = "savedSearches",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub saved_searches: Option<Vec<SavedSearchApi>>,
 }
 
 impl AddAssign<Vec<TaggedItemApi>> for FolderResponse {
@@ -29,6 +46,13 @@ impl AddAssign<Vec<TaggedItemApi>> for FolderResponse {
     }
 }
 
+impl AddAssign<ItemDetails> for FolderResponse {
+    fn add_assign(&mut self, rhs: ItemDetails) {
+        self.description = rhs.description;
+        self.properties = rhs.properties;
+    }
+}
+
 impl AddAssign<Vec<FileApi>> for FolderResponse {
     fn add_assign(&mut self, rhs: Vec<FileApi>) {
         self.files = rhs;
@@ -55,6 +79,10 @@ impl From<Folder> for FolderResponse {
             folders: Vec::new(),
             files: Vec::new(),
             tags: Vec::new(),
+            description: None,
+            properties: BTreeMap::new(),
+            favorite: false,
+            saved_searches: None,
         }
     }
 }
@@ -74,7 +102,7 @@ pub enum GetFolderResponse {
 #[derive(Responder)]
 pub enum DownloadFolderResponse {
     #[response(status = 200, content_type = "application/x-tar")]
-    Success(File),
+    Success(StreamedContents),
     #[response(status = 400, content_type = "json")]
     BadRequest(Json<BasicMessage>),
     #[response(status = 401)]
@@ -135,6 +163,40 @@ pub enum DeleteFolderResponse {
     Unauthorized(String),
 }
 
+#[derive(Responder)]
+pub enum GetFavoriteFoldersResponse {
+    #[response(status = 200)]
+    Success(Json<Vec<FolderResponse>>),
+    #[response(status = 401)]
+    Unauthorized(String),
+    #[response(status = 500, content_type = "json")]
+    FolderDbError(Json<BasicMessage>),
+}
+
+#[derive(Responder, Debug)]
+pub enum SearchFolderResponse {
+    #[response(status = 200)]
+    Success(Json<Vec<FolderResponse>>),
+    #[response(status = 400, content_type = "json")]
+    BadRequest(Json<BasicMessage>),
+    #[response(status = 401)]
+    Unauthorized(String),
+    #[response(status = 500, content_type = "json")]
+    GenericError(Json<BasicMessage>),
+}
+
+#[derive(Responder)]
+pub enum FavoriteFolderResponse {
+    #[response(status = 204)]
+    Success(NoContent),
+    #[response(status = 401)]
+    Unauthorized(String),
+    #[response(status = 404, content_type = "json")]
+    FolderNotFound(Json<BasicMessage>),
+    #[response(status = 500, content_type = "json")]
+    FolderDbError(Json<BasicMessage>),
+}
+
 #[derive(Responder)]
 pub enum GetMultiPreviewResponse {
     /// takes a json string of Vec<Vec<u8>>
@@ -17,12 +17,60 @@ pub struct BasicMessage {
 
 /// this will be the same no matter if it's a request or a response. This is a bit
 /// different than how Files and Folders are
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Default)]
 #[serde(crate = "rocket::serde")]
 pub struct TagApi {
     /// will be None if new
     pub id: Option<u32>,
     pub title: String,
+    /// the id of the tag this one is nested under, if any
+    #[serde(default, rename = "parentId")]
+    pub parent_id: Option<u32>,
+    /// hex color clients show the tag in, e.g. `#ff8800`
+    #[serde(default)]
+    pub color: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// parsed from the title, and ignored in requests. See [Tag::namespace]
+    #[serde(default)]
+    pub namespace: Option<String>,
+    /// other titles that resolve to this tag when tagging and searching, e.g. `pic` for `picture`
+    #[serde(default)]
+    pub aliases: Vec<String>,
+}
+
+/// a tag along with how often it's used and every tag nested under it, used to list all tags as a tree
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(crate = "rocket::serde")]
+pub struct TagTreeApi {
+    pub id: u32,
+    pub title: String,
+    #[serde(rename = "parentId")]
+    pub parent_id: Option<u32>,
+    pub color: Option<String>,
+    pub description: Option<String>,
+    pub namespace: Option<String>,
+    pub aliases: Vec<String>,
+    /// how many files have the tag explicitly
+    #[serde(rename = "fileCount")]
+    pub file_count: u32,
+    /// how many folders have the tag explicitly
+    #[serde(rename = "folderCount")]
+    pub folder_count: u32,
+    /// how many files and folders inherit the tag from an ancestor folder
+    #[serde(rename = "implicitCount")]
+    pub implicit_count: u32,
+    /// the tags nested directly under this one, ordered by title
+    pub children: Vec<TagTreeApi>,
+}
+
+/// a tag namespace (e.g. `person` for `person:alice`) along with how many tags are in it
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(crate = "rocket::serde")]
+pub struct TagNamespaceApi {
+    pub name: String,
+    #[serde(rename = "tagCount")]
+    pub tag_count: u32,
 }
 
 /// represents a tag _on_ a file or folder, not just a standalone tag.
@@ -42,6 +90,20 @@ pub struct TaggedItemApi {
     pub implicit_from: Option<u32>,
 }
 
+/// which tags a file or folder doesn't inherit from the folders above it.
+/// A folder's blocks apply to everything under it too, but it still passes its own tags down.
+/// This is the same whether it's a request or a response
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[serde(crate = "rocket::serde")]
+pub struct TagInheritanceApi {
+    /// when true, no tags are inherited and `blocked_tags` is ignored
+    #[serde(default, rename = "blockAll")]
+    pub block_all: bool,
+    /// titles of the tags that aren't inherited, ordered by title. Tags that don't exist yet are created, and aliases resolve to the aliased tag
+    #[serde(default, rename = "blockedTags")]
+    pub blocked_tags: Vec<String>,
+}
+
 // ----------------------------------
 
 impl BasicMessage {
@@ -70,7 +132,12 @@ impl From<Tag> for TagApi {
     fn from(value: Tag) -> Self {
         TagApi {
             id: Some(value.id),
+            namespace: value.namespace(),
             title: value.title,
+            parent_id: value.parent_id,
+            color: value.color,
+            description: value.description,
+            aliases: Vec::new(),
         }
     }
 }
@@ -1,6 +1,8 @@
 use rocket::serde::json::Json;
 
-use crate::model::response::{BasicMessage, TagApi};
+use crate::model::response::{
+    BasicMessage, TagApi, TagInheritanceApi, TagNamespaceApi, TagTreeApi,
+};
 
 pub type NoContent = ();
 
@@ -16,10 +18,34 @@ pub enum GetTagResponse {
     Unauthorized(String),
 }
 
+#[derive(Responder)]
+pub enum ListTagsResponse {
+    #[response(status = 500, content_type = "json")]
+    TagDbError(Json<BasicMessage>),
+    #[response(status = 400, content_type = "json")]
+    BadRequest(Json<BasicMessage>),
+    #[response(status = 200)]
+    Success(Json<Vec<TagTreeApi>>),
+    #[response(status = 401)]
+    Unauthorized(String),
+}
+
+#[derive(Responder)]
+pub enum ListTagNamespacesResponse {
+    #[response(status = 500, content_type = "json")]
+    TagDbError(Json<BasicMessage>),
+    #[response(status = 200)]
+    Success(Json<Vec<TagNamespaceApi>>),
+    #[response(status = 401)]
+    Unauthorized(String),
+}
+
 #[derive(Responder)]
 pub enum CreateTagResponse {
     #[response(status = 500, content_type = "json")]
     TagDbError(Json<BasicMessage>),
+    #[response(status = 400, content_type = "json")]
+    BadRequest(Json<BasicMessage>),
     #[response(status = 401)]
     Unauthorized(String),
     #[response(status = 201, content_type = "json")]
@@ -34,6 +60,24 @@ pub enum UpdateTagResponse {
     TagDbError(Json<BasicMessage>),
     #[response(status = 400, content_type = "json")]
     TagAlreadyExists(Json<BasicMessage>),
+    #[response(status = 400, content_type = "json")]
+    BadParent(Json<BasicMessage>),
+    #[response(status = 400, content_type = "json")]
+    BadRequest(Json<BasicMessage>),
+    #[response(status = 200)]
+    Success(Json<TagApi>),
+    #[response(status = 401)]
+    Unauthorized(String),
+}
+
+#[derive(Responder)]
+pub enum MergeTagResponse {
+    #[response(status = 404, content_type = "json")]
+    TagNotFound(Json<BasicMessage>),
+    #[response(status = 500, content_type = "json")]
+    TagDbError(Json<BasicMessage>),
+    #[response(status = 400, content_type = "json")]
+    BadRequest(Json<BasicMessage>),
     #[response(status = 200)]
     Success(Json<TagApi>),
     #[response(status = 401)]
@@ -49,3 +93,15 @@ pub enum DeleteTagResponse {
     #[response(status = 204)]
     Success(NoContent),
 }
+
+#[derive(Responder)]
+pub enum TagInheritanceResponse {
+    #[response(status = 404, content_type = "json")]
+    NotFound(Json<BasicMessage>),
+    #[response(status = 500, content_type = "json")]
+    TagDbError(Json<BasicMessage>),
+    #[response(status = 200)]
+    Success(Json<TagInheritanceApi>),
+    #[response(status = 401)]
+    Unauthorized(String),
+}
@@ -32,3 +32,24 @@ pub fn get_folder_previews(
         }
     })
 }
+
+/// streams the previews of every file currently matching the saved search with the passed id
+#[get("/search/<id>")]
+pub fn get_saved_search_previews(
+    id: u32,
+    auth: HeaderAuth,
+    last_request_time: &State<Arc<Mutex<Instant>>>,
+) -> Result<EventStream![], GetFolderPreviewsError> {
+    match auth.validate() {
+        ValidateResult::Ok => { /*no op*/ }
+        ValidateResult::NoPasswordSet => return Err(GetFolderPreviewsError::Unauthorized("No password has been set. You can set a username and password by making a POST to `/api/password`".to_string())),
+        ValidateResult::Invalid => return Err(GetFolderPreviewsError::Unauthorized("Bad Credentials".to_string()))
+    };
+    update_last_request_time(last_request_time);
+    let data_stream = service::get_previews_for_saved_search(id)?;
+    Ok(EventStream! {
+        for await evt in data_stream {
+            yield evt.into();
+        }
+    })
+}
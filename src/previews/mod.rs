@@ -3,11 +3,12 @@ pub mod models;
 mod service;
 
 pub use service::{
-    delete_file_preview, generate_preview, get_file_preview, load_all_files_in_preview_queue,
+    delete_file_preview, generate_preview, get_file_preview, get_preview_ids,
+    load_all_files_in_preview_queue,
 };
 
 #[cfg(not(test))]
-fn preview_dir() -> String {
+pub fn preview_dir() -> String {
     "./file_previews".to_string()
 }
 
@@ -22,4 +23,4 @@ pub fn preview_dir() -> String {
 mod tests;
 
 #[cfg(test)]
-pub use service::{ensure_preview_dir, get_previews_for_folder};
+pub use service::{ensure_preview_dir, get_previews_for_folder, get_previews_for_saved_search};
@@ -1,13 +1,17 @@
 use super::preview_dir;
+use crate::encryption;
 use crate::model::error::file_errors::GetPreviewError;
 use crate::model::file_types::FileTypes;
+use crate::model::request::file_requests::SearchSort;
 use crate::model::response::BasicMessage;
 use crate::previews::models::{GetFolderPreviewsError, PreviewEvent};
 use crate::repository::{folder_repository, open_connection};
-use crate::service::file_service::{self, file_dir};
+use crate::saved_searches::{self, SavedSearchError};
+use crate::service::file_service;
+use crate::storage;
 use crate::{model::error::file_errors::GetFileError, service::file_service::get_file_path};
 use rocket::futures::{Stream, StreamExt, stream};
-use rocket::tokio::{self, fs};
+use rocket::tokio::fs;
 use std::backtrace::Backtrace;
 use std::io::ErrorKind;
 use std::path::{Path, PathBuf};
@@ -76,10 +80,7 @@ pub async fn generate_preview(message_data: String) -> bool {
         }
     };
     let path = match get_file_path(id) {
-        Ok(p) => {
-            let path = PathBuf::from(file_dir()).join(p);
-            path.to_string_lossy().into_owned()
-        }
+        Ok(p) => p,
         Err(GetFileError::NotFound) => {
             // file is no longer on disk, meaning it was deleted
             return true;
@@ -103,6 +104,18 @@ pub async fn generate_preview(message_data: String) -> bool {
         return true;
     }
 
+    // ffmpeg can only read plain files from the disk, so encrypted or remotely stored files get a temporary local copy instead
+    let local_file = match storage::local_copy(&path) {
+        Ok(f) => f,
+        Err(e) => {
+            log::error!(
+                "Failed to get a local copy of file id {id} to generate a preview. Exception is {e:?}\n{}",
+                Backtrace::force_capture()
+            );
+            return true;
+        }
+    };
+    let path = local_file.path().to_string_lossy().into_owned();
     let mut command = Command::new("ffmpeg");
     let is_gif = file_data
         .name()
@@ -139,6 +152,13 @@ pub async fn generate_preview(message_data: String) -> bool {
             "Failed to perform ffmpeg conversion for file with id [{id}]. Status code is {:?}",
             output.status.code()
         );
+    } else if encryption::is_enabled()
+        && let Err(e) = encryption::encrypt_file_in_place(&preview_file_path)
+    {
+        log::error!(
+            "Failed to encrypt preview for file id [{id}]. Exception is {e:?}\n{}",
+            Backtrace::force_capture()
+        );
     }
     true
 }
@@ -153,7 +173,13 @@ pub async fn generate_preview(message_data: String) -> bool {
 pub async fn get_file_preview(id: u32) -> Result<Vec<u8>, GetPreviewError> {
     let preview_path = format!("{}/{id}.png", preview_dir());
     match fs::read(&preview_path).await {
-        Ok(contents) => Ok(contents),
+        Ok(contents) => encryption::decrypt_contents(contents).map_err(|e| {
+            log::error!(
+                "Failed to decrypt preview file at path {preview_path}! Exception is {e:?}\n{}",
+                Backtrace::force_capture()
+            );
+            GetPreviewError::FileSystemError
+        }),
         Err(e) => {
             if e.kind() == std::io::ErrorKind::NotFound {
                 Err(GetPreviewError::NotFound)
@@ -189,6 +215,28 @@ pub fn delete_file_preview(id: u32) {
     }
 }
 
+/// lists the ids of every file that has a generated preview. A missing preview directory means no file has a preview
+pub fn get_preview_ids() -> Result<Vec<u32>, std::io::Error> {
+    let entries = match std::fs::read_dir(preview_dir()) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut ids = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().is_some_and(|it| it == "png")
+            && let Some(id) = path
+                .file_stem()
+                .and_then(|it| it.to_str())
+                .and_then(|it| it.parse().ok())
+        {
+            ids.push(id);
+        }
+    }
+    Ok(ids)
+}
+
 /// retrieves all file IDs from the database and publishes them to the preview generation queue.
 /// This function is intended to be called in a background thread as it may take some time
 /// to publish all messages depending on the number of files.
@@ -257,20 +305,59 @@ pub fn get_previews_for_folder(
         }
     };
     log::debug!("files: {files:?}");
-    let file_ids = files
-        .into_iter()
-        .map(|it| {
-            it.id
-                .expect("file id should never be None when pulled from database")
-        })
-        .map(|id| (id, PathBuf::from(preview_dir()).join(format!("{id}.png"))))
-        .filter(|it| it.1.exists());
-    Ok(stream::iter(file_ids)
-        .map(|(id, path)| async move {
-            let data = tokio::fs::read(path).await.unwrap_or_default();
-            PreviewEvent { id, data }
+    let file_ids = files.into_iter().map(|it| {
+        it.id
+            .expect("file id should never be None when pulled from database")
+    });
+    Ok(stream_previews(file_ids.collect()))
+}
+
+/// Retrieves the previews of every file currently matching the saved search with the passed id,
+/// in the same way as [get_previews_for_folder]
+///
+/// # Errors
+/// Returns [`GetFolderPreviewsError::NotFound`] when the saved search is missing, or
+/// [`GetFolderPreviewsError::Database`] when the search failed.
+pub fn get_previews_for_saved_search(
+    id: u32,
+) -> Result<impl Stream<Item = PreviewEvent>, GetFolderPreviewsError> {
+    let files = match saved_searches::service::search_saved_search_files(
+        id,
+        SearchSort::default(),
+        (u32::MAX, 0),
+    ) {
+        Ok((files, _)) => files,
+        Err(SavedSearchError::NotFound) => {
+            return Err(GetFolderPreviewsError::NotFound(BasicMessage::new(
+                "The saved search with the passed id could not be found.",
+            )));
+        }
+        Err(_) => {
+            return Err(GetFolderPreviewsError::Database(BasicMessage::new(
+                "Failed to evaluate saved search",
+            )));
+        }
+    };
+    Ok(stream_previews(files.into_iter().map(|it| it.id).collect()))
+}
+
+/// streams the previews for each of the passed files that has one.
+/// Previews that can't be read or decrypted are logged and skipped, see [get_file_preview]
+fn stream_previews(file_ids: Vec<u32>) -> impl Stream<Item = PreviewEvent> {
+    let file_ids = file_ids.into_iter().filter(|id| {
+        PathBuf::from(preview_dir())
+            .join(format!("{id}.png"))
+            .exists()
+    });
+    stream::iter(file_ids)
+        .map(|id| async move {
+            get_file_preview(id)
+                .await
+                .ok()
+                .map(|data| PreviewEvent { id, data })
         })
-        .buffer_unordered(2))
+        .buffer_unordered(2)
+        .filter_map(|it| async move { it })
 }
 
 /// checks if ffmpeg is installed on the system
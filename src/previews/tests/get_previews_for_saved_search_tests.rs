@@ -0,0 +1,42 @@
+use rocket::futures::StreamExt;
+use rocket::tokio;
+
+use crate::previews::get_previews_for_saved_search;
+use crate::previews::models::GetFolderPreviewsError;
+use crate::saved_searches::SavedSearchApi;
+use crate::saved_searches::service::create_saved_search;
+use crate::test::*;
+
+#[tokio::test]
+async fn should_return_previews_for_matching_files() {
+    init_db_folder();
+    create_file_db_entry("cat.png", None);
+    create_file_db_entry("dog.png", None);
+    create_file_preview(1);
+    create_file_preview(2);
+    create_saved_search(SavedSearchApi {
+        id: None,
+        title: "cats".to_string(),
+        search: "cat".to_string(),
+        tags: vec![],
+        attributes: vec![],
+    })
+    .unwrap();
+    let ids: Vec<u32> = get_previews_for_saved_search(1)
+        .unwrap()
+        .map(|it| it.id)
+        .collect()
+        .await;
+    assert_eq!(vec![1], ids);
+    cleanup();
+}
+
+#[test]
+fn should_return_not_found_for_missing_search() {
+    init_db_folder();
+    assert!(matches!(
+        get_previews_for_saved_search(1),
+        Err(GetFolderPreviewsError::NotFound(_))
+    ));
+    cleanup();
+}
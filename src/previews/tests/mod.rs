@@ -3,4 +3,5 @@ mod delete_file_preview_tests;
 mod generate_preview_tests;
 mod get_file_preview_tests;
 mod get_previews_for_folder_tests;
+mod get_previews_for_saved_search_tests;
 mod handler_tests;
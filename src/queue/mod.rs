@@ -49,6 +49,44 @@ pub mod queue {
         );
     }
 
+    /// sets up a long-running consumer job for indexing the contents of text-like files that invokes the passed [function](Fn)
+    /// whenever there are items in the rabbit queue
+    /// * `last_request_time` - the last time a request was made. Indexing will not occur as long as this value is less than the configured `QueueJobs.sleepTimeMillis` value
+    /// * `function` - the async function to be called on the value consumed from the queue. It must take the data
+    ///   as a [String] and output `true` if the operation was a success, and `false` if the operation was a failure
+    ///   That boolean status will be used to determine if the rabbit message should be acknowledged or not
+    pub fn content_index_consumer<F, Fut>(last_request_time: &Arc<Mutex<Instant>>, function: F)
+    where
+        F: Fn(String) -> Fut + Send + 'static,
+        Fut: Future<Output = bool> + Send,
+    {
+        consumer_impl(
+            "content_index",
+            "content_index_consumer",
+            last_request_time,
+            function,
+        );
+    }
+
+    /// sets up a long-running consumer job for applying auto tag rules to folders or every file that invokes the passed [function](Fn)
+    /// whenever there are items in the rabbit queue
+    /// * `last_request_time` - the last time a request was made. Rules will not be applied as long as this value is less than the configured `QueueJobs.sleepTimeMillis` value
+    /// * `function` - the async function to be called on the value consumed from the queue. It must take the data
+    ///   as a [String] and output `true` if the operation was a success, and `false` if the operation was a failure
+    ///   That boolean status will be used to determine if the rabbit message should be acknowledged or not
+    pub fn auto_tags_consumer<F, Fut>(last_request_time: &Arc<Mutex<Instant>>, function: F)
+    where
+        F: Fn(String) -> Fut + Send + 'static,
+        Fut: Future<Output = bool> + Send,
+    {
+        consumer_impl(
+            "auto_tags",
+            "auto_tags_consumer",
+            last_request_time,
+            function,
+        );
+    }
+
     fn consumer_impl<F, Fut>(
         queue_name: &'static str,
         consumer_name: &'static str,
@@ -198,6 +236,14 @@ pub mod queue {
                     .queue_declare("exif_process", queue_options, FieldTable::default())
                     .await
                     .unwrap();
+                channel
+                    .queue_declare("content_index", queue_options, FieldTable::default())
+                    .await
+                    .unwrap();
+                channel
+                    .queue_declare("auto_tags", queue_options, FieldTable::default())
+                    .await
+                    .unwrap();
                 (rabbit_connection, channel)
             });
             RabbitProvider {
@@ -241,5 +287,19 @@ pub mod queue {
     {
     }
 
+    pub fn content_index_consumer<F, Fut>(_: &Arc<Mutex<Instant>>, _: F)
+    where
+        F: Fn(String) -> Fut + Send + 'static,
+        Fut: Future<Output = bool> + Send,
+    {
+    }
+
+    pub fn auto_tags_consumer<F, Fut>(_: &Arc<Mutex<Instant>>, _: F)
+    where
+        F: Fn(String) -> Fut + Send + 'static,
+        Fut: Future<Output = bool> + Send,
+    {
+    }
+
     pub fn publish_message(_: &str, _: &str) {}
 }
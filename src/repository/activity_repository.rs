@@ -0,0 +1,190 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::NaiveDateTime;
+use itertools::Itertools;
+use rusqlite::Connection;
+
+use crate::model::repository::{FileActivity, Folder};
+
+/// stars the file with the passed `file_id`. Starring an already-starred file does nothing
+pub fn add_favorite_file(file_id: u32, con: &Connection) -> Result<(), rusqlite::Error> {
+    con.execute(
+        include_str!("../assets/queries/activity/add_favorite_file.sql"),
+        [file_id],
+    )?;
+    Ok(())
+}
+
+/// un-stars the file with the passed `file_id`. Un-starring a file that isn't starred does nothing
+pub fn remove_favorite_file(file_id: u32, con: &Connection) -> Result<(), rusqlite::Error> {
+    con.execute(
+        include_str!("../assets/queries/activity/remove_favorite_file.sql"),
+        [file_id],
+    )?;
+    Ok(())
+}
+
+/// stars the folder with the passed `folder_id`. Starring an already-starred folder does nothing
+pub fn add_favorite_folder(folder_id: u32, con: &Connection) -> Result<(), rusqlite::Error> {
+    con.execute(
+        include_str!("../assets/queries/activity/add_favorite_folder.sql"),
+        [folder_id],
+    )?;
+    Ok(())
+}
+
+/// un-stars the folder with the passed `folder_id`. Un-starring a folder that isn't starred does nothing
+pub fn remove_favorite_folder(folder_id: u32, con: &Connection) -> Result<(), rusqlite::Error> {
+    con.execute(
+        include_str!("../assets/queries/activity/remove_favorite_folder.sql"),
+        [folder_id],
+    )?;
+    Ok(())
+}
+
+/// returns which of the passed `folder_ids` have been starred
+pub fn get_favorite_folder_ids(
+    folder_ids: &[u32],
+    con: &Connection,
+) -> Result<HashSet<u32>, rusqlite::Error> {
+    let in_clause = folder_ids.iter().map(|it| format!("'{it}'")).join(",");
+    let query = format!(
+        include_str!("../assets/queries/activity/get_favorite_folder_ids.sql"),
+        in_clause
+    );
+    let mut pst = con.prepare(query.as_str())?;
+    let rows = pst.query_map([], |row| row.get(0))?;
+    rows.collect()
+}
+
+/// retrieves all starred folders, ordered by their path
+pub fn get_favorite_folders(con: &Connection) -> Result<Vec<Folder>, rusqlite::Error> {
+    let mut pst = con.prepare(include_str!(
+        "../assets/queries/activity/get_favorite_folders.sql"
+    ))?;
+    let rows = pst.query_map([], |row| {
+        Ok(Folder {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            parent_id: row.get(2)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// increments the download count of the file with the passed `file_id`, and sets its last accessed time to `accessed`
+pub fn record_file_download(
+    file_id: u32,
+    accessed: NaiveDateTime,
+    con: &Connection,
+) -> Result<(), rusqlite::Error> {
+    con.execute(
+        include_str!("../assets/queries/activity/record_file_download.sql"),
+        rusqlite::params![file_id, accessed],
+    )?;
+    Ok(())
+}
+
+/// retrieves the favorite status and download statistics for all the files with the passed `file_ids` in bulk.
+///
+/// Files that don't exist will not have an entry in the returned map
+pub fn get_activity_for_files(
+    file_ids: &[u32],
+    con: &Connection,
+) -> Result<HashMap<u32, FileActivity>, rusqlite::Error> {
+    let in_clause = file_ids.iter().map(|it| format!("'{it}'")).join(",");
+    let query = format!(
+        include_str!("../assets/queries/activity/get_activity_for_files.sql"),
+        in_clause
+    );
+    let mut pst = con.prepare(query.as_str())?;
+    let rows = pst.query_map([], |row| {
+        let download_count: Option<u32> = row.get(2)?;
+        Ok((
+            row.get(0)?,
+            FileActivity {
+                favorite: row.get(1)?,
+                download_count: download_count.unwrap_or_default(),
+                last_accessed: row.get(3)?,
+            },
+        ))
+    })?;
+    rows.collect()
+}
+
+#[cfg(test)]
+mod favorite_tests {
+    use super::*;
+    use crate::repository::open_connection;
+    use crate::test::{cleanup, create_file_db_entry, create_folder_db_entry, init_db_folder};
+
+    #[test]
+    fn add_favorite_file_is_idempotent() {
+        init_db_folder();
+        create_file_db_entry("test", None);
+        let con = open_connection();
+        add_favorite_file(1, &con).unwrap();
+        add_favorite_file(1, &con).unwrap();
+        let res = get_activity_for_files(&[1], &con).unwrap();
+        assert!(res.get(&1).unwrap().favorite);
+        remove_favorite_file(1, &con).unwrap();
+        let res = get_activity_for_files(&[1], &con).unwrap();
+        con.close().unwrap();
+        assert!(!res.get(&1).unwrap().favorite);
+        cleanup();
+    }
+
+    #[test]
+    fn get_favorite_folders_returns_full_path() {
+        init_db_folder();
+        create_folder_db_entry("top", None);
+        create_folder_db_entry("bottom", Some(1));
+        create_folder_db_entry("not starred", None);
+        let con = open_connection();
+        add_favorite_folder(2, &con).unwrap();
+        let res = get_favorite_folders(&con).unwrap();
+        let ids = get_favorite_folder_ids(&[1, 2, 3], &con).unwrap();
+        con.close().unwrap();
+        assert_eq!(
+            vec![Folder {
+                id: Some(2),
+                name: "top/bottom".to_string(),
+                parent_id: Some(1),
+            }],
+            res
+        );
+        assert_eq!(HashSet::from([2]), ids);
+        cleanup();
+    }
+}
+
+#[cfg(test)]
+mod record_file_download_tests {
+    use super::*;
+    use crate::repository::open_connection;
+    use crate::test::{cleanup, create_file_db_entry, init_db_folder, now};
+
+    #[test]
+    fn increments_download_count() {
+        init_db_folder();
+        create_file_db_entry("downloaded", None);
+        create_file_db_entry("not downloaded", None);
+        let con = open_connection();
+        let first = now();
+        record_file_download(1, first, &con).unwrap();
+        let second = first + chrono::Duration::seconds(5);
+        record_file_download(1, second, &con).unwrap();
+        let res = get_activity_for_files(&[1, 2], &con).unwrap();
+        con.close().unwrap();
+        assert_eq!(
+            &FileActivity {
+                favorite: false,
+                download_count: 2,
+                last_accessed: Some(second),
+            },
+            res.get(&1).unwrap()
+        );
+        assert_eq!(&FileActivity::default(), res.get(&2).unwrap());
+        cleanup();
+    }
+}
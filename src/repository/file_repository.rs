@@ -1,31 +1,45 @@
-use std::{backtrace::Backtrace, collections::HashSet};
+use std::backtrace::Backtrace;
+use std::rc::Rc;
 
 use chrono::NaiveDateTime;
+use rusqlite::types::Value;
 use rusqlite::{Connection, ToSql, params};
 
 /// a sql where clause part with named parameter tuple
-type WhereClause = (String, Option<(String, String)>);
+type WhereClause = (String, Vec<(String, String)>);
 
+use crate::exif::MapBounds;
+use crate::exif::repository as exif_repository;
 use crate::model::{
+    api::SearchFacets,
     file_types::FileTypes,
     repository::FileRecord,
     request::attributes::{
-        AliasedAttribute, AliasedComparisonTypes, AttributeSearch, AttributeTypes,
-        EqualityOperator, FileSizes, FullComparisonAttribute, FullComparisonTypes, NamedAttributes,
-        NamedComparisonAttribute,
+        AliasedAttribute, AliasedComparisonTypes, AttributeTypes, DetailAttribute, DetailTypes,
+        EqualityOperator, ExifAttribute, FileSizes, FullComparisonAttribute, FullComparisonTypes,
+        LocationAttribute, NamedAttributes, NamedComparisonAttribute, TagCountAttribute,
+        parse_radius,
     },
+    request::file_requests::{FileSort, SearchSort, SearchSortField, UnprocessedKind},
+    request::search_query::SearchQuery,
 };
+use crate::repository::title_search::TITLE_SCORE_FUNCTION;
 
 pub fn create_file(file: &FileRecord, con: &Connection) -> Result<u32, rusqlite::Error> {
     let mut pst = con
         .prepare(include_str!("../assets/queries/file/create_file.sql"))
         .unwrap();
 
+    let upload_date = file
+        .upload_date
+        .unwrap_or(chrono::offset::Local::now().naive_local());
     match pst.insert(params![
         file.name,
         file.size,
         file.create_date,
-        file.file_type
+        file.file_type,
+        upload_date,
+        file.modified_date.unwrap_or(upload_date)
     ]) {
         Ok(id) => Ok(id as u32),
         Err(e) => {
@@ -90,6 +104,8 @@ pub fn update_file(record: &FileRecord, con: &Connection) -> Result<(), rusqlite
         create_date: _,
         size: _,
         file_type,
+        upload_date: _,
+        modified_date: _,
     } = record;
     // now to rename the file
     update_name_pst.execute(rusqlite::params![file_name, file_type, file_id])?;
@@ -104,48 +120,189 @@ pub fn update_file(record: &FileRecord, con: &Connection) -> Result<(), rusqlite
     Ok(())
 }
 
-/// performs a fuzzy search using the passed criteria.
-/// The fuzzy search mashes all the fields together and performs a sql `LIKE` clause on the input
-pub fn search_files(criteria: &str, con: &Connection) -> Result<Vec<FileRecord>, rusqlite::Error> {
-    let criteria = format!("%{criteria}%");
-    let mut pst = con.prepare(include_str!("../assets/queries/file/search_files.sql"))?;
-    let rows = pst.query_map([&criteria], map_file_all_fields)?;
-    rows.into_iter().collect()
+/// updates the fields that change when a file's contents are replaced: size, type, and modified date
+pub fn update_file_contents(record: &FileRecord, con: &Connection) -> Result<(), rusqlite::Error> {
+    let mut pst = con.prepare(include_str!(
+        "../assets/queries/file/update_file_contents.sql"
+    ))?;
+    pst.execute(params![
+        record.size,
+        record.file_type,
+        record.modified_date,
+        record.id
+    ])?;
+    Ok(())
+}
+
+/// quotes every word in `criteria` so that it can be safely passed to an fts `match`. Quoted words are implicitly ANDed by sqlite
+fn build_fts_query(criteria: &str) -> String {
+    criteria
+        .split_whitespace()
+        .map(|word| format!("\"{}\"", word.replace('"', "\"\"")))
+        .collect::<Vec<String>>()
+        .join(" ")
 }
 
-pub fn search_files_by_tags(
-    tags: &HashSet<String>,
+/// retrieves a page of files matching the passed boolean `query` ordered by `sort`, compiled into a single sql query
+pub fn search_files_by_query(
+    query: &SearchQuery,
+    sort: SearchSort,
+    limit: u32,
+    offset: u32,
     con: &Connection,
-) -> Result<HashSet<FileRecord>, rusqlite::Error> {
-    let base_sql_string = include_str!("../assets/queries/file/get_files_by_all_tags.sql");
-    // need to fill out the in clause and the count clause
-    let joined_tags = tags
+) -> Result<Vec<FileRecord>, rusqlite::Error> {
+    let (sql, params) = build_search_query_sql(query, sort, limit, offset);
+    let params: Vec<(&str, &dyn ToSql)> = params
         .iter()
-        .map(|t| format!("'{}'", t.replace('\'', "''")))
-        .reduce(|combined, current| format!("{combined},{current}"))
-        .unwrap();
-    let replaced_string = base_sql_string
-        .to_string()
-        .replace("?1", joined_tags.as_str())
-        .replace("?2", tags.len().to_string().as_str());
-    log::debug!("built sql: {replaced_string}");
-    let mut pst = con.prepare(replaced_string.as_str())?;
-    let res = pst.query_map([], map_file_all_fields)?;
+        .map(|(pname, pvalue)| (pname.as_str(), pvalue as &dyn ToSql))
+        .collect();
+    let mut pst = con.prepare(&sql)?;
+    let res = pst.query_map(&params[..], map_file_all_fields)?;
     res.into_iter().collect()
 }
 
-pub fn search_files_by_attributes(
-    attributes: AttributeSearch,
+/// counts every file matching the passed boolean `query`, regardless of paging
+pub fn count_files_by_query(query: &SearchQuery, con: &Connection) -> Result<u32, rusqlite::Error> {
+    let mut params: Vec<(String, String)> = Vec::new();
+    let where_clause = convert_query_to_where_clause(query, &mut params);
+    let sql = format!(
+        "select count(*) from FileRecords f left join folder_files ff on ff.fileId = f.id where {where_clause}"
+    );
+    let params: Vec<(&str, &dyn ToSql)> = params
+        .iter()
+        .map(|(pname, pvalue)| (pname.as_str(), pvalue as &dyn ToSql))
+        .collect();
+    con.query_row(&sql, &params[..], |row| row.get(0))
+}
+
+/// checks if the file with the passed id matches the passed boolean `query`
+pub fn file_matches_query(
+    file_id: u32,
+    query: &SearchQuery,
     con: &Connection,
-) -> Result<HashSet<FileRecord>, rusqlite::Error> {
-    let (where_clause, params) = build_search_attribute_sql(attributes);
+) -> Result<bool, rusqlite::Error> {
+    let mut params: Vec<(String, String)> = Vec::new();
+    let where_clause = convert_query_to_where_clause(query, &mut params);
+    let placeholder = format!(":fileId{}", params.len());
+    params.push((placeholder.clone(), file_id.to_string()));
+    let sql = format!(
+        "select count(*) from FileRecords f left join folder_files ff on ff.fileId = f.id where f.id = {placeholder} and {where_clause}"
+    );
     let params: Vec<(&str, &dyn ToSql)> = params
         .iter()
         .map(|(pname, pvalue)| (pname.as_str(), pvalue as &dyn ToSql))
         .collect();
-    let mut pst = con.prepare(&where_clause)?;
-    let res = pst.query_map(&params[..], map_file_all_fields)?;
-    res.into_iter().collect()
+    let count: u32 = con.query_row(&sql, &params[..], |row| row.get(0))?;
+    Ok(count > 0)
+}
+
+/// counts how many files matching the passed boolean `query` have each file type, tag, size alias, and creation year
+pub fn get_search_facets(
+    query: &SearchQuery,
+    con: &Connection,
+) -> Result<SearchFacets, rusqlite::Error> {
+    let mut params: Vec<(String, String)> = Vec::new();
+    let where_clause = convert_query_to_where_clause(query, &mut params);
+    let bucket_end = |size: FileSizes| determine_file_size_range(size).1;
+    let sql = format!(
+        include_str!("../assets/queries/file/get_search_facets.sql"),
+        where_clause,
+        bucket_end(FileSizes::Tiny),
+        bucket_end(FileSizes::Small),
+        bucket_end(FileSizes::Medium),
+        bucket_end(FileSizes::Large)
+    );
+    let params: Vec<(&str, &dyn ToSql)> = params
+        .iter()
+        .map(|(pname, pvalue)| (pname.as_str(), pvalue as &dyn ToSql))
+        .collect();
+    let mut pst = con.prepare(&sql)?;
+    let mut rows = pst.query(&params[..])?;
+    let mut facets = SearchFacets::default();
+    while let Some(row) = rows.next()? {
+        let facet: String = row.get(0)?;
+        let value: String = row.get(1)?;
+        let count: u32 = row.get(2)?;
+        match facet.as_str() {
+            "fileType" => {
+                // unrecognized types all become Unknown, so they need to be added together
+                *facets
+                    .file_types
+                    .entry(FileTypes::from(value.as_str()))
+                    .or_default() += count;
+            }
+            "fileSize" => {
+                facets.file_sizes.insert(value, count);
+            }
+            "year" => {
+                facets.years.insert(value, count);
+            }
+            _ => {
+                facets.tags.insert(value, count);
+            }
+        }
+    }
+    Ok(facets)
+}
+
+/// retrieves a page of files that are missing any of the passed `kinds` of processing, oldest uploads first,
+/// along with the total number of files missing them
+///
+/// ## Parameters
+/// - `preview_ids`: the ids of every file that has a preview, since previews aren't stored in the database
+pub fn get_unprocessed_files(
+    kinds: &[UnprocessedKind],
+    preview_ids: &[u32],
+    limit: u32,
+    offset: u32,
+    con: &Connection,
+) -> Result<(Vec<FileRecord>, u32), rusqlite::Error> {
+    let media_clause = format!("f.type in ('{}', '{}')", FileTypes::Image, FileTypes::Video);
+    let clauses: Vec<String> = kinds
+        .iter()
+        .map(|kind| match kind {
+            UnprocessedKind::Tags => {
+                "not exists (select 1 from TaggedItems ti where ti.fileId = f.id)".to_string()
+            }
+            UnprocessedKind::Exif => format!(
+                "({media_clause} and not exists (select 1 from FileExif e where e.fileId = f.id))"
+            ),
+            UnprocessedKind::Preview => format!(
+                "({media_clause} and not exists (select 1 from rarray(:previewIds) p where p.value = f.id))"
+            ),
+        })
+        .collect();
+    let where_clause = if clauses.is_empty() {
+        "0".to_string()
+    } else {
+        clauses.join(" or ")
+    };
+    let preview_ids: Rc<Vec<Value>> = Rc::new(
+        preview_ids
+            .iter()
+            .map(|&it| Value::Integer(it.into()))
+            .collect(),
+    );
+    let mut params: Vec<(&str, &dyn ToSql)> = Vec::new();
+    if kinds.contains(&UnprocessedKind::Preview) {
+        params.push((":previewIds", &preview_ids));
+    }
+    let query = format!(
+        include_str!("../assets/queries/file/count_unprocessed_files.sql"),
+        where_clause
+    );
+    let total = con.query_row(query.as_str(), params.as_slice(), |row| row.get(0))?;
+    let query = format!(
+        include_str!("../assets/queries/file/get_unprocessed_files.sql"),
+        where_clause
+    );
+    params.push((":limit", &limit));
+    params.push((":offset", &offset));
+    let mut pst = con.prepare(query.as_str())?;
+    let files = pst
+        .query_map(params.as_slice(), map_file_all_fields)?
+        .collect::<Result<Vec<FileRecord>, rusqlite::Error>>()?;
+    Ok((files, total))
 }
 
 /// retrieves all [FileRecord]s from the database
@@ -155,6 +312,40 @@ pub fn get_all_files(con: &Connection) -> Result<Vec<FileRecord>, rusqlite::Erro
     rows.into_iter().collect()
 }
 
+/// retrieves a page of files ordered by `sort`, most recent first.
+///
+/// Files without a value for the sorted field (e.g. files that were never downloaded when sorting by [FileSort::LastAccessed]) are excluded
+pub fn get_recent_files(
+    sort: FileSort,
+    limit: u32,
+    offset: u32,
+    con: &Connection,
+) -> Result<Vec<FileRecord>, rusqlite::Error> {
+    let (column, direction) = file_sort_order(sort);
+    let query = format!(
+        include_str!("../assets/queries/file/get_recent_files.sql"),
+        column, direction
+    );
+    let mut pst = con.prepare(query.as_str())?;
+    let rows = pst.query_map([limit, offset], map_file_all_fields)?;
+    rows.into_iter().collect()
+}
+
+/// retrieves all starred files ordered by `sort`
+pub fn get_favorite_files(
+    sort: FileSort,
+    con: &Connection,
+) -> Result<Vec<FileRecord>, rusqlite::Error> {
+    let (column, direction) = file_sort_order(sort);
+    let query = format!(
+        include_str!("../assets/queries/file/get_favorite_files.sql"),
+        column, direction
+    );
+    let mut pst = con.prepare(query.as_str())?;
+    let rows = pst.query_map([], map_file_all_fields)?;
+    rows.into_iter().collect()
+}
+
 // right now clippy says it's unused, but it _will_ be used later
 pub fn get_all_file_ids(con: &Connection) -> Result<Vec<u32>, rusqlite::Error> {
     let mut pst = con.prepare(include_str!("../assets/queries/file/get_all_file_ids.sql"))?;
@@ -199,6 +390,8 @@ pub fn map_file_all_fields(row: &rusqlite::Row) -> Result<FileRecord, rusqlite::
     let file_type: String = row.get(4)?;
     let file_type: FileTypes = FileTypes::from(&file_type as &str);
     let parent_id = row.get(5)?;
+    let upload_date = row.get(6)?;
+    let modified_date = row.get(7)?;
     Ok(FileRecord {
         id,
         name,
@@ -206,38 +399,176 @@ pub fn map_file_all_fields(row: &rusqlite::Row) -> Result<FileRecord, rusqlite::
         create_date,
         size: size.try_into().unwrap_or(0),
         file_type,
+        upload_date,
+        modified_date,
     })
 }
 
-/// builds the entire sql query to search for files by attributes
+/// maps the passed sort to the column and direction used in an `order by` clause
+fn file_sort_order(sort: FileSort) -> (&'static str, &'static str) {
+    match sort {
+        FileSort::Name => ("lower(f.name)", "asc"),
+        FileSort::DateUploaded => ("f.dateUploaded", "desc"),
+        FileSort::LastAccessed => ("s.lastAccessed", "desc"),
+        FileSort::DownloadCount => ("s.downloadCount", "desc"),
+    }
+}
+
+/// maps the passed sort to the columns and directions used in an `order by` clause, adding the values it needs to `params`.
+///
+/// Files are ranked by relevance to the positive title terms in `query`
+fn search_sort_order(
+    sort: SearchSort,
+    query: &SearchQuery,
+    params: &mut Vec<(String, String)>,
+) -> String {
+    let direction = if sort.descending { "desc" } else { "asc" };
+    let column = match sort.field {
+        SearchSortField::Relevance => {
+            let terms = query.title_terms();
+            if terms.is_empty() {
+                return "lower(f.name) asc,\n    f.id asc".to_string();
+            }
+            let scores = terms
+                .into_iter()
+                .map(|term| {
+                    let placeholder = format!(":relevance{}", params.len());
+                    params.push((placeholder.clone(), term.to_string()));
+                    format!("{TITLE_SCORE_FUNCTION}(f.name, {placeholder})")
+                })
+                .collect::<Vec<String>>()
+                .join(" + ");
+            return format!("({scores}) {direction},\n    lower(f.name) asc,\n    f.id asc");
+        }
+        SearchSortField::Name => "lower(f.name)",
+        SearchSortField::Size => "f.fileSize",
+        SearchSortField::DateCreated => "f.dateCreated",
+        SearchSortField::Type => "f.type",
+    };
+    format!("{column} {direction},\n    f.id {direction}")
+}
+
+/// builds the entire sql query to retrieve a page of files matching `query`
 ///
 /// The first part of the returned tuple is the sql query with parameter placeholders.
 /// The second part of the returned tuple is the collection of parameters to be used for those placeholders
-fn build_search_attribute_sql(attributes: AttributeSearch) -> (String, Vec<(String, String)>) {
-    let base_sql = r"select
+fn build_search_query_sql(
+    query: &SearchQuery,
+    sort: SearchSort,
+    limit: u32,
+    offset: u32,
+) -> (String, Vec<(String, String)>) {
+    let mut params: Vec<(String, String)> = Vec::new();
+    let where_clause = convert_query_to_where_clause(query, &mut params);
+    let order_by = search_sort_order(sort, query, &mut params);
+    let sql = format!(
+        r"select
     f.id,
     f.name,
     f.fileSize,
     f.dateCreated,
     f.type,
-    ff.folderId
+    ff.folderId,
+    f.dateUploaded,
+    f.dateModified
 from
     FileRecords f
     left join folder_files ff on ff.fileId = f.id
-where ";
-    let mut where_clause = base_sql.to_string();
-    let mut params: Vec<(String, String)> = Vec::with_capacity(attributes.len());
-    for (arg_count, attr) in attributes.clone().into_iter().enumerate() {
-        let (sql, param_data) = convert_attribute_to_where_clause(attr, arg_count);
-        if let Some(p_data) = param_data {
-            params.push(p_data);
+where {where_clause}
+order by
+    {order_by}
+limit {limit} offset {offset}"
+    );
+    (sql, params)
+}
+
+/// recursively converts the passed `query` into a sql where clause, adding the values it needs to `params`.
+/// The number of params so far is used to keep parameter names unique.
+/// Queries from users are limited in how deeply they can be nested when they're parsed, which keeps the recursion here shallow
+fn convert_query_to_where_clause(
+    query: &SearchQuery,
+    params: &mut Vec<(String, String)>,
+) -> String {
+    let counter = params.len();
+    match query {
+        SearchQuery::And(queries) | SearchQuery::Or(queries) => {
+            let joiner = if matches!(query, SearchQuery::And(_)) {
+                " AND "
+            } else {
+                " OR "
+            };
+            let clauses: Vec<String> = queries
+                .iter()
+                .map(|it| convert_query_to_where_clause(it, params))
+                .collect();
+            format!("({})", clauses.join(joiner))
+        }
+        SearchQuery::Not(inner) => {
+            format!("NOT ({})", convert_query_to_where_clause(inner, params))
+        }
+        SearchQuery::Title(title) => {
+            let placeholder = format!(":title{counter}");
+            params.push((placeholder.clone(), title.clone()));
+            format!("{TITLE_SCORE_FUNCTION}(f.name, {placeholder}) > 0")
         }
-        where_clause += sql.as_str();
-        if arg_count < attributes.len() - 1 {
-            where_clause += " AND ";
+        SearchQuery::Tag(title) => {
+            let placeholder = format!(":tag{counter}");
+            params.push((placeholder.clone(), title.clone()));
+            // TaggedItems holds the tags inherited from parent folders too, so they don't need to be looked up separately.
+            // Tags nested under the searched tag count as the searched tag
+            let tag_ids = include_str!("../assets/queries/tags/get_tag_and_descendant_ids.sql")
+                .replace("?1", &placeholder);
+            format!(
+                "f.id in (select ti.fileId from TaggedItems ti where ti.fileId is not null and ti.tagId in ({tag_ids}))"
+            )
+        }
+        SearchQuery::Namespace(namespace) => {
+            let placeholder = format!(":namespace{counter}");
+            params.push((placeholder.clone(), namespace.clone()));
+            let tag_ids = include_str!("../assets/queries/tags/get_tag_ids_in_namespace.sql")
+                .replace("?1", &placeholder);
+            format!(
+                "f.id in (select ti.fileId from TaggedItems ti where ti.fileId is not null and ti.tagId in ({tag_ids}))"
+            )
+        }
+        SearchQuery::Content(words) => {
+            let fts_query = build_fts_query(words);
+            if fts_query.is_empty() {
+                return "0".to_string();
+            }
+            let placeholder = format!(":content{counter}");
+            params.push((placeholder.clone(), fts_query));
+            format!(
+                "f.id in (select rowid from FileContents where FileContents match {placeholder})"
+            )
+        }
+        SearchQuery::Attribute(attr) => {
+            let (sql, attr_params) = convert_attribute_to_where_clause(attr.clone(), counter);
+            params.extend(attr_params);
+            format!("({sql})")
+        }
+        // files in the root folder aren't in folder_files
+        SearchQuery::Folder { id: 0, recursive } => {
+            if *recursive {
+                "1".to_string()
+            } else {
+                "ff.folderId is null".to_string()
+            }
+        }
+        SearchQuery::Folder { id, recursive } => {
+            let placeholder = format!(":folder{counter}");
+            params.push((placeholder.clone(), id.to_string()));
+            if *recursive {
+                let child_folders =
+                    include_str!("../assets/queries/folder/get_child_folder_ids_recursive.sql")
+                        .replace("?1", &placeholder)
+                        .replace(';', "");
+                format!("(ff.folderId = {placeholder} or ff.folderId in ({child_folders}))")
+            } else {
+                format!("ff.folderId = {placeholder}")
+            }
         }
     }
-    (where_clause, params)
 }
 
 /// converts the passed attr to a tuple with both the where clause part and the parameters needed to populate
@@ -249,11 +580,17 @@ fn convert_attribute_to_where_clause(attr: AttributeTypes, counter: usize) -> Wh
         AttributeTypes::FullComp(at) => convert_full_comp_attribute_to_where_clause(at, counter),
         AttributeTypes::Named(at) => convert_named_comp_attribute_to_where_clause(at, counter),
         AttributeTypes::Aliased(at) => convert_aliased_attribute_to_where_clause(at, counter),
+        AttributeTypes::Detail(at) => convert_detail_attribute_to_where_clause(at, counter),
+        AttributeTypes::Exif(at) => convert_exif_attribute_to_where_clause(at, counter),
+        AttributeTypes::Location(at) => convert_location_attribute_to_where_clause(at),
+        AttributeTypes::TagCount(at) => convert_tag_count_attribute_to_where_clause(at, counter),
     }
 }
 
 /// converts the passed `attr` to a string that can be used in a sql where clause and the parameters needed to populate
-/// that where clause in a parameterized sql query
+/// that where clause in a parameterized sql query.
+///
+/// If the value is a `<start>,<end>` range (see [FullComparisonAttribute::value]), it's compared against both ends of the range
 /// * `attr` the attribute to generate parameters for
 /// * `counter` the counter used to keep track of how many parameters there are. This is _only_ used to make sure parameter names are unique, and is not updated by this function
 fn convert_full_comp_attribute_to_where_clause(
@@ -263,11 +600,41 @@ fn convert_full_comp_attribute_to_where_clause(
     let field_name = match attr.field {
         FullComparisonTypes::FileSize => "fileSize",
         FullComparisonTypes::DateCreated => "dateCreated",
+        FullComparisonTypes::DateUploaded => "dateUploaded",
+        FullComparisonTypes::DateModified => "dateModified",
     };
     let field_placeholder = format!(":{field_name}{counter}");
+    if let Some((start, end)) = attr.value.split_once(',') {
+        let end_placeholder = format!(":{field_name}End{counter}");
+        let sql = match attr.operator {
+            EqualityOperator::Lt => {
+                return (
+                    format!("{field_name} < {field_placeholder}"),
+                    vec![(field_placeholder, start.to_string())],
+                );
+            }
+            EqualityOperator::Gt => {
+                return (
+                    format!("{field_name} >= {end_placeholder}"),
+                    vec![(end_placeholder, end.to_string())],
+                );
+            }
+            EqualityOperator::Eq | EqualityOperator::Between => format!(
+                "({field_name} >= {field_placeholder} AND {field_name} < {end_placeholder})"
+            ),
+            EqualityOperator::Neq => {
+                format!("({field_name} < {field_placeholder} OR {field_name} >= {end_placeholder})")
+            }
+        };
+        let params = vec![
+            (field_placeholder, start.to_string()),
+            (end_placeholder, end.to_string()),
+        ];
+        return (sql, params);
+    }
     let op: &str = attr.operator.into();
     let sql = format!("{field_name} {op} {field_placeholder}");
-    (sql, Some((field_placeholder, attr.value)))
+    (sql, vec![(field_placeholder, attr.value)])
 }
 
 /// converts the passed `attr` to a string that can be used in a sql where clause and the parameters needed to populate
@@ -284,7 +651,7 @@ fn convert_named_comp_attribute_to_where_clause(
     let op: &str = attr.operator.into();
     let field_placeholder = format!(":{field_name}{counter}");
     let sql = format!("{field_name} {op} {field_placeholder}");
-    (sql, Some((field_placeholder, attr.value)))
+    (sql, vec![(field_placeholder, attr.value)])
 }
 
 /// converts the passed `attr` to a string that can be used in a sql where clause and the parameters needed to populate
@@ -303,7 +670,7 @@ fn convert_aliased_attribute_to_where_clause(
     };
     let field_placeholder = format!(":{field_name}{counter}");
     let sql = format!("{field_name} = {field_placeholder}");
-    (sql, Some((field_placeholder, attr.value)))
+    (sql, vec![(field_placeholder, attr.value)])
 }
 
 fn convert_aliased_file_size_to_where_clause(attr: AliasedAttribute) -> WhereClause {
@@ -331,10 +698,174 @@ fn convert_aliased_file_size_to_where_clause(attr: AliasedAttribute) -> WhereCla
                 format!("fileSize >= {max}")
             }
         }
-        EqualityOperator::Eq => format!("(fileSize >= {min} AND fileSize < {max})"),
+        EqualityOperator::Eq | EqualityOperator::Between => {
+            format!("(fileSize >= {min} AND fileSize < {max})")
+        }
         EqualityOperator::Neq => format!("(fileSize < {min} OR fileSize >= {max})"),
     };
-    (sql, None)
+    (sql, Vec::new())
+}
+
+/// converts the passed `attr` to a string that can be used in a sql where clause and the parameters needed to populate
+/// that where clause in a parameterized sql query
+///
+/// - descriptions are matched case-insensitively on whether they contain the value
+/// - properties are matched case-insensitively on their key. `lt` and `gt` compare numerically if the value is a number
+///
+/// `neq` also matches files that don't have the detail at all
+/// * `attr` the attribute to generate parameters for
+/// * `counter` the counter used to keep track of how many parameters there are. This is _only_ used to make sure parameter names are unique, and is not updated by this function
+fn convert_detail_attribute_to_where_clause(attr: DetailAttribute, counter: usize) -> WhereClause {
+    let in_clause = if attr.operator == EqualityOperator::Neq {
+        "not in"
+    } else {
+        "in"
+    };
+    match attr.field {
+        DetailTypes::Description => {
+            let field_placeholder = format!(":description{counter}");
+            let sql = format!(
+                "f.id {in_clause} (select fileId from ItemDescriptions where fileId is not null and lower(description) like {field_placeholder})"
+            );
+            let value = format!("%{}%", attr.value.to_lowercase());
+            (sql, vec![(field_placeholder, value)])
+        }
+        DetailTypes::Property(key) => {
+            let field_placeholder = format!(":property{counter}");
+            let key_placeholder = format!(":propertyKey{counter}");
+            let comparison = match attr.operator {
+                EqualityOperator::Eq | EqualityOperator::Neq => {
+                    format!("lower(value) = lower({field_placeholder})")
+                }
+                op if attr.value.parse::<f64>().is_ok() => {
+                    let op: &str = op.into();
+                    format!("cast(value as real) {op} cast({field_placeholder} as real)")
+                }
+                op => {
+                    let op: &str = op.into();
+                    format!("value {op} {field_placeholder}")
+                }
+            };
+            let sql = format!(
+                "f.id {in_clause} (select fileId from ItemProperties where fileId is not null and lower(key) = lower({key_placeholder}) and {comparison})"
+            );
+            (
+                sql,
+                vec![(key_placeholder, key), (field_placeholder, attr.value)],
+            )
+        }
+    }
+}
+
+/// converts the passed `attr` to a string that can be used in a sql where clause and the parameters needed to populate
+/// that where clause in a parameterized sql query
+///
+/// - text fields are matched ignoring case
+/// - number fields can use every operator, and `between` includes both ends
+///
+/// `neq` also matches files that don't have any EXIF data
+/// * `attr` the attribute to generate parameters for
+/// * `counter` the counter used to keep track of how many parameters there are. This is _only_ used to make sure parameter names are unique, and is not updated by this function
+fn convert_exif_attribute_to_where_clause(attr: ExifAttribute, counter: usize) -> WhereClause {
+    let column = attr.field.to_string();
+    let field_placeholder = format!(":{column}{counter}");
+    let in_clause = if attr.operator == EqualityOperator::Neq {
+        "not in"
+    } else {
+        "in"
+    };
+    let (comparison, params) = match attr.value.split_once(',') {
+        Some((min, max)) if attr.operator == EqualityOperator::Between => {
+            let end_placeholder = format!(":{column}End{counter}");
+            (
+                format!("{column} >= {field_placeholder} AND {column} <= {end_placeholder}"),
+                vec![
+                    (field_placeholder, min.to_string()),
+                    (end_placeholder, max.to_string()),
+                ],
+            )
+        }
+        _ => {
+            let comparison = if attr.field.is_text() {
+                format!("lower({column}) = lower({field_placeholder})")
+            } else if attr.operator == EqualityOperator::Neq {
+                // neq is handled by the not in clause, so that files without the field match too
+                format!("{column} = {field_placeholder}")
+            } else {
+                let op: &str = attr.operator.into();
+                format!("{column} {op} {field_placeholder}")
+            };
+            (comparison, vec![(field_placeholder, attr.value)])
+        }
+    };
+    let sql = format!("f.id {in_clause} (select fileId from FileExif where {comparison})");
+    (sql, params)
+}
+
+/// converts the passed `attr` to a string that can be used in a sql where clause along with its named parameters
+fn convert_tag_count_attribute_to_where_clause(
+    attr: TagCountAttribute,
+    counter: usize,
+) -> WhereClause {
+    let explicit_clause = if attr.explicit_only {
+        " and ti.implicitFromId is null"
+    } else {
+        ""
+    };
+    // a file can have the same tag both explicitly and inherited from a folder
+    let count = format!(
+        "(select count(distinct ti.tagId) from TaggedItems ti where ti.fileId = f.id{explicit_clause})"
+    );
+    let placeholder = format!(":tagCount{counter}");
+    // count doesn't have a column affinity, so the parameter needs to be converted to a number before comparing
+    match attr.value.split_once(',') {
+        Some((min, max)) if attr.operator == EqualityOperator::Between => {
+            let end_placeholder = format!(":tagCountEnd{counter}");
+            (
+                format!(
+                    "{count} between cast({placeholder} as integer) and cast({end_placeholder} as integer)"
+                ),
+                vec![
+                    (placeholder, min.to_string()),
+                    (end_placeholder, max.to_string()),
+                ],
+            )
+        }
+        _ => {
+            let op: &str = attr.operator.into();
+            (
+                format!("{count} {op} cast({placeholder} as integer)"),
+                vec![(placeholder, attr.value)],
+            )
+        }
+    }
+}
+
+/// converts the passed `attr` to a string that can be used in a sql where clause.
+/// The coordinates are validated numbers, so they don't need to be parameters
+fn convert_location_attribute_to_where_clause(attr: LocationAttribute) -> WhereClause {
+    // at this point the attr value should be validated, but just in case we need to match nothing
+    let condition = if attr.operator == EqualityOperator::Between {
+        MapBounds::try_from(attr.value.as_str())
+            .ok()
+            .map(exif_repository::location_bounds_clause)
+    } else {
+        parse_radius(&attr.value)
+            .ok()
+            .map(|(latitude, longitude, kilometers)| {
+                exif_repository::location_radius_clause(
+                    latitude,
+                    longitude,
+                    kilometers,
+                    attr.operator == EqualityOperator::Gt,
+                )
+            })
+    };
+    let sql = match condition {
+        Some(condition) => format!("f.id in (select fileId from FileExif where {condition})"),
+        None => "0".to_string(),
+    };
+    (sql, Vec::new())
 }
 
 /// determines a range of positive integers for a give [FileSizes] alias. These ranges are as follows:
@@ -360,13 +891,12 @@ fn determine_file_size_range(size: FileSizes) -> (u64, u64) {
 
 #[cfg(test)]
 mod get_files_by_all_tags_tests {
-    use std::collections::HashSet;
-
     use rusqlite::Connection;
 
     use crate::model::file_types::FileTypes;
     use crate::model::repository::FileRecord;
-    use crate::repository::file_repository::search_files_by_tags;
+    use crate::model::request::search_query::SearchQuery;
+    use crate::repository::file_repository::search_files_by_query;
     use crate::repository::open_connection;
     use crate::test::{cleanup, create_file_db_entry, create_tag_files, init_db_folder, now};
 
@@ -383,13 +913,12 @@ mod get_files_by_all_tags_tests {
         create_tag_files("asdf", vec![3, 4]);
         create_tag_files("fda", vec![2, 3, 4]);
 
-        let res = search_files_by_tags(
-            &HashSet::from(["tag1".to_string(), "fda".to_string(), "asdf".to_string()]),
-            &con,
-        )
-        .unwrap()
-        .into_iter()
-        .collect::<Vec<FileRecord>>();
+        let query = SearchQuery::And(vec![
+            SearchQuery::Tag("tag1".to_string()),
+            SearchQuery::Tag("fda".to_string()),
+            SearchQuery::Tag("asdf".to_string()),
+        ]);
+        let res = search_files_by_query(&query, Default::default(), 50, 0, &con).unwrap();
         con.close().unwrap();
         assert_eq!(2, res.len());
         assert!(res.contains(&FileRecord {
@@ -398,7 +927,9 @@ mod get_files_by_all_tags_tests {
             parent_id: None,
             create_date: now(),
             size: 0,
-            file_type: FileTypes::Unknown
+            file_type: FileTypes::Unknown,
+            upload_date: None,
+            modified_date: None,
         }));
         assert!(res.contains(&FileRecord {
             id: Some(4),
@@ -406,7 +937,9 @@ mod get_files_by_all_tags_tests {
             parent_id: None,
             create_date: now(),
             size: 0,
-            file_type: FileTypes::Unknown
+            file_type: FileTypes::Unknown,
+            upload_date: None,
+            modified_date: None,
         }));
         cleanup();
     }
@@ -436,6 +969,8 @@ mod create_file_tests {
             create_date,
             size,
             file_type,
+            upload_date: None,
+            modified_date: None,
         };
         let con = open_connection();
         super::create_file(&record, &con).unwrap();
@@ -448,6 +983,180 @@ mod create_file_tests {
         assert_eq!(file_type, retrieved.file_type);
         cleanup();
     }
+
+    #[test]
+    fn defaults_modified_date_to_upload_date() {
+        init_db_folder();
+        let record = FileRecord {
+            id: None,
+            name: "test.txt".to_string(),
+            parent_id: None,
+            create_date: now(),
+            size: 0,
+            file_type: FileTypes::Text,
+            upload_date: None,
+            modified_date: None,
+        };
+        let con = open_connection();
+        super::create_file(&record, &con).unwrap();
+        let retrieved = super::get_file(1, &con).unwrap();
+        con.close().unwrap();
+        assert!(retrieved.upload_date.is_some());
+        assert_eq!(retrieved.upload_date, retrieved.modified_date);
+        cleanup();
+    }
+}
+
+#[cfg(test)]
+mod get_recent_files_tests {
+    use chrono::Duration;
+
+    use crate::model::file_types::FileTypes;
+    use crate::model::repository::FileRecord;
+    use crate::model::request::file_requests::FileSort;
+    use crate::repository::{activity_repository, open_connection};
+    use crate::test::{cleanup, init_db_folder, now};
+
+    fn create_file(name: &str, minutes_ago: i64) {
+        FileRecord {
+            id: None,
+            name: name.to_string(),
+            parent_id: None,
+            create_date: now(),
+            size: 0,
+            file_type: FileTypes::Unknown,
+            upload_date: Some(now() - Duration::minutes(minutes_ago)),
+            modified_date: None,
+        }
+        .save_to_db();
+    }
+
+    #[test]
+    fn orders_by_upload_date_and_pages() {
+        init_db_folder();
+        create_file("oldest", 30);
+        create_file("newest", 0);
+        create_file("middle", 15);
+        let con = open_connection();
+        let first_page = super::get_recent_files(FileSort::DateUploaded, 2, 0, &con).unwrap();
+        let second_page = super::get_recent_files(FileSort::DateUploaded, 2, 2, &con).unwrap();
+        con.close().unwrap();
+        let first_page: Vec<String> = first_page.into_iter().map(|it| it.name).collect();
+        let second_page: Vec<String> = second_page.into_iter().map(|it| it.name).collect();
+        assert_eq!(vec!["newest", "middle"], first_page);
+        assert_eq!(vec!["oldest"], second_page);
+        cleanup();
+    }
+
+    #[test]
+    fn excludes_files_never_downloaded_when_sorting_by_download_count() {
+        init_db_folder();
+        create_file("once", 0);
+        create_file("never", 0);
+        create_file("twice", 0);
+        let con = open_connection();
+        activity_repository::record_file_download(1, now(), &con).unwrap();
+        activity_repository::record_file_download(3, now(), &con).unwrap();
+        activity_repository::record_file_download(3, now(), &con).unwrap();
+        let res = super::get_recent_files(FileSort::DownloadCount, 50, 0, &con).unwrap();
+        con.close().unwrap();
+        let res: Vec<String> = res.into_iter().map(|it| it.name).collect();
+        assert_eq!(vec!["twice", "once"], res);
+        cleanup();
+    }
+
+    #[test]
+    fn get_favorite_files_only_returns_starred_files() {
+        init_db_folder();
+        create_file("b", 0);
+        create_file("not starred", 0);
+        create_file("A", 0);
+        let con = open_connection();
+        activity_repository::add_favorite_file(1, &con).unwrap();
+        activity_repository::add_favorite_file(3, &con).unwrap();
+        let res = super::get_favorite_files(FileSort::Name, &con).unwrap();
+        con.close().unwrap();
+        let res: Vec<String> = res.into_iter().map(|it| it.name).collect();
+        assert_eq!(vec!["A", "b"], res);
+        cleanup();
+    }
+}
+
+#[cfg(test)]
+mod get_unprocessed_files_tests {
+    use crate::exif::ExifData;
+    use crate::exif::repository::save_file_exif;
+    use crate::model::request::file_requests::UnprocessedKind;
+    use crate::repository::open_connection;
+    use crate::test::{
+        cleanup, create_file_db_entry, create_folder_db_entry, create_tag_file, imply_tag_on_file,
+        init_db_folder,
+    };
+
+    fn unprocessed(kinds: &[UnprocessedKind], preview_ids: &[u32]) -> (Vec<String>, u32) {
+        let con = open_connection();
+        let (files, total) = super::get_unprocessed_files(kinds, preview_ids, 50, 0, &con).unwrap();
+        con.close().unwrap();
+        (files.into_iter().map(|it| it.name).collect(), total)
+    }
+
+    fn setup() {
+        init_db_folder();
+        create_file_db_entry("tagged.png", None); // 1
+        create_file_db_entry("untagged.png", None); // 2
+        create_file_db_entry("inherited.txt", None); // 3
+        create_file_db_entry("untagged.mp4", None); // 4
+        create_tag_file("tag", 1);
+        create_folder_db_entry("folder", None);
+        // tags inherited from a folder still count as tagged
+        imply_tag_on_file(1, 3, 1);
+        let con = open_connection();
+        save_file_exif(1, &ExifData::default(), &con).unwrap();
+        con.close().unwrap();
+    }
+
+    #[test]
+    fn lists_untagged_files() {
+        setup();
+        let (names, total) = unprocessed(&[UnprocessedKind::Tags], &[]);
+        cleanup();
+        assert_eq!(vec!["untagged.png", "untagged.mp4"], names);
+        assert_eq!(2, total);
+    }
+
+    #[test]
+    fn lists_media_without_exif_or_previews() {
+        setup();
+        let (exif, _) = unprocessed(&[UnprocessedKind::Exif], &[]);
+        let (previews, _) = unprocessed(&[UnprocessedKind::Preview], &[1, 4]);
+        let (no_previews, _) = unprocessed(&[UnprocessedKind::Preview], &[]);
+        cleanup();
+        assert_eq!(vec!["untagged.png", "untagged.mp4"], exif);
+        assert_eq!(vec!["untagged.png"], previews);
+        assert_eq!(
+            vec!["tagged.png", "untagged.png", "untagged.mp4"],
+            no_previews
+        );
+    }
+
+    #[test]
+    fn combines_kinds_and_pages() {
+        setup();
+        let con = open_connection();
+        let (files, total) = super::get_unprocessed_files(
+            &[UnprocessedKind::Exif, UnprocessedKind::Preview],
+            &[1, 2, 4],
+            1,
+            1,
+            &con,
+        )
+        .unwrap();
+        con.close().unwrap();
+        cleanup();
+        let names: Vec<String> = files.into_iter().map(|it| it.name).collect();
+        assert_eq!(vec!["untagged.mp4"], names);
+        assert_eq!(2, total);
+    }
 }
 
 #[cfg(test)]
@@ -486,7 +1195,7 @@ mod convert_named_comp_attribute_to_where_clause {
             operator: EqualityOperator::Eq,
         };
         let (_, params) = convert_named_comp_attribute_to_where_clause(attr, 23);
-        let (_, var) = params.unwrap();
+        let (_, var) = params[0].clone();
         assert_eq!("test".to_string(), var);
     }
 
@@ -509,7 +1218,7 @@ mod convert_named_comp_attribute_to_where_clause {
             operator: EqualityOperator::Eq,
         };
         let (_, params) = convert_named_comp_attribute_to_where_clause(attr, 10);
-        let (var_name, _) = params.unwrap();
+        let (var_name, _) = params[0].clone();
         assert_eq!(var_name, ":type10".to_string());
     }
 }
@@ -549,7 +1258,7 @@ mod convert_full_comp_attribute_to_where_clause {
             operator: EqualityOperator::Eq,
         };
         let (_, params) = convert_full_comp_attribute_to_where_clause(attr, 23);
-        let (_, var) = params.unwrap();
+        let (_, var) = params[0].clone();
         assert_eq!("test".to_string(), var);
     }
 
@@ -563,32 +1272,81 @@ mod convert_full_comp_attribute_to_where_clause {
         let (sql, _) = convert_full_comp_attribute_to_where_clause(attr, 23);
         assert_eq!("dateCreated <> :dateCreated23".to_string(), sql);
     }
+
+    #[test]
+    fn compares_ranges_against_both_ends() {
+        let clause = |operator| {
+            convert_full_comp_attribute_to_where_clause(
+                FullComparisonAttribute {
+                    field: FullComparisonTypes::DateCreated,
+                    value: "2021-01-01,2022-01-01".to_string(),
+                    operator,
+                },
+                4,
+            )
+        };
+        let start = (":dateCreated4".to_string(), "2021-01-01".to_string());
+        let end = (":dateCreatedEnd4".to_string(), "2022-01-01".to_string());
+        assert_eq!(
+            (
+                "(dateCreated >= :dateCreated4 AND dateCreated < :dateCreatedEnd4)".to_string(),
+                vec![start.clone(), end.clone()]
+            ),
+            clause(EqualityOperator::Between)
+        );
+        assert_eq!(
+            clause(EqualityOperator::Between),
+            clause(EqualityOperator::Eq)
+        );
+        assert_eq!(
+            (
+                "(dateCreated < :dateCreated4 OR dateCreated >= :dateCreatedEnd4)".to_string(),
+                vec![start.clone(), end.clone()]
+            ),
+            clause(EqualityOperator::Neq)
+        );
+        assert_eq!(
+            ("dateCreated < :dateCreated4".to_string(), vec![start]),
+            clause(EqualityOperator::Lt)
+        );
+        assert_eq!(
+            ("dateCreated >= :dateCreatedEnd4".to_string(), vec![end]),
+            clause(EqualityOperator::Gt)
+        );
+    }
 }
 
 #[cfg(test)]
-mod build_search_attribute_sql {
+mod build_search_query_sql {
     use super::*;
     use crate::model::request::attributes::*;
 
     #[test]
     fn handles_single_param() {
-        let attributes = vec![AttributeTypes::FullComp(FullComparisonAttribute {
+        let attribute = AttributeTypes::FullComp(FullComparisonAttribute {
             field: FullComparisonTypes::FileSize,
             operator: EqualityOperator::Eq,
             value: "5000".to_string(),
-        })];
+        });
         let expected = r"select
     f.id,
     f.name,
     f.fileSize,
     f.dateCreated,
     f.type,
-    ff.folderId
+    ff.folderId,
+    f.dateUploaded,
+    f.dateModified
 from
     FileRecords f
     left join folder_files ff on ff.fileId = f.id
-where fileSize = :fileSize0";
-        let (actual, _) = build_search_attribute_sql(AttributeSearch { attributes });
+where (fileSize = :fileSize0)
+order by
+    lower(f.name) asc,
+    f.id asc
+limit 50 offset 0";
+        let query = SearchQuery::Attribute(attribute);
+        let (actual, _) = build_search_query_sql(&query, SearchSort::default(), 50, 0);
         assert_eq!(expected, actual);
     }
 
@@ -612,27 +1370,55 @@ where fileSize = :fileSize0";
     f.fileSize,
     f.dateCreated,
     f.type,
-    ff.folderId
+    ff.folderId,
+    f.dateUploaded,
+    f.dateModified
 from
     FileRecords f
     left join folder_files ff on ff.fileId = f.id
-where (fileSize >= 104857600 AND fileSize < 1073741824) AND type <> :type1";
-        let (actual, _) = build_search_attribute_sql(AttributeSearch { attributes });
+where (((fileSize >= 104857600 AND fileSize < 1073741824)) AND (type <> :type0))
+order by
+    f.fileSize desc,
+    f.id desc
+limit 10 offset 20";
+        let query = SearchQuery::And(attributes.into_iter().map(SearchQuery::Attribute).collect());
+        let sort = SearchSort {
+            field: SearchSortField::Size,
+            descending: true,
+        };
+        let (actual, _) = build_search_query_sql(&query, sort, 10, 20);
         assert_eq!(expected, actual);
     }
 }
 
 #[cfg(test)]
 mod search_files_by_attributes {
+    use std::collections::{BTreeMap, HashSet};
+
     use chrono::NaiveTime;
 
     use super::*;
     use crate::{
-        model::request::attributes::*,
-        repository::open_connection,
+        model::{repository::ItemDetails, request::attributes::*},
+        repository::{open_connection, property_repository},
         test::{cleanup, init_db_folder, now},
     };
 
+    fn search_attributes(
+        attributes: AttributeSearch,
+        con: &Connection,
+    ) -> Result<HashSet<FileRecord>, rusqlite::Error> {
+        let query = SearchQuery::And(
+            attributes
+                .attributes
+                .into_iter()
+                .map(SearchQuery::Attribute)
+                .collect(),
+        );
+        search_files_by_query(&query, SearchSort::default(), 50, 0, con)
+            .map(|it| it.into_iter().collect())
+    }
+
     #[test]
     fn properly_retrieves_files_with_1_attr() {
         init_db_folder();
@@ -643,6 +1429,8 @@ mod search_files_by_attributes {
             create_date: now(),
             size: 5 * 1024 * 1024 * 1024,
             file_type: FileTypes::Text,
+            upload_date: None,
+            modified_date: None,
         }
         .save_to_db();
         let _bad = FileRecord {
@@ -652,6 +1440,8 @@ mod search_files_by_attributes {
             create_date: now(),
             size: 5 * 1024,
             file_type: FileTypes::Image,
+            upload_date: None,
+            modified_date: None,
         }
         .save_to_db();
         let attributes = vec![AttributeTypes::FullComp(FullComparisonAttribute {
@@ -660,7 +1450,7 @@ mod search_files_by_attributes {
             value: "1073741824".to_string(),
         })];
         let con = open_connection();
-        let res = search_files_by_attributes(AttributeSearch { attributes }, &con);
+        let res = search_attributes(AttributeSearch { attributes }, &con);
         con.close().unwrap();
         let expected: HashSet<FileRecord> = [good].into_iter().collect();
         assert_eq!(Ok(expected), res);
@@ -682,6 +1472,8 @@ mod search_files_by_attributes {
                 // `Large` size
                 size: 100 * 1024 * 1024,
                 file_type: FileTypes::Image,
+                upload_date: None,
+                modified_date: None,
             }
             .save_to_db(),
             FileRecord {
@@ -692,6 +1484,8 @@ mod search_files_by_attributes {
                 // `ExtraLarge` size
                 size: 1024 * 1024 * 1024,
                 file_type: FileTypes::Text,
+                upload_date: None,
+                modified_date: None,
             }
             .save_to_db(),
         ]
@@ -705,6 +1499,8 @@ mod search_files_by_attributes {
             // `Medium` size
             size: 10 * 1024 * 1024,
             file_type: FileTypes::Application,
+            upload_date: None,
+            modified_date: None,
         }
         .save_to_db();
         FileRecord {
@@ -715,6 +1511,8 @@ mod search_files_by_attributes {
             // `small` size
             size: 10 * 1024,
             file_type: FileTypes::Image,
+            upload_date: None,
+            modified_date: None,
         }
         .save_to_db();
         // must not be an application, must be newer than 5 days ago, and must be larger than medium
@@ -738,13 +1536,343 @@ mod search_files_by_attributes {
             ],
         };
         let con = open_connection();
-        let actual = search_files_by_attributes(search, &con);
+        let actual = search_attributes(search, &con);
+        con.close().unwrap();
+        assert_eq!(Ok(expected), actual);
+        cleanup();
+    }
+
+    #[test]
+    fn properly_retrieves_files_in_ranges() {
+        init_db_folder();
+        let created = |y, m, d| {
+            NaiveDateTime::new(
+                chrono::NaiveDate::from_ymd_opt(y, m, d).unwrap(),
+                NaiveTime::from_hms_opt(23, 0, 0).unwrap(),
+            )
+        };
+        let file = |name: &str, create_date, size| {
+            FileRecord {
+                id: None,
+                name: name.to_string(),
+                parent_id: None,
+                create_date,
+                size,
+                file_type: FileTypes::Text,
+                upload_date: None,
+                modified_date: None,
+            }
+            .save_to_db()
+        };
+        let good = file("good", created(2021, 12, 31), 2 * 1024 * 1024);
+        file("too old", created(2020, 12, 31), 2 * 1024 * 1024);
+        file("too new", created(2022, 1, 1), 2 * 1024 * 1024);
+        file("too big", created(2021, 6, 15), 3 * 1024 * 1024 + 1);
+        let search = AttributeSearch::try_from(vec![
+            "dateCreated.eq;2021".to_string(),
+            "fileSize.between;1MB,3MB".to_string(),
+        ])
+        .unwrap();
+        let con = open_connection();
+        let actual = search_attributes(search, &con);
+        con.close().unwrap();
+        let expected: HashSet<FileRecord> = [good].into_iter().collect();
+        assert_eq!(Ok(expected), actual);
+        cleanup();
+    }
+
+    #[test]
+    fn properly_retrieves_files_on_a_day() {
+        init_db_folder();
+        let today = chrono::Local::now().date_naive();
+        let file = |name: &str, create_date| {
+            FileRecord {
+                id: None,
+                name: name.to_string(),
+                parent_id: None,
+                create_date,
+                size: 0,
+                file_type: FileTypes::Text,
+                upload_date: None,
+                modified_date: None,
+            }
+            .save_to_db()
+        };
+        let good = file(
+            "today",
+            NaiveDateTime::new(today, NaiveTime::from_hms_opt(23, 0, 0).unwrap()),
+        );
+        let yesterday = file(
+            "yesterday",
+            NaiveDateTime::new(
+                today.pred_opt().unwrap(),
+                NaiveTime::from_hms_opt(23, 0, 0).unwrap(),
+            ),
+        );
+        let search = |attr: &str| {
+            let con = open_connection();
+            let res = search_attributes(
+                AttributeSearch::try_from(vec![attr.to_string()]).unwrap(),
+                &con,
+            );
+            con.close().unwrap();
+            res
+        };
+        let today_files: HashSet<FileRecord> = [good].into_iter().collect();
+        assert_eq!(Ok(today_files), search("dateCreated.eq;today"));
+        // the day being searched on isn't after itself
+        let after_yesterday = search(&format!("dateCreated.gt;{}", today.pred_opt().unwrap()));
+        assert!(!after_yesterday.unwrap().contains(&yesterday));
+        cleanup();
+    }
+
+    #[test]
+    fn properly_retrieves_files_with_exif() {
+        init_db_folder();
+        let file = |name: &str| {
+            FileRecord {
+                id: None,
+                name: name.to_string(),
+                parent_id: None,
+                create_date: now(),
+                size: 0,
+                file_type: FileTypes::Image,
+                upload_date: None,
+                modified_date: None,
+            }
+            .save_to_db()
+        };
+        let good = file("good");
+        let wrong_camera = file("wrong camera");
+        let too_small = file("too small");
+        // files without exif data never match positive searches
+        file("no exif");
+        let con = open_connection();
+        let exif = |model: &str, width| crate::exif::ExifData {
+            camera_model: Some(model.to_string()),
+            width: Some(width),
+            ..Default::default()
+        };
+        crate::exif::repository::save_file_exif(good.id.unwrap(), &exif("Pixel 7", 4000), &con)
+            .unwrap();
+        crate::exif::repository::save_file_exif(
+            wrong_camera.id.unwrap(),
+            &exif("Canon", 4000),
+            &con,
+        )
+        .unwrap();
+        crate::exif::repository::save_file_exif(
+            too_small.id.unwrap(),
+            &exif("pixel 7", 1000),
+            &con,
+        )
+        .unwrap();
+        let search = AttributeSearch::try_from(vec![
+            "cameraModel.eq;PIXEL 7".to_string(),
+            "width.between;3000,4000".to_string(),
+        ])
+        .unwrap();
+        let actual = search_attributes(search, &con);
         con.close().unwrap();
+        let expected: HashSet<FileRecord> = [good].into_iter().collect();
+        assert_eq!(Ok(expected), actual);
+        cleanup();
+    }
+
+    #[test]
+    fn properly_retrieves_files_by_location() {
+        init_db_folder();
+        let file = |name: &str| {
+            FileRecord {
+                id: None,
+                name: name.to_string(),
+                parent_id: None,
+                create_date: now(),
+                size: 0,
+                file_type: FileTypes::Image,
+                upload_date: None,
+                modified_date: None,
+            }
+            .save_to_db()
+        };
+        let paris = file("paris");
+        let versailles = file("versailles");
+        let london = file("london");
+        file("no location");
+        let con = open_connection();
+        let save = |record: &FileRecord, latitude, longitude| {
+            let exif = crate::exif::ExifData {
+                location: Some(crate::exif::FileLocation {
+                    latitude,
+                    longitude,
+                    altitude: None,
+                }),
+                ..Default::default()
+            };
+            crate::exif::repository::save_file_exif(record.id.unwrap(), &exif, &con).unwrap();
+        };
+        save(&paris, 48.8566, 2.3522);
+        save(&versailles, 48.8049, 2.1204);
+        save(&london, 51.5072, -0.1276);
+        let search = |attribute: &str| {
+            search_attributes(
+                AttributeSearch::try_from(vec![attribute.to_string()]).unwrap(),
+                &con,
+            )
+            .unwrap()
+        };
+        let in_france = search("location.between;41,-5,51,10");
+        // versailles is ~18km from the center of paris
+        let near_paris = search("location.lt;48.8566,2.3522,10");
+        let far_from_paris = search("location.gt;48.8566,2.3522,10");
+        con.close().unwrap();
+        assert_eq!(
+            [paris.clone(), versailles.clone()]
+                .into_iter()
+                .collect::<HashSet<FileRecord>>(),
+            in_france
+        );
+        assert_eq!(
+            [paris].into_iter().collect::<HashSet<FileRecord>>(),
+            near_paris
+        );
+        assert_eq!(
+            [versailles, london]
+                .into_iter()
+                .collect::<HashSet<FileRecord>>(),
+            far_from_paris
+        );
+        cleanup();
+    }
+
+    #[test]
+    fn properly_retrieves_files_by_tag_count() {
+        init_db_folder();
+        crate::test::create_file_db_entry("untagged", None);
+        crate::test::create_file_db_entry("explicit", None);
+        crate::test::create_file_db_entry("implicit", None);
+        crate::test::create_file_db_entry("both", None);
+        crate::test::create_tag_files("a", vec![2, 4]);
+        crate::test::create_tag_file("b", 2);
+        crate::test::create_folder_db_entry("folder", None);
+        crate::test::imply_tag_on_file(1, 3, 1);
+        crate::test::imply_tag_on_file(2, 4, 1);
+        let con = open_connection();
+        let search = |attribute: &str| -> Vec<u32> {
+            let mut ids: Vec<u32> = search_attributes(
+                AttributeSearch::try_from(vec![attribute.to_string()]).unwrap(),
+                &con,
+            )
+            .unwrap()
+            .into_iter()
+            .map(|it| it.id.unwrap())
+            .collect();
+            ids.sort();
+            ids
+        };
+        assert_eq!(vec![1], search("tagCount.eq;0"));
+        assert_eq!(vec![1, 3], search("explicitTagCount.eq;0"));
+        assert_eq!(vec![2, 4], search("tagCount.gt;1"));
+        assert_eq!(vec![2], search("explicitTagCount.gt;1"));
+        assert_eq!(vec![1, 3], search("tagCount.between;0,1"));
+        assert_eq!(vec![2, 3, 4], search("tagCount.neq;0"));
+        con.close().unwrap();
+        cleanup();
+    }
+
+    #[test]
+    fn properly_retrieves_files_with_details() {
+        init_db_folder();
+        let good = FileRecord {
+            id: None,
+            name: "good".to_string(),
+            parent_id: None,
+            create_date: now(),
+            size: 0,
+            file_type: FileTypes::Image,
+            upload_date: None,
+            modified_date: None,
+        }
+        .save_to_db();
+        let bad = FileRecord {
+            id: None,
+            name: "bad".to_string(),
+            parent_id: None,
+            create_date: now(),
+            size: 0,
+            file_type: FileTypes::Image,
+            upload_date: None,
+            modified_date: None,
+        }
+        .save_to_db();
+        let con = open_connection();
+        let good_details = ItemDetails {
+            description: Some("Beach trip with the family".to_string()),
+            properties: BTreeMap::from([("camera".to_string(), "Pixel 7".to_string())]),
+        };
+        let bad_details = ItemDetails {
+            description: Some("beach trip".to_string()),
+            properties: BTreeMap::from([("camera".to_string(), "Canon".to_string())]),
+        };
+        property_repository::set_file_details(good.id.unwrap(), &good_details, &con).unwrap();
+        property_repository::set_file_details(bad.id.unwrap(), &bad_details, &con).unwrap();
+        let search = AttributeSearch {
+            attributes: vec![
+                AttributeTypes::Detail(DetailAttribute {
+                    field: DetailTypes::Description,
+                    value: "BEACH".to_string(),
+                    operator: EqualityOperator::Eq,
+                }),
+                AttributeTypes::Detail(DetailAttribute {
+                    field: DetailTypes::Property("Camera".to_string()),
+                    value: "pixel 7".to_string(),
+                    operator: EqualityOperator::Eq,
+                }),
+            ],
+        };
+        let actual = search_attributes(search, &con);
+        con.close().unwrap();
+        let expected: HashSet<FileRecord> = [good].into_iter().collect();
         assert_eq!(Ok(expected), actual);
         cleanup();
     }
 }
 
+#[cfg(test)]
+mod convert_exif_attribute_to_where_clause {
+    use super::*;
+    use crate::model::request::attributes::*;
+
+    #[test]
+    fn neq_includes_files_without_exif() {
+        let attr = ExifAttribute {
+            field: ExifTypes::Iso,
+            value: "100".to_string(),
+            operator: EqualityOperator::Neq,
+        };
+        let (sql, params) = convert_exif_attribute_to_where_clause(attr, 2);
+        assert_eq!(
+            "f.id not in (select fileId from FileExif where iso = :iso2)",
+            sql
+        );
+        assert_eq!(vec![(":iso2".to_string(), "100".to_string())], params);
+    }
+
+    #[test]
+    fn text_ignores_case() {
+        let attr = ExifAttribute {
+            field: ExifTypes::CameraMake,
+            value: "Google".to_string(),
+            operator: EqualityOperator::Eq,
+        };
+        let (sql, _) = convert_exif_attribute_to_where_clause(attr, 0);
+        assert_eq!(
+            "f.id in (select fileId from FileExif where lower(cameraMake) = lower(:cameraMake0))",
+            sql
+        );
+    }
+}
+
 #[cfg(test)]
 mod convert_aliased_file_size_to_where_clause {
     use super::*;
@@ -823,6 +1951,79 @@ mod convert_aliased_file_size_to_where_clause {
     }
 }
 
+#[cfg(test)]
+mod convert_detail_attribute_to_where_clause {
+    use super::*;
+    use crate::model::request::attributes::*;
+
+    #[test]
+    fn description_uses_contains() {
+        let attr = DetailAttribute {
+            field: DetailTypes::Description,
+            value: "Vacation".to_string(),
+            operator: EqualityOperator::Eq,
+        };
+        let (sql, params) = convert_detail_attribute_to_where_clause(attr, 3);
+        assert_eq!(
+            "f.id in (select fileId from ItemDescriptions where fileId is not null and lower(description) like :description3)",
+            sql
+        );
+        assert_eq!(
+            vec![(":description3".to_string(), "%vacation%".to_string())],
+            params
+        );
+    }
+
+    #[test]
+    fn neq_excludes_matching_files() {
+        let attr = DetailAttribute {
+            field: DetailTypes::Property("camera".to_string()),
+            value: "pixel".to_string(),
+            operator: EqualityOperator::Neq,
+        };
+        let (sql, _) = convert_detail_attribute_to_where_clause(attr, 0);
+        assert_eq!(
+            "f.id not in (select fileId from ItemProperties where fileId is not null and lower(key) = lower(:propertyKey0) and lower(value) = lower(:property0))",
+            sql
+        );
+    }
+
+    #[test]
+    fn property_compares_numerically_for_numbers() {
+        let attr = DetailAttribute {
+            field: DetailTypes::Property("Rating".to_string()),
+            value: "3".to_string(),
+            operator: EqualityOperator::Gt,
+        };
+        let (sql, _) = convert_detail_attribute_to_where_clause(attr, 1);
+        assert_eq!(
+            "f.id in (select fileId from ItemProperties where fileId is not null and lower(key) = lower(:propertyKey1) and cast(value as real) > cast(:property1 as real))",
+            sql
+        );
+    }
+
+    #[test]
+    fn property_binds_key() {
+        let attr = DetailAttribute {
+            field: DetailTypes::Property("it's".to_string()),
+            value: "b".to_string(),
+            operator: EqualityOperator::Lt,
+        };
+        let (sql, params) = convert_detail_attribute_to_where_clause(attr, 1);
+        assert_eq!(
+            "f.id in (select fileId from ItemProperties where fileId is not null and lower(key) = lower(:propertyKey1) and value < :property1)",
+            sql
+        );
+        assert_eq!(
+            vec![
+                (":propertyKey1".to_string(), "it's".to_string()),
+                (":property1".to_string(), "b".to_string())
+            ],
+            params
+        );
+    }
+}
+
 #[cfg(test)]
 mod get_all_ancestors_tests {
     use super::*;
@@ -897,3 +2098,309 @@ mod get_all_ancestors_tests {
         cleanup();
     }
 }
+
+#[cfg(test)]
+mod search_files_by_query_tests {
+    use crate::content_index::repository::index_file_contents;
+    use crate::model::request::search_query::SearchQuery;
+    use crate::repository::file_repository::{
+        count_files_by_query, file_matches_query, search_files_by_query,
+    };
+    use crate::repository::open_connection;
+    use crate::test::{
+        cleanup, create_file_db_entry, create_folder_db_entry, create_tag_file, create_tag_files,
+        create_tag_folder, imply_tag_on_file, init_db_folder,
+    };
+
+    fn search(query: &str) -> Vec<u32> {
+        let con = open_connection();
+        let query = SearchQuery::try_from(query).unwrap();
+        let mut ids: Vec<u32> = search_files_by_query(&query, Default::default(), 50, 0, &con)
+            .unwrap()
+            .into_iter()
+            .map(|it| it.id.unwrap())
+            .collect();
+        con.close().unwrap();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn combines_tags_types_and_negation() {
+        init_db_folder();
+        create_file_db_entry("cat.png", None); // 1
+        create_file_db_entry("dog.jpg", None); // 2
+        create_file_db_entry("blurry cat.png", None); // 3
+        create_file_db_entry("cat video.mp4", None); // 4
+        create_file_db_entry("bird.png", None); // 5
+        create_tag_files("cats", vec![1, 3, 4]);
+        create_tag_file("dogs", 2);
+        create_tag_file("blurry", 3);
+        assert_eq!(
+            vec![1, 2],
+            search("(tag:cats OR tag:dogs) AND type:image AND NOT tag:blurry")
+        );
+        assert_eq!(vec![5], search("NOT tag:cats NOT tag:dogs"));
+        cleanup();
+    }
+
+    #[test]
+    fn matches_tags_nested_under_searched_tag() {
+        init_db_folder();
+        create_file_db_entry("cat.png", None); // 1
+        create_file_db_entry("kitten.png", None); // 2
+        create_file_db_entry("animal.png", None); // 3
+        create_file_db_entry("car.png", None); // 4
+        create_tag_file("animal", 3);
+        create_tag_file("cat", 1);
+        create_tag_file("kitten", 2);
+        create_tag_file("car", 4);
+        let con = open_connection();
+        con.execute_batch(
+            "update Tags set parentId = 1 where id = 2; update Tags set parentId = 2 where id = 3;",
+        )
+        .unwrap();
+        con.close().unwrap();
+        assert_eq!(vec![1, 2, 3], search("tag:animal"));
+        assert_eq!(vec![1, 2], search("tag:cat"));
+        assert_eq!(vec![2], search("tag:kitten"));
+        assert_eq!(vec![4], search("NOT tag:animal"));
+        cleanup();
+    }
+
+    #[test]
+    fn matches_tag_aliases() {
+        init_db_folder();
+        create_file_db_entry("beach.png", None); // 1
+        create_file_db_entry("clip.mp4", None); // 2
+        create_tag_file("picture", 1);
+        create_tag_file("video", 2);
+        let con = open_connection();
+        crate::tags::repository::set_tag_aliases(1, &["pic".to_string()], &con).unwrap();
+        con.close().unwrap();
+        assert_eq!(vec![1], search("tag:pic"));
+        assert_eq!(vec![1], search("tag:PIC"));
+        assert_eq!(vec![2], search("NOT tag:pic"));
+        cleanup();
+    }
+
+    #[test]
+    fn matches_tags_in_namespace() {
+        init_db_folder();
+        create_file_db_entry("alice.png", None); // 1
+        create_file_db_entry("bob.png", None); // 2
+        create_file_db_entry("garage.png", None); // 3
+        create_file_db_entry("person.png", None); // 4
+        create_tag_file("person:alice", 1);
+        create_tag_file("Person:bob", 2);
+        create_tag_file("project:garage", 3);
+        create_tag_file("person", 4);
+        assert_eq!(vec![1, 2], search("namespace:PERSON"));
+        assert_eq!(vec![3], search("namespace:project NOT namespace:person"));
+        assert_eq!(Vec::<u32>::new(), search("namespace:place"));
+        cleanup();
+    }
+
+    #[test]
+    fn matches_titles_and_attributes() {
+        init_db_folder();
+        create_file_db_entry("Report.pdf", None);
+        create_file_db_entry("report.txt", None);
+        create_file_db_entry("notes.txt", None);
+        assert_eq!(vec![1, 2], search("report"));
+        assert_eq!(vec![2, 3], search("fileType.eq;text"));
+        assert_eq!(vec![1, 3], search("report fileType.neq;text OR notes"));
+        cleanup();
+    }
+
+    #[test]
+    fn matches_inherited_tags() {
+        init_db_folder();
+        create_folder_db_entry("folder", None);
+        create_file_db_entry("inside", Some(1));
+        create_file_db_entry("outside", None);
+        create_tag_folder("tag", 1);
+        imply_tag_on_file(1, 1, 1);
+        assert_eq!(vec![1], search("tag:tag"));
+        cleanup();
+    }
+
+    #[test]
+    fn matches_contents() {
+        init_db_folder();
+        create_file_db_entry("a.txt", None);
+        create_file_db_entry("b.txt", None);
+        let con = open_connection();
+        index_file_contents(1, "hello world", &con).unwrap();
+        index_file_contents(2, "goodbye world", &con).unwrap();
+        con.close().unwrap();
+        assert_eq!(vec![1, 2], search("content:world"));
+        assert_eq!(vec![2], search("content:world NOT content:hello"));
+        cleanup();
+    }
+
+    #[test]
+    fn scopes_to_folder() {
+        init_db_folder();
+        create_folder_db_entry("top", None); // 1
+        create_folder_db_entry("middle", Some(1)); // 2
+        create_folder_db_entry("bottom", Some(2)); // 3
+        create_folder_db_entry("other", None); // 4
+        create_file_db_entry("root invoice", None); // 1
+        create_file_db_entry("top invoice", Some(1)); // 2
+        create_file_db_entry("middle invoice", Some(2)); // 3
+        create_file_db_entry("bottom invoice", Some(3)); // 4
+        create_file_db_entry("other invoice", Some(4)); // 5
+        let scoped = |id: u32, recursive: bool| {
+            let con = open_connection();
+            let query = SearchQuery::And(vec![
+                SearchQuery::Title("invoice".to_string()),
+                SearchQuery::Folder { id, recursive },
+            ]);
+            let mut ids: Vec<u32> = search_files_by_query(&query, Default::default(), 50, 0, &con)
+                .unwrap()
+                .into_iter()
+                .map(|it| it.id.unwrap())
+                .collect();
+            let total = count_files_by_query(&query, &con).unwrap();
+            con.close().unwrap();
+            ids.sort();
+            assert_eq!(ids.len() as u32, total);
+            ids
+        };
+        assert_eq!(vec![2], scoped(1, false));
+        assert_eq!(vec![2, 3, 4], scoped(1, true));
+        assert_eq!(vec![3, 4], scoped(2, true));
+        assert_eq!(vec![1], scoped(0, false));
+        assert_eq!(vec![1, 2, 3, 4, 5], scoped(0, true));
+        cleanup();
+    }
+
+    #[test]
+    fn ranks_fuzzy_title_matches_by_relevance() {
+        init_db_folder();
+        create_file_db_entry("old receipt scan.pdf", None);
+        create_file_db_entry("receipt.pdf", None);
+        create_file_db_entry("receive.txt", None);
+        create_file_db_entry("recipt.pdf", None);
+        let con = open_connection();
+        let query = SearchQuery::try_from("recipt").unwrap();
+        let ids: Vec<u32> = search_files_by_query(&query, Default::default(), 50, 0, &con)
+            .unwrap()
+            .into_iter()
+            .map(|it| it.id.unwrap())
+            .collect();
+        con.close().unwrap();
+        // receive is too many typos away
+        assert_eq!(vec![4, 2, 1], ids);
+        cleanup();
+    }
+
+    #[test]
+    fn treats_values_as_parameters() {
+        init_db_folder();
+        create_file_db_entry("bobby'); drop table FileRecords; --", None);
+        assert_eq!(vec![1], search(r#""bobby'); drop table FileRecords; --""#));
+        assert_eq!(vec![1], search("bobby"));
+        cleanup();
+    }
+
+    #[test]
+    fn file_matches_query_only_checks_the_file() {
+        init_db_folder();
+        create_file_db_entry("cat.png", None);
+        create_file_db_entry("dog.png", None);
+        create_tag_file("cute", 2);
+        let con = open_connection();
+        let query = SearchQuery::try_from("cat OR tag:cute").unwrap();
+        assert!(file_matches_query(1, &query, &con).unwrap());
+        assert!(file_matches_query(2, &query, &con).unwrap());
+        let query = SearchQuery::try_from("cat AND tag:cute").unwrap();
+        assert!(!file_matches_query(1, &query, &con).unwrap());
+        assert!(!file_matches_query(3, &query, &con).unwrap());
+        con.close().unwrap();
+        cleanup();
+    }
+}
+
+#[cfg(test)]
+mod get_search_facets_tests {
+    use std::collections::BTreeMap;
+
+    use chrono::NaiveDateTime;
+
+    use crate::model::api::SearchFacets;
+    use crate::model::file_types::FileTypes;
+    use crate::model::repository::FileRecord;
+    use crate::model::request::search_query::SearchQuery;
+    use crate::repository::file_repository::get_search_facets;
+    use crate::repository::open_connection;
+    use crate::test::{
+        cleanup, create_folder_db_entry, create_tag_file, create_tag_files, imply_tag_on_file,
+        init_db_folder,
+    };
+
+    fn file(name: &str, file_type: FileTypes, size: u64, year: i32) {
+        FileRecord {
+            id: None,
+            name: name.to_string(),
+            parent_id: None,
+            create_date: NaiveDateTime::parse_from_str(
+                &format!("{year}-06-15 12:00:00"),
+                "%Y-%m-%d %H:%M:%S",
+            )
+            .unwrap(),
+            size,
+            file_type,
+            upload_date: None,
+            modified_date: None,
+        }
+        .save_to_db();
+    }
+
+    #[test]
+    fn counts_only_matching_files() {
+        init_db_folder();
+        file("beach 1", FileTypes::Image, 100, 2021);
+        file("beach 2", FileTypes::Image, 2 * 1024 * 1024, 2022);
+        file("beach 3", FileTypes::Video, 2 * 1024 * 1024 * 1024, 2022);
+        file("mountain", FileTypes::Image, 100, 2021);
+        create_tag_files("beach", vec![1, 2, 4]);
+        create_tag_file("family", 4);
+        create_folder_db_entry("trips", None);
+        imply_tag_on_file(1, 3, 1);
+        let con = open_connection();
+        let facets = get_search_facets(&SearchQuery::Title("beach".to_string()), &con).unwrap();
+        con.close().unwrap();
+        assert_eq!(
+            BTreeMap::from([(FileTypes::Image, 2), (FileTypes::Video, 1)]),
+            facets.file_types
+        );
+        assert_eq!(BTreeMap::from([("beach".to_string(), 3)]), facets.tags);
+        assert_eq!(
+            BTreeMap::from([
+                ("tiny".to_string(), 1),
+                ("small".to_string(), 1),
+                ("extraLarge".to_string(), 1)
+            ]),
+            facets.file_sizes
+        );
+        assert_eq!(
+            BTreeMap::from([("2021".to_string(), 1), ("2022".to_string(), 2)]),
+            facets.years
+        );
+        cleanup();
+    }
+
+    #[test]
+    fn returns_no_facets_without_matches() {
+        init_db_folder();
+        file("beach", FileTypes::Image, 100, 2021);
+        create_tag_file("beach", 1);
+        let con = open_connection();
+        let facets = get_search_facets(&SearchQuery::Title("nothing".to_string()), &con).unwrap();
+        con.close().unwrap();
+        assert_eq!(SearchFacets::default(), facets);
+        cleanup();
+    }
+}
@@ -1,7 +1,7 @@
 use std::backtrace::Backtrace;
 use std::collections::HashSet;
 
-use rusqlite::{Connection, Rows, params};
+use rusqlite::{Connection, Rows, ToSql, params};
 
 use crate::model::repository;
 use crate::repository::file_repository;
@@ -233,6 +233,60 @@ pub fn get_ancestor_folders_with_id(
     Ok(ids)
 }
 
+/// retrieves all folders whose name contains `name` (ignoring case) and that have every tag in `tags`,
+/// including tags inherited from a parent folder. Folders are ordered by their path, and their name is their full path.
+///
+/// Tags are resolved the same way as in file searches: titles ignore case, aliases resolve to their tag,
+/// and tags nested under a searched tag count as the searched tag
+pub fn search_folders(
+    name: &str,
+    tags: &HashSet<String>,
+    con: &Connection,
+) -> Result<Vec<repository::Folder>, rusqlite::Error> {
+    let mut clauses: Vec<String> = Vec::new();
+    let mut params: Vec<(String, String)> = Vec::new();
+    if !name.is_empty() {
+        clauses.push("lower(f.name) like :name escape '\\'".to_string());
+        params.push((
+            ":name".to_string(),
+            format!("%{}%", escape_like(&name.to_lowercase())),
+        ));
+    }
+    for (index, tag) in tags.iter().enumerate() {
+        let placeholder = format!(":tag{index}");
+        let tag_ids = include_str!("../assets/queries/tags/get_tag_and_descendant_ids.sql")
+            .replace("?1", &placeholder);
+        clauses.push(format!(
+            "f.id in (select ti.folderId from TaggedItems ti where ti.folderId is not null and ti.tagId in ({tag_ids}))"
+        ));
+        params.push((placeholder, tag.clone()));
+    }
+    let where_clause = if clauses.is_empty() {
+        "1".to_string()
+    } else {
+        clauses.join(" AND ")
+    };
+    let sql = format!(
+        include_str!("../assets/queries/folder/search_folders.sql"),
+        where_clause
+    );
+    let params: Vec<(&str, &dyn ToSql)> = params
+        .iter()
+        .map(|(pname, pvalue)| (pname.as_str(), pvalue as &dyn ToSql))
+        .collect();
+    let mut pst = con.prepare(&sql)?;
+    let rows = pst.query_map(&params[..], map_folder)?;
+    rows.into_iter().collect()
+}
+
+/// escapes the `%` and `_` wildcards in `value` with a backslash, so that it matches literally in a `like ... escape` clause
+fn escape_like(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
 fn map_folder(row: &rusqlite::Row) -> Result<repository::Folder, rusqlite::Error> {
     let id: Option<u32> = row.get(0)?;
     let name: String = row.get(1)?;
@@ -370,3 +424,84 @@ mod get_ancestor_folders_with_id {
         cleanup();
     }
 }
+
+#[cfg(test)]
+mod search_folders_tests {
+    use std::collections::HashSet;
+
+    use crate::repository::folder_repository::search_folders;
+    use crate::repository::open_connection;
+    use crate::test::{
+        cleanup, create_folder_db_entry, create_tag_folder, create_tag_folders, init_db_folder,
+    };
+
+    fn search(name: &str, tags: &[&str]) -> Vec<String> {
+        let con = open_connection();
+        let tags: HashSet<String> = tags.iter().map(|it| it.to_string()).collect();
+        let res = search_folders(name, &tags, &con).unwrap();
+        con.close().unwrap();
+        res.into_iter().map(|it| it.name).collect()
+    }
+
+    #[test]
+    fn matches_name_ignoring_case() {
+        init_db_folder();
+        create_folder_db_entry("documents", None); // 1
+        create_folder_db_entry("2019 Taxes", Some(1)); // 2
+        create_folder_db_entry("2020 taxes", Some(1)); // 3
+        create_folder_db_entry("photos", None); // 4
+        assert_eq!(
+            vec!["documents/2019 Taxes", "documents/2020 taxes"],
+            search("TAXES", &[])
+        );
+        cleanup();
+    }
+
+    #[test]
+    fn matches_explicit_and_inherited_tags() {
+        init_db_folder();
+        create_folder_db_entry("old", None); // 1
+        create_folder_db_entry("inner", Some(1)); // 2
+        create_folder_db_entry("new", None); // 3
+        create_tag_folders("archive", vec![1]);
+        create_tag_folder("important", 2);
+        let con = open_connection();
+        crate::tags::repository::add_implicit_tag_to_folders(1, &[2], 1, &con).unwrap();
+        con.close().unwrap();
+        assert_eq!(vec!["old", "old/inner"], search("", &["archive"]));
+        assert_eq!(vec!["old/inner"], search("", &["archive", "important"]));
+        assert_eq!(vec!["old/inner"], search("inner", &["archive"]));
+        assert!(search("new", &["archive"]).is_empty());
+        cleanup();
+    }
+
+    #[test]
+    fn matches_tag_aliases_case_and_descendants() {
+        init_db_folder();
+        create_folder_db_entry("pets", None); // 1
+        create_folder_db_entry("kittens", None); // 2
+        create_folder_db_entry("cars", None); // 3
+        create_tag_folder("Animal", 1);
+        let con = open_connection();
+        let kitten = crate::tags::repository::create_tag("kitten", Some(1), &con).unwrap();
+        crate::tags::repository::add_explicit_tag_to_folder(2, kitten.id, &con).unwrap();
+        crate::tags::repository::set_tag_aliases(1, &["critter".to_string()], &con).unwrap();
+        con.close().unwrap();
+        assert_eq!(vec!["kittens", "pets"], search("", &["animal"]));
+        assert_eq!(vec!["kittens", "pets"], search("", &["critter"]));
+        assert_eq!(vec!["kittens"], search("", &["KITTEN"]));
+        cleanup();
+    }
+
+    #[test]
+    fn treats_like_wildcards_literally() {
+        init_db_folder();
+        create_folder_db_entry("100% done", None); // 1
+        create_folder_db_entry("1000 done", None); // 2
+        create_folder_db_entry("my_stuff", None); // 3
+        create_folder_db_entry("my stuff", None); // 4
+        assert_eq!(vec!["100% done"], search("0%", &[]));
+        assert_eq!(vec!["my_stuff"], search("_", &[]));
+        cleanup();
+    }
+}
@@ -6,14 +6,19 @@ use rusqlite::OpenFlags;
 use rusqlite::{Connection, Result};
 
 use crate::db_migrations::migrate_db;
+use crate::encryption;
 use crate::model::file_types::FileTypes;
 use crate::model::repository::{FileRecord, Folder};
 use crate::queue;
-use crate::service::file_service::{determine_file_type, file_dir};
+use crate::service::file_service::determine_file_type;
+use crate::storage;
 
+pub mod activity_repository;
 pub mod file_repository;
 pub mod folder_repository;
 pub mod metadata_repository;
+pub mod property_repository;
+pub mod title_search;
 
 #[cfg(test)]
 mod tests;
@@ -27,7 +32,7 @@ pub fn open_connection() -> Connection {
         Path::new(FILE_SERVER_CONFIG.clone().database.location.as_str()),
         OpenFlags::default(),
     ) {
-        Ok(con) => con,
+        Ok(con) => with_functions(con),
         Err(error) => panic!("Failed to get a connection to the database!: {error}"),
     }
 }
@@ -36,11 +41,23 @@ pub fn open_connection() -> Connection {
 pub fn open_connection() -> Connection {
     let db_name = format!("{}.sqlite", crate::test::current_thread_name());
     match Connection::open_with_flags(Path::new(db_name.as_str()), rusqlite::OpenFlags::default()) {
-        Ok(con) => con,
+        Ok(con) => with_functions(con),
         Err(error) => panic!("Failed to get a connection to the database!: {error}"),
     }
 }
 
+/// registers the custom sql functions queries rely on, see [title_search::register_title_score].
+/// Also loads the `rarray` table-valued function, so that lists of values can be bound as a single parameter
+fn with_functions(con: Connection) -> Connection {
+    if let Err(error) = title_search::register_title_score(&con) {
+        panic!("Failed to register sql functions on the database connection!: {error}");
+    }
+    if let Err(error) = rusqlite::vtab::array::load_module(&con) {
+        panic!("Failed to load the rarray module on the database connection!: {error}");
+    }
+    con
+}
+
 /// runs init.sql on the database
 fn create_db(con: &mut Connection) {
     let sql = include_str!("../assets/init.sql");
@@ -89,7 +106,12 @@ pub fn generate_database_from_files(parent_folder: Option<u32>, con: &Connection
         return Ok(());
     }
 
-    let base_path = file_dir();
+    // remote storage can't be walked like a directory, so existing files there have to be imported some other way
+    let Some(root) = storage::backend().local_root() else {
+        log::warn!("Files are not stored on the local disk, skipping database generation");
+        return Ok(());
+    };
+    let base_path = root.to_string_lossy().into_owned();
     let path = Path::new(&base_path);
     if !path.exists() || !path.is_dir() {
         return Ok(());
@@ -167,10 +189,12 @@ fn generate_database_from_files_internal(
         let file_name = file_entry.file_name().to_string_lossy().to_string();
         let file_path = file_entry.path();
 
-        // Get file size
-        let file_size = fs::metadata(&file_path)
-            .map(|m| m.len())
-            .unwrap_or_default();
+        // Get file size and when it was last modified
+        let metadata = fs::metadata(&file_path).ok();
+        let file_size = encryption::plaintext_size(&file_path).unwrap_or_default();
+        let modified_date = metadata
+            .and_then(|m| m.modified().ok())
+            .map(|it| chrono::DateTime::<chrono::Local>::from(it).naive_local());
 
         // Determine file type
         let file_type: FileTypes = determine_file_type(&file_name);
@@ -183,6 +207,8 @@ fn generate_database_from_files_internal(
             create_date: chrono::offset::Local::now().naive_local(),
             size: file_size,
             file_type,
+            upload_date: None,
+            modified_date,
         };
 
         let file_id = file_repository::create_file(&file_record, con)?;
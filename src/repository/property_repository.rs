@@ -0,0 +1,243 @@
+use std::collections::{BTreeMap, HashMap};
+
+use itertools::Itertools;
+use rusqlite::Connection;
+
+use crate::model::repository::ItemDetails;
+
+/// retrieves the description and custom properties for the file with the passed `file_id`.
+///
+/// A file without any details will return an empty [ItemDetails]
+pub fn get_file_details(file_id: u32, con: &Connection) -> Result<ItemDetails, rusqlite::Error> {
+    get_details(
+        file_id,
+        include_str!("../assets/queries/properties/get_file_description.sql"),
+        include_str!("../assets/queries/properties/get_file_properties.sql"),
+        con,
+    )
+}
+
+/// retrieves the description and custom properties for the folder with the passed `folder_id`.
+///
+/// A folder without any details will return an empty [ItemDetails]
+pub fn get_folder_details(
+    folder_id: u32,
+    con: &Connection,
+) -> Result<ItemDetails, rusqlite::Error> {
+    get_details(
+        folder_id,
+        include_str!("../assets/queries/properties/get_folder_description.sql"),
+        include_str!("../assets/queries/properties/get_folder_properties.sql"),
+        con,
+    )
+}
+
+/// retrieves the details for all the files with the passed `file_ids` in bulk.
+///
+/// Files without any details will not have an entry in the returned map
+pub fn get_details_for_files(
+    file_ids: &[u32],
+    con: &Connection,
+) -> Result<HashMap<u32, ItemDetails>, rusqlite::Error> {
+    let in_clause = file_ids.iter().map(|it| format!("'{it}'")).join(",");
+    let mut mapped: HashMap<u32, ItemDetails> = HashMap::new();
+    let description_query = format!(
+        include_str!("../assets/queries/properties/get_descriptions_for_files.sql"),
+        in_clause
+    );
+    let mut pst = con.prepare(description_query.as_str())?;
+    let mut rows = pst.query([])?;
+    while let Some(row) = rows.next()? {
+        let file_id: u32 = row.get(0)?;
+        mapped.entry(file_id).or_default().description = Some(row.get(1)?);
+    }
+    let property_query = format!(
+        include_str!("../assets/queries/properties/get_properties_for_files.sql"),
+        in_clause
+    );
+    let mut pst = con.prepare(property_query.as_str())?;
+    let mut rows = pst.query([])?;
+    while let Some(row) = rows.next()? {
+        let file_id: u32 = row.get(0)?;
+        mapped
+            .entry(file_id)
+            .or_default()
+            .properties
+            .insert(row.get(1)?, row.get(2)?);
+    }
+    Ok(mapped)
+}
+
+/// replaces the description and all custom properties on the file with the passed `file_id`.
+///
+/// This does not run in a transaction, so the caller should wrap this if needed
+pub fn set_file_details(
+    file_id: u32,
+    details: &ItemDetails,
+    con: &Connection,
+) -> Result<(), rusqlite::Error> {
+    con.execute(
+        include_str!("../assets/queries/properties/delete_file_description.sql"),
+        [file_id],
+    )?;
+    con.execute(
+        include_str!("../assets/queries/properties/delete_file_properties.sql"),
+        [file_id],
+    )?;
+    if let Some(description) = &details.description {
+        con.execute(
+            include_str!("../assets/queries/properties/add_file_description.sql"),
+            rusqlite::params![file_id, description],
+        )?;
+    }
+    let mut pst = con.prepare(include_str!(
+        "../assets/queries/properties/add_file_property.sql"
+    ))?;
+    for (key, value) in details.properties.iter() {
+        pst.execute(rusqlite::params![file_id, key, value])?;
+    }
+    Ok(())
+}
+
+/// replaces the description and all custom properties on the folder with the passed `folder_id`.
+///
+/// This does not run in a transaction, so the caller should wrap this if needed
+pub fn set_folder_details(
+    folder_id: u32,
+    details: &ItemDetails,
+    con: &Connection,
+) -> Result<(), rusqlite::Error> {
+    con.execute(
+        include_str!("../assets/queries/properties/delete_folder_description.sql"),
+        [folder_id],
+    )?;
+    con.execute(
+        include_str!("../assets/queries/properties/delete_folder_properties.sql"),
+        [folder_id],
+    )?;
+    if let Some(description) = &details.description {
+        con.execute(
+            include_str!("../assets/queries/properties/add_folder_description.sql"),
+            rusqlite::params![folder_id, description],
+        )?;
+    }
+    let mut pst = con.prepare(include_str!(
+        "../assets/queries/properties/add_folder_property.sql"
+    ))?;
+    for (key, value) in details.properties.iter() {
+        pst.execute(rusqlite::params![folder_id, key, value])?;
+    }
+    Ok(())
+}
+
+fn get_details(
+    id: u32,
+    description_sql: &str,
+    properties_sql: &str,
+    con: &Connection,
+) -> Result<ItemDetails, rusqlite::Error> {
+    let description = match con.query_row(description_sql, [id], |row| row.get(0)) {
+        Ok(d) => Some(d),
+        Err(rusqlite::Error::QueryReturnedNoRows) => None,
+        Err(e) => return Err(e),
+    };
+    let mut pst = con.prepare(properties_sql)?;
+    let properties = pst
+        .query_map([id], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<BTreeMap<String, String>, rusqlite::Error>>()?;
+    Ok(ItemDetails {
+        description,
+        properties,
+    })
+}
+
+#[cfg(test)]
+mod file_details_tests {
+    use super::*;
+    use crate::repository::open_connection;
+    use crate::test::{cleanup, create_file_db_entry, init_db_folder};
+
+    #[test]
+    fn get_file_details_returns_empty_when_nothing_set() {
+        init_db_folder();
+        create_file_db_entry("test", None);
+        let con = open_connection();
+        let res = get_file_details(1, &con).unwrap();
+        con.close().unwrap();
+        assert_eq!(ItemDetails::default(), res);
+        cleanup();
+    }
+
+    #[test]
+    fn set_file_details_replaces_existing_details() {
+        init_db_folder();
+        create_file_db_entry("test", None);
+        let con = open_connection();
+        let original = ItemDetails {
+            description: Some("old".to_string()),
+            properties: BTreeMap::from([
+                ("camera".to_string(), "pixel".to_string()),
+                ("removed".to_string(), "value".to_string()),
+            ]),
+        };
+        set_file_details(1, &original, &con).unwrap();
+        let updated = ItemDetails {
+            description: Some("new".to_string()),
+            properties: BTreeMap::from([("camera".to_string(), "canon".to_string())]),
+        };
+        set_file_details(1, &updated, &con).unwrap();
+        let res = get_file_details(1, &con).unwrap();
+        con.close().unwrap();
+        assert_eq!(updated, res);
+        cleanup();
+    }
+
+    #[test]
+    fn get_details_for_files_only_includes_files_with_details() {
+        init_db_folder();
+        create_file_db_entry("has description", None);
+        create_file_db_entry("has properties", None);
+        create_file_db_entry("has nothing", None);
+        let con = open_connection();
+        let description = ItemDetails {
+            description: Some("test".to_string()),
+            properties: BTreeMap::new(),
+        };
+        let properties = ItemDetails {
+            description: None,
+            properties: BTreeMap::from([("key".to_string(), "value".to_string())]),
+        };
+        set_file_details(1, &description, &con).unwrap();
+        set_file_details(2, &properties, &con).unwrap();
+        let res = get_details_for_files(&[1, 2, 3], &con).unwrap();
+        con.close().unwrap();
+        assert_eq!(2, res.len());
+        assert_eq!(&description, res.get(&1).unwrap());
+        assert_eq!(&properties, res.get(&2).unwrap());
+        cleanup();
+    }
+}
+
+#[cfg(test)]
+mod folder_details_tests {
+    use super::*;
+    use crate::repository::open_connection;
+    use crate::test::{cleanup, create_folder_db_entry, init_db_folder};
+
+    #[test]
+    fn set_folder_details_removes_description_when_none() {
+        init_db_folder();
+        create_folder_db_entry("test", None);
+        let con = open_connection();
+        let original = ItemDetails {
+            description: Some("test".to_string()),
+            properties: BTreeMap::from([("key".to_string(), "value".to_string())]),
+        };
+        set_folder_details(1, &original, &con).unwrap();
+        set_folder_details(1, &ItemDetails::default(), &con).unwrap();
+        let res = get_folder_details(1, &con).unwrap();
+        con.close().unwrap();
+        assert_eq!(ItemDetails::default(), res);
+        cleanup();
+    }
+}
@@ -394,6 +394,8 @@ mod generate_database_existing_db {
                 create_date: chrono::offset::Local::now().naive_local(),
                 size: 100,
                 file_type: crate::model::file_types::FileTypes::Text,
+                upload_date: None,
+                modified_date: None,
             },
             &con,
         )
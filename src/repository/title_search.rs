@@ -0,0 +1,238 @@
+use rusqlite::Connection;
+use rusqlite::functions::FunctionFlags;
+
+/// the name of the sql function registered by [register_title_score], called as `title_score(<file name>, <search>)`
+pub const TITLE_SCORE_FUNCTION: &str = "title_score";
+
+/// scores how well a file name matches a title search, from 0 (no match) to 1 (every word matches exactly).
+///
+/// The name and search are split into words on anything that isn't a letter, number, or underscore, so word order doesn't matter.
+/// Every word in the search has to match a word in the name, either:
+/// - exactly
+/// - as the start of the word (e.g. `rec` matches `receipt`)
+/// - somewhere inside the word (e.g. `voice` matches `invoice`)
+/// - with a typo, or as the start of the word with a typo (e.g. `recipt` matches `receipt`).
+///   Words shorter than 4 characters can't have typos, words shorter than 8 can have 1, and longer words can have 2.
+///   Swapping 2 letters counts as 1 typo. Words with numbers or underscores in them can't have typos, since `2023` and `2024` are different things
+///
+/// Names that contain the whole search as-is always match, so punctuation-only searches like `.tar.gz` still work
+pub fn title_score(name: &str, search: &str) -> f64 {
+    let lower_name = name.to_lowercase();
+    let lower_search = search.trim().to_lowercase();
+    if lower_search.is_empty() {
+        return 0.0;
+    }
+    let name_words = words(&lower_name);
+    let search_words = words(&lower_search);
+    let mut matched_name_words = vec![false; name_words.len()];
+    let mut total = 0.0;
+    for search_word in search_words.iter() {
+        let best = name_words
+            .iter()
+            .enumerate()
+            .map(|(i, name_word)| (i, word_score(name_word, search_word)))
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        match best {
+            Some((i, score)) if score > 0.0 => {
+                matched_name_words[i] = true;
+                total += score;
+            }
+            _ => {
+                total = 0.0;
+                break;
+            }
+        }
+    }
+    let mut score = if search_words.is_empty() {
+        0.0
+    } else {
+        total / search_words.len() as f64
+    };
+    if score == 0.0 && lower_name.contains(&lower_search) {
+        score = SUBSTRING_SCORE;
+    }
+    if score == 0.0 {
+        return 0.0;
+    }
+    // names with fewer unmatched words are closer to what was searched for
+    let coverage = matched_name_words.iter().filter(|it| **it).count().max(1) as f64
+        / name_words.len().max(1) as f64;
+    score * (0.9 + 0.1 * coverage)
+}
+
+/// averages the [title_score] of the file name against each of the `searches`
+pub fn average_title_score(name: &str, searches: &[&str]) -> f64 {
+    if searches.is_empty() {
+        return 0.0;
+    }
+    searches.iter().map(|it| title_score(name, it)).sum::<f64>() / searches.len() as f64
+}
+
+/// registers [title_score] on the connection so it can be used in queries, see [TITLE_SCORE_FUNCTION]
+pub fn register_title_score(con: &Connection) -> Result<(), rusqlite::Error> {
+    con.create_scalar_function(
+        TITLE_SCORE_FUNCTION,
+        2,
+        FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+        |ctx| {
+            let name: Option<String> = ctx.get(0)?;
+            let search: Option<String> = ctx.get(1)?;
+            Ok(match (name, search) {
+                (Some(name), Some(search)) => title_score(&name, &search),
+                _ => 0.0,
+            })
+        },
+    )
+}
+
+const EXACT_SCORE: f64 = 1.0;
+const PREFIX_SCORE: f64 = 0.9;
+const SUBSTRING_SCORE: f64 = 0.75;
+const TYPO_SCORE: f64 = 0.6;
+const PREFIX_TYPO_SCORE: f64 = 0.5;
+/// how much each typo lowers the score
+const TYPO_PENALTY: f64 = 0.15;
+
+fn words(value: &str) -> Vec<Vec<char>> {
+    value
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .filter(|it| !it.is_empty())
+        .map(|it| it.chars().collect())
+        .collect()
+}
+
+fn word_score(name_word: &[char], search_word: &[char]) -> f64 {
+    if name_word == search_word {
+        return EXACT_SCORE;
+    }
+    if name_word.starts_with(search_word) {
+        return PREFIX_SCORE;
+    }
+    if name_word
+        .windows(search_word.len())
+        .any(|it| it == search_word)
+    {
+        return SUBSTRING_SCORE;
+    }
+    if !search_word.iter().all(|it| it.is_alphabetic()) {
+        return 0.0;
+    }
+    let max_typos = match search_word.len() {
+        0..4 => return 0.0,
+        4..8 => 1,
+        _ => 2,
+    };
+    let typos = edit_distance(name_word, search_word);
+    if typos <= max_typos {
+        return TYPO_SCORE - TYPO_PENALTY * (typos - 1) as f64;
+    }
+    if name_word.len() > search_word.len() {
+        let typos = edit_distance(&name_word[..search_word.len()], search_word);
+        if typos <= max_typos {
+            return PREFIX_TYPO_SCORE - TYPO_PENALTY * (typos - 1) as f64;
+        }
+    }
+    0.0
+}
+
+/// counts the insertions, deletions, substitutions, and swaps of neighboring characters it takes to turn `a` into `b`
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    // rows[0] is 2 rows back, rows[1] is the previous row, and rows[2] is the current row
+    let mut rows = [
+        vec![0; b.len() + 1],
+        (0..=b.len()).collect(),
+        vec![0; b.len() + 1],
+    ];
+    for i in 1..=a.len() {
+        rows[2][0] = i;
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (rows[1][j] + 1)
+                .min(rows[2][j - 1] + 1)
+                .min(rows[1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(rows[0][j - 2] + 1);
+            }
+            rows[2][j] = distance;
+        }
+        rows.rotate_left(1);
+    }
+    rows[1][b.len()]
+}
+
+#[cfg(test)]
+mod title_score_tests {
+    use super::*;
+
+    #[test]
+    fn ranks_exact_words_above_prefixes_and_typos() {
+        let exact = title_score("receipt.pdf", "receipt");
+        let prefix = title_score("receipt.pdf", "rece");
+        let typo = title_score("receipt.pdf", "recipt");
+        assert!(exact > prefix, "{exact} should be greater than {prefix}");
+        assert!(prefix > typo, "{prefix} should be greater than {typo}");
+        assert!(typo > 0.0);
+    }
+
+    #[test]
+    fn ignores_word_order_and_case() {
+        assert_eq!(
+            title_score("Tax Receipt 2023.pdf", "2023 RECEIPT"),
+            title_score("Tax Receipt 2023.pdf", "receipt 2023")
+        );
+    }
+
+    #[test]
+    fn requires_every_word_to_match() {
+        assert_eq!(0.0, title_score("receipt 2023.pdf", "receipt 2024"));
+        assert_eq!(0.0, title_score("receipt.pdf", "invoice"));
+    }
+
+    #[test]
+    fn keeps_underscored_words_together() {
+        assert_eq!(0.0, title_score("should_not_return.txt", "should_return"));
+        assert!(title_score("should_return.txt", "should_return") > 0.0);
+    }
+
+    #[test]
+    fn short_words_cannot_have_typos() {
+        assert_eq!(0.0, title_score("cat.png", "cot"));
+        assert!(title_score("kitten.png", "kiten") > 0.0);
+    }
+
+    #[test]
+    fn long_words_can_have_2_typos() {
+        assert!(title_score("vacation.png", "vactaion") > 0.0);
+        assert!(title_score("documentation.md", "documnetaton") > 0.0);
+        assert_eq!(0.0, title_score("vacation.png", "vcatoinx"));
+    }
+
+    #[test]
+    fn matches_start_of_word_with_typo() {
+        assert!(title_score("photograph.png", "fotog") == 0.0);
+        assert!(title_score("photograph.png", "photp") > 0.0);
+    }
+
+    #[test]
+    fn matches_whole_search_as_substring() {
+        assert!(title_score("backup.tar.gz", ".tar.gz") > 0.0);
+        assert!(title_score("backup.tar.gz", "--") == 0.0);
+    }
+
+    #[test]
+    fn prefers_names_with_fewer_extra_words() {
+        assert!(
+            title_score("receipt.pdf", "receipt")
+                > title_score("old scanned receipt copy.pdf", "receipt")
+        );
+    }
+
+    #[test]
+    fn edit_distance_counts_swaps_as_1() {
+        let chars = |it: &str| it.chars().collect::<Vec<char>>();
+        assert_eq!(1, edit_distance(&chars("recieve"), &chars("receive")));
+        assert_eq!(1, edit_distance(&chars("recipt"), &chars("receipt")));
+        assert_eq!(3, edit_distance(&chars("kitten"), &chars("sitting")));
+        assert_eq!(0, edit_distance(&chars(""), &chars("")));
+    }
+}
@@ -0,0 +1,153 @@
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use rocket::State;
+use rocket::http::Header;
+use rocket::serde::json::Json;
+
+use super::service;
+use super::{
+    DeleteSavedSearchResponse, ListSavedSearchesResponse, SavedSearchApi, SavedSearchError,
+    SavedSearchFolderResponse, SavedSearchResponse,
+};
+use crate::guard::HeaderAuth;
+use crate::model::guard::auth::ValidateResult;
+use crate::model::request::file_requests::SearchFilesRequest;
+use crate::model::response::BasicMessage;
+use crate::util::update_last_request_time;
+
+/// lists all saved searches, ordered by title
+#[get("/")]
+pub fn get_saved_searches(
+    auth: HeaderAuth,
+    last_request_time: &State<Arc<Mutex<Instant>>>,
+) -> ListSavedSearchesResponse {
+    match auth.validate() {
+        ValidateResult::Ok => { /*no op*/ }
+        ValidateResult::NoPasswordSet => return ListSavedSearchesResponse::Unauthorized("No password has been set. You can set a username and password by making a POST to `/api/password`".to_string()),
+        ValidateResult::Invalid => return ListSavedSearchesResponse::Unauthorized("Bad Credentials".to_string())
+    };
+    update_last_request_time(last_request_time);
+    match service::get_all_saved_searches() {
+        Ok(searches) => ListSavedSearchesResponse::Success(Json::from(searches)),
+        Err(_) => ListSavedSearchesResponse::GenericError(BasicMessage::new(
+            "Failed to retrieve saved searches. Check server logs for details",
+        )),
+    }
+}
+
+/// evaluates the saved search, returning it as a folder containing a page of the files that currently match it.
+/// The total number of matching files is in the `X-Total-Count` header.
+///
+/// Only the `sort`, `order`, `page`, and `pageSize` parameters are used
+#[get("/<id>?<request..>")]
+pub fn get_saved_search_folder(
+    id: u32,
+    request: SearchFilesRequest,
+    auth: HeaderAuth,
+    last_request_time: &State<Arc<Mutex<Instant>>>,
+) -> SavedSearchFolderResponse {
+    match auth.validate() {
+        ValidateResult::Ok => { /*no op*/ }
+        ValidateResult::NoPasswordSet => return SavedSearchFolderResponse::Unauthorized("No password has been set. You can set a username and password by making a POST to `/api/password`".to_string()),
+        ValidateResult::Invalid => return SavedSearchFolderResponse::Unauthorized("Bad Credentials".to_string())
+    };
+    update_last_request_time(last_request_time);
+    let sort = match request.search_sort() {
+        Ok(sort) => sort,
+        Err(e) => return SavedSearchFolderResponse::BadRequest(BasicMessage::new(e.as_str())),
+    };
+    let page = match request.limit_offset() {
+        Ok(it) => it,
+        Err(e) => return SavedSearchFolderResponse::BadRequest(BasicMessage::new(e.as_str())),
+    };
+    match service::get_saved_search_folder(id, sort, page) {
+        Ok((folder, total)) => SavedSearchFolderResponse::Success(
+            Json::from(Box::new(folder)),
+            Header::new("X-Total-Count", total.to_string()),
+        ),
+        Err(SavedSearchError::NotFound) => SavedSearchFolderResponse::NotFound(BasicMessage::new(
+            "The saved search with the passed id could not be found.",
+        )),
+        Err(SavedSearchError::Invalid(e)) => {
+            SavedSearchFolderResponse::BadRequest(BasicMessage::new(e.as_str()))
+        }
+        Err(_) => SavedSearchFolderResponse::GenericError(BasicMessage::new(
+            "Failed to evaluate saved search. Check server logs for details",
+        )),
+    }
+}
+
+#[post("/", data = "<search>")]
+pub fn create_saved_search(
+    search: Json<SavedSearchApi>,
+    auth: HeaderAuth,
+    last_request_time: &State<Arc<Mutex<Instant>>>,
+) -> SavedSearchResponse {
+    match auth.validate() {
+        ValidateResult::Ok => { /*no op*/ }
+        ValidateResult::NoPasswordSet => return SavedSearchResponse::Unauthorized("No password has been set. You can set a username and password by making a POST to `/api/password`".to_string()),
+        ValidateResult::Invalid => return SavedSearchResponse::Unauthorized("Bad Credentials".to_string())
+    };
+    update_last_request_time(last_request_time);
+    match service::create_saved_search(search.into_inner()) {
+        Ok(search) => SavedSearchResponse::Created(Json::from(search)),
+        Err(e) => saved_search_error_response(e),
+    }
+}
+
+#[put("/", data = "<search>")]
+pub fn update_saved_search(
+    search: Json<SavedSearchApi>,
+    auth: HeaderAuth,
+    last_request_time: &State<Arc<Mutex<Instant>>>,
+) -> SavedSearchResponse {
+    match auth.validate() {
+        ValidateResult::Ok => { /*no op*/ }
+        ValidateResult::NoPasswordSet => return SavedSearchResponse::Unauthorized("No password has been set. You can set a username and password by making a POST to `/api/password`".to_string()),
+        ValidateResult::Invalid => return SavedSearchResponse::Unauthorized("Bad Credentials".to_string())
+    };
+    update_last_request_time(last_request_time);
+    match service::update_saved_search(search.into_inner()) {
+        Ok(search) => SavedSearchResponse::Success(Json::from(search)),
+        Err(e) => saved_search_error_response(e),
+    }
+}
+
+#[delete("/<id>")]
+pub fn delete_saved_search(
+    id: u32,
+    auth: HeaderAuth,
+    last_request_time: &State<Arc<Mutex<Instant>>>,
+) -> DeleteSavedSearchResponse {
+    match auth.validate() {
+        ValidateResult::Ok => { /*no op*/ }
+        ValidateResult::NoPasswordSet => return DeleteSavedSearchResponse::Unauthorized("No password has been set. You can set a username and password by making a POST to `/api/password`".to_string()),
+        ValidateResult::Invalid => return DeleteSavedSearchResponse::Unauthorized("Bad Credentials".to_string())
+    };
+    update_last_request_time(last_request_time);
+    match service::delete_saved_search(id) {
+        Ok(()) => DeleteSavedSearchResponse::Success(()),
+        Err(SavedSearchError::NotFound) => DeleteSavedSearchResponse::NotFound(BasicMessage::new(
+            "The saved search with the passed id could not be found.",
+        )),
+        Err(_) => DeleteSavedSearchResponse::GenericError(BasicMessage::new(
+            "Failed to delete saved search. Check server logs for details",
+        )),
+    }
+}
+
+fn saved_search_error_response(e: SavedSearchError) -> SavedSearchResponse {
+    match e {
+        SavedSearchError::NotFound => SavedSearchResponse::NotFound(BasicMessage::new(
+            "The saved search with the passed id could not be found.",
+        )),
+        SavedSearchError::AlreadyExists => SavedSearchResponse::BadRequest(BasicMessage::new(
+            "A saved search with that title already exists.",
+        )),
+        SavedSearchError::Invalid(e) => SavedSearchResponse::BadRequest(BasicMessage::new(&e)),
+        SavedSearchError::DbError => SavedSearchResponse::GenericError(BasicMessage::new(
+            "Failed to save search. Check server logs for details",
+        )),
+    }
+}
@@ -0,0 +1,103 @@
+use rocket::http::Header;
+use rocket::serde::json::Json;
+use rocket::serde::{Deserialize, Serialize};
+
+use crate::model::api::FileApi;
+use crate::model::response::BasicMessage;
+
+/// a file search saved under a name, so that it can be browsed like a folder.
+/// This is the same whether it's a request or a response
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+#[serde(crate = "rocket::serde")]
+pub struct SavedSearchApi {
+    /// will be None if new
+    #[serde(default)]
+    pub id: Option<u32>,
+    pub title: String,
+    /// text that file names must contain
+    #[serde(default)]
+    pub search: String,
+    /// titles of tags files must have, including tags inherited from a parent folder
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// attribute searches files must match, in the same format as the `attributes` file search parameter
+    #[serde(default)]
+    pub attributes: Vec<String>,
+}
+
+/// a saved search evaluated like a folder, containing a page of the files that currently match it.
+/// This has its own type instead of being a folder so that its id can't be mistaken for a real folder's id
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(crate = "rocket::serde")]
+pub struct SavedSearchFolderApi {
+    #[serde(rename = "savedSearchId")]
+    pub saved_search_id: u32,
+    /// the title of the saved search
+    pub name: String,
+    pub files: Vec<FileApi>,
+}
+
+#[derive(PartialEq, Debug)]
+pub enum SavedSearchError {
+    /// no saved search with the id exists
+    NotFound,
+    /// a different saved search already has the title
+    AlreadyExists,
+    /// the title is blank, the search has no criteria, or an attribute couldn't be parsed
+    Invalid(String),
+    /// an error with the database
+    DbError,
+}
+
+#[derive(Responder, Debug)]
+pub enum SavedSearchResponse {
+    #[response(status = 200)]
+    Success(Json<SavedSearchApi>),
+    #[response(status = 201)]
+    Created(Json<SavedSearchApi>),
+    #[response(status = 400, content_type = "json")]
+    BadRequest(Json<BasicMessage>),
+    #[response(status = 401)]
+    Unauthorized(String),
+    #[response(status = 404, content_type = "json")]
+    NotFound(Json<BasicMessage>),
+    #[response(status = 500, content_type = "json")]
+    GenericError(Json<BasicMessage>),
+}
+
+#[derive(Responder, Debug)]
+pub enum ListSavedSearchesResponse {
+    #[response(status = 200)]
+    Success(Json<Vec<SavedSearchApi>>),
+    #[response(status = 401)]
+    Unauthorized(String),
+    #[response(status = 500, content_type = "json")]
+    GenericError(Json<BasicMessage>),
+}
+
+#[derive(Responder, Debug)]
+pub enum SavedSearchFolderResponse {
+    /// the header is the total number of files matching the search, across all pages
+    #[response(status = 200)]
+    Success(Json<Box<SavedSearchFolderApi>>, Header<'static>),
+    #[response(status = 400, content_type = "json")]
+    BadRequest(Json<BasicMessage>),
+    #[response(status = 401)]
+    Unauthorized(String),
+    #[response(status = 404, content_type = "json")]
+    NotFound(Json<BasicMessage>),
+    #[response(status = 500, content_type = "json")]
+    GenericError(Json<BasicMessage>),
+}
+
+#[derive(Responder, Debug)]
+pub enum DeleteSavedSearchResponse {
+    #[response(status = 204)]
+    Success(()),
+    #[response(status = 401)]
+    Unauthorized(String),
+    #[response(status = 404, content_type = "json")]
+    NotFound(Json<BasicMessage>),
+    #[response(status = 500, content_type = "json")]
+    GenericError(Json<BasicMessage>),
+}
@@ -0,0 +1,147 @@
+use rusqlite::{Connection, params};
+
+use super::SavedSearchApi;
+
+const TAG_CRITERION: &str = "tag";
+const ATTRIBUTE_CRITERION: &str = "attribute";
+
+/// creates a saved search along with its tags and attributes, returning its new id.
+/// This runs in a transaction, so either everything is saved or nothing is.
+/// This does not check if a saved search with the same title exists, so the caller must check that themselves
+pub fn create_saved_search(
+    search: &SavedSearchApi,
+    con: &Connection,
+) -> Result<u32, rusqlite::Error> {
+    let tx = con.unchecked_transaction()?;
+    let id = {
+        let mut pst = tx.prepare(include_str!(
+            "../assets/queries/saved_searches/create_saved_search.sql"
+        ))?;
+        pst.insert(params![search.title, search.search])? as u32
+    };
+    add_criteria(id, search, &tx)?;
+    tx.commit()?;
+    Ok(id)
+}
+
+/// retrieves the saved search with the passed `id`, including its tags and attributes
+pub fn get_saved_search(id: u32, con: &Connection) -> Result<SavedSearchApi, rusqlite::Error> {
+    let mut pst = con.prepare(include_str!(
+        "../assets/queries/saved_searches/get_saved_search.sql"
+    ))?;
+    let search = pst.query_row([id], map_saved_search)?;
+    with_criteria(search, con)
+}
+
+/// searches for a saved search that case-insensitively matches the passed title.
+///
+/// if `None` is returned, that means there was no match
+pub fn get_saved_search_by_title(
+    title: &str,
+    con: &Connection,
+) -> Result<Option<SavedSearchApi>, rusqlite::Error> {
+    let mut pst = con.prepare(include_str!(
+        "../assets/queries/saved_searches/get_saved_search_by_title.sql"
+    ))?;
+    match pst.query_row([title], map_saved_search) {
+        Ok(search) => Ok(Some(search)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// retrieves every saved search ordered by title, including their tags and attributes
+pub fn get_all_saved_searches(con: &Connection) -> Result<Vec<SavedSearchApi>, rusqlite::Error> {
+    let mut pst = con.prepare(include_str!(
+        "../assets/queries/saved_searches/get_all_saved_searches.sql"
+    ))?;
+    let searches = pst
+        .query_map([], map_saved_search)?
+        .collect::<Result<Vec<SavedSearchApi>, rusqlite::Error>>()?;
+    searches
+        .into_iter()
+        .map(|it| with_criteria(it, con))
+        .collect()
+}
+
+/// replaces the title, search text, tags, and attributes of the saved search.
+/// This runs in a transaction, so the old criteria are never removed without the new ones being added.
+/// Checking to make sure it exists needs to be done on the caller's end
+pub fn update_saved_search(
+    search: &SavedSearchApi,
+    con: &Connection,
+) -> Result<(), rusqlite::Error> {
+    let id = search.id.unwrap_or(0);
+    let tx = con.unchecked_transaction()?;
+    tx.execute(
+        include_str!("../assets/queries/saved_searches/update_saved_search.sql"),
+        params![search.title, search.search, id],
+    )?;
+    delete_criteria(id, &tx)?;
+    add_criteria(id, search, &tx)?;
+    tx.commit()
+}
+
+/// deletes the saved search and its criteria, returning `true` if it existed
+pub fn delete_saved_search(id: u32, con: &Connection) -> Result<bool, rusqlite::Error> {
+    let tx = con.unchecked_transaction()?;
+    delete_criteria(id, &tx)?;
+    let deleted = tx.execute(
+        include_str!("../assets/queries/saved_searches/delete_saved_search.sql"),
+        [id],
+    )? > 0;
+    tx.commit()?;
+    Ok(deleted)
+}
+
+fn add_criteria(id: u32, search: &SavedSearchApi, con: &Connection) -> Result<(), rusqlite::Error> {
+    let mut pst = con.prepare(include_str!(
+        "../assets/queries/saved_searches/add_saved_search_criterion.sql"
+    ))?;
+    for tag in search.tags.iter() {
+        pst.execute(params![id, TAG_CRITERION, tag])?;
+    }
+    for attribute in search.attributes.iter() {
+        pst.execute(params![id, ATTRIBUTE_CRITERION, attribute])?;
+    }
+    Ok(())
+}
+
+fn delete_criteria(id: u32, con: &Connection) -> Result<(), rusqlite::Error> {
+    let mut pst = con.prepare(include_str!(
+        "../assets/queries/saved_searches/delete_saved_search_criteria.sql"
+    ))?;
+    pst.execute([id])?;
+    Ok(())
+}
+
+/// fills out the tags and attributes of the passed saved search
+fn with_criteria(
+    mut search: SavedSearchApi,
+    con: &Connection,
+) -> Result<SavedSearchApi, rusqlite::Error> {
+    let mut pst = con.prepare(include_str!(
+        "../assets/queries/saved_searches/get_saved_search_criteria.sql"
+    ))?;
+    let mut rows = pst.query([search.id])?;
+    while let Some(row) = rows.next()? {
+        let criterion_type: String = row.get(0)?;
+        let value: String = row.get(1)?;
+        if criterion_type == TAG_CRITERION {
+            search.tags.push(value);
+        } else {
+            search.attributes.push(value);
+        }
+    }
+    Ok(search)
+}
+
+fn map_saved_search(row: &rusqlite::Row) -> Result<SavedSearchApi, rusqlite::Error> {
+    Ok(SavedSearchApi {
+        id: row.get(0)?,
+        title: row.get(1)?,
+        search: row.get(2)?,
+        tags: Vec::new(),
+        attributes: Vec::new(),
+    })
+}
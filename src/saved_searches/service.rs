@@ -0,0 +1,158 @@
+use std::backtrace::Backtrace;
+
+use rusqlite::Connection;
+
+use super::repository;
+use super::{SavedSearchApi, SavedSearchError, SavedSearchFolderApi};
+use crate::model::api::FileApi;
+use crate::model::request::attributes::{AttributeSearch, ParseError};
+use crate::model::request::file_requests::{SearchFilesParams, SearchSort};
+use crate::repository::open_connection;
+use crate::service::search_service;
+
+/// saves a new search, returning it with its id
+pub fn create_saved_search(search: SavedSearchApi) -> Result<SavedSearchApi, SavedSearchError> {
+    let search = validate(search)?;
+    let con = open_connection();
+    let res = check_title_is_free(&search, &con)
+        .and_then(|_| repository::create_saved_search(&search, &con).map_err(log_db_error));
+    con.close().unwrap();
+    let id = res?;
+    Ok(SavedSearchApi {
+        id: Some(id),
+        ..search
+    })
+}
+
+/// retrieves the saved search with the passed id
+pub fn get_saved_search(id: u32) -> Result<SavedSearchApi, SavedSearchError> {
+    let con = open_connection();
+    let res = repository::get_saved_search(id, &con);
+    con.close().unwrap();
+    match res {
+        Ok(search) => Ok(search),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Err(SavedSearchError::NotFound),
+        Err(e) => Err(log_db_error(e)),
+    }
+}
+
+/// lists all saved searches, ordered by title
+pub fn get_all_saved_searches() -> Result<Vec<SavedSearchApi>, SavedSearchError> {
+    let con = open_connection();
+    let res = repository::get_all_saved_searches(&con);
+    con.close().unwrap();
+    res.map_err(log_db_error)
+}
+
+/// replaces everything about the saved search with the id in `search`
+pub fn update_saved_search(search: SavedSearchApi) -> Result<SavedSearchApi, SavedSearchError> {
+    let search = validate(search)?;
+    get_saved_search(search.id.unwrap_or(0))?;
+    let con = open_connection();
+    let res = check_title_is_free(&search, &con)
+        .and_then(|_| repository::update_saved_search(&search, &con).map_err(log_db_error));
+    con.close().unwrap();
+    res.map(|_| search)
+}
+
+pub fn delete_saved_search(id: u32) -> Result<(), SavedSearchError> {
+    let con = open_connection();
+    let res = repository::delete_saved_search(id, &con);
+    con.close().unwrap();
+    match res {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(SavedSearchError::NotFound),
+        Err(e) => Err(log_db_error(e)),
+    }
+}
+
+/// evaluates the saved search, returning the requested page of matching files and the total number of matching files
+pub fn search_saved_search_files(
+    id: u32,
+    sort: SearchSort,
+    page: (u32, u32),
+) -> Result<(Vec<FileApi>, u32), SavedSearchError> {
+    evaluate(get_saved_search(id)?, sort, page)
+}
+
+fn evaluate(
+    search: SavedSearchApi,
+    sort: SearchSort,
+    page: (u32, u32),
+) -> Result<(Vec<FileApi>, u32), SavedSearchError> {
+    let attributes = parse_attributes(search.attributes)?;
+    search_service::search_files(SearchFilesParams {
+        title: search.search,
+        tags: search.tags,
+        attributes,
+        sort,
+        page,
+        ..Default::default()
+    })
+    .map_err(|_| SavedSearchError::DbError)
+}
+
+/// evaluates the saved search as a virtual folder named after it, whose files are the requested page of matching files.
+/// The total number of matching files is returned alongside the folder
+pub fn get_saved_search_folder(
+    id: u32,
+    sort: SearchSort,
+    page: (u32, u32),
+) -> Result<(SavedSearchFolderApi, u32), SavedSearchError> {
+    let search = get_saved_search(id)?;
+    let name = search.title.clone();
+    let (files, total) = evaluate(search, sort, page)?;
+    let folder = SavedSearchFolderApi {
+        saved_search_id: id,
+        name,
+        files,
+    };
+    Ok((folder, total))
+}
+
+/// trims the title and makes sure the search can be run
+fn validate(mut search: SavedSearchApi) -> Result<SavedSearchApi, SavedSearchError> {
+    search.title = search.title.trim().to_string();
+    search.search = search.search.trim().to_string();
+    if search.title.is_empty() {
+        return Err(SavedSearchError::Invalid(
+            "Saved search title cannot be empty".to_string(),
+        ));
+    }
+    if search.search.is_empty() && search.tags.is_empty() && search.attributes.is_empty() {
+        return Err(SavedSearchError::Invalid(
+            "Search string, attributes, or tags are required.".to_string(),
+        ));
+    }
+    parse_attributes(search.attributes.clone())?;
+    Ok(search)
+}
+
+fn parse_attributes(attributes: Vec<String>) -> Result<AttributeSearch, SavedSearchError> {
+    AttributeSearch::try_from(attributes).map_err(|e| {
+        SavedSearchError::Invalid(match e {
+            ParseError::BadEqualityOperator(message)
+            | ParseError::MissingValue(message)
+            | ParseError::BadValue(message)
+            | ParseError::InvalidSearch(message)
+            | ParseError::InvalidQuery(message) => message,
+        })
+    })
+}
+
+/// makes sure no other saved search has the same title
+fn check_title_is_free(search: &SavedSearchApi, con: &Connection) -> Result<(), SavedSearchError> {
+    match repository::get_saved_search_by_title(&search.title, con) {
+        Ok(Some(existing)) if existing.id != search.id => Err(SavedSearchError::AlreadyExists),
+        Ok(_) => Ok(()),
+        Err(e) => Err(log_db_error(e)),
+    }
+}
+
+fn log_db_error(e: rusqlite::Error) -> SavedSearchError {
+    log::error!(
+        "Failed to access saved searches in the database. Exception is {e:?}\n{}",
+        Backtrace::force_capture()
+    );
+    SavedSearchError::DbError
+}
@@ -0,0 +1,116 @@
+use rocket::http::{Header, Status};
+
+use crate::model::api::FileApi;
+use crate::model::response::folder_responses::FolderResponse;
+use crate::saved_searches::{SavedSearchApi, SavedSearchFolderApi};
+use crate::test::*;
+
+#[test]
+fn requires_auth() {
+    init_db_folder();
+    let client = client();
+    let res = client.get(uri!("/searches")).dispatch();
+    assert_eq!(res.status(), Status::Unauthorized);
+    cleanup();
+}
+
+#[test]
+fn create_list_and_browse() {
+    set_password();
+    create_file_db_entry("b invoice.pdf", None);
+    create_file_db_entry("a invoice.pdf", None);
+    create_file_db_entry("notes.txt", None);
+    let client = client();
+    let res = client
+        .post(uri!("/searches"))
+        .header(Header::new("Authorization", AUTH))
+        .body(r#"{"title":"invoices","search":"invoice"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Created);
+    let created: SavedSearchApi = res.into_json().unwrap();
+    assert_eq!(Some(1), created.id);
+    let res = client
+        .get(uri!("/searches"))
+        .header(Header::new("Authorization", AUTH))
+        .dispatch();
+    assert_eq!(
+        vec![created],
+        res.into_json::<Vec<SavedSearchApi>>().unwrap()
+    );
+    let res = client
+        .get("/searches/1?sort=name&pageSize=1")
+        .header(Header::new("Authorization", AUTH))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    assert_eq!(Some("2"), res.headers().get_one("X-Total-Count"));
+    let folder: SavedSearchFolderApi = res.into_json().unwrap();
+    assert_eq!(1, folder.saved_search_id);
+    assert_eq!("invoices", folder.name);
+    assert_eq!(
+        vec!["a invoice.pdf".to_string()],
+        folder
+            .files
+            .into_iter()
+            .map(|it: FileApi| it.name)
+            .collect::<Vec<String>>()
+    );
+    cleanup();
+}
+
+#[test]
+fn listed_in_root_folder() {
+    set_password();
+    create_folder_db_entry("documents", None);
+    let client = client();
+    let res = client
+        .post(uri!("/searches"))
+        .header(Header::new("Authorization", AUTH))
+        .body(r#"{"title":"invoices","search":"invoice"}"#)
+        .dispatch();
+    let created: SavedSearchApi = res.into_json().unwrap();
+    let res = client
+        .get(uri!("/folders/metadata/0"))
+        .header(Header::new("Authorization", AUTH))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let root: FolderResponse = res.into_json().unwrap();
+    assert_eq!(Some(vec![created]), root.saved_searches);
+    // saved searches aren't inside any real folder
+    let res = client
+        .get(uri!("/folders/metadata/1"))
+        .header(Header::new("Authorization", AUTH))
+        .dispatch();
+    let folder: FolderResponse = res.into_json().unwrap();
+    assert_eq!(None, folder.saved_searches);
+    cleanup();
+}
+
+#[test]
+fn create_with_bad_attribute() {
+    set_password();
+    let client = client();
+    let res = client
+        .post(uri!("/searches"))
+        .header(Header::new("Authorization", AUTH))
+        .body(r#"{"title":"big","attributes":["fileSize.eq;huge"]}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::BadRequest);
+    cleanup();
+}
+
+#[test]
+fn missing_search_returns_404() {
+    set_password();
+    let client = client();
+    let res = client
+        .get(uri!("/searches/5"))
+        .header(Header::new("Authorization", AUTH))
+        .dispatch();
+    assert_eq!(res.status(), Status::NotFound);
+    let res = client
+        .delete(uri!("/searches/5"))
+        .header(Header::new("Authorization", AUTH))
+        .dispatch();
+    assert_eq!(res.status(), Status::NotFound);
+    cleanup();
+}
@@ -0,0 +1,108 @@
+use crate::model::request::file_requests::SearchSort;
+use crate::saved_searches::service::*;
+use crate::saved_searches::{SavedSearchApi, SavedSearchError};
+use crate::test::*;
+
+fn saved_search(title: &str, tags: Vec<&str>, attributes: Vec<&str>) -> SavedSearchApi {
+    SavedSearchApi {
+        id: None,
+        title: title.to_string(),
+        search: String::new(),
+        tags: tags.into_iter().map(String::from).collect(),
+        attributes: attributes.into_iter().map(String::from).collect(),
+    }
+}
+
+#[test]
+fn create_and_get_saved_search() {
+    init_db_folder();
+    let created = create_saved_search(saved_search(
+        " photos ",
+        vec!["cat"],
+        vec!["fileType.eq;image"],
+    ))
+    .unwrap();
+    assert_eq!(Some(1), created.id);
+    assert_eq!("photos", created.title);
+    assert_eq!(Ok(created), get_saved_search(1));
+    cleanup();
+}
+
+#[test]
+fn create_rejects_invalid_searches() {
+    init_db_folder();
+    assert!(matches!(
+        create_saved_search(saved_search(" ", vec!["cat"], vec![])),
+        Err(SavedSearchError::Invalid(_))
+    ));
+    assert!(matches!(
+        create_saved_search(saved_search("empty", vec![], vec![])),
+        Err(SavedSearchError::Invalid(_))
+    ));
+    assert!(matches!(
+        create_saved_search(saved_search("bad", vec![], vec!["fileSize.eq;huge"])),
+        Err(SavedSearchError::Invalid(_))
+    ));
+    create_saved_search(saved_search("cats", vec!["cat"], vec![])).unwrap();
+    assert_eq!(
+        Err(SavedSearchError::AlreadyExists),
+        create_saved_search(saved_search("CATS", vec!["cat"], vec![]))
+    );
+    cleanup();
+}
+
+#[test]
+fn update_replaces_criteria() {
+    init_db_folder();
+    create_saved_search(saved_search("cats", vec!["cat", "cute"], vec![])).unwrap();
+    create_saved_search(saved_search("dogs", vec!["dog"], vec![])).unwrap();
+    let mut updated = saved_search("kittens", vec!["kitten"], vec!["fileType.eq;image"]);
+    updated.id = Some(1);
+    update_saved_search(updated.clone()).unwrap();
+    assert_eq!(Ok(updated.clone()), get_saved_search(1));
+    updated.title = "dogs".to_string();
+    assert_eq!(
+        Err(SavedSearchError::AlreadyExists),
+        update_saved_search(updated.clone())
+    );
+    updated.id = Some(3);
+    assert_eq!(
+        Err(SavedSearchError::NotFound),
+        update_saved_search(updated)
+    );
+    cleanup();
+}
+
+#[test]
+fn delete_removes_saved_search() {
+    init_db_folder();
+    create_saved_search(saved_search("cats", vec!["cat"], vec![])).unwrap();
+    assert_eq!(Ok(()), delete_saved_search(1));
+    assert_eq!(Err(SavedSearchError::NotFound), get_saved_search(1));
+    assert_eq!(Err(SavedSearchError::NotFound), delete_saved_search(1));
+    cleanup();
+}
+
+#[test]
+fn folder_contains_live_results() {
+    init_db_folder();
+    create_file_db_entry("cat.png", None);
+    create_file_db_entry("dog.png", None);
+    create_tag_file("cat", 1);
+    create_saved_search(saved_search("cats", vec!["cat"], vec!["fileType.eq;image"])).unwrap();
+    let (folder, total) = get_saved_search_folder(1, SearchSort::default(), (50, 0)).unwrap();
+    assert_eq!("cats", folder.name);
+    assert_eq!(1, total);
+    assert_eq!(
+        vec![1],
+        folder.files.iter().map(|it| it.id).collect::<Vec<u32>>()
+    );
+    // files tagged after the search was saved show up too
+    let con = crate::repository::open_connection();
+    crate::tags::repository::add_explicit_tag_to_file(2, 1, &con).unwrap();
+    con.close().unwrap();
+    let (folder, total) = get_saved_search_folder(1, SearchSort::default(), (50, 0)).unwrap();
+    assert_eq!(2, total);
+    assert_eq!(2, folder.files.len());
+    cleanup();
+}
@@ -1,30 +1,41 @@
 use std::backtrace::Backtrace;
 use std::collections::HashMap;
 use std::ffi::OsStr;
-use std::fs::File;
 use std::fs::{self};
 use std::os::unix::fs::MetadataExt;
 use std::path::Path;
 use std::string::ToString;
+use std::sync::Mutex;
 
+use chrono::NaiveDateTime;
+use itertools::Itertools;
 use once_cell::sync::Lazy;
 use regex::Regex;
-use rocket::tokio::fs::create_dir;
 use rusqlite::Connection;
+use sha2::{Digest, Sha256};
 
+use crate::auto_tags::service as auto_tag_service;
+use crate::exif::repository as exif_repository;
 use crate::model::api::FileApi;
 use crate::model::error::file_errors::{
-    CreateFileError, DeleteFileError, GetFileError, UpdateFileError,
+    CreateFileError, DeleteFileError, FavoriteFileError, GetFileError, ListFilesError,
+    UpdateFileContentsError, UpdateFileError,
 };
 use crate::model::error::folder_errors::{GetFolderError, LinkFolderError};
 use crate::model::file_types::FileTypes;
 use crate::model::repository::FileRecord;
-use crate::model::request::file_requests::CreateFileRequest;
+use crate::model::request::file_requests::{CreateFileRequest, FileSort, UnprocessedKind};
 use crate::model::response::TaggedItemApi;
 use crate::model::response::folder_responses::FolderResponse;
 use crate::previews;
-use crate::repository::{file_repository, folder_repository, open_connection};
+use crate::repository::{
+    activity_repository, file_repository, folder_repository, open_connection, property_repository,
+};
 use crate::service::folder_service;
+use crate::storage::{
+    self,
+    models::{ContentsStream, StorageError},
+};
 use crate::tags::repository as tag_repository;
 use crate::tags::service as tag_service;
 use crate::{queue, repository};
@@ -184,13 +195,10 @@ pub fn file_dir() -> String {
     dir_name
 }
 
-/// ensures that the passed directory exists on the file system
-pub async fn check_root_dir(dir: String) {
-    let path = Path::new(dir.as_str());
-    if !path.exists() {
-        if let Err(e) = create_dir(path).await {
-            panic!("Failed to create file directory: \n {e:?}")
-        }
+/// ensures that the root of the file storage exists
+pub fn check_root_dir() {
+    if let Err(e) = storage::backend().create_dir("") {
+        panic!("Failed to create file directory: \n {e:?}")
     }
 }
 
@@ -201,7 +209,8 @@ pub async fn save_file(
     force: bool,
 ) -> Result<FileApi, CreateFileError> {
     let file_name = String::from(file_input.file.name().unwrap());
-    check_root_dir(file_dir()).await;
+    let date_modified = file_input.date_modified()?;
+    check_root_dir();
     if !force {
         check_file_in_dir(file_input, &file_name)?;
     }
@@ -224,7 +233,8 @@ pub async fn save_file(
         })?;
         // folder exists, now try to create the file
         let mut created =
-            persist_save_file_to_folder(file_input, &folder, file_name.to_string()).await?;
+            persist_save_file_to_folder(file_input, &folder, file_name.to_string(), date_modified)
+                .await?;
         created.name = String::from(root_regex.replace(&file_name, ""));
         file_id = created.id.unwrap();
         created.into()
@@ -235,14 +245,22 @@ pub async fn save_file(
             String::from("")
         };
         let file_name = format!("{}/{}{}", &file_dir(), file_name, file_extension);
-        let mut created = persist_save_file(file_input).await?;
+        let mut created = persist_save_file(file_input, date_modified).await?;
         created.name = String::from(root_regex.replace(&file_name, ""));
         file_id = created.id.unwrap();
         created.into()
     };
     tag_service::update_file_tags(file_id, vec![]).map_err(|_| CreateFileError::FailWriteDb)?;
+    // failing to auto tag shouldn't fail the upload
+    if let Err(e) = auto_tag_service::apply_rules_to_file(file_id) {
+        log::error!(
+            "Failed to apply auto tag rules to uploaded file {file_id}. Error is {e:?}\n{}",
+            Backtrace::force_capture()
+        );
+    }
     // now publish the file to the rabbit queue so a preview can be generated for it later
     queue::publish_message("icon_gen", &file_id.to_string());
+    queue::publish_message("content_index", &file_id.to_string());
     Ok(resulting_file)
 }
 
@@ -271,8 +289,45 @@ pub fn get_file_metadata(id: u32) -> Result<FileApi, GetFileError> {
             return Err(GetFileError::TagError);
         }
     };
+    let details = match property_repository::get_file_details(id, &con) {
+        Ok(d) => d,
+        Err(e) => {
+            con.close().unwrap();
+            log::error!(
+                "Failed to pull file details from database. Nested exception is {e:?}\n{}",
+                Backtrace::force_capture()
+            );
+            return Err(GetFileError::DbFailure);
+        }
+    };
+    let activity = match activity_repository::get_activity_for_files(&[id], &con) {
+        Ok(mut a) => a.remove(&id).unwrap_or_default(),
+        Err(e) => {
+            con.close().unwrap();
+            log::error!(
+                "Failed to pull file activity from database. Nested exception is {e:?}\n{}",
+                Backtrace::force_capture()
+            );
+            return Err(GetFileError::DbFailure);
+        }
+    };
+    let location = match exif_repository::get_locations_for_files(&[id], &con) {
+        Ok(mut l) => l.remove(&id),
+        Err(e) => {
+            con.close().unwrap();
+            log::error!(
+                "Failed to pull file location from database. Nested exception is {e:?}\n{}",
+                Backtrace::force_capture()
+            );
+            return Err(GetFileError::DbFailure);
+        }
+    };
     con.close().unwrap();
-    Ok(FileApi::from_with_tags(file, tags))
+    let mut file = FileApi::from_with_tags(file, tags);
+    file += details;
+    file += activity;
+    file.location = location;
+    Ok(file)
 }
 
 pub fn check_file_exists(id: u32) -> bool {
@@ -285,12 +340,137 @@ pub fn check_file_exists(id: u32) -> bool {
     true
 }
 
-/// reads the contents of the file with the passed id from the disk and returns it
-pub fn get_file_contents(id: u32) -> Result<File, GetFileError> {
+/// retrieves a page of files ordered by `sort`, most recent first
+pub fn get_recent_files(
+    sort: FileSort,
+    limit: u32,
+    offset: u32,
+) -> Result<Vec<FileApi>, ListFilesError> {
+    let con = open_connection();
+    let res = file_repository::get_recent_files(sort, limit, offset, &con)
+        .and_then(|records| convert_records_to_api(records, &con));
+    con.close().unwrap();
+    res.map_err(|e| {
+        log::error!(
+            "Failed to retrieve recent files. Nested exception is {e:?}\n{}",
+            Backtrace::force_capture()
+        );
+        ListFilesError::DbError
+    })
+}
+
+/// retrieves a page of files missing any of the passed `kinds` of processing, oldest uploads first,
+/// along with the total number of files missing them
+pub fn get_unprocessed_files(
+    kinds: &[UnprocessedKind],
+    limit: u32,
+    offset: u32,
+) -> Result<(Vec<FileApi>, u32), ListFilesError> {
+    let preview_ids = if kinds.contains(&UnprocessedKind::Preview) {
+        match previews::get_preview_ids() {
+            Ok(ids) => ids,
+            Err(e) => {
+                log::error!(
+                    "Failed to list file previews. Nested exception is {e:?}\n{}",
+                    Backtrace::force_capture()
+                );
+                return Err(ListFilesError::DbError);
+            }
+        }
+    } else {
+        Vec::new()
+    };
+    let con = open_connection();
+    let res = file_repository::get_unprocessed_files(kinds, &preview_ids, limit, offset, &con)
+        .and_then(|(records, total)| {
+            convert_records_to_api(records, &con).map(|files| (files, total))
+        });
+    con.close().unwrap();
+    res.map_err(|e| {
+        log::error!(
+            "Failed to retrieve unprocessed files. Nested exception is {e:?}\n{}",
+            Backtrace::force_capture()
+        );
+        ListFilesError::DbError
+    })
+}
+
+/// retrieves all starred files ordered by `sort`
+pub fn get_favorite_files(sort: FileSort) -> Result<Vec<FileApi>, ListFilesError> {
+    let con = open_connection();
+    let res = file_repository::get_favorite_files(sort, &con)
+        .and_then(|records| convert_records_to_api(records, &con));
+    con.close().unwrap();
+    res.map_err(|e| {
+        log::error!(
+            "Failed to retrieve favorite files. Nested exception is {e:?}\n{}",
+            Backtrace::force_capture()
+        );
+        ListFilesError::DbError
+    })
+}
+
+/// stars or un-stars the file with the passed id
+pub fn set_file_favorite(id: u32, favorite: bool) -> Result<(), FavoriteFileError> {
+    let con = open_connection();
+    if let Err(e) = file_repository::get_file(id, &con) {
+        con.close().unwrap();
+        return if e == rusqlite::Error::QueryReturnedNoRows {
+            Err(FavoriteFileError::NotFound)
+        } else {
+            log::error!(
+                "Failed to retrieve file to favorite. Nested exception is {e:?}\n{}",
+                Backtrace::force_capture()
+            );
+            Err(FavoriteFileError::DbError)
+        };
+    }
+    let res = if favorite {
+        activity_repository::add_favorite_file(id, &con)
+    } else {
+        activity_repository::remove_favorite_file(id, &con)
+    };
+    con.close().unwrap();
+    res.map_err(|e| {
+        log::error!(
+            "Failed to update favorite status of file. Nested exception is {e:?}\n{}",
+            Backtrace::force_capture()
+        );
+        FavoriteFileError::DbError
+    })
+}
+
+/// increments the download count and last accessed time of the file with the passed id.
+///
+/// Failures are only logged, since they shouldn't prevent the file from being downloaded
+pub fn record_file_download(id: u32) {
+    let con = open_connection();
+    let now = chrono::offset::Local::now().naive_local();
+    if let Err(e) = activity_repository::record_file_download(id, now, &con) {
+        log::error!(
+            "Failed to record download of file {id}. Nested exception is {e:?}\n{}",
+            Backtrace::force_capture()
+        );
+    }
+    con.close().unwrap();
+}
+
+/// streams the plain contents of the file with the passed id from storage
+pub fn get_file_contents(id: u32) -> Result<ContentsStream, GetFileError> {
     let res = get_file_path(id);
     if let Ok(path) = res {
-        let path = format!("{}/{}", file_dir(), path);
-        File::open(path).map_err(|_| GetFileError::NotFound)
+        storage::open(&path)
+            .and_then(storage::stream_contents)
+            .map_err(|e| match e {
+                StorageError::NotFound => GetFileError::NotFound,
+                e => {
+                    log::error!(
+                        "Failed to read file contents from the disk. Nested exception is {e:?}\n{}",
+                        Backtrace::force_capture()
+                    );
+                    GetFileError::FileSystemError
+                }
+            })
     } else {
         Err(res.unwrap_err())
     }
@@ -298,7 +478,7 @@ pub fn get_file_contents(id: u32) -> Result<File, GetFileError> {
 
 pub fn delete_file(id: u32) -> Result<(), DeleteFileError> {
     let file_path = match get_file_path(id) {
-        Ok(path) => format!("{}/{}", file_dir(), path),
+        Ok(path) => path,
         Err(GetFileError::NotFound) => return Err(DeleteFileError::NotFound),
         Err(_) => return Err(DeleteFileError::DbError),
     };
@@ -308,9 +488,10 @@ pub fn delete_file(id: u32) -> Result<(), DeleteFileError> {
     con.close().unwrap();
     // helps avoid nested matches
     delete_result?;
-    fs::remove_file(&file_path).map_err(|e| {
+    queue::publish_message("content_index", &id.to_string());
+    storage::backend().delete(&file_path).map_err(|e| {
         log::error!(
-            "Failed to delete file from disk at location {file_path:?}!\n Nested exception is {e:?}\n{}", Backtrace::force_capture()
+            "Failed to delete file from storage at location {file_path:?}!\n Nested exception is {e:?}\n{}", Backtrace::force_capture()
         );
         DeleteFileError::FileSystemError
     })
@@ -415,11 +596,7 @@ pub fn update_file(file: FileApi) -> Result<FileApi, UpdateFileError> {
         }
     }
     // we have to create this before we update the file
-    let old_path = format!(
-        "{}/{}",
-        file_dir(),
-        file_repository::get_file_path(file.id, &con).unwrap()
-    );
+    let old_path = file_repository::get_file_path(file.id, &con).unwrap();
 
     // Check if the parent folder has changed and remove old ancestor tags if so
     let new_parent_id = file.folder_id.filter(|&it| it != 0);
@@ -442,12 +619,7 @@ pub fn update_file(file: FileApi) -> Result<FileApi, UpdateFileError> {
     }
     // now that we've updated the file in the database, it's time to update the file system.
     // sanitization is handled in file.name()
-    let new_path = format!(
-        "{}/{}/{}",
-        file_dir(),
-        new_parent_folder.path,
-        file.name().unwrap()
-    );
+    let new_path = format!("{}/{}", new_parent_folder.path, file.name().unwrap());
     // update the file's tags in the db
     let explicit_tags: Vec<TaggedItemApi> = file
         .tags
@@ -459,6 +631,16 @@ pub fn update_file(file: FileApi) -> Result<FileApi, UpdateFileError> {
         con.close().unwrap();
         return Err(UpdateFileError::TagError);
     }
+    // auto tag rules can match on the file's name and folder, so they need to be checked again when either changes
+    if (new_parent_id != old_parent_id || repo_file.name != file_name)
+        && let Err(e) = auto_tag_service::apply_rules_to_file(file.id)
+    {
+        log::error!(
+            "Failed to apply auto tag rules to updated file {}. Error is {e:?}\n{}",
+            file.id,
+            Backtrace::force_capture()
+        );
+    }
     let tags = match tag_service::get_tags_on_file(file.id) {
         Ok(t) => t,
         Err(_) => {
@@ -466,16 +648,59 @@ pub fn update_file(file: FileApi) -> Result<FileApi, UpdateFileError> {
             return Err(UpdateFileError::TagError);
         }
     };
+    let details = match property_repository::get_file_details(file.id, &con) {
+        Ok(details) => details.updated_with(file.description, file.properties),
+        Err(e) => {
+            con.close().unwrap();
+            log::error!(
+                "Failed to retrieve file details from database. Nested exception is {e:?}\n{}",
+                Backtrace::force_capture()
+            );
+            return Err(UpdateFileError::DbError);
+        }
+    };
+    if let Err(e) = property_repository::set_file_details(file.id, &details, &con) {
+        con.close().unwrap();
+        log::error!(
+            "Failed to update file details in database. Nested exception is {e:?}\n{}",
+            Backtrace::force_capture()
+        );
+        return Err(UpdateFileError::DbError);
+    }
+    let activity = match activity_repository::get_activity_for_files(&[file.id], &con) {
+        Ok(mut a) => a.remove(&file.id).unwrap_or_default(),
+        Err(e) => {
+            con.close().unwrap();
+            log::error!(
+                "Failed to retrieve file activity from database. Nested exception is {e:?}\n{}",
+                Backtrace::force_capture()
+            );
+            return Err(UpdateFileError::DbError);
+        }
+    };
+    let location = match exif_repository::get_locations_for_files(&[file.id], &con) {
+        Ok(mut l) => l.remove(&file.id),
+        Err(e) => {
+            con.close().unwrap();
+            log::error!(
+                "Failed to retrieve file location from database. Nested exception is {e:?}\n{}",
+                Backtrace::force_capture()
+            );
+            return Err(UpdateFileError::DbError);
+        }
+    };
     // we're done with the database for now
     con.close().unwrap();
-    let new_path = Regex::new("/root").unwrap().replace(new_path.as_str(), "");
-    if let Err(e) = fs::rename(old_path, new_path.to_string()) {
+    let new_path = Regex::new("^root/").unwrap().replace(new_path.as_str(), "");
+    if let Err(e) = storage::backend().rename(&old_path, &new_path) {
         log::error!(
             "Failed to move file in the file system. Nested exception is {e:?}\n{}",
             Backtrace::force_capture()
         );
         return Err(UpdateFileError::FileSystemError);
     }
+    // renaming the file can change whether its contents should be indexed
+    queue::publish_message("content_index", &file.id.to_string());
     Ok(FileApi {
         id: file.id,
         folder_id: new_parent_id,
@@ -484,9 +709,202 @@ pub fn update_file(file: FileApi) -> Result<FileApi, UpdateFileError> {
         size: Some(repo_file.size),
         date_created: Some(repo_file.create_date),
         file_type: file.file_type,
+        description: details.description,
+        properties: Some(details.properties),
+        date_uploaded: repo_file.upload_date,
+        date_modified: repo_file.modified_date,
+        favorite: activity.favorite,
+        download_count: activity.download_count,
+        last_accessed: activity.last_accessed,
+        location,
+        relevance: None,
     })
 }
 
+/// file types whose contents can be replaced in place by [update_file_contents]
+const EDITABLE_FILE_TYPES: [FileTypes; 3] =
+    [FileTypes::Text, FileTypes::Code, FileTypes::Configuration];
+
+/// extensions that aren't one of [EDITABLE_FILE_TYPES] but are still plain text, such as markdown being a [FileTypes::Document]
+const EDITABLE_EXTENSIONS: [&str; 2] = ["md", "markdown"];
+
+/// held while a file's contents are being compared and replaced, so that 2 edits with the same hash can't both succeed
+static CONTENT_UPDATE_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+/// returns the hash of the passed file contents, used as the ETag when editing a file's contents
+pub fn hash_file_contents(contents: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(contents))
+}
+
+/// checks if the contents of the file with the passed name can be edited, see [EDITABLE_FILE_TYPES] and [EDITABLE_EXTENSIONS]
+fn is_editable(name: &str) -> bool {
+    let extension = Path::new(name)
+        .extension()
+        .and_then(OsStr::to_str)
+        .map(str::to_lowercase)
+        .unwrap_or_default();
+    EDITABLE_FILE_TYPES.contains(&determine_file_type(name))
+        || EDITABLE_EXTENSIONS.contains(&extension.as_str())
+}
+
+/// returns the ETag of the file with the passed id, which is the quoted [hash_file_contents] of its contents.
+///
+/// Only files that can be edited have an ETag, since it's only needed to call [update_file_contents].
+/// `None` is returned for other files, or if the contents couldn't be read
+pub fn get_file_etag(id: u32) -> Option<String> {
+    let con = open_connection();
+    let res = file_repository::get_file(id, &con)
+        .and_then(|record| Ok((record, file_repository::get_file_path(id, &con)?)));
+    con.close().unwrap();
+    let (record, path) = match res {
+        Ok(it) => it,
+        Err(e) => {
+            log::error!(
+                "Failed to retrieve file {id} to hash its contents. Nested exception is {e:?}\n{}",
+                Backtrace::force_capture()
+            );
+            return None;
+        }
+    };
+    if !is_editable(&record.name) {
+        return None;
+    }
+    // the contents are hashed as they're read, so that large files don't have to be held in memory
+    let mut hasher = Sha256::new();
+    let hashed = storage::open(&path)
+        .and_then(|mut contents| Ok(std::io::copy(&mut contents, &mut hasher)?));
+    match hashed {
+        Ok(_) => Some(format!("\"{:x}\"", hasher.finalize())),
+        Err(e) => {
+            log::error!(
+                "Failed to hash the contents of file {id}. Nested exception is {e:?}\n{}",
+                Backtrace::force_capture()
+            );
+            None
+        }
+    }
+}
+
+/// replaces the contents of the file with the passed id, keeping its id, tags, and folder.
+///
+/// `expected_hash` must be the [hash_file_contents] of the file's current contents. If it isn't,
+/// someone else has edited the file since the client last retrieved it and the update is rejected.
+/// Only [EDITABLE_FILE_TYPES] and [EDITABLE_EXTENSIONS] can have their contents replaced
+pub fn update_file_contents(
+    id: u32,
+    contents: &[u8],
+    expected_hash: &str,
+) -> Result<FileApi, UpdateFileContentsError> {
+    let con = open_connection();
+    let mut record = match file_repository::get_file(id, &con) {
+        Ok(f) => f,
+        Err(rusqlite::Error::QueryReturnedNoRows) => {
+            con.close().unwrap();
+            return Err(UpdateFileContentsError::NotFound);
+        }
+        Err(e) => {
+            con.close().unwrap();
+            log::error!(
+                "Failed to retrieve file to update contents. Nested exception is {e:?}\n{}",
+                Backtrace::force_capture()
+            );
+            return Err(UpdateFileContentsError::DbError);
+        }
+    };
+    if !is_editable(&record.name) {
+        con.close().unwrap();
+        return Err(UpdateFileContentsError::UnsupportedType);
+    }
+    let path = match file_repository::get_file_path(id, &con) {
+        Ok(p) => p,
+        Err(e) => {
+            con.close().unwrap();
+            log::error!(
+                "Failed to retrieve file path to update contents. Nested exception is {e:?}\n{}",
+                Backtrace::force_capture()
+            );
+            return Err(UpdateFileContentsError::DbError);
+        }
+    };
+    // clients may send the ETag with its surrounding quotes
+    let expected_hash = expected_hash.trim().trim_matches('"').to_lowercase();
+    let lock = CONTENT_UPDATE_LOCK.lock().unwrap();
+    let current_contents = match storage::read(&path) {
+        Ok(c) => c,
+        Err(e) => {
+            con.close().unwrap();
+            log::error!(
+                "Failed to read current file contents. Nested exception is {e:?}\n{}",
+                Backtrace::force_capture()
+            );
+            return Err(UpdateFileContentsError::FileSystemError);
+        }
+    };
+    if hash_file_contents(&current_contents) != expected_hash {
+        con.close().unwrap();
+        return Err(UpdateFileContentsError::HashMismatch);
+    }
+    if let Err(e) = storage::write(&path, contents) {
+        con.close().unwrap();
+        log::error!(
+            "Failed to write new file contents. Nested exception is {e:?}\n{}",
+            Backtrace::force_capture()
+        );
+        return Err(UpdateFileContentsError::FileSystemError);
+    }
+    drop(lock);
+    record.size = contents.len() as u64;
+    record.file_type = determine_file_type(&record.name);
+    record.modified_date = Some(chrono::offset::Local::now().naive_local());
+    let res = file_repository::update_file_contents(&record, &con);
+    con.close().unwrap();
+    if let Err(e) = res {
+        log::error!(
+            "Failed to update file record after replacing contents. Nested exception is {e:?}\n{}",
+            Backtrace::force_capture()
+        );
+        return Err(UpdateFileContentsError::DbError);
+    }
+    // the old preview no longer matches the contents, so a new one needs to be generated
+    previews::delete_file_preview(id);
+    queue::publish_message("icon_gen", &id.to_string());
+    queue::publish_message("content_index", &id.to_string());
+    get_file_metadata(id).map_err(|_| UpdateFileContentsError::DbError)
+}
+
+/// converts the passed records into [FileApi]s, retrieving their tags, details, activity, and locations in bulk
+fn convert_records_to_api(
+    records: Vec<FileRecord>,
+    con: &Connection,
+) -> Result<Vec<FileApi>, rusqlite::Error> {
+    let file_ids: Vec<u32> = records
+        .iter()
+        .map(|f| f.id.expect("files pulled from database didn't have ID!"))
+        .collect();
+    let mut tags = tag_repository::get_all_tags_for_files(file_ids.clone(), con)?;
+    let mut details = property_repository::get_details_for_files(&file_ids, con)?;
+    let mut activity = activity_repository::get_activity_for_files(&file_ids, con)?;
+    let mut locations = exif_repository::get_locations_for_files(&file_ids, con)?;
+    let converted = records
+        .into_iter()
+        .map(|record| {
+            let id = record.id.unwrap();
+            let file_tags: Vec<TaggedItemApi> = tags
+                .remove(&id)
+                .unwrap_or_default()
+                .into_iter()
+                .map_into()
+                .collect();
+            let mut file = FileApi::from_with_tags(record, file_tags);
+            file += details.remove(&id).unwrap_or_default();
+            file += activity.remove(&id).unwrap_or_default();
+            file.location = locations.remove(&id);
+            file
+        })
+        .collect();
+    Ok(converted)
+}
+
 /// retrieves the full path to the file with the passed id
 pub fn get_file_path(id: u32) -> Result<String, GetFileError> {
     let con = repository::open_connection();
@@ -524,91 +942,127 @@ pub fn determine_file_type(file_name: &str) -> FileTypes {
 
 // ==== private functions ==== \\
 
-/// persists the file to the disk and the database
+/// persists the file to storage and the database
 async fn persist_save_file_to_folder(
     file_input: &mut CreateFileRequest<'_>,
     folder: &FolderResponse,
     file_name: String,
+    date_modified: Option<NaiveDateTime>,
 ) -> Result<FileRecord, CreateFileError> {
     let file_name = determine_file_name(&file_name, &file_input.extension);
-    let formatted_name = format!("{}/{}/{}", file_dir(), folder.path, file_name);
-    match file_input.file.persist_to(&formatted_name).await {
-        Ok(_) => {
-            // path function here is guaranteed to return some at this point, according to docs
-            let file_path = file_input.file.path().unwrap();
-            let file_size = if let Ok(metadata) = fs::metadata(file_path) {
-                metadata.size()
-            } else {
-                0
-            };
-            let res = save_file_record(&formatted_name, file_size)?;
-            // file and folder are both in repository, now link them
-            if link_folder_to_file(res.id.unwrap(), folder.id).is_err() {
-                return Err(CreateFileError::FailWriteDb);
-            }
-            Ok(res)
-        }
-        Err(e) => {
-            log::error!(
-                "Failed to save file to disk. Nested exception is {e:?}\n{}",
-                Backtrace::force_capture()
-            );
-            Err(CreateFileError::FailWriteDisk)
-        }
+    let path = format!("{}/{}", folder.path, file_name);
+    let res = persist_upload(file_input, &path, date_modified).await?;
+    // file and folder are both in repository, now link them
+    if link_folder_to_file(res.id.unwrap(), folder.id).is_err() {
+        return Err(CreateFileError::FailWriteDb);
     }
+    Ok(res)
 }
 
-/// persists the passed file to the disk and the database
+/// persists the passed file to storage and the database
 async fn persist_save_file(
     file_input: &mut CreateFileRequest<'_>,
+    date_modified: Option<NaiveDateTime>,
 ) -> Result<FileRecord, CreateFileError> {
     let file_name = determine_file_name(file_input.file.name().unwrap(), &file_input.extension);
-    let file_name = format!("{}/{}", &file_dir(), file_name);
-    match file_input.file.persist_to(&file_name).await {
-        Ok(()) => {
-            // path function here is guaranteed to return some at this point, according to docs
-            let file_path = file_input.file.path().unwrap();
-            let file_size = if let Ok(metadata) = fs::metadata(file_path) {
-                metadata.size()
-            } else {
-                0
-            };
-            save_file_record(&file_name, file_size)
-        }
+    persist_upload(file_input, &file_name, date_modified).await
+}
+
+/// moves the uploaded file into storage at `path`, and saves its record to the database.
+///
+/// The upload is written to the temp directory first so that its size and EXIF data can be read before it's encrypted or sent to remote storage
+async fn persist_upload(
+    file_input: &mut CreateFileRequest<'_>,
+    path: &str,
+    date_modified: Option<NaiveDateTime>,
+) -> Result<FileRecord, CreateFileError> {
+    let temp_path = Path::new(&crate::temp_dir()).join(nanoid::nanoid!());
+    if let Err(e) = file_input.file.persist_to(&temp_path).await {
+        log::error!(
+            "Failed to save file to disk. Nested exception is {e:?}\n{}",
+            Backtrace::force_capture()
+        );
+        return Err(CreateFileError::FailWriteDisk);
+    }
+    let file_size = if let Ok(metadata) = fs::metadata(&temp_path) {
+        metadata.size()
+    } else {
+        0
+    };
+    let record = match save_file_record(path, &temp_path, file_size, date_modified) {
+        Ok(record) => record,
         Err(e) => {
+            fs::remove_file(&temp_path).unwrap_or(());
+            return Err(e);
+        }
+    };
+    if let Err(e) = storage::store(&temp_path, path) {
+        // storage removes uploads it can't encrypt, but other failures can leave the plain upload behind
+        fs::remove_file(&temp_path).unwrap_or(());
+        log::error!(
+            "Failed to move uploaded file into storage. Nested exception is {e:?}\n{}",
+            Backtrace::force_capture()
+        );
+        // the upload failed, so its record can't be left pointing at contents that were never stored
+        let con = open_connection();
+        if let Err(e) = file_repository::delete_file(record.id.unwrap(), &con) {
             log::error!(
-                "Failed to save file to disk. Nested exception is {e:?}\n{}",
+                "Failed to remove the record of a file that couldn't be stored. Nested exception is {e:?}\n{}",
                 Backtrace::force_capture()
             );
-            Err(CreateFileError::FailWriteDisk)
         }
+        con.close().unwrap();
+        return Err(CreateFileError::FailWriteDisk);
     }
+    Ok(record)
 }
 
-fn save_file_record(name: &str, size: u64) -> Result<FileRecord, CreateFileError> {
-    // remove the './' from the file name
-    let begin_path_regex = Regex::new("\\.?(/.*/)+?").unwrap();
-    let formatted_name = begin_path_regex.replace(name, "");
+/// saves a new file record to the database.
+///
+/// `contents_path` is where the contents of the file can currently be read from, which is used to parse EXIF data.
+/// `date_modified` is the client-provided modification date, and defaults to the upload date if not passed
+fn save_file_record(
+    name: &str,
+    contents_path: &Path,
+    size: u64,
+    date_modified: Option<NaiveDateTime>,
+) -> Result<FileRecord, CreateFileError> {
+    // the record only holds the file name, not the path to its folder
+    let formatted_name = Path::new(name)
+        .file_name()
+        .map(|it| it.to_string_lossy().to_string())
+        .unwrap_or_default();
     let file_type = determine_file_type(name);
 
-    // Try to parse EXIF data for creation date if it's an image or video
-    let create_date = match file_type {
-        FileTypes::Image | FileTypes::Video => crate::exif::service::parse_exif_date(name)
-            .unwrap_or_else(|| chrono::offset::Local::now().naive_local()),
-        _ => chrono::offset::Local::now().naive_local(),
+    let upload_date = chrono::offset::Local::now().naive_local();
+    // Try to parse EXIF data for creation date and camera details if it's an image or video
+    let exif = match file_type {
+        FileTypes::Image | FileTypes::Video => {
+            crate::exif::service::parse_exif(&contents_path.to_string_lossy()).unwrap_or_default()
+        }
+        _ => crate::exif::ExifData::default(),
     };
+    let create_date = exif.date_taken.unwrap_or(upload_date);
 
     let mut file_record = FileRecord {
         id: None,
-        name: formatted_name.to_string(),
+        name: formatted_name,
         parent_id: None,
         create_date,
         size,
         file_type,
+        upload_date: Some(upload_date),
+        modified_date: Some(date_modified.unwrap_or(upload_date)),
     };
     let con = repository::open_connection();
     let res =
         file_repository::create_file(&file_record, &con).map_err(|_| CreateFileError::FailWriteDb);
+    if let Ok(id) = res
+        && exif.has_details()
+    {
+        // the file is still usable without its camera details, so failing to save them isn't fatal. The repository logs the error
+        let _ = crate::exif::repository::save_file_exif(id, &exif, &con);
+    }
     con.close().unwrap();
     file_record.id = Some(res.unwrap());
     Ok(file_record)
@@ -638,7 +1092,7 @@ mod save_file_record_tests {
         std::fs::create_dir_all(file_dir()).unwrap();
         std::fs::write(&file_path, "fake image data").unwrap();
 
-        let result = save_file_record(&file_path, 100);
+        let result = save_file_record(&file_path, Path::new(&file_path), 100, None);
         assert!(result.is_ok(), "Should successfully save file record");
 
         let record = result.unwrap();
@@ -659,7 +1113,7 @@ mod save_file_record_tests {
         std::fs::write(&file_path, "test content").unwrap();
 
         let before_time = chrono::offset::Local::now().naive_local();
-        let result = save_file_record(&file_path, 100);
+        let result = save_file_record(&file_path, Path::new(&file_path), 100, None);
         assert!(result.is_ok(), "Should successfully save file record");
 
         let record = result.unwrap();
@@ -742,6 +1196,7 @@ mod determine_file_name_tests {
 
 #[cfg(test)]
 mod update_file_tests {
+    use std::collections::BTreeMap;
     use std::fs;
 
     use crate::model::api::FileApi;
@@ -777,6 +1232,15 @@ mod update_file_tests {
             size: Some(0),
             date_created: Some(now()),
             file_type: None,
+            description: None,
+            properties: Some(BTreeMap::new()),
+            date_uploaded: None,
+            date_modified: None,
+            favorite: false,
+            download_count: 0,
+            last_accessed: None,
+            location: None,
+            relevance: None,
         })
         .unwrap();
         let res = get_file_metadata(1).unwrap();
@@ -796,6 +1260,73 @@ mod update_file_tests {
         cleanup();
     }
 
+    #[test]
+    fn update_file_sets_details() {
+        init_db_folder();
+        create_file_db_entry("test.txt", None);
+        create_file_disk("test.txt", "test");
+        let properties = BTreeMap::from([("camera".to_string(), "Pixel 7".to_string())]);
+        update_file(FileApi {
+            id: 1,
+            folder_id: Some(0),
+            name: "test.txt".to_string(),
+            tags: vec![],
+            size: None,
+            date_created: None,
+            file_type: None,
+            description: Some("a test file".to_string()),
+            properties: Some(properties.clone()),
+            date_uploaded: None,
+            date_modified: None,
+            favorite: false,
+            download_count: 0,
+            last_accessed: None,
+            location: None,
+            relevance: None,
+        })
+        .unwrap();
+        let res = get_file_metadata(1).unwrap();
+        assert_eq!(Some("a test file".to_string()), res.description);
+        assert_eq!(properties, res.properties.unwrap());
+        // blank descriptions remove the description
+        update_file(FileApi {
+            description: Some("  ".to_string()),
+            properties: Some(BTreeMap::new()),
+            ..res
+        })
+        .unwrap();
+        let res = get_file_metadata(1).unwrap();
+        assert_eq!(None, res.description);
+        assert!(res.properties.unwrap().is_empty());
+        cleanup();
+    }
+
+    #[test]
+    fn update_file_keeps_details_when_missing() {
+        init_db_folder();
+        create_file_db_entry("test.txt", None);
+        create_file_disk("test.txt", "test");
+        let properties = BTreeMap::from([("camera".to_string(), "Pixel 7".to_string())]);
+        let res = update_file(FileApi {
+            description: Some("a test file".to_string()),
+            properties: Some(properties.clone()),
+            ..FileApi::new(1, Some(0), "test.txt".to_string())
+        })
+        .unwrap();
+        // renaming the file without sending its details shouldn't remove them
+        update_file(FileApi {
+            name: "renamed.txt".to_string(),
+            description: None,
+            properties: None,
+            ..res
+        })
+        .unwrap();
+        let res = get_file_metadata(1).unwrap();
+        assert_eq!(Some("a test file".to_string()), res.description);
+        assert_eq!(Some(properties), res.properties);
+        cleanup();
+    }
+
     #[test]
     fn update_file_removes_tags() {
         init_db_folder();
@@ -810,6 +1341,15 @@ mod update_file_tests {
             size: None,
             date_created: None,
             file_type: None,
+            description: None,
+            properties: Some(BTreeMap::new()),
+            date_uploaded: None,
+            date_modified: None,
+            favorite: false,
+            download_count: 0,
+            last_accessed: None,
+            location: None,
+            relevance: None,
         })
         .unwrap();
         let res = get_file_metadata(1).unwrap();
@@ -833,6 +1373,15 @@ mod update_file_tests {
             size: Some(0),
             date_created: Some(now()),
             file_type: None,
+            description: None,
+            properties: Some(BTreeMap::new()),
+            date_uploaded: None,
+            date_modified: None,
+            favorite: false,
+            download_count: 0,
+            last_accessed: None,
+            location: None,
+            relevance: None,
         })
         .unwrap_err();
         assert_eq!(UpdateFileError::NotFound, res);
@@ -851,6 +1400,15 @@ mod update_file_tests {
             size: Some(0),
             date_created: Some(now()),
             file_type: None,
+            description: None,
+            properties: Some(BTreeMap::new()),
+            date_uploaded: None,
+            date_modified: None,
+            favorite: false,
+            download_count: 0,
+            last_accessed: None,
+            location: None,
+            relevance: None,
         })
         .unwrap_err();
         assert_eq!(UpdateFileError::FolderNotFound, res);
@@ -872,6 +1430,15 @@ mod update_file_tests {
             size: Some(0),
             date_created: Some(now()),
             file_type: None,
+            description: None,
+            properties: Some(BTreeMap::new()),
+            date_uploaded: None,
+            date_modified: None,
+            favorite: false,
+            download_count: 0,
+            last_accessed: None,
+            location: None,
+            relevance: None,
         })
         .unwrap_err();
         assert_eq!(UpdateFileError::FileAlreadyExists, res);
@@ -899,6 +1466,15 @@ mod update_file_tests {
             size: Some(0),
             date_created: Some(now()),
             file_type: None,
+            description: None,
+            properties: Some(BTreeMap::new()),
+            date_uploaded: None,
+            date_modified: None,
+            favorite: false,
+            download_count: 0,
+            last_accessed: None,
+            location: None,
+            relevance: None,
         })
         .unwrap_err();
         assert_eq!(UpdateFileError::FileAlreadyExists, res);
@@ -923,6 +1499,15 @@ mod update_file_tests {
             size: Some(0),
             date_created: Some(now()),
             file_type: None,
+            description: None,
+            properties: Some(BTreeMap::new()),
+            date_uploaded: None,
+            date_modified: None,
+            favorite: false,
+            download_count: 0,
+            last_accessed: None,
+            location: None,
+            relevance: None,
         })
         .unwrap();
         let res = get_file_metadata(1).unwrap();
@@ -950,6 +1535,15 @@ mod update_file_tests {
             size: Some(0),
             date_created: Some(now()),
             file_type: None,
+            description: None,
+            properties: Some(BTreeMap::new()),
+            date_uploaded: None,
+            date_modified: None,
+            favorite: false,
+            download_count: 0,
+            last_accessed: None,
+            location: None,
+            relevance: None,
         })
         .unwrap();
         assert_eq!(1, res.id);
@@ -972,6 +1566,15 @@ mod update_file_tests {
             size: Some(0),
             date_created: Some(now()),
             file_type: None,
+            description: None,
+            properties: Some(BTreeMap::new()),
+            date_uploaded: None,
+            date_modified: None,
+            favorite: false,
+            download_count: 0,
+            last_accessed: None,
+            location: None,
+            relevance: None,
         })
         .unwrap_err();
         assert_eq!(UpdateFileError::FolderAlreadyExistsWithSameName, res);
@@ -1002,6 +1605,15 @@ mod update_file_tests {
             size: Some(0),
             date_created: Some(now()),
             file_type: None,
+            description: None,
+            properties: Some(BTreeMap::new()),
+            date_uploaded: None,
+            date_modified: None,
+            favorite: false,
+            download_count: 0,
+            last_accessed: None,
+            location: None,
+            relevance: None,
         })
         .unwrap_err();
         assert_eq!(UpdateFileError::FolderAlreadyExistsWithSameName, res);
@@ -1025,6 +1637,15 @@ mod update_file_tests {
             size: Some(0),
             date_created: Some(now()),
             file_type: None,
+            description: None,
+            properties: Some(BTreeMap::new()),
+            date_uploaded: None,
+            date_modified: None,
+            favorite: false,
+            download_count: 0,
+            last_accessed: None,
+            location: None,
+            relevance: None,
         })
         .unwrap_err();
         assert_eq!(UpdateFileError::FolderAlreadyExistsWithSameName, res);
@@ -1040,6 +1661,10 @@ mod update_file_tests {
                 tags: vec![],
                 path: "test/a".to_string(),
                 files: vec![],
+                description: None,
+                properties: BTreeMap::new(),
+                favorite: false,
+                saved_searches: None,
             }
         );
         cleanup();
@@ -1062,6 +1687,15 @@ mod update_file_tests {
             size: Some(0),
             date_created: Some(now()),
             file_type: None,
+            description: None,
+            properties: Some(BTreeMap::new()),
+            date_uploaded: None,
+            date_modified: None,
+            favorite: false,
+            download_count: 0,
+            last_accessed: None,
+            location: None,
+            relevance: None,
         })
         .unwrap();
         let folder_files = folder_service::get_folder(Some(0)).unwrap().files;
@@ -1088,6 +1722,15 @@ mod update_file_tests {
             size: None,
             date_created: None,
             file_type: Some(FileTypes::Text),
+            description: None,
+            properties: Some(BTreeMap::new()),
+            date_uploaded: None,
+            date_modified: None,
+            favorite: false,
+            download_count: 0,
+            last_accessed: None,
+            location: None,
+            relevance: None,
         };
         update_file(file).unwrap();
         let retrieved = get_file_metadata(1);
@@ -1127,6 +1770,15 @@ mod update_file_tests {
             size: Some(0),
             date_created: Some(now()),
             file_type: None,
+            description: None,
+            properties: Some(BTreeMap::new()),
+            date_uploaded: None,
+            date_modified: None,
+            favorite: false,
+            download_count: 0,
+            last_accessed: None,
+            location: None,
+            relevance: None,
         })
         .unwrap();
 
@@ -1166,6 +1818,15 @@ mod update_file_tests {
             size: Some(0),
             date_created: Some(now()),
             file_type: None,
+            description: None,
+            properties: Some(BTreeMap::new()),
+            date_uploaded: None,
+            date_modified: None,
+            favorite: false,
+            download_count: 0,
+            last_accessed: None,
+            location: None,
+            relevance: None,
         })
         .unwrap();
 
@@ -1213,6 +1874,15 @@ mod update_file_tests {
             size: Some(0),
             date_created: Some(now()),
             file_type: None,
+            description: None,
+            properties: Some(BTreeMap::new()),
+            date_uploaded: None,
+            date_modified: None,
+            favorite: false,
+            download_count: 0,
+            last_accessed: None,
+            location: None,
+            relevance: None,
         })
         .unwrap();
 
@@ -1252,6 +1922,15 @@ mod update_file_tests {
             size: Some(0),
             date_created: Some(now()),
             file_type: None,
+            description: None,
+            properties: Some(BTreeMap::new()),
+            date_uploaded: None,
+            date_modified: None,
+            favorite: false,
+            download_count: 0,
+            last_accessed: None,
+            location: None,
+            relevance: None,
         })
         .unwrap();
 
@@ -1305,6 +1984,15 @@ mod update_file_tests {
             size: Some(0),
             date_created: Some(now()),
             file_type: None,
+            description: None,
+            properties: Some(BTreeMap::new()),
+            date_uploaded: None,
+            date_modified: None,
+            favorite: false,
+            download_count: 0,
+            last_accessed: None,
+            location: None,
+            relevance: None,
         })
         .unwrap();
 
@@ -1320,6 +2008,89 @@ mod update_file_tests {
     }
 }
 
+#[cfg(test)]
+mod update_file_contents_tests {
+    use std::fs;
+    use std::path::Path;
+
+    use crate::encryption;
+    use crate::model::error::file_errors::UpdateFileContentsError;
+    use crate::service::file_service::{file_dir, hash_file_contents, update_file_contents};
+    use crate::storage;
+    use crate::test::{
+        cleanup, create_file_db_entry, create_file_disk, create_tag_file, init_db_folder,
+    };
+
+    #[test]
+    fn replaces_contents_and_keeps_tags() {
+        init_db_folder();
+        create_file_db_entry("notes.md", None);
+        create_file_disk("notes.md", "old");
+        create_tag_file("tag", 1);
+        let res = update_file_contents(1, b"new contents", &hash_file_contents(b"old")).unwrap();
+        assert_eq!(1, res.id);
+        assert_eq!(Some(12), res.size);
+        assert_eq!("tag", res.tags[0].title);
+        let on_disk = fs::read_to_string(format!("{}/notes.md", file_dir())).unwrap();
+        assert_eq!("new contents", on_disk);
+        cleanup();
+    }
+
+    #[test]
+    fn keeps_encrypted_files_encrypted() {
+        init_db_folder();
+        encryption::set_test_secret(Some("passphrase"));
+        create_file_db_entry("test.txt", None);
+        create_file_disk("test.txt", "old");
+        let path = Path::new(&file_dir()).join("test.txt");
+        encryption::encrypt_file_in_place(&path).unwrap();
+        update_file_contents(1, b"new", &hash_file_contents(b"old")).unwrap();
+        assert!(encryption::is_encrypted(&path).unwrap());
+        assert_eq!(b"new".to_vec(), storage::read("test.txt").unwrap());
+        cleanup();
+    }
+
+    #[test]
+    fn accepts_quoted_etag() {
+        init_db_folder();
+        create_file_db_entry("test.txt", None);
+        create_file_disk("test.txt", "old");
+        let etag = format!("\"{}\"", hash_file_contents(b"old"));
+        assert!(update_file_contents(1, b"new", &etag).is_ok());
+        cleanup();
+    }
+
+    #[test]
+    fn rejects_stale_hash() {
+        init_db_folder();
+        create_file_db_entry("test.txt", None);
+        create_file_disk("test.txt", "someone else's edit");
+        let res = update_file_contents(1, b"new", &hash_file_contents(b"old")).unwrap_err();
+        assert_eq!(UpdateFileContentsError::HashMismatch, res);
+        let on_disk = fs::read_to_string(format!("{}/test.txt", file_dir())).unwrap();
+        assert_eq!("someone else's edit", on_disk);
+        cleanup();
+    }
+
+    #[test]
+    fn rejects_non_text_files() {
+        init_db_folder();
+        create_file_db_entry("test.png", None);
+        create_file_disk("test.png", "old");
+        let res = update_file_contents(1, b"new", &hash_file_contents(b"old")).unwrap_err();
+        assert_eq!(UpdateFileContentsError::UnsupportedType, res);
+        cleanup();
+    }
+
+    #[test]
+    fn returns_not_found() {
+        init_db_folder();
+        let res = update_file_contents(1, b"new", "").unwrap_err();
+        assert_eq!(UpdateFileContentsError::NotFound, res);
+        cleanup();
+    }
+}
+
 #[cfg(test)]
 mod delete_file_with_id_tests {
     use rocket::tokio;
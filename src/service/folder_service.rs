@@ -1,7 +1,6 @@
 use std::backtrace::Backtrace;
 use std::collections::HashMap;
-use std::fs::{self, File};
-use std::path::Path;
+use std::io::Read;
 
 use itertools::Itertools;
 use regex::Regex;
@@ -12,17 +11,26 @@ use model::repository::Folder;
 use crate::model::api::FileApi;
 use crate::model::error::file_errors::{DeleteFileError, GetBulkPreviewError};
 use crate::model::error::folder_errors::{
-    CreateFolderError, DeleteFolderError, DownloadFolderError, GetChildFilesError, GetFolderError,
-    UpdateFolderError,
+    CreateFolderError, DeleteFolderError, DownloadFolderError, FavoriteFolderError,
+    GetChildFilesError, GetFolderError, UpdateFolderError,
 };
 
+use crate::auto_tags::service as auto_tag_service;
+use crate::exif::repository as exif_repository;
 use crate::model::request::folder_requests::{CreateFolderRequest, UpdateFolderRequest};
 use crate::model::response::TaggedItemApi;
 use crate::model::response::folder_responses::FolderResponse;
 use crate::previews;
-use crate::repository::{folder_repository, open_connection};
+use crate::repository::{
+    activity_repository, folder_repository, open_connection, property_repository,
+};
+use crate::saved_searches::repository as saved_search_repository;
 use crate::service::file_service;
 use crate::service::file_service::{check_root_dir, file_dir};
+use crate::storage::{
+    self,
+    models::{ContentsStream, StorageError, StoredContents, StoredEntry},
+};
 use crate::tags::repository as tag_repository;
 use crate::tags::service as tag_service;
 use crate::{model, repository};
@@ -47,6 +55,20 @@ pub fn get_folder(id: Option<u32>) -> Result<FolderResponse, GetFolderError> {
             return Err(GetFolderError::DbFailure);
         }
     };
+    let mut folder_ids: Vec<u32> = child_folders.iter().filter_map(|f| f.id).collect();
+    folder_ids.extend(db_id);
+    let favorite_ids = match activity_repository::get_favorite_folder_ids(&folder_ids, &con) {
+        Ok(ids) => ids,
+        Err(e) => {
+            log::error!(
+                "Failed to retrieve favorite folders from database! Nested exception is {e:?}\n{}",
+                Backtrace::force_capture()
+            );
+            con.close().unwrap();
+            return Err(GetFolderError::DbFailure);
+        }
+    };
+    folder.favorite = favorite_ids.contains(&folder.id);
     let mut converted_folders: Vec<FolderResponse> = Vec::new();
     for child in child_folders {
         let tags: Vec<TaggedItemApi> =
@@ -63,6 +85,7 @@ pub fn get_folder(id: Option<u32>) -> Result<FolderResponse, GetFolderError> {
             };
         let mut converted: FolderResponse = child.into();
         converted += tags;
+        converted.favorite = favorite_ids.contains(&converted.id);
         converted_folders.push(converted);
     }
     let tag_db_id = id.unwrap_or_default();
@@ -73,6 +96,34 @@ pub fn get_folder(id: Option<u32>) -> Result<FolderResponse, GetFolderError> {
             return Err(GetFolderError::TagError);
         }
     };
+    // the root folder isn't in the database, so it can't have any details
+    if let Some(db_id) = db_id {
+        match property_repository::get_folder_details(db_id, &con) {
+            Ok(details) => folder += details,
+            Err(e) => {
+                log::error!(
+                    "Failed to retrieve details for folder. Exception is {e:?}\n{}",
+                    Backtrace::force_capture()
+                );
+                con.close().unwrap();
+                return Err(GetFolderError::DbFailure);
+            }
+        }
+    }
+    // saved searches are browsed like folders, so they're listed alongside the folders in root
+    if db_id.is_none() {
+        match saved_search_repository::get_all_saved_searches(&con) {
+            Ok(searches) => folder.saved_searches = Some(searches),
+            Err(e) => {
+                log::error!(
+                    "Failed to retrieve saved searches for the root folder. Exception is {e:?}\n{}",
+                    Backtrace::force_capture()
+                );
+                con.close().unwrap();
+                return Err(GetFolderError::DbFailure);
+            }
+        }
+    }
     folder += get_files_for_folder(db_id, &con).unwrap();
     con.close().unwrap();
     folder += converted_folders;
@@ -80,10 +131,74 @@ pub fn get_folder(id: Option<u32>) -> Result<FolderResponse, GetFolderError> {
     Ok(folder)
 }
 
+/// retrieves all starred folders, without their child files and folders
+pub fn get_favorite_folders() -> Result<Vec<FolderResponse>, GetFolderError> {
+    let con: Connection = repository::open_connection();
+    let folders = match activity_repository::get_favorite_folders(&con) {
+        Ok(f) => f,
+        Err(e) => {
+            log::error!(
+                "Failed to pull favorite folders from database! Nested exception is {e:?}\n{}",
+                Backtrace::force_capture()
+            );
+            con.close().unwrap();
+            return Err(GetFolderError::DbFailure);
+        }
+    };
+    let mut converted_folders: Vec<FolderResponse> = Vec::new();
+    for folder in folders {
+        let tags: Vec<TaggedItemApi> =
+            match tag_repository::get_all_tags_for_folder(folder.id.unwrap_or(0), &con) {
+                Ok(t) => t.into_iter().map_into().collect(),
+                Err(e) => {
+                    log::error!(
+                        "Failed to retrieve tags for folder. Exception is {e:?}\n{}",
+                        Backtrace::force_capture()
+                    );
+                    con.close().unwrap();
+                    return Err(GetFolderError::TagError);
+                }
+            };
+        let mut converted: FolderResponse = folder.into();
+        converted += tags;
+        converted.favorite = true;
+        converted_folders.push(converted);
+    }
+    con.close().unwrap();
+    Ok(converted_folders)
+}
+
+/// stars or un-stars the folder with the passed id
+pub fn set_folder_favorite(id: u32, favorite: bool) -> Result<(), FavoriteFolderError> {
+    // the root folder isn't in the database, so it can't be starred
+    if id == 0 {
+        return Err(FavoriteFolderError::NotFound);
+    }
+    match get_folder_by_id(Some(id)) {
+        Ok(_) => {}
+        Err(GetFolderError::NotFound) => return Err(FavoriteFolderError::NotFound),
+        Err(_) => return Err(FavoriteFolderError::DbFailure),
+    };
+    let con = repository::open_connection();
+    let res = if favorite {
+        activity_repository::add_favorite_folder(id, &con)
+    } else {
+        activity_repository::remove_favorite_folder(id, &con)
+    };
+    con.close().unwrap();
+    res.map_err(|e| {
+        log::error!(
+            "Failed to update favorite status of folder. Nested exception is {e:?}\n{}",
+            Backtrace::force_capture()
+        );
+        FavoriteFolderError::DbFailure
+    })
+}
+
 pub async fn create_folder(
     folder: &CreateFolderRequest,
 ) -> Result<FolderResponse, CreateFolderError> {
-    check_root_dir(file_dir()).await;
+    check_root_dir();
     // the client can pass 0 for the folder id, in which case it needs to be translated to None for the database
     let db_folder = if let Some(0) = folder.parent_id {
         None
@@ -97,11 +212,9 @@ pub async fn create_folder(
     };
     match create_folder_internal(&db_folder) {
         Ok(f) => {
-            let folder_path = format!("{}/{}", file_dir(), f.name);
-            let fs_path = Path::new(folder_path.as_str());
             tag_service::update_folder_tags(f.id.unwrap(), vec![])
                 .map_err(|_| CreateFolderError::DbFailure)?;
-            match fs::create_dir(fs_path) {
+            match storage::backend().create_dir(&f.name) {
                 Ok(_) => Ok(f.into()),
                 Err(_) => Err(CreateFolderError::FileSystemFailure),
             }
@@ -153,11 +266,10 @@ pub fn update_folder(folder: &UpdateFolderRequest) -> Result<FolderResponse, Upd
     };
 
     let updated_folder = update_folder_internal(&db_folder)?;
+    let root_regex = Regex::new(format!("^{}/", file_dir()).as_str()).unwrap();
+    let new_path = root_regex.replace(&updated_folder.name, "").to_string();
     // if we can't rename the folder, then we have problems
-    if let Err(e) = fs::rename(
-        format!("{}/{}", file_dir(), original_folder.name),
-        &updated_folder.name,
-    ) {
+    if let Err(e) = storage::backend().rename_dir(&original_folder.name, &new_path) {
         log::error!(
             "Failed to move folder! Nested exception is {e:?}\n{}",
             Backtrace::force_capture()
@@ -187,17 +299,42 @@ pub fn update_folder(folder: &UpdateFolderRequest) -> Result<FolderResponse, Upd
         .collect();
     tag_service::update_folder_tags(updated_folder.id.unwrap(), explicit_tags)
         .map_err(|_| UpdateFolderError::TagError)?;
+    // moving or renaming the folder changes the path of every file under it.
+    // That can be a lot of files, so the rules are applied in the background
+    if parent_id_changed || original_folder.name != new_path {
+        auto_tag_service::queue_apply_rules_to_folder(folder.id);
+    }
+    let con = repository::open_connection();
+    let details_res = property_repository::get_folder_details(folder.id, &con)
+        .map(|it| it.updated_with(folder.description.clone(), folder.properties.clone()))
+        .and_then(|details| {
+            property_repository::set_folder_details(folder.id, &details, &con)?;
+            let favorite_ids = activity_repository::get_favorite_folder_ids(&[folder.id], &con)?;
+            Ok((details, favorite_ids))
+        });
+    con.close().unwrap();
+    let (details, favorite_ids) = match details_res {
+        Ok(res) => res,
+        Err(e) => {
+            log::error!(
+                "Failed to update folder details. Error is {e:?}\n{}",
+                Backtrace::force_capture()
+            );
+            return Err(UpdateFolderError::DbFailure);
+        }
+    };
     Ok(FolderResponse {
         id: updated_folder.id.unwrap(),
         folders: Vec::new(),
         files: Vec::new(),
         parent_id: updated_folder.parent_id,
-        path: Regex::new(format!("^{}/", file_dir()).as_str())
-            .unwrap()
-            .replace(&updated_folder.name, "")
-            .to_string(),
+        path: new_path,
         name,
         tags: folder.tags.clone(),
+        description: details.description,
+        properties: details.properties,
+        favorite: favorite_ids.contains(&folder.id),
+        saved_searches: None,
     })
 }
 
@@ -220,8 +357,7 @@ pub fn delete_folder(id: u32) -> Result<(), DeleteFolderError> {
     con.close().unwrap();
     let deleted_folder = deleted_folder?;
     // delete went well, now time to actually remove the folder
-    let path = format!("{}/{}", file_dir(), deleted_folder.name);
-    if let Err(e) = fs::remove_dir_all(path) {
+    if let Err(e) = storage::backend().delete_dir(&deleted_folder.name) {
         log::error!(
             "Failed to recursively delete folder from disk! Nested exception is {e:?}\n{}",
             Backtrace::force_capture()
@@ -264,11 +400,11 @@ pub async fn get_file_previews_for_folder(
     Ok(map)
 }
 
-/// compresses the folder with the passed id in tar format, stores it in the system temp directory, and returns the resulting file.
+/// archives the folder with the passed id in tar format, returning the archive as a stream that's built as it's read.
 /// If the id is 0, this function fails if the folder isn't found or if the folder is root. While technically possible, the root folder shouldn't
 /// be downloaded in its entirety - that just seems suspicious. Regular backups should be made outside of the api, and I don't want this endpoint to be
 /// used in place of properly backup up your stuff
-pub fn download_folder(id: u32) -> Result<File, DownloadFolderError> {
+pub fn download_folder(id: u32) -> Result<ContentsStream, DownloadFolderError> {
     if id == 0 {
         return Err(DownloadFolderError::RootFolder);
     }
@@ -279,39 +415,132 @@ pub fn download_folder(id: u32) -> Result<File, DownloadFolderError> {
         );
         DownloadFolderError::NotFound
     })?;
-    let temp_dir = std::env::temp_dir();
-    // nano id used here to ensure file names are unique if the same file is downloaded multiple times
-    let tarchive_dir = format!(
-        "{}/{}-{}.tar",
-        temp_dir.display(),
-        folder.name,
-        nanoid::nanoid!()
-    );
-    // so we have to actually create the tar archive file first before passing it to the builder
-    let tarchive = File::create(tarchive_dir.clone()).map_err(|e| {
+    let tarchive = FolderTar::new(&folder.path).map_err(|e| {
         log::error!(
-            "Failed to create tar archive for {tarchive_dir}; {e:?}\n{}",
+            "Failed to list the contents of {} to tarchive; {e:?}\n{}",
+            folder.path,
             Backtrace::force_capture()
         );
         DownloadFolderError::Tar
     })?;
-    let mut tarchive_builder = tar::Builder::new(tarchive);
-    if let Err(e) = tarchive_builder.append_dir_all("", format!("{}/{}", file_dir(), folder.path)) {
+    // the archive is built as it's streamed, so that the plain contents of encrypted files are never written to the disk
+    storage::stream_reader(tarchive, None).map_err(|e| {
         log::error!(
-            "Failed to tarchive {}/{}; {e:?}\n{}",
-            file_dir(),
+            "Failed to tarchive {}; {e:?}\n{}",
             folder.path,
             Backtrace::force_capture()
         );
-        return Err(DownloadFolderError::Tar);
-    };
-    if let Err(e) = tarchive_builder.finish() {
-        log::error!(
-            "Failed to close tarchive {tarchive_dir}; {e:?}\n{}",
-            Backtrace::force_capture()
-        );
+        DownloadFolderError::Tar
+    })
+}
+
+/// a tar archive of everything stored inside of a folder, with paths relative to the folder, that's built as it's read.
+/// Stored files are opened (and decrypted) one at a time once the archive reaches them
+struct FolderTar {
+    prefix: String,
+    entries: std::vec::IntoIter<StoredEntry>,
+    /// only used to write headers, since the contents of files are read straight from storage
+    builder: tar::Builder<Vec<u8>>,
+    /// headers and padding waiting to be read, and how much of them has been read already
+    pending: Vec<u8>,
+    position: usize,
+    /// the file being added and how many of its bytes haven't been read yet
+    current: Option<(StoredContents, u64)>,
+    finished: bool,
+}
+
+impl FolderTar {
+    fn new(dir: &str) -> Result<Self, StorageError> {
+        Ok(Self {
+            prefix: format!("{dir}/"),
+            entries: storage::backend().list(dir)?.into_iter(),
+            builder: tar::Builder::new(Vec::new()),
+            pending: Vec::new(),
+            position: 0,
+            current: None,
+            finished: false,
+        })
+    }
+
+    /// queues the header of the next entry, or the end of the archive once every entry has been added.
+    /// Returns `false` if there's nothing left to queue
+    fn queue_next(&mut self) -> Result<bool, StorageError> {
+        let Some(entry) = self.entries.next() else {
+            if self.finished {
+                return Ok(false);
+            }
+            self.builder.finish()?;
+            self.finished = true;
+            self.take_pending();
+            return Ok(true);
+        };
+        let archive_path = entry.path.strip_prefix(&self.prefix).unwrap_or(&entry.path);
+        // entries might not exist on the local disk and files are decrypted as they're added, so their headers have to be built by hand
+        let mut header = tar::Header::new_gnu();
+        header.set_mtime(chrono::offset::Local::now().timestamp() as u64);
+        if entry.is_dir {
+            header.set_entry_type(tar::EntryType::Directory);
+            header.set_mode(0o755);
+            header.set_size(0);
+        } else {
+            let contents = storage::open(&entry.path)?;
+            header.set_entry_type(tar::EntryType::Regular);
+            header.set_mode(0o644);
+            header.set_size(contents.size());
+            let size = contents.size();
+            self.current = Some((contents, size));
+        }
+        self.builder
+            .append_data(&mut header, archive_path, std::io::empty())?;
+        self.take_pending();
+        Ok(true)
+    }
+
+    /// moves everything the builder has written into the pending bytes
+    fn take_pending(&mut self) {
+        self.pending = std::mem::take(self.builder.get_mut());
+        self.position = 0;
+    }
+}
+
+impl Read for FolderTar {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        loop {
+            if self.position < self.pending.len() {
+                let len = buf.len().min(self.pending.len() - self.position);
+                buf[..len].copy_from_slice(&self.pending[self.position..self.position + len]);
+                self.position += len;
+                return Ok(len);
+            }
+            if let Some((contents, remaining)) = self.current.as_mut() {
+                if *remaining > 0 {
+                    let limit = (*remaining).min(buf.len() as u64) as usize;
+                    let len = contents.read(&mut buf[..limit])?;
+                    if len == 0 {
+                        return Err(std::io::Error::new(
+                            std::io::ErrorKind::UnexpectedEof,
+                            "a stored file ended before its size was reached",
+                        ));
+                    }
+                    *remaining -= len as u64;
+                    return Ok(len);
+                }
+                // tar entries are padded to the next full block
+                let size = contents.size();
+                self.current = None;
+                self.pending = vec![0; ((512 - size % 512) % 512) as usize];
+                self.position = 0;
+                continue;
+            }
+            let queued = self.queue_next().map_err(|e| match e {
+                StorageError::Io(e) => e,
+                e => std::io::Error::other(e.to_string()),
+            })?;
+            if !queued {
+                return Ok(0);
+            }
+        }
     }
-    File::open(tarchive_dir.clone()).map_err(|_| DownloadFolderError::NotFound)
 }
 
 fn get_folder_by_id(id: Option<u32>) -> Result<Folder, GetFolderError> {
@@ -357,7 +586,7 @@ fn create_folder_internal(folder: &Folder) -> Result<Folder, CreateFolderError>
                 return Err(CreateFolderError::ParentNotFound);
             }
         };
-    } else if Path::new(format!("{}/{}", file_dir(), folder_path).as_str()).exists() {
+    } else if storage::backend().exists(&folder_path).unwrap_or(false) {
         con.close().unwrap();
         return Err(CreateFolderError::AlreadyExists);
     }
@@ -543,6 +772,8 @@ fn is_attempt_move_to_sub_child(
 ///
 /// When a folder's parent changes, this function removes implicit tags from all
 /// descendant files and folders that originated from the old ancestor chain.
+/// Tags from the new ancestor chain are implied afterwards when the folder's tags are updated, which skips tags
+/// blocked by the folder or anything under it.
 ///
 /// ## Parameters
 /// - `folder_id`: the id of the folder being moved
@@ -632,6 +863,36 @@ fn get_files_for_folder(
         .iter()
         .map(|f| f.id.expect("files pulled from database didn't have ID!"))
         .collect();
+    let mut file_details = match property_repository::get_details_for_files(&file_ids, con) {
+        Ok(res) => res,
+        Err(e) => {
+            log::error!(
+                "Failed to get details on files {e:?}\n{}",
+                Backtrace::force_capture()
+            );
+            return Err(GetChildFilesError::DbFailure);
+        }
+    };
+    let mut file_activity = match activity_repository::get_activity_for_files(&file_ids, con) {
+        Ok(res) => res,
+        Err(e) => {
+            log::error!(
+                "Failed to get activity on files {e:?}\n{}",
+                Backtrace::force_capture()
+            );
+            return Err(GetChildFilesError::DbFailure);
+        }
+    };
+    let mut file_locations = match exif_repository::get_locations_for_files(&file_ids, con) {
+        Ok(res) => res,
+        Err(e) => {
+            log::error!(
+                "Failed to get locations of files {e:?}\n{}",
+                Backtrace::force_capture()
+            );
+            return Err(GetChildFilesError::DbFailure);
+        }
+    };
     let file_tags = match tag_repository::get_all_tags_for_files(file_ids, con) {
         Ok(res) => res,
         Err(e) => {
@@ -650,7 +911,12 @@ fn get_files_for_folder(
             Vec::new()
         };
         let tags: Vec<TaggedItemApi> = tags.iter().cloned().map_into().collect();
-        result.push(FileApi::from_with_tags(file, tags));
+        let details = file_details.remove(&file.id.unwrap()).unwrap_or_default();
+        let mut file = FileApi::from_with_tags(file, tags);
+        file += details;
+        file += file_activity.remove(&file.id).unwrap_or_default();
+        file.location = file_locations.remove(&file.id);
+        result.push(file);
     }
     Ok(result)
 }
@@ -697,6 +963,8 @@ fn delete_folder_recursively(id: u32, con: &Connection) -> Result<Folder, Delete
 
 #[cfg(test)]
 mod get_folder_tests {
+    use std::collections::BTreeMap;
+
     use crate::model::error::folder_errors::GetFolderError;
     use crate::model::response::TaggedItemApi;
     use crate::model::response::folder_responses::FolderResponse;
@@ -717,6 +985,10 @@ mod get_folder_tests {
                 folders: vec![],
                 files: vec![],
                 tags: vec![],
+                description: None,
+                properties: BTreeMap::new(),
+                favorite: false,
+                saved_searches: None,
             },
             folder
         );
@@ -748,6 +1020,10 @@ mod get_folder_tests {
                 title: "tag1".to_string(),
                 implicit_from: None,
             }],
+            description: None,
+            properties: BTreeMap::new(),
+            favorite: false,
+            saved_searches: None,
         };
         assert_eq!(expected, get_folder(Some(1)).unwrap());
         cleanup();
@@ -756,6 +1032,8 @@ mod get_folder_tests {
 
 #[cfg(test)]
 mod update_folder_tests {
+    use std::collections::BTreeMap;
+
     use crate::model::error::folder_errors::UpdateFolderError;
     use crate::model::request::folder_requests::UpdateFolderRequest;
     use crate::model::response::TaggedItemApi;
@@ -768,6 +1046,62 @@ mod update_folder_tests {
         create_tag_folder, imply_tag_on_folder, init_db_folder,
     };
 
+    #[test]
+    fn update_folder_sets_details() {
+        init_db_folder();
+        create_folder_db_entry("test", None);
+        create_folder_disk("test");
+        let properties = BTreeMap::from([("project".to_string(), "taxes".to_string())]);
+        let res = update_folder(&UpdateFolderRequest {
+            id: 1,
+            name: "test".to_string(),
+            parent_id: None,
+            tags: vec![],
+            description: Some("2024 paperwork".to_string()),
+            properties: Some(properties.clone()),
+        })
+        .unwrap();
+        assert_eq!(Some("2024 paperwork".to_string()), res.description);
+        assert_eq!(properties, res.properties);
+        let res = get_folder(Some(1)).unwrap();
+        assert_eq!(Some("2024 paperwork".to_string()), res.description);
+        assert_eq!(properties, res.properties);
+        cleanup();
+    }
+
+    #[test]
+    fn update_folder_keeps_details_when_missing() {
+        init_db_folder();
+        create_folder_db_entry("test", None);
+        create_folder_disk("test");
+        let properties = BTreeMap::from([("project".to_string(), "taxes".to_string())]);
+        update_folder(&UpdateFolderRequest {
+            id: 1,
+            name: "test".to_string(),
+            parent_id: None,
+            tags: vec![],
+            description: Some("2024 paperwork".to_string()),
+            properties: Some(properties.clone()),
+        })
+        .unwrap();
+        // renaming the folder without sending its details shouldn't remove them
+        let res = update_folder(&UpdateFolderRequest {
+            id: 1,
+            name: "renamed".to_string(),
+            parent_id: None,
+            tags: vec![],
+            description: None,
+            properties: None,
+        })
+        .unwrap();
+        assert_eq!(Some("2024 paperwork".to_string()), res.description);
+        assert_eq!(properties, res.properties);
+        let res = get_folder(Some(1)).unwrap();
+        assert_eq!(Some("2024 paperwork".to_string()), res.description);
+        assert_eq!(properties, res.properties);
+        cleanup();
+    }
+
     #[test]
     fn update_folder_adds_tags() {
         init_db_folder();
@@ -782,6 +1116,8 @@ mod update_folder_tests {
                 title: "tag1".to_string(),
                 implicit_from: None,
             }],
+            description: None,
+            properties: Some(BTreeMap::new()),
         })
         .unwrap();
         let expected = FolderResponse {
@@ -796,6 +1132,10 @@ mod update_folder_tests {
                 title: "tag1".to_string(),
                 implicit_from: None,
             }],
+            description: None,
+            properties: BTreeMap::new(),
+            favorite: false,
+            saved_searches: None,
         };
         assert_eq!(expected, get_folder(Some(1)).unwrap());
         cleanup();
@@ -811,6 +1151,8 @@ mod update_folder_tests {
             name: "test2".to_string(),
             parent_id: None,
             tags: vec![],
+            description: None,
+            properties: Some(BTreeMap::new()),
         })
         .unwrap_err();
         assert_eq!(UpdateFolderError::AlreadyExists, res);
@@ -830,6 +1172,8 @@ mod update_folder_tests {
             name: "test".to_string(),
             parent_id: None,
             tags: vec![],
+            description: None,
+            properties: Some(BTreeMap::new()),
         })
         .unwrap();
         let expected = FolderResponse {
@@ -840,6 +1184,10 @@ mod update_folder_tests {
             folders: vec![],
             files: vec![],
             tags: vec![],
+            description: None,
+            properties: BTreeMap::new(),
+            favorite: false,
+            saved_searches: None,
         };
         assert_eq!(expected, get_folder(Some(1)).unwrap());
         cleanup();
@@ -862,6 +1210,8 @@ mod update_folder_tests {
                 title: "tag1".to_string(),
                 implicit_from: None,
             }],
+            description: None,
+            properties: Some(BTreeMap::new()),
         })
         .unwrap();
 
@@ -894,6 +1244,8 @@ mod update_folder_tests {
                 title: "tag1".to_string(),
                 implicit_from: None,
             }],
+            description: None,
+            properties: Some(BTreeMap::new()),
         })
         .unwrap();
 
@@ -927,6 +1279,8 @@ mod update_folder_tests {
                 title: "tag1".to_string(),
                 implicit_from: None,
             }],
+            description: None,
+            properties: Some(BTreeMap::new()),
         })
         .unwrap();
 
@@ -940,6 +1294,8 @@ mod update_folder_tests {
             name: "parent".to_string(),
             parent_id: None,
             tags: vec![],
+            description: None,
+            properties: Some(BTreeMap::new()),
         })
         .unwrap();
 
@@ -973,6 +1329,8 @@ mod update_folder_tests {
                 title: "grandparent_tag".to_string(),
                 implicit_from: None,
             }],
+            description: None,
+            properties: Some(BTreeMap::new()),
         })
         .unwrap();
 
@@ -992,6 +1350,8 @@ mod update_folder_tests {
             name: "parent".to_string(),
             parent_id: Some(4), // new_parent folder
             tags: vec![],
+            description: None,
+            properties: Some(BTreeMap::new()),
         })
         .unwrap();
 
@@ -1030,6 +1390,8 @@ mod update_folder_tests {
                 title: "grandparent_tag".to_string(),
                 implicit_from: None,
             }],
+            description: None,
+            properties: Some(BTreeMap::new()),
         })
         .unwrap();
 
@@ -1048,6 +1410,8 @@ mod update_folder_tests {
             name: "parent".to_string(),
             parent_id: None, // moving to root
             tags: vec![],
+            description: None,
+            properties: Some(BTreeMap::new()),
         })
         .unwrap();
 
@@ -1090,6 +1454,8 @@ mod update_folder_tests {
                 title: "grandparent_tag".to_string(),
                 implicit_from: None,
             }],
+            description: None,
+            properties: Some(BTreeMap::new()),
         })
         .unwrap();
 
@@ -1103,6 +1469,8 @@ mod update_folder_tests {
                 title: "explicit_tag".to_string(),
                 implicit_from: None,
             }],
+            description: None,
+            properties: Some(BTreeMap::new()),
         })
         .unwrap();
 
@@ -1140,6 +1508,8 @@ mod update_folder_tests {
             name: "parent".to_string(),
             parent_id: None, // moving to root
             tags: vec![],
+            description: None,
+            properties: Some(BTreeMap::new()),
         })
         .unwrap();
 
@@ -1189,6 +1559,8 @@ mod update_folder_tests {
                 title: "tag1".to_string(),
                 implicit_from: None,
             }],
+            description: None,
+            properties: Some(BTreeMap::new()),
         })
         .unwrap();
 
@@ -1226,6 +1598,8 @@ mod update_folder_tests {
                 title: "tag1".to_string(),
                 implicit_from: None,
             }],
+            description: None,
+            properties: Some(BTreeMap::new()),
         })
         .unwrap();
 
@@ -1239,6 +1613,8 @@ mod update_folder_tests {
             name: "parent".to_string(),
             parent_id: None,
             tags: vec![],
+            description: None,
+            properties: Some(BTreeMap::new()),
         })
         .unwrap();
 
@@ -1275,6 +1651,8 @@ mod update_folder_tests {
                 title: "grandparent_tag".to_string(),
                 implicit_from: None,
             }],
+            description: None,
+            properties: Some(BTreeMap::new()),
         })
         .unwrap();
 
@@ -1296,6 +1674,8 @@ mod update_folder_tests {
             name: "parent".to_string(),
             parent_id: Some(4), // new_parent folder
             tags: vec![],
+            description: None,
+            properties: Some(BTreeMap::new()),
         })
         .unwrap();
 
@@ -1338,6 +1718,8 @@ mod update_folder_tests {
                 title: "grandparent_tag".to_string(),
                 implicit_from: None,
             }],
+            description: None,
+            properties: Some(BTreeMap::new()),
         })
         .unwrap();
 
@@ -1354,6 +1736,8 @@ mod update_folder_tests {
             name: "parent".to_string(),
             parent_id: None, // moving to root
             tags: vec![],
+            description: None,
+            properties: Some(BTreeMap::new()),
         })
         .unwrap();
 
@@ -1400,6 +1784,8 @@ mod update_folder_tests {
                 title: "grandparent_tag".to_string(),
                 implicit_from: None,
             }],
+            description: None,
+            properties: Some(BTreeMap::new()),
         })
         .unwrap();
 
@@ -1424,6 +1810,8 @@ mod update_folder_tests {
             name: "parent".to_string(),
             parent_id: None, // moving to root
             tags: vec![],
+            description: None,
+            properties: Some(BTreeMap::new()),
         })
         .unwrap();
 
@@ -1482,6 +1870,8 @@ mod update_folder_tests {
                     implicit_from: Some(1),
                 },
             ],
+            description: None,
+            properties: Some(BTreeMap::new()),
         })
         .unwrap();
 
@@ -1510,11 +1900,30 @@ mod update_folder_tests {
 
 #[cfg(test)]
 mod download_folder_tests {
+    use std::io::{Cursor, Read};
+    use std::path::Path;
+
+    use rocket::futures::StreamExt;
+
     use crate::{
-        service::folder_service::download_folder,
-        test::{cleanup, create_folder_db_entry, create_folder_disk, init_db_folder},
+        encryption,
+        service::{file_service::file_dir, folder_service::download_folder},
+        test::{
+            cleanup, create_file_disk, create_folder_db_entry, create_folder_disk, init_db_folder,
+        },
     };
 
+    /// reads the whole streamed archive of the folder with the passed id
+    async fn read_archive(id: u32) -> tar::Archive<Cursor<Vec<u8>>> {
+        let stream = download_folder(id).unwrap();
+        let mut archive = Vec::new();
+        let mut chunks = stream.chunks;
+        while let Some(chunk) = chunks.next().await {
+            archive.extend(chunk.unwrap());
+        }
+        tar::Archive::new(Cursor::new(archive))
+    }
+
     #[test]
     fn works() {
         init_db_folder();
@@ -1526,6 +1935,64 @@ mod download_folder_tests {
         assert!(download_folder(2).is_ok());
         cleanup();
     }
+
+    #[rocket::async_test]
+    async fn archives_nested_files() {
+        init_db_folder();
+        create_folder_disk("test/inner");
+        create_folder_db_entry("test", None);
+        let large = "abc".repeat(50_000);
+        create_file_disk("test/top.txt", "top");
+        create_file_disk("test/inner/large.txt", &large);
+        let mut contents: Vec<(String, String)> = read_archive(1)
+            .await
+            .entries()
+            .unwrap()
+            .map(|it| it.unwrap())
+            .filter(|it| it.header().entry_type().is_file())
+            .map(|mut it| {
+                let mut contents = String::new();
+                it.read_to_string(&mut contents).unwrap();
+                (it.path().unwrap().to_string_lossy().to_string(), contents)
+            })
+            .collect();
+        contents.sort();
+        assert_eq!(
+            vec![
+                ("inner/large.txt".to_string(), large),
+                ("top.txt".to_string(), "top".to_string())
+            ],
+            contents
+        );
+        cleanup();
+    }
+
+    #[rocket::async_test]
+    async fn decrypts_encrypted_files() {
+        init_db_folder();
+        encryption::set_test_secret(Some("passphrase"));
+        create_folder_disk("test/inner");
+        create_folder_db_entry("test", None);
+        create_file_disk("test/inner/secret.txt", "hello");
+        encryption::encrypt_file_in_place(&Path::new(&file_dir()).join("test/inner/secret.txt"))
+            .unwrap();
+        let mut archive = read_archive(1).await;
+        let mut entry = archive
+            .entries()
+            .unwrap()
+            .map(|it| it.unwrap())
+            .find(|it| it.path().unwrap() == Path::new("inner/secret.txt"))
+            .unwrap();
+        let mut contents = String::new();
+        entry.read_to_string(&mut contents).unwrap();
+        assert_eq!("hello", contents);
+        // the archive is streamed, so no plain copy of it is written to the disk
+        let temp_files = std::fs::read_dir(crate::temp_dir())
+            .map(|it| it.count())
+            .unwrap_or(0);
+        assert_eq!(0, temp_files);
+        cleanup();
+    }
 }
 
 #[cfg(test)]
@@ -4,75 +4,62 @@ use std::collections::HashSet;
 use itertools::Itertools;
 use rusqlite::Connection;
 
-use crate::model::api::FileApi;
+use crate::exif::repository as exif_repository;
+use crate::model::api::{FileApi, SearchFacets};
 use crate::model::error::file_errors::SearchFileError;
+use crate::model::error::folder_errors::GetFolderError;
 use crate::model::request::attributes::AttributeSearch;
-use crate::repository::{file_repository, open_connection};
+use crate::model::request::file_requests::SearchFilesParams;
+use crate::model::request::search_query::SearchQuery;
+use crate::model::response::TaggedItemApi;
+use crate::model::response::folder_responses::FolderResponse;
+use crate::repository::{
+    activity_repository, file_repository, folder_repository, open_connection, property_repository,
+    title_search,
+};
 use crate::tags::repository as tag_repository;
 
-pub fn search_files(
-    search_title: &str,
-    search_tags: Vec<String>,
-    search_attributes: AttributeSearch,
-) -> Result<HashSet<FileApi>, SearchFileError> {
-    let search_tags: HashSet<String> = HashSet::from_iter(search_tags);
+/// searches for files matching all of the criteria in `params`, returning the requested page of results ordered by its sort,
+/// along with the total number of matching files
+pub fn search_files(params: SearchFilesParams) -> Result<(Vec<FileApi>, u32), SearchFileError> {
+    let SearchFilesParams {
+        title,
+        tags,
+        attributes,
+        content,
+        query,
+        sort,
+        page,
+    } = params;
+    let query = build_query(&title, tags, attributes, &content, query);
+    let (limit, offset) = page;
     let con: Connection = open_connection();
-    let mut search_results: Vec<Result<HashSet<FileApi>, SearchFileError>> = vec![];
-    if !search_tags.is_empty() {
-        search_results.push(search_files_by_tags(&search_tags, &con).inspect_err(|e| {
+    let files = match file_repository::search_files_by_query(&query, sort, limit, offset, &con) {
+        Ok(files) => files,
+        Err(e) => {
+            con.close().unwrap();
             log::error!(
-                "Failed to search files by tags. Exception is {e:?}\n{}",
+                "Failed to search files. Error is {e:?}\n{}",
                 Backtrace::force_capture()
-            )
-        }));
-    }
-    if !search_title.is_empty() {
-        search_results.push(search_files_by_title(search_title, &con).inspect_err(|e| {
+            );
+            return Err(SearchFileError::DbError);
+        }
+    };
+    let total = match file_repository::count_files_by_query(&query, &con) {
+        Ok(total) => total,
+        Err(e) => {
+            con.close().unwrap();
             log::error!(
-                "Failed to search files by title. Exception is {e:?}\n{}",
+                "Failed to count searched files. Error is {e:?}\n{}",
                 Backtrace::force_capture()
-            )
-        }));
-    }
-    if !search_attributes.is_empty() {
-        search_results.push(search_files_by_attributes(search_attributes, &con));
-    }
-    // TODO need to modularize this method because will be adding in searching on metadata. My idea is to perform each type of search
-    // individually (tags, then metadata, then text), retain only the ones that intersect between all 3,
-    // but pulling tags for search by tags is NECESSARY, so we can't pull tags at the end.
-    //
-    // I could make the retain function only care about looking at file IDs. idk your tests should cover any mistakes
-    let res: Result<Vec<HashSet<FileApi>>, SearchFileError> = search_results.into_iter().collect();
-    if let Err(e) = res {
-        con.close().unwrap();
-        log::error!(
-            "Failed to search files. Error is {e:?}\n{}",
-            Backtrace::force_capture()
-        );
-        return Err(e);
-    }
-    let condensed: Vec<HashSet<FileApi>> = res.into_iter().flatten().collect();
-    // compare all the files and only include the ones in all the returned lists
-    let all_files: HashSet<FileApi> = condensed.iter().flatten().cloned().collect();
-    let mut final_set: HashSet<FileApi> = HashSet::new();
-    for file in all_files {
-        let mut all_match = true;
-        for file_set in condensed.iter() {
-            if !file_set.iter().any(|f| f.id == file.id) {
-                all_match = false;
-                break;
-            }
-        }
-        if all_match {
-            final_set.insert(file.clone());
+            );
+            return Err(SearchFileError::DbError);
         }
-    }
-    final_set = final_set.into_iter().unique_by(|f| f.id).collect();
+    };
+    let files: Vec<FileApi> = files.into_iter().map(FileApi::from).collect();
     // now make sure all files have their tags or else we'll get inconsistent response bodies
-    let tag_mapping = match tag_repository::get_all_tags_for_files(
-        final_set.iter().map(|f| f.id).collect(),
-        &con,
-    ) {
+    let file_ids: Vec<u32> = files.iter().map(|f| f.id).collect();
+    let tag_mapping = match tag_repository::get_all_tags_for_files(file_ids.clone(), &con) {
         Ok(tags) => tags,
         Err(e) => {
             con.close().unwrap();
@@ -83,12 +70,49 @@ pub fn search_files(
             return Err(SearchFileError::DbError);
         }
     };
-    // using a normal for loop here confuses the rust compiler, and it offers suggestions that just further breaks things.
-    // am I doing this wrong? probably...but it works
-    let final_set: HashSet<FileApi> = final_set
+    let mut details_mapping = match property_repository::get_details_for_files(&file_ids, &con) {
+        Ok(details) => details,
+        Err(e) => {
+            con.close().unwrap();
+            log::error!(
+                "Failed to search files - failed to retrieve details on all files. Error is {e:?}\n{}",
+                Backtrace::force_capture()
+            );
+            return Err(SearchFileError::DbError);
+        }
+    };
+    let mut activity_mapping = match activity_repository::get_activity_for_files(&file_ids, &con) {
+        Ok(activity) => activity,
+        Err(e) => {
+            con.close().unwrap();
+            log::error!(
+                "Failed to search files - failed to retrieve activity on all files. Error is {e:?}\n{}",
+                Backtrace::force_capture()
+            );
+            return Err(SearchFileError::DbError);
+        }
+    };
+    let mut location_mapping = match exif_repository::get_locations_for_files(&file_ids, &con) {
+        Ok(locations) => locations,
+        Err(e) => {
+            con.close().unwrap();
+            log::error!(
+                "Failed to search files - failed to retrieve locations of all files. Error is {e:?}\n{}",
+                Backtrace::force_capture()
+            );
+            return Err(SearchFileError::DbError);
+        }
+    };
+    con.close().unwrap();
+    let title_terms = query.title_terms();
+    let files = files
         .into_iter()
         .map(|mut file| {
-            let tags = tag_mapping
+            if !title_terms.is_empty() {
+                let score = title_search::average_title_score(&file.name, &title_terms);
+                file.relevance = Some((score * 100.0).round() as u32);
+            }
+            file.tags = tag_mapping
                 .get(&file.id)
                 // not all files here will have tags, especially if the search didn't specify any tags
                 .unwrap_or(&Vec::new())
@@ -96,71 +120,134 @@ pub fn search_files(
                 .cloned()
                 .map_into()
                 .collect();
-            file.tags = tags;
+            file += details_mapping.remove(&file.id).unwrap_or_default();
+            file += activity_mapping.remove(&file.id).unwrap_or_default();
+            file.location = location_mapping.remove(&file.id);
             file
         })
         .collect();
-    con.close().unwrap();
+    Ok((files, total))
+}
 
-    Ok(final_set)
+/// counts how many files matching all of the criteria in `params` have each file type, tag, size, and creation year.
+/// The sort and page in `params` are ignored, since every matching file is counted
+pub fn search_facets(params: SearchFilesParams) -> Result<SearchFacets, SearchFileError> {
+    let SearchFilesParams {
+        title,
+        tags,
+        attributes,
+        content,
+        query,
+        ..
+    } = params;
+    let query = build_query(&title, tags, attributes, &content, query);
+    let con: Connection = open_connection();
+    let res = file_repository::get_search_facets(&query, &con);
+    con.close().unwrap();
+    res.map_err(|e| {
+        log::error!(
+            "Failed to count search facets. Error is {e:?}\n{}",
+            Backtrace::force_capture()
+        );
+        SearchFileError::DbError
+    })
 }
 
-fn search_files_by_title(
+/// combines all the separate search criteria into a single query that files must match all parts of,
+/// so that ordering and paging can be done by the database
+fn build_query(
     search_title: &str,
-    con: &Connection,
-) -> Result<HashSet<FileApi>, SearchFileError> {
-    // search text isn't empty
-    file_repository::search_files(search_title, con)
-        .map(|it| it.into_iter().map(FileApi::from).collect())
-        .map_err(|e| {
-            log::error!(
-                "Failed to search files by title. Error is {e:?}\n{}",
-                Backtrace::force_capture()
-            );
-            SearchFileError::DbError
-        })
+    search_tags: Vec<String>,
+    search_attributes: AttributeSearch,
+    search_content: &str,
+    search_query: Option<SearchQuery>,
+) -> SearchQuery {
+    let mut parts: Vec<SearchQuery> = Vec::new();
+    if !search_title.is_empty() {
+        parts.push(SearchQuery::Title(search_title.to_string()));
+    }
+    parts.extend(search_tags.into_iter().unique().map(SearchQuery::Tag));
+    parts.extend(
+        search_attributes
+            .attributes
+            .into_iter()
+            .map(SearchQuery::Attribute),
+    );
+    if !search_content.trim().is_empty() {
+        parts.push(SearchQuery::Content(search_content.to_string()));
+    }
+    parts.extend(search_query);
+    if parts.len() == 1 {
+        parts.remove(0)
+    } else {
+        SearchQuery::And(parts)
+    }
 }
 
-fn search_files_by_tags(
-    search_tags: &HashSet<String>,
-    con: &Connection,
-) -> Result<HashSet<FileApi>, SearchFileError> {
-    let retrieved = file_repository::search_files_by_tags(search_tags, con);
-    let matching_files = match retrieved {
-        Ok(f) => f,
+/// searches for folders whose name contains `search_title` and that have all of `search_tags`,
+/// including tags inherited from a parent folder. Each folder's path is included, but not its contents
+pub fn search_folders(
+    search_title: &str,
+    search_tags: Vec<String>,
+) -> Result<Vec<FolderResponse>, GetFolderError> {
+    let search_tags: HashSet<String> = HashSet::from_iter(search_tags);
+    let con: Connection = open_connection();
+    let folders = match folder_repository::search_folders(search_title, &search_tags, &con) {
+        Ok(folders) => folders,
         Err(e) => {
+            con.close().unwrap();
             log::error!(
-                "Failed to search files by tags: {e:?}\n{:?}",
+                "Failed to search folders. Error is {e:?}\n{}",
                 Backtrace::force_capture()
             );
-            return Err(SearchFileError::DbError);
+            return Err(GetFolderError::DbFailure);
         }
     };
-    Ok(matching_files.into_iter().map(|it| it.into()).collect())
-}
-
-fn search_files_by_attributes(
-    attributes: AttributeSearch,
-    con: &Connection,
-) -> Result<HashSet<FileApi>, SearchFileError> {
-    file_repository::search_files_by_attributes(attributes, con)
-        .map(|it| it.into_iter().map(FileApi::from).collect())
-        .map_err(|it| {
+    let folder_ids: Vec<u32> = folders.iter().filter_map(|it| it.id).collect();
+    let favorite_ids = match activity_repository::get_favorite_folder_ids(&folder_ids, &con) {
+        Ok(ids) => ids,
+        Err(e) => {
+            con.close().unwrap();
             log::error!(
-                "failed to search file attributes; {it:?}\n{}",
+                "Failed to search folders - failed to retrieve favorites. Error is {e:?}\n{}",
                 Backtrace::force_capture()
             );
-            SearchFileError::DbError
-        })
+            return Err(GetFolderError::DbFailure);
+        }
+    };
+    let mut converted_folders: Vec<FolderResponse> = Vec::with_capacity(folders.len());
+    for folder in folders {
+        let tags: Vec<TaggedItemApi> =
+            match tag_repository::get_all_tags_for_folder(folder.id.unwrap_or(0), &con) {
+                Ok(t) => t.into_iter().map_into().collect(),
+                Err(e) => {
+                    con.close().unwrap();
+                    log::error!(
+                        "Failed to search folders - failed to retrieve tags. Error is {e:?}\n{}",
+                        Backtrace::force_capture()
+                    );
+                    return Err(GetFolderError::TagError);
+                }
+            };
+        let mut converted: FolderResponse = folder.into();
+        converted += tags;
+        converted.favorite = favorite_ids.contains(&converted.id);
+        converted_folders.push(converted);
+    }
+    con.close().unwrap();
+    Ok(converted_folders)
 }
 
 #[cfg(test)]
 mod search_files_tests {
-    use std::collections::HashSet;
+    use std::collections::{BTreeMap, HashSet};
 
     use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
 
+    use itertools::Itertools;
+
     use super::search_files;
+    use crate::content_index::repository::index_file_contents;
     use crate::model::api::FileApi;
     use crate::model::file_types::FileTypes;
     use crate::model::repository::FileRecord;
@@ -168,21 +255,28 @@ mod search_files_tests {
         AttributeSearch, AttributeTypes, EqualityOperator, NamedAttributes,
         NamedComparisonAttribute,
     };
+    use crate::model::request::file_requests::{SearchFilesParams, SearchSort, SearchSortField};
     use crate::model::response::TaggedItemApi;
     use crate::test::{
         cleanup, create_file_db_entry, create_folder_db_entry, create_tag_file, create_tag_files,
         create_tag_folder, create_tag_folders, imply_tag_on_file, init_db_folder,
     };
 
+    const PAGE: (u32, u32) = (50, 0);
+
     #[test]
     fn search_files_works() {
         init_db_folder();
         create_file_db_entry("test", None);
         create_file_db_entry("test2", None);
-        let res = search_files("test2", vec![], vec![].try_into().unwrap())
-            .unwrap()
-            .into_iter()
-            .collect::<Vec<FileApi>>();
+        let res = search_files(SearchFilesParams {
+            title: "test2".to_string(),
+            ..Default::default()
+        })
+        .unwrap()
+        .0
+        .into_iter()
+        .collect::<Vec<FileApi>>();
         assert_eq!(1, res.len());
         let res = &res[0];
         assert_eq!(res.id, 2);
@@ -201,12 +295,12 @@ mod search_files_tests {
         create_file_db_entry("second", None);
         create_tag_file("tag1", 1);
         create_tag_files("tag", vec![1, 2]);
-        let res = search_files(
-            "",
-            vec!["tag1".to_string(), "tag".to_string()],
-            vec![].try_into().unwrap(),
-        )
+        let res = search_files(SearchFilesParams {
+            tags: vec!["tag1".to_string(), "tag".to_string()],
+            ..Default::default()
+        })
         .unwrap()
+        .0
         .into_iter()
         .collect::<Vec<FileApi>>();
         assert_eq!(1, res.len());
@@ -234,16 +328,37 @@ mod search_files_tests {
         cleanup();
     }
 
+    #[test]
+    fn search_files_ignores_tag_case() {
+        init_db_folder();
+        create_file_db_entry("first", None);
+        create_file_db_entry("second", None);
+        create_tag_file("Vacation", 1);
+        let res = search_files(SearchFilesParams {
+            tags: vec!["vACATION".to_string()],
+            ..Default::default()
+        })
+        .unwrap()
+        .0;
+        assert_eq!(vec![1], res.iter().map(|it| it.id).collect::<Vec<u32>>());
+        cleanup();
+    }
+
     #[test]
     fn search_files_tags_and_title() {
         init_db_folder();
         create_file_db_entry("first", None);
         create_file_db_entry("second", None);
         create_tag_files("tag", vec![1, 2]);
-        let res = search_files("first", vec!["tag".to_string()], vec![].try_into().unwrap())
-            .unwrap()
-            .into_iter()
-            .collect::<Vec<FileApi>>();
+        let res = search_files(SearchFilesParams {
+            title: "first".to_string(),
+            tags: vec!["tag".to_string()],
+            ..Default::default()
+        })
+        .unwrap()
+        .0
+        .into_iter()
+        .collect::<Vec<FileApi>>();
         assert_eq!(1, res.len());
         let res = &res[0];
         assert_eq!(res.id, 1);
@@ -276,9 +391,13 @@ mod search_files_tests {
         imply_tag_on_file(1, 2, 3);
         imply_tag_on_file(2, 2, 3);
         // tag1 should retrieve all files
-        let res = search_files("", vec!["tag1".to_string()], vec![].try_into().unwrap()).unwrap();
+        let res = search_files(SearchFilesParams {
+            tags: vec!["tag1".to_string()],
+            ..Default::default()
+        })
+        .unwrap();
         // we have to convert res to a vec in order to not care about the create date, since hash set `contains` relies on hash
-        let res: Vec<FileApi> = res.iter().cloned().collect();
+        let res: Vec<FileApi> = res.0;
         log::debug!("first round: {res:?}");
         assert_eq!(2, res.len());
         assert!(res.contains(&FileApi {
@@ -292,7 +411,16 @@ mod search_files_tests {
             }],
             size: Some(0),
             date_created: None,
-            file_type: Some(FileTypes::Unknown)
+            file_type: Some(FileTypes::Unknown),
+            description: None,
+            properties: Some(BTreeMap::new()),
+            date_uploaded: None,
+            date_modified: None,
+            favorite: false,
+            download_count: 0,
+            last_accessed: None,
+            location: None,
+            relevance: None,
         }));
         assert!(res.contains(&FileApi {
             id: 2,
@@ -312,10 +440,23 @@ mod search_files_tests {
             ],
             size: Some(0),
             date_created: None,
-            file_type: Some(FileTypes::Unknown)
+            file_type: Some(FileTypes::Unknown),
+            description: None,
+            properties: Some(BTreeMap::new()),
+            date_uploaded: None,
+            date_modified: None,
+            favorite: false,
+            download_count: 0,
+            last_accessed: None,
+            location: None,
+            relevance: None,
         }));
-        let res = search_files("", vec!["tag2".to_string()], vec![].try_into().unwrap()).unwrap();
-        let res: Vec<FileApi> = res.iter().cloned().collect();
+        let res = search_files(SearchFilesParams {
+            tags: vec!["tag2".to_string()],
+            ..Default::default()
+        })
+        .unwrap();
+        let res: Vec<FileApi> = res.0;
         log::debug!("{res:?}");
         assert!(res.contains(&FileApi {
             id: 2,
@@ -335,7 +476,16 @@ mod search_files_tests {
             ],
             size: Some(0),
             date_created: None,
-            file_type: Some(FileTypes::Unknown)
+            file_type: Some(FileTypes::Unknown),
+            description: None,
+            properties: Some(BTreeMap::new()),
+            date_uploaded: None,
+            date_modified: None,
+            favorite: false,
+            download_count: 0,
+            last_accessed: None,
+            location: None,
+            relevance: None,
         }));
         cleanup();
     }
@@ -349,12 +499,12 @@ mod search_files_tests {
         create_tag_folders("tag1", vec![1]);
         create_tag_file("tag2", 1);
         imply_tag_on_file(1, 1, 1);
-        let res: HashSet<String> = search_files(
-            "",
-            vec!["tag1".to_string(), "tag2".to_string()],
-            vec![].try_into().unwrap(),
-        )
+        let res: HashSet<String> = search_files(SearchFilesParams {
+            tags: vec!["tag1".to_string(), "tag2".to_string()],
+            ..Default::default()
+        })
         .unwrap()
+        .0
         .into_iter()
         .map(|it| it.name)
         .collect();
@@ -380,6 +530,15 @@ mod search_files_tests {
             size: Some(0),
             date_created: Some(NaiveDateTime::default()),
             file_type: Some(FileTypes::Unknown),
+            description: None,
+            properties: Some(BTreeMap::new()),
+            date_uploaded: None,
+            date_modified: None,
+            favorite: false,
+            download_count: 0,
+            last_accessed: None,
+            location: None,
+            relevance: None,
         }
         .save_to_db();
         imply_tag_on_file(1, 1, 1);
@@ -395,14 +554,22 @@ mod search_files_tests {
             size: None,
             date_created: None,
             file_type: None,
+            description: None,
+            properties: Some(BTreeMap::new()),
+            date_uploaded: None,
+            date_modified: None,
+            favorite: false,
+            download_count: 0,
+            last_accessed: None,
+            location: None,
+            relevance: None,
         }
         .save_to_db();
-        let res: HashSet<u32> = search_files(
-            "",
-            vec!["top".to_string(), "file".to_string()],
-            vec![].try_into().unwrap(),
-        )
-        .map(|it| it.iter().map(|i| i.id).collect())
+        let res: HashSet<u32> = search_files(SearchFilesParams {
+            tags: vec!["top".to_string(), "file".to_string()],
+            ..Default::default()
+        })
+        .map(|(files, _)| files.iter().map(|i| i.id).collect())
         .unwrap();
         let expected: HashSet<u32> = HashSet::from_iter(vec![good_file.id]);
         assert_eq!(expected, res);
@@ -421,6 +588,8 @@ mod search_files_tests {
             create_date: NaiveDateTime::new(day, time),
             size: 9087239875,
             file_type: FileTypes::Unknown,
+            upload_date: None,
+            modified_date: None,
         }
         .save_to_db();
         FileRecord {
@@ -430,6 +599,8 @@ mod search_files_tests {
             create_date: crate::test::now(),
             size: 0,
             file_type: FileTypes::Application,
+            upload_date: None,
+            modified_date: None,
         }
         .save_to_db();
         let attributes = AttributeSearch {
@@ -440,7 +611,11 @@ mod search_files_tests {
             })],
         };
         let expected: HashSet<FileApi> = [good].into_iter().map(FileApi::from).collect();
-        let actual = search_files("", vec![], attributes);
+        let actual = search_files(SearchFilesParams {
+            attributes,
+            ..Default::default()
+        })
+        .map(|(files, _)| files.into_iter().collect());
         assert_eq!(Ok(expected), actual);
         cleanup();
     }
@@ -457,6 +632,8 @@ mod search_files_tests {
             create_date: NaiveDateTime::new(day, time),
             size: 9087239875,
             file_type: FileTypes::Unknown,
+            upload_date: None,
+            modified_date: None,
         }
         .save_to_db();
         FileRecord {
@@ -466,6 +643,8 @@ mod search_files_tests {
             create_date: crate::test::now(),
             size: 0,
             file_type: FileTypes::Unknown,
+            upload_date: None,
+            modified_date: None,
         }
         .save_to_db();
         let attributes = AttributeSearch {
@@ -475,8 +654,19 @@ mod search_files_tests {
                 operator: EqualityOperator::Eq,
             })],
         };
-        let expected: HashSet<FileApi> = [good].into_iter().map(FileApi::from).collect();
-        let actual = search_files("good", vec![], attributes);
+        let expected: HashSet<FileApi> = [good]
+            .into_iter()
+            .map(|it| FileApi {
+                relevance: Some(100),
+                ..FileApi::from(it)
+            })
+            .collect();
+        let actual = search_files(SearchFilesParams {
+            title: "good".to_string(),
+            attributes,
+            ..Default::default()
+        })
+        .map(|(files, _)| files.into_iter().collect());
         assert_eq!(Ok(expected), actual);
         cleanup();
     }
@@ -493,6 +683,8 @@ mod search_files_tests {
             create_date: NaiveDateTime::new(day, time),
             size: 9087239875,
             file_type: FileTypes::Unknown,
+            upload_date: None,
+            modified_date: None,
         }
         .save_to_db();
         create_tag_file("good", 1);
@@ -503,6 +695,8 @@ mod search_files_tests {
             create_date: crate::test::now(),
             size: 0,
             file_type: FileTypes::Unknown,
+            upload_date: None,
+            modified_date: None,
         }
         .save_to_db();
         create_tag_file("bad", 2);
@@ -513,12 +707,91 @@ mod search_files_tests {
                 operator: EqualityOperator::Eq,
             })],
         };
-        let actual: Vec<FileApi> = search_files("good", vec![], attributes)
-            .unwrap()
-            .into_iter()
-            .collect();
+        let actual: Vec<FileApi> = search_files(SearchFilesParams {
+            title: "good".to_string(),
+            attributes,
+            ..Default::default()
+        })
+        .unwrap()
+        .0
+        .into_iter()
+        .collect();
         assert_eq!(1, actual[0].id);
         assert_eq!(1, actual.len());
         cleanup();
     }
+
+    #[test]
+    fn search_content_intersects_with_title() {
+        init_db_folder();
+        create_file_db_entry("notes.txt", None);
+        create_file_db_entry("other notes.txt", None);
+        create_file_db_entry("recipe.txt", None);
+        let con = crate::repository::open_connection();
+        index_file_contents(1, "buy some apples", &con).unwrap();
+        index_file_contents(2, "apples are red", &con).unwrap();
+        index_file_contents(3, "bake the apples", &con).unwrap();
+        con.close().unwrap();
+        let res: Vec<FileApi> = search_files(SearchFilesParams {
+            title: "notes".to_string(),
+            content: "apples".to_string(),
+            ..Default::default()
+        })
+        .unwrap()
+        .0
+        .into_iter()
+        .sorted_by_key(|it| it.id)
+        .collect();
+        assert_eq!(vec![1, 2], res.iter().map(|it| it.id).collect::<Vec<u32>>());
+        let res = search_files(SearchFilesParams {
+            content: "red apple".to_string(),
+            ..Default::default()
+        })
+        .unwrap()
+        .0;
+        assert_eq!(1, res.len());
+        assert_eq!(2, res.into_iter().next().unwrap().id);
+        cleanup();
+    }
+
+    #[test]
+    fn search_files_sorts_and_pages_results() {
+        init_db_folder();
+        for (name, size) in [("b.txt", 30), ("C.txt", 10), ("a.txt", 20)] {
+            FileRecord {
+                id: None,
+                name: name.to_string(),
+                parent_id: None,
+                create_date: crate::test::now(),
+                size,
+                file_type: FileTypes::Text,
+                upload_date: None,
+                modified_date: None,
+            }
+            .save_to_db();
+        }
+        let names = |sort: SearchSort, page: (u32, u32)| {
+            let (files, total) = search_files(SearchFilesParams {
+                title: "txt".to_string(),
+                sort,
+                page,
+                ..Default::default()
+            })
+            .unwrap();
+            assert_eq!(3, total);
+            files.into_iter().map(|it| it.name).collect::<Vec<String>>()
+        };
+        assert_eq!(
+            vec!["a.txt", "b.txt", "C.txt"],
+            names(SearchSort::default(), PAGE)
+        );
+        let by_size = SearchSort {
+            field: SearchSortField::Size,
+            descending: true,
+        };
+        assert_eq!(vec!["b.txt", "a.txt", "C.txt"], names(by_size, PAGE));
+        assert_eq!(vec!["a.txt"], names(by_size, (1, 1)));
+        assert!(names(by_size, (2, 4)).is_empty());
+        cleanup();
+    }
 }
@@ -0,0 +1,102 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::StorageBackend;
+use super::models::{LocalFile, StorageError, StoredEntry};
+use crate::service::file_service::file_dir;
+
+/// stores files in the local files directory. This is the default backend
+pub struct LocalBackend;
+
+impl LocalBackend {
+    fn full_path(path: &str) -> PathBuf {
+        PathBuf::from(file_dir()).join(path)
+    }
+
+    fn list_internal(
+        dir: &Path,
+        relative_dir: &str,
+        entries: &mut Vec<StoredEntry>,
+    ) -> Result<(), StorageError> {
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            let path = if relative_dir.is_empty() {
+                name
+            } else {
+                format!("{relative_dir}/{name}")
+            };
+            let is_dir = entry.file_type()?.is_dir();
+            entries.push(StoredEntry {
+                path: path.clone(),
+                is_dir,
+            });
+            if is_dir {
+                Self::list_internal(&entry.path(), &path, entries)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl StorageBackend for LocalBackend {
+    fn store(&self, source: &Path, path: &str) -> Result<(), StorageError> {
+        let destination = Self::full_path(path);
+        // renaming fails if the temp directory is on a different file system than the files directory
+        if fs::rename(source, &destination).is_err() {
+            fs::copy(source, &destination)?;
+            fs::remove_file(source)?;
+        }
+        Ok(())
+    }
+
+    fn write(&self, path: &str, contents: &[u8]) -> Result<(), StorageError> {
+        Ok(fs::write(Self::full_path(path), contents)?)
+    }
+
+    fn read(&self, path: &str) -> Result<Vec<u8>, StorageError> {
+        Ok(fs::read(Self::full_path(path))?)
+    }
+
+    fn fetch(&self, path: &str) -> Result<LocalFile, StorageError> {
+        let full_path = Self::full_path(path);
+        if !full_path.is_file() {
+            return Err(StorageError::NotFound);
+        }
+        Ok(LocalFile::existing(full_path))
+    }
+
+    fn exists(&self, path: &str) -> Result<bool, StorageError> {
+        Ok(Self::full_path(path).exists())
+    }
+
+    fn delete(&self, path: &str) -> Result<(), StorageError> {
+        Ok(fs::remove_file(Self::full_path(path))?)
+    }
+
+    fn rename(&self, from: &str, to: &str) -> Result<(), StorageError> {
+        Ok(fs::rename(Self::full_path(from), Self::full_path(to))?)
+    }
+
+    fn create_dir(&self, path: &str) -> Result<(), StorageError> {
+        Ok(fs::create_dir_all(Self::full_path(path))?)
+    }
+
+    fn rename_dir(&self, from: &str, to: &str) -> Result<(), StorageError> {
+        Ok(fs::rename(Self::full_path(from), Self::full_path(to))?)
+    }
+
+    fn delete_dir(&self, path: &str) -> Result<(), StorageError> {
+        Ok(fs::remove_dir_all(Self::full_path(path))?)
+    }
+
+    fn list(&self, dir: &str) -> Result<Vec<StoredEntry>, StorageError> {
+        let mut entries = Vec::new();
+        Self::list_internal(&Self::full_path(dir), dir, &mut entries)?;
+        Ok(entries)
+    }
+
+    fn local_root(&self) -> Option<PathBuf> {
+        Some(PathBuf::from(file_dir()))
+    }
+}
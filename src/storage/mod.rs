@@ -0,0 +1,85 @@
+use std::path::{Path, PathBuf};
+
+use models::{LocalFile, StorageError, StoredEntry};
+
+mod local;
+pub mod models;
+mod s3;
+mod service;
+
+pub use local::LocalBackend;
+pub use s3::{S3Backend, S3Options};
+pub use service::{local_copy, open, read, store, stream_contents, stream_reader, write};
+
+/// where the contents of files and folders are kept. All paths are relative to the root of the storage,
+/// and use `/` as the separator no matter the platform.
+///
+/// Implementations only move raw bytes around - encryption is handled by the functions in this module
+/// (see [`open`], [`read`], [`write`], [`store`], and [`local_copy`]), so callers should prefer those over calling the backend directly.
+pub trait StorageBackend: Send + Sync {
+    /// moves the local file at `source` into storage at `path`, replacing anything already stored there
+    fn store(&self, source: &Path, path: &str) -> Result<(), StorageError>;
+
+    /// stores `contents` at `path`, replacing anything already stored there
+    fn write(&self, path: &str, contents: &[u8]) -> Result<(), StorageError>;
+
+    /// reads everything stored at `path` into memory
+    fn read(&self, path: &str) -> Result<Vec<u8>, StorageError>;
+
+    /// returns a local file with the contents stored at `path`, downloading it first if needed
+    fn fetch(&self, path: &str) -> Result<LocalFile, StorageError>;
+
+    /// checks if a file or folder is stored at `path`
+    fn exists(&self, path: &str) -> Result<bool, StorageError>;
+
+    /// removes the file stored at `path`
+    fn delete(&self, path: &str) -> Result<(), StorageError>;
+
+    /// moves the file stored at `from` to `to`
+    fn rename(&self, from: &str, to: &str) -> Result<(), StorageError>;
+
+    /// creates an empty folder at `path`. Passing an empty path makes sure the storage root exists
+    fn create_dir(&self, path: &str) -> Result<(), StorageError>;
+
+    /// moves the folder at `from` and everything in it to `to`
+    fn rename_dir(&self, from: &str, to: &str) -> Result<(), StorageError>;
+
+    /// removes the folder at `path` and everything in it
+    fn delete_dir(&self, path: &str) -> Result<(), StorageError>;
+
+    /// recursively lists every file and folder inside the folder at `dir`. Passing an empty path lists the entire storage
+    fn list(&self, dir: &str) -> Result<Vec<StoredEntry>, StorageError>;
+
+    /// the directory the files are stored in, if they are stored on the local disk
+    fn local_root(&self) -> Option<PathBuf>;
+}
+
+/// the backend configured by `Storage.backend` in FileServer.toml
+#[cfg(not(test))]
+static BACKEND: once_cell::sync::Lazy<Box<dyn StorageBackend>> = once_cell::sync::Lazy::new(|| {
+    let config = &crate::config::FILE_SERVER_CONFIG.storage;
+    match config.backend.to_lowercase().as_str() {
+        "local" => Box::new(LocalBackend),
+        "s3" => {
+            let options = config
+                .s3
+                .clone()
+                .expect("Storage.backend is s3, but the Storage.S3 section is missing");
+            Box::new(S3Backend::new(options))
+        }
+        other => panic!("Unknown storage backend {other}. Supported backends are local and s3"),
+    }
+});
+
+#[cfg(not(test))]
+pub fn backend() -> &'static dyn StorageBackend {
+    BACKEND.as_ref()
+}
+
+#[cfg(test)]
+pub fn backend() -> &'static dyn StorageBackend {
+    &LocalBackend
+}
+
+#[cfg(test)]
+mod tests;
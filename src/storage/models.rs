@@ -0,0 +1,152 @@
+use std::ffi::OsStr;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+
+use rocket::futures::Stream;
+
+use crate::encryption::models::EncryptionError;
+
+/// represents error states that can arise from reading or writing stored files
+#[derive(Debug)]
+pub enum StorageError {
+    /// nothing is stored at the requested path
+    NotFound,
+    /// a generic error reading from or writing to the local file system
+    Io(std::io::Error),
+    /// the remote storage service rejected the request or could not be reached
+    Remote(String),
+    /// the stored file could not be encrypted or decrypted
+    Encryption(EncryptionError),
+}
+
+impl std::fmt::Display for StorageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotFound => write!(f, "nothing is stored at the requested path"),
+            Self::Io(e) => write!(f, "file system error: {e}"),
+            Self::Remote(message) => write!(f, "remote storage error: {message}"),
+            Self::Encryption(e) => write!(f, "encryption error: {e:?}"),
+        }
+    }
+}
+
+impl From<std::io::Error> for StorageError {
+    fn from(value: std::io::Error) -> Self {
+        if value.kind() == std::io::ErrorKind::NotFound {
+            Self::NotFound
+        } else {
+            Self::Io(value)
+        }
+    }
+}
+
+impl From<EncryptionError> for StorageError {
+    fn from(value: EncryptionError) -> Self {
+        match value {
+            EncryptionError::Io(e) => e.into(),
+            e => Self::Encryption(e),
+        }
+    }
+}
+
+/// a single file or folder in storage
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct StoredEntry {
+    /// the path of the entry, relative to the storage root
+    pub path: String,
+    pub is_dir: bool,
+}
+
+/// a file on the local disk holding the contents of a stored file, for tools such as ffmpeg and tar that can only read local files.
+///
+/// If the contents had to be downloaded or decrypted, the file is temporary and will be deleted when this is dropped
+#[derive(Debug)]
+pub struct LocalFile {
+    path: PathBuf,
+    temporary: bool,
+}
+
+impl LocalFile {
+    /// wraps a file that already exists on the disk and must not be deleted
+    pub fn existing(path: PathBuf) -> Self {
+        Self {
+            path,
+            temporary: false,
+        }
+    }
+
+    /// reserves a new path in the temp directory. Some tools rely on the `extension` to determine how to read the file
+    pub fn temporary(extension: Option<&OsStr>) -> std::io::Result<Self> {
+        fs::create_dir_all(crate::temp_dir())?;
+        let file_name = match extension {
+            Some(ext) => format!("{}.{}", nanoid::nanoid!(), ext.to_string_lossy()),
+            None => nanoid::nanoid!(),
+        };
+        Ok(Self {
+            path: PathBuf::from(crate::temp_dir()).join(file_name),
+            temporary: true,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for LocalFile {
+    fn drop(&mut self) {
+        if self.temporary {
+            fs::remove_file(&self.path).unwrap_or(());
+        }
+    }
+}
+
+/// plain contents streamed in chunks, see [`crate::storage::stream_contents`].
+/// A chunk that couldn't be read or decrypted is passed along as an error, after which the stream ends
+pub struct ContentsStream {
+    /// the total size of the plain contents, if it's known before they're streamed
+    pub size: Option<u64>,
+    pub chunks: Pin<Box<dyn Stream<Item = std::io::Result<Vec<u8>>> + Send>>,
+}
+
+impl std::fmt::Debug for ContentsStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ContentsStream")
+            .field("size", &self.size)
+            .finish()
+    }
+}
+
+/// the plain contents of a stored file, read straight from storage. Encrypted files are decrypted as they're read,
+/// so their plain contents are never written to the disk
+pub struct StoredContents {
+    reader: Box<dyn Read + Send>,
+    size: u64,
+}
+
+impl StoredContents {
+    pub fn new(reader: Box<dyn Read + Send>, size: u64) -> Self {
+        Self { reader, size }
+    }
+
+    /// the size of the plain contents, in bytes
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+}
+
+impl Read for StoredContents {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.reader.read(buf)
+    }
+}
+
+impl std::fmt::Debug for StoredContents {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StoredContents")
+            .field("size", &self.size)
+            .finish()
+    }
+}
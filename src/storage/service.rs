@@ -0,0 +1,113 @@
+use std::backtrace::Backtrace;
+use std::fs::{self, File};
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+use rocket::futures::{StreamExt, stream};
+use rocket::tokio::task::spawn_blocking;
+
+use super::backend;
+use super::models::{ContentsStream, LocalFile, StorageError, StoredContents};
+use crate::encryption::{self, DecryptReader};
+
+/// how many bytes are read at a time when streaming stored contents
+const STREAM_CHUNK_LEN: usize = 64 * 1024;
+
+/// opens the plain contents of the file stored at `path` for reading. Encrypted files are decrypted as they're read
+pub fn open(path: &str) -> Result<StoredContents, StorageError> {
+    let fetched = backend().fetch(path)?;
+    // downloaded copies are deleted once `fetched` is dropped, but the open handle keeps the contents readable until it's closed
+    let file = File::open(fetched.path())?;
+    let size = encryption::plaintext_size(fetched.path())?;
+    if !encryption::is_encrypted(fetched.path())? {
+        return Ok(StoredContents::new(Box::new(file), size));
+    }
+    let reader = DecryptReader::new(BufReader::new(file))?;
+    Ok(StoredContents::new(Box::new(reader), size))
+}
+
+/// streams the passed contents in chunks, see [stream_reader]
+pub fn stream_contents(contents: StoredContents) -> Result<ContentsStream, StorageError> {
+    let size = contents.size();
+    Ok(stream_reader(contents, Some(size))?)
+}
+
+/// streams everything read from `reader` in chunks. Reading happens on a blocking thread so that decrypting doesn't hold up other requests.
+///
+/// The first chunk is read before returning, so that contents that can't be read or decrypted at all fail here
+/// instead of after a response has started. If reading fails part of the way through, the error is logged and
+/// passed along as the last item of the stream
+pub fn stream_reader(
+    mut reader: impl Read + Send + 'static,
+    size: Option<u64>,
+) -> std::io::Result<ContentsStream> {
+    let first = read_stream_chunk(&mut reader)?;
+    let rest = stream::unfold(Some(reader), |reader| async move {
+        let mut reader = reader?;
+        let read = spawn_blocking(move || match read_stream_chunk(&mut reader) {
+            Ok(chunk) if chunk.is_empty() => None,
+            Ok(chunk) => Some((Ok(chunk), Some(reader))),
+            Err(e) => {
+                log::error!(
+                    "Failed to read stored contents while streaming them. Exception is {e:?}\n{}",
+                    Backtrace::force_capture()
+                );
+                Some((Err(e), None))
+            }
+        })
+        .await;
+        read.unwrap_or_else(|e| Some((Err(std::io::Error::other(e)), None)))
+    });
+    Ok(ContentsStream {
+        size,
+        chunks: Box::pin(stream::once(async { Ok(first) }).chain(rest)),
+    })
+}
+
+/// reads up to [STREAM_CHUNK_LEN] bytes from `reader`. An empty chunk means everything has been read
+fn read_stream_chunk(reader: &mut impl Read) -> std::io::Result<Vec<u8>> {
+    let mut chunk = Vec::with_capacity(STREAM_CHUNK_LEN);
+    reader
+        .by_ref()
+        .take(STREAM_CHUNK_LEN as u64)
+        .read_to_end(&mut chunk)?;
+    Ok(chunk)
+}
+
+/// returns a local file with the plain contents of the file stored at `path`, for tools that can only read files by their path (such as ffmpeg).
+/// Decrypted copies are temporary, and leftovers are removed when the server starts
+pub fn local_copy(path: &str) -> Result<LocalFile, StorageError> {
+    let fetched = backend().fetch(path)?;
+    if !encryption::is_encrypted(fetched.path())? {
+        return Ok(fetched);
+    }
+    let decrypted = LocalFile::temporary(fetched.path().extension())?;
+    encryption::decrypt_file(fetched.path(), decrypted.path())?;
+    Ok(decrypted)
+}
+
+/// reads the plain contents of the file stored at `path` into memory
+pub fn read(path: &str) -> Result<Vec<u8>, StorageError> {
+    Ok(encryption::decrypt_contents(backend().read(path)?)?)
+}
+
+/// stores `contents` at `path`, encrypting them first if encryption is enabled
+pub fn write(path: &str, contents: &[u8]) -> Result<(), StorageError> {
+    if encryption::is_enabled() {
+        backend().write(path, &encryption::encrypt_contents(contents)?)
+    } else {
+        backend().write(path, contents)
+    }
+}
+
+/// moves the local file at `source` into storage at `path`, encrypting it first if encryption is enabled.
+/// If it can't be encrypted, the local file is deleted so that its plain contents aren't left on the disk
+pub fn store(source: &Path, path: &str) -> Result<(), StorageError> {
+    if encryption::is_enabled()
+        && let Err(e) = encryption::encrypt_file_in_place(source)
+    {
+        fs::remove_file(source).unwrap_or(());
+        return Err(e.into());
+    }
+    backend().store(source, path)
+}
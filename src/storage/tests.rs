@@ -0,0 +1,235 @@
+//! tests for the s3 backend, run against a minimal in-memory stand-in for an S3-compatible service such as MinIO
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+
+use crate::storage::models::{StorageError, StoredEntry};
+use crate::storage::{S3Backend, S3Options, StorageBackend};
+use crate::test::cleanup;
+
+type Objects = Arc<Mutex<BTreeMap<String, Vec<u8>>>>;
+
+/// starts a fake S3 server on a random port, returning a backend pointed at it and the objects it holds
+fn fake_s3(prefix: &str) -> (S3Backend, Objects) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let objects: Objects = Arc::new(Mutex::new(BTreeMap::new()));
+    let server_objects = objects.clone();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            handle_request(stream.unwrap(), &server_objects);
+        }
+    });
+    let backend = S3Backend::new(S3Options {
+        endpoint: format!("http://127.0.0.1:{port}"),
+        bucket: "bucket".to_string(),
+        region: "us-east-1".to_string(),
+        access_key: "access".to_string(),
+        secret_key: "secret".to_string(),
+        prefix: prefix.to_string(),
+    });
+    (backend, objects)
+}
+
+fn decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            decoded.push(u8::from_str_radix(&value[i + 1..i + 3], 16).unwrap());
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(decoded).unwrap()
+}
+
+fn handle_request(mut stream: TcpStream, objects: &Objects) {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).unwrap();
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap().to_string();
+    let target = parts.next().unwrap().to_string();
+    let mut headers = BTreeMap::new();
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        let (name, value) = line.split_once(':').unwrap();
+        headers.insert(name.to_lowercase(), value.trim().to_string());
+    }
+    let mut body = vec![
+        0;
+        headers
+            .get("content-length")
+            .map_or(0, |it| it.parse().unwrap())
+    ];
+    reader.read_exact(&mut body).unwrap();
+    let (path, query) = target.split_once('?').unwrap_or((&target, ""));
+    let query: BTreeMap<String, String> = query
+        .split('&')
+        .filter_map(|it| it.split_once('='))
+        .map(|(k, v)| (decode(k), decode(v)))
+        .collect();
+    let key = decode(path.trim_start_matches("/bucket").trim_start_matches('/'));
+    let signed = headers
+        .get("authorization")
+        .is_some_and(|it| it.starts_with("AWS4-HMAC-SHA256 Credential=access/"));
+    let mut objects = objects.lock().unwrap();
+    let (status, response_body): (&str, Vec<u8>) = if !signed {
+        ("403 Forbidden", Vec::new())
+    } else if method == "GET" && key.is_empty() {
+        let prefix = query.get("prefix").cloned().unwrap_or_default();
+        let contents: String = objects
+            .keys()
+            .filter(|it| it.starts_with(&prefix))
+            .map(|it| {
+                format!(
+                    "<Contents><Key>{}</Key></Contents>",
+                    it.replace('&', "&amp;")
+                )
+            })
+            .collect();
+        let xml = format!(
+            "<ListBucketResult><IsTruncated>false</IsTruncated>{contents}</ListBucketResult>"
+        );
+        ("200 OK", xml.into_bytes())
+    } else if method == "GET" || method == "HEAD" {
+        match objects.get(&key) {
+            Some(contents) if method == "GET" => ("200 OK", contents.clone()),
+            Some(_) => ("200 OK", Vec::new()),
+            None => ("404 Not Found", Vec::new()),
+        }
+    } else if method == "PUT" {
+        if let Some(source) = headers.get("x-amz-copy-source") {
+            let source = decode(source.trim_start_matches("/bucket/"));
+            let contents = objects.get(&source).cloned().unwrap();
+            objects.insert(key, contents);
+        } else {
+            objects.insert(key, body);
+        }
+        ("200 OK", Vec::new())
+    } else if method == "DELETE" {
+        objects.remove(&key);
+        ("204 No Content", Vec::new())
+    } else {
+        ("405 Method Not Allowed", Vec::new())
+    };
+    let content_length = if method == "HEAD" {
+        0
+    } else {
+        response_body.len()
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Length: {content_length}\r\nConnection: close\r\n\r\n"
+    )
+    .unwrap();
+    if method != "HEAD" {
+        stream.write_all(&response_body).unwrap();
+    }
+    stream.flush().unwrap();
+}
+
+#[test]
+fn write_and_read() {
+    let (backend, objects) = fake_s3("");
+    backend.write("folder/test file.txt", b"hello").unwrap();
+    assert_eq!(
+        Some(&b"hello".to_vec()),
+        objects.lock().unwrap().get("folder/test file.txt")
+    );
+    assert_eq!(
+        b"hello".to_vec(),
+        backend.read("folder/test file.txt").unwrap()
+    );
+    assert!(backend.exists("folder/test file.txt").unwrap());
+    assert!(backend.exists("folder").unwrap());
+    assert!(!backend.exists("other").unwrap());
+}
+
+#[test]
+fn read_missing_file_returns_not_found() {
+    let (backend, _) = fake_s3("");
+    assert!(matches!(
+        backend.read("missing.txt"),
+        Err(StorageError::NotFound)
+    ));
+    assert!(matches!(
+        backend.fetch("missing.txt"),
+        Err(StorageError::NotFound)
+    ));
+}
+
+#[test]
+fn store_uploads_and_removes_local_file() {
+    let (backend, objects) = fake_s3("");
+    fs::create_dir_all(crate::temp_dir()).unwrap();
+    let source = std::path::Path::new(&crate::temp_dir()).join("upload.txt");
+    fs::write(&source, "uploaded").unwrap();
+    backend.store(&source, "upload.txt").unwrap();
+    assert!(!source.exists());
+    assert_eq!(
+        Some(&b"uploaded".to_vec()),
+        objects.lock().unwrap().get("upload.txt")
+    );
+    let fetched = backend.fetch("upload.txt").unwrap();
+    assert_eq!("uploaded", fs::read_to_string(fetched.path()).unwrap());
+    let fetched_path = fetched.path().to_path_buf();
+    drop(fetched);
+    assert!(!fetched_path.exists());
+    cleanup();
+}
+
+#[test]
+fn folders_are_stored_as_markers() {
+    let (backend, objects) = fake_s3("prefix");
+    backend.create_dir("top/empty").unwrap();
+    backend.write("top/file.txt", b"file").unwrap();
+    assert!(objects.lock().unwrap().contains_key("prefix/top/empty/"));
+    let mut entries = backend.list("top").unwrap();
+    entries.sort_by(|a, b| a.path.cmp(&b.path));
+    assert_eq!(
+        vec![
+            StoredEntry {
+                path: "top/empty".to_string(),
+                is_dir: true,
+            },
+            StoredEntry {
+                path: "top/file.txt".to_string(),
+                is_dir: false,
+            },
+        ],
+        entries
+    );
+}
+
+#[test]
+fn rename_dir_moves_everything_inside() {
+    let (backend, objects) = fake_s3("");
+    backend.create_dir("old").unwrap();
+    backend.write("old/a.txt", b"a").unwrap();
+    backend.write("old/nested/b.txt", b"b").unwrap();
+    backend.write("older/c.txt", b"c").unwrap();
+    backend.rename_dir("old", "new").unwrap();
+    let keys: Vec<String> = objects.lock().unwrap().keys().cloned().collect();
+    assert_eq!(
+        vec!["new/", "new/a.txt", "new/nested/b.txt", "older/c.txt"],
+        keys
+    );
+    backend.rename("new/a.txt", "new/renamed.txt").unwrap();
+    assert_eq!(b"a".to_vec(), backend.read("new/renamed.txt").unwrap());
+    backend.delete_dir("new").unwrap();
+    let keys: Vec<String> = objects.lock().unwrap().keys().cloned().collect();
+    assert_eq!(vec!["older/c.txt"], keys);
+}
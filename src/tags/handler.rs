@@ -5,16 +5,23 @@ use rocket::State;
 use rocket::serde::json::Json;
 
 use crate::guard::HeaderAuth;
-use crate::model::error::tag_errors::{GetTagError, UpdateTagError};
+use crate::model::error::tag_errors::{CreateTagError, GetTagError, MergeTagError, UpdateTagError};
 use crate::model::guard::auth::ValidateResult;
+use crate::model::request::tag_requests::{MergeTagRequest, UpdateTagRequest};
 use crate::model::response::tag_responses::{
-    CreateTagResponse, DeleteTagResponse, GetTagResponse, UpdateTagResponse,
+    CreateTagResponse, DeleteTagResponse, GetTagResponse, ListTagNamespacesResponse,
+    ListTagsResponse, MergeTagResponse, UpdateTagResponse,
 };
 use crate::model::response::{BasicMessage, TagApi};
 use crate::util::update_last_request_time;
 
+use super::TagSort;
 use super::service;
 
+const INVALID_COLOR_MESSAGE: &str = "Tag color must be a hex color like #f80 or #ff8800.";
+const ALIAS_ALREADY_EXISTS_MESSAGE: &str =
+    "An alias is already the name or an alias of another tag.";
+
 #[get("/<id>")]
 pub fn get_tag(
     id: u32,
@@ -38,6 +45,56 @@ pub fn get_tag(
     }
 }
 
+/// lists every tag as a tree, where each tag holds the tags nested under it and how often it's used.
+/// `sort` can be `name` (the default) or `usage`, `prefix` only lists tags starting with it,
+/// and `namespace` only lists tags in that namespace. Tags that are listed are always listed along with the tags they're nested under
+#[get("/?<prefix>&<namespace>&<sort>")]
+pub fn get_all_tags(
+    prefix: Option<String>,
+    namespace: Option<String>,
+    sort: Option<String>,
+    auth: HeaderAuth,
+    last_request_time: &State<Arc<Mutex<Instant>>>,
+) -> ListTagsResponse {
+    match auth.validate() {
+        ValidateResult::Ok => {/* no op */},
+        ValidateResult::NoPasswordSet => return ListTagsResponse::Unauthorized("No password has been set. you can set a username and password by making a POST to `/api/password`".to_string()),
+        ValidateResult::Invalid => return ListTagsResponse::Unauthorized("Bad Credentials".to_string())
+    };
+    update_last_request_time(last_request_time);
+    let sort = match sort.as_deref().map(TagSort::try_from) {
+        Some(Ok(sort)) => sort,
+        Some(Err(e)) => return ListTagsResponse::BadRequest(BasicMessage::new(e.as_str())),
+        None => TagSort::Name,
+    };
+    match service::get_all_tags(prefix.as_deref(), namespace.as_deref(), sort) {
+        Ok(tags) => ListTagsResponse::Success(Json::from(tags)),
+        Err(_) => ListTagsResponse::TagDbError(BasicMessage::new(
+            "Failed to pull tags from database. Check server logs for details",
+        )),
+    }
+}
+
+/// lists every tag namespace (the part before the `:` in tags like `person:alice`) along with how many tags are in it
+#[get("/namespaces")]
+pub fn get_tag_namespaces(
+    auth: HeaderAuth,
+    last_request_time: &State<Arc<Mutex<Instant>>>,
+) -> ListTagNamespacesResponse {
+    match auth.validate() {
+        ValidateResult::Ok => {/* no op */},
+        ValidateResult::NoPasswordSet => return ListTagNamespacesResponse::Unauthorized("No password has been set. you can set a username and password by making a POST to `/api/password`".to_string()),
+        ValidateResult::Invalid => return ListTagNamespacesResponse::Unauthorized("Bad Credentials".to_string())
+    };
+    update_last_request_time(last_request_time);
+    match service::get_tag_namespaces() {
+        Ok(namespaces) => ListTagNamespacesResponse::Success(Json::from(namespaces)),
+        Err(_) => ListTagNamespacesResponse::TagDbError(BasicMessage::new(
+            "Failed to pull tags from database. Check server logs for details",
+        )),
+    }
+}
+
 #[post("/", data = "<tag>")]
 pub fn create_tag(
     tag: Json<TagApi>,
@@ -50,8 +107,17 @@ pub fn create_tag(
         ValidateResult::Invalid => return CreateTagResponse::Unauthorized("Bad Credentials".to_string())
     };
     update_last_request_time(last_request_time);
-    match service::create_tag(tag.title.clone()) {
+    match service::create_tag_with_details(tag.into_inner()) {
         Ok(tag) => CreateTagResponse::Success(Json::from(tag)),
+        Err(CreateTagError::ParentNotFound) => CreateTagResponse::BadRequest(BasicMessage::new(
+            "No tag with the passed parentId was found.",
+        )),
+        Err(CreateTagError::InvalidColor) => {
+            CreateTagResponse::BadRequest(BasicMessage::new(INVALID_COLOR_MESSAGE))
+        }
+        Err(CreateTagError::AliasAlreadyExists) => {
+            CreateTagResponse::BadRequest(BasicMessage::new(ALIAS_ALREADY_EXISTS_MESSAGE))
+        }
         Err(_) => CreateTagResponse::TagDbError(BasicMessage::new(
             "Failed to create tag info in database. Check server logs for details",
         )),
@@ -60,7 +126,7 @@ pub fn create_tag(
 
 #[put("/", data = "<tag>")]
 pub fn update_tag(
-    tag: Json<TagApi>,
+    tag: Json<UpdateTagRequest>,
     auth: HeaderAuth,
     last_request_time: &State<Arc<Mutex<Instant>>>,
 ) -> UpdateTagResponse {
@@ -78,12 +144,55 @@ pub fn update_tag(
         Err(UpdateTagError::NewNameAlreadyExists) => UpdateTagResponse::TagAlreadyExists(
             BasicMessage::new("A tag with that name already exists."),
         ),
+        Err(UpdateTagError::ParentNotFound) => UpdateTagResponse::BadParent(BasicMessage::new(
+            "No tag with the passed parentId was found.",
+        )),
+        Err(UpdateTagError::CircularParent) => UpdateTagResponse::BadParent(BasicMessage::new(
+            "A tag cannot be nested under itself or a tag nested under it.",
+        )),
+        Err(UpdateTagError::InvalidColor) => {
+            UpdateTagResponse::BadRequest(BasicMessage::new(INVALID_COLOR_MESSAGE))
+        }
+        Err(UpdateTagError::AliasAlreadyExists) => {
+            UpdateTagResponse::TagAlreadyExists(BasicMessage::new(ALIAS_ALREADY_EXISTS_MESSAGE))
+        }
         Err(_) => UpdateTagResponse::TagDbError(BasicMessage::new(
             "Failed to update tag in database. Check server logs for details",
         )),
     }
 }
 
+/// merges the tag with the passed id into the tag in the request body, moving every file and folder with it over to the other tag
+#[post("/<id>/merge", data = "<request>")]
+pub fn merge_tag(
+    id: u32,
+    request: Json<MergeTagRequest>,
+    auth: HeaderAuth,
+    last_request_time: &State<Arc<Mutex<Instant>>>,
+) -> MergeTagResponse {
+    match auth.validate() {
+        ValidateResult::Ok => {/* no op */},
+        ValidateResult::NoPasswordSet => return MergeTagResponse::Unauthorized("No password has been set. you can set a username and password by making a POST to `/api/password`".to_string()),
+        ValidateResult::Invalid => return MergeTagResponse::Unauthorized("Bad Credentials".to_string())
+    };
+    update_last_request_time(last_request_time);
+    match service::merge_tags(id, request.target_id) {
+        Ok(tag) => MergeTagResponse::Success(Json::from(tag)),
+        Err(MergeTagError::TagNotFound) => MergeTagResponse::TagNotFound(BasicMessage::new(
+            "The tag with the passed id could not be found.",
+        )),
+        Err(MergeTagError::TargetNotFound) => MergeTagResponse::BadRequest(BasicMessage::new(
+            "No tag with the passed targetId was found.",
+        )),
+        Err(MergeTagError::SameTag) => {
+            MergeTagResponse::BadRequest(BasicMessage::new("A tag cannot be merged into itself."))
+        }
+        Err(MergeTagError::DbError) => MergeTagResponse::TagDbError(BasicMessage::new(
+            "Failed to merge tags. Check server logs for details",
+        )),
+    }
+}
+
 #[delete("/<id>")]
 pub fn delete_tag(
     id: u32,
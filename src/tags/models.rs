@@ -14,6 +14,71 @@ pub struct Tag {
     pub id: u32,
     /// the display name of the tag
     pub title: String,
+    /// the id of the tag this one is nested under. Files with this tag match searches for the parent tag
+    pub parent_id: Option<u32>,
+    /// hex color clients show the tag in, e.g. `#ff8800`
+    pub color: Option<String>,
+    pub description: Option<String>,
+}
+
+impl Tag {
+    /// the lowercase namespace of tags titled like `<namespace>:<value>`, e.g. `person` for `person:alice`.
+    /// Tags without a colon, or with nothing before or after it, have no namespace
+    pub fn namespace(&self) -> Option<String> {
+        namespace_of(&self.title)
+    }
+}
+
+/// see [Tag::namespace]
+pub fn namespace_of(title: &str) -> Option<String> {
+    let (namespace, value) = title.split_once(':')?;
+    let namespace = namespace.trim();
+    if namespace.is_empty() || value.trim().is_empty() {
+        None
+    } else {
+        Some(namespace.to_lowercase())
+    }
+}
+
+/// a [`Tag`] along with how many files and folders it's on
+#[derive(Debug, PartialEq, Clone)]
+pub struct TagUsage {
+    pub tag: Tag,
+    /// how many files have the tag explicitly
+    pub file_count: u32,
+    /// how many folders have the tag explicitly
+    pub folder_count: u32,
+    /// how many files and folders inherit the tag from an ancestor folder
+    pub implicit_count: u32,
+}
+
+impl TagUsage {
+    /// how many files and folders have the tag, explicitly or implied
+    pub fn total(&self) -> u32 {
+        self.file_count + self.folder_count + self.implicit_count
+    }
+}
+
+/// how to order listings of tags
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum TagSort {
+    /// alphabetically, ignoring case
+    #[default]
+    Name,
+    /// most used first, counting both explicit and implied uses
+    Usage,
+}
+
+impl TryFrom<&str> for TagSort {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.to_lowercase().as_str() {
+            "name" => Ok(Self::Name),
+            "usage" => Ok(Self::Usage),
+            _ => Err(format!("Invalid sort {value}. Must be one of name, usage")),
+        }
+    }
 }
 
 /// represents a tag on a file or a folder, with optional implication.
@@ -1,25 +1,35 @@
+use std::rc::Rc;
 use std::{backtrace::Backtrace, collections::HashMap};
 
 use itertools::Itertools;
 use rusqlite::Connection;
+use rusqlite::types::Value;
 use std::fmt::Write;
 
 use crate::tags::TagTypes;
 
 use super::models;
 
-/// creates a new tag in the database. This does not check if the tag already exists,
-/// so the caller must check that themselves
-pub fn create_tag(title: &str, con: &Connection) -> Result<models::Tag, rusqlite::Error> {
+/// creates a new tag in the database without a color or description, nested under the tag with the id `parent_id` if passed.
+/// This does not check if the tag already exists or if the parent exists, so the caller must check that themselves
+pub fn create_tag(
+    title: &str,
+    parent_id: Option<u32>,
+    con: &Connection,
+) -> Result<models::Tag, rusqlite::Error> {
     let mut pst = con.prepare(include_str!("../assets/queries/tags/create_tag.sql"))?;
-    let id = pst.insert(rusqlite::params![title])? as u32;
+    let id = pst.insert(rusqlite::params![title, parent_id])? as u32;
     Ok(models::Tag {
         id,
         title: title.to_string(),
+        parent_id,
+        color: None,
+        description: None,
     })
 }
 
-/// searches for a tag that case-insensitively matches that passed title.
+/// searches for a tag that case-insensitively matches that passed title, or has it as an alias.
+/// A tag with the title is returned over a tag with the alias.
 ///
 /// if `None` is returned, that means there was no match
 pub fn get_tag_by_title(
@@ -58,19 +68,149 @@ pub fn get_tag(id: u32, con: &Connection) -> Result<models::Tag, rusqlite::Error
     pst.query_row(rusqlite::params![id], tag_mapper)
 }
 
-/// updates the past tag. Checking to make sure the tag exists needs to be done on the caller's end
+/// retrieves every tag along with how many files and folders it's on, ordered by title
+pub fn get_all_tags(con: &Connection) -> Result<Vec<models::TagUsage>, rusqlite::Error> {
+    let mut pst = con.prepare(include_str!("../assets/queries/tags/get_all_tags.sql"))?;
+    let rows = pst.query_map([], |row| {
+        Ok(models::TagUsage {
+            tag: tag_mapper(row)?,
+            file_count: row.get(5)?,
+            folder_count: row.get(6)?,
+            implicit_count: row.get(7)?,
+        })
+    })?;
+    rows.collect::<Result<Vec<models::TagUsage>, rusqlite::Error>>()
+}
+
+/// retrieves the ids of every tag the tag with the passed `id` is nested under, directly or through other tags.
+/// If the tag has no parent or doesn't exist, an empty Vec is returned
+pub fn get_tag_ancestor_ids(id: u32, con: &Connection) -> Result<Vec<u32>, rusqlite::Error> {
+    let mut pst = con.prepare(include_str!(
+        "../assets/queries/tags/get_tag_ancestor_ids.sql"
+    ))?;
+    let rows = pst.query_map([id], |row| row.get(0))?;
+    rows.collect::<Result<Vec<u32>, rusqlite::Error>>()
+}
+
+/// updates the past tag. Checking to make sure the tag exists and that its parent won't make a cycle needs to be done on the caller's end
 pub fn update_tag(tag: models::Tag, con: &Connection) -> Result<(), rusqlite::Error> {
     let mut pst = con.prepare(include_str!("../assets/queries/tags/update_tag.sql"))?;
-    pst.execute(rusqlite::params![tag.title, tag.id])?;
+    pst.execute(rusqlite::params![
+        tag.title,
+        tag.parent_id,
+        tag.color,
+        tag.description,
+        tag.id
+    ])?;
     Ok(())
 }
 
+/// deletes the tag, moving any tags nested under it up to its own parent
 pub fn delete_tag(id: u32, con: &Connection) -> Result<(), rusqlite::Error> {
+    let mut pst = con.prepare(include_str!(
+        "../assets/queries/tags/reparent_child_tags.sql"
+    ))?;
+    pst.execute(rusqlite::params![id])?;
+    delete_tag_aliases(id, con)?;
     let mut pst = con.prepare(include_str!("../assets/queries/tags/delete_tag.sql"))?;
     pst.execute(rusqlite::params![id])?;
     Ok(())
 }
 
+/// retrieves the aliases of the tag with the passed id, ordered case-insensitively
+pub fn get_tag_aliases(tag_id: u32, con: &Connection) -> Result<Vec<String>, rusqlite::Error> {
+    let mut pst = con.prepare(include_str!("../assets/queries/tags/get_tag_aliases.sql"))?;
+    let rows = pst.query_map(rusqlite::params![tag_id], |row| row.get(0))?;
+    rows.collect::<Result<Vec<String>, rusqlite::Error>>()
+}
+
+/// retrieves the aliases of every tag, keyed by tag id. Tags without aliases aren't in the map
+pub fn get_all_tag_aliases(con: &Connection) -> Result<HashMap<u32, Vec<String>>, rusqlite::Error> {
+    let mut pst = con.prepare(include_str!(
+        "../assets/queries/tags/get_all_tag_aliases.sql"
+    ))?;
+    let mut aliases: HashMap<u32, Vec<String>> = HashMap::new();
+    let mut rows = pst.query([])?;
+    while let Some(row) = rows.next()? {
+        aliases.entry(row.get(0)?).or_default().push(row.get(1)?);
+    }
+    Ok(aliases)
+}
+
+/// replaces the aliases of the tag with the passed id.
+/// This does not check if the aliases are used by other tags, so the caller must check that themselves
+pub fn set_tag_aliases(
+    tag_id: u32,
+    aliases: &[String],
+    con: &Connection,
+) -> Result<(), rusqlite::Error> {
+    delete_tag_aliases(tag_id, con)?;
+    let mut pst = con.prepare(include_str!("../assets/queries/tags/add_tag_alias.sql"))?;
+    for alias in aliases {
+        pst.execute(rusqlite::params![tag_id, alias])?;
+    }
+    Ok(())
+}
+
+fn delete_tag_aliases(tag_id: u32, con: &Connection) -> Result<(), rusqlite::Error> {
+    let mut pst = con.prepare(include_str!(
+        "../assets/queries/tags/delete_tag_aliases.sql"
+    ))?;
+    pst.execute(rusqlite::params![tag_id])?;
+    Ok(())
+}
+
+/// moves every file and folder tagged with the tag with the id `source_id` over to the tag with the id `target_id`, explicit or implied,
+/// moves the tags nested under the source to the target, and then deletes the source.
+/// The source's title and aliases become aliases of the target, and items that blocked inheriting the source block inheriting the target instead.
+/// Items that already have the target tag keep only 1 of them, preferring the explicit one.
+///
+/// If the target is nested under the source, it's moved up to the source's parent first.
+///
+/// This runs in a transaction, so either everything is merged or nothing is.
+/// Checking that both tags exist and aren't the same tag needs to be done on the caller's end
+pub fn merge_tags(source_id: u32, target_id: u32, con: &Connection) -> Result<(), rusqlite::Error> {
+    let tx = con.unchecked_transaction()?;
+    let params = rusqlite::params![source_id, target_id];
+    tx.execute(
+        include_str!("../assets/queries/tags/lift_merge_target.sql"),
+        params,
+    )?;
+    tx.execute(
+        include_str!("../assets/queries/tags/delete_implicit_items_overridden_by_merge.sql"),
+        params,
+    )?;
+    tx.execute(
+        include_str!("../assets/queries/tags/delete_duplicate_items_for_merge.sql"),
+        params,
+    )?;
+    tx.execute(
+        include_str!("../assets/queries/tags/move_tagged_items.sql"),
+        params,
+    )?;
+    tx.execute(
+        include_str!("../assets/queries/tags/move_child_tags.sql"),
+        params,
+    )?;
+    tx.execute(
+        include_str!("../assets/queries/tags/add_merged_title_as_alias.sql"),
+        params,
+    )?;
+    tx.execute(
+        include_str!("../assets/queries/tags/move_tag_aliases.sql"),
+        params,
+    )?;
+    tx.execute(
+        include_str!("../assets/queries/tags/move_inheritance_blocks.sql"),
+        params,
+    )?;
+    // only items that now implicitly have the target can have picked up a tag they block
+    let (file_ids, folder_ids) = get_implicitly_tagged_items(target_id, &tx)?;
+    remove_blocked_implicit_tags(&file_ids, &folder_ids, &tx)?;
+    delete_tag(source_id, &tx)?;
+    tx.commit()
+}
+
 // ================= file functions =================
 /// the caller of this function will need to make sure the tag already exists and isn't already on the file
 pub fn add_explicit_tag_to_file(
@@ -268,6 +408,49 @@ pub fn remove_explicit_tag_from_file(
     Ok(())
 }
 
+/// retrieves the titles of the tags the file doesn't inherit from its ancestor folders, ordered case-insensitively.
+/// A `None` title means the file doesn't inherit any tags
+pub fn get_inheritance_blocks_for_file(
+    file_id: u32,
+    con: &Connection,
+) -> Result<Vec<Option<String>>, rusqlite::Error> {
+    let mut pst = con.prepare(include_str!(
+        "../assets/queries/tags/get_inheritance_blocks_for_file.sql"
+    ))?;
+    let rows = pst.query_map([file_id], |row| row.get(0))?;
+    rows.collect()
+}
+
+/// replaces the tags the file doesn't inherit from its ancestor folders. If `block_all` is true, `tag_ids` is ignored and the file won't inherit any tags.
+///
+/// This runs in a transaction, so either every block is replaced or none are.
+/// This doesn't remove the tags the file already inherited, see [remove_blocked_implicit_tags]
+pub fn set_inheritance_blocks_for_file(
+    file_id: u32,
+    block_all: bool,
+    tag_ids: &[u32],
+    con: &Connection,
+) -> Result<(), rusqlite::Error> {
+    let tx = con.unchecked_transaction()?;
+    tx.execute(
+        include_str!("../assets/queries/tags/delete_inheritance_blocks_for_file.sql"),
+        [file_id],
+    )?;
+    {
+        let mut pst = tx.prepare(include_str!(
+            "../assets/queries/tags/add_inheritance_block_to_file.sql"
+        ))?;
+        if block_all {
+            pst.execute(rusqlite::params![file_id, None::<u32>])?;
+        } else {
+            for tag_id in tag_ids {
+                pst.execute(rusqlite::params![file_id, tag_id])?;
+            }
+        }
+    }
+    tx.commit()
+}
+
 // ================= folder functions =================
 pub fn add_explicit_tag_to_folder(
     folder_id: u32,
@@ -369,6 +552,50 @@ pub fn remove_explicit_tag_from_folder(
     Ok(())
 }
 
+/// retrieves the titles of the tags the folder doesn't inherit from its ancestor folders, ordered case-insensitively.
+/// A `None` title means the folder doesn't inherit any tags
+pub fn get_inheritance_blocks_for_folder(
+    folder_id: u32,
+    con: &Connection,
+) -> Result<Vec<Option<String>>, rusqlite::Error> {
+    let mut pst = con.prepare(include_str!(
+        "../assets/queries/tags/get_inheritance_blocks_for_folder.sql"
+    ))?;
+    let rows = pst.query_map([folder_id], |row| row.get(0))?;
+    rows.collect()
+}
+
+/// replaces the tags the folder doesn't inherit from its ancestor folders. Everything under the folder doesn't inherit those tags from above the folder either.
+/// If `block_all` is true, `tag_ids` is ignored and no tags are inherited.
+///
+/// This runs in a transaction, so either every block is replaced or none are.
+/// This doesn't remove the tags that were already inherited, see [remove_blocked_implicit_tags]
+pub fn set_inheritance_blocks_for_folder(
+    folder_id: u32,
+    block_all: bool,
+    tag_ids: &[u32],
+    con: &Connection,
+) -> Result<(), rusqlite::Error> {
+    let tx = con.unchecked_transaction()?;
+    tx.execute(
+        include_str!("../assets/queries/tags/delete_inheritance_blocks_for_folder.sql"),
+        [folder_id],
+    )?;
+    {
+        let mut pst = tx.prepare(include_str!(
+            "../assets/queries/tags/add_inheritance_block_to_folder.sql"
+        ))?;
+        if block_all {
+            pst.execute(rusqlite::params![folder_id, None::<u32>])?;
+        } else {
+            for tag_id in tag_ids {
+                pst.execute(rusqlite::params![folder_id, tag_id])?;
+            }
+        }
+    }
+    tx.commit()
+}
+
 // ================= both =================
 
 /// for a given folder id, removes all implicit tags from descendants, so long as the tags being removed shouldn't be implied for the folder.
@@ -451,6 +678,59 @@ pub fn batch_remove_implicit_tags(
     Ok(())
 }
 
+/// removes the implicit tags that the passed files and folders block from being inherited.
+///
+/// A file that blocks a tag doesn't inherit it from any folder. Everything at or under a folder that blocks a tag doesn't inherit it
+/// from folders above the blocking folder, but still inherits it from the blocking folder and the folders under it.
+///
+/// Tags are implied without checking blocks, so this needs to be called after implying tags.
+///
+/// ## Parameters:
+/// - `file_ids`: the ids of the files that may have been implied tags they block
+/// - `folder_ids`: the ids of the folders that may have been implied tags they block
+/// - `con`: a connection to the database. Must be closed by the caller
+pub fn remove_blocked_implicit_tags(
+    file_ids: &[u32],
+    folder_ids: &[u32],
+    con: &Connection,
+) -> Result<(), rusqlite::Error> {
+    let to_values = |ids: &[u32]| -> Rc<Vec<Value>> {
+        Rc::new(ids.iter().map(|&it| Value::Integer(it.into())).collect())
+    };
+    let file_ids = to_values(file_ids);
+    let folder_ids = to_values(folder_ids);
+    con.execute(
+        include_str!("../assets/queries/tags/remove_blocked_implicit_tags.sql"),
+        rusqlite::named_params! {
+            ":fileIds": file_ids,
+            ":folderIds": folder_ids,
+        },
+    )
+    .and(Ok(()))
+}
+
+/// retrieves the ids of the files and folders that implicitly have the tag with the passed id, in that order
+fn get_implicitly_tagged_items(
+    tag_id: u32,
+    con: &Connection,
+) -> Result<(Vec<u32>, Vec<u32>), rusqlite::Error> {
+    let mut pst = con.prepare(include_str!(
+        "../assets/queries/tags/get_implicitly_tagged_items.sql"
+    ))?;
+    let mut rows = pst.query([tag_id])?;
+    let mut file_ids: Vec<u32> = Vec::new();
+    let mut folder_ids: Vec<u32> = Vec::new();
+    while let Some(row) = rows.next()? {
+        if let Some(file_id) = row.get::<_, Option<u32>>(0)? {
+            file_ids.push(file_id);
+        }
+        if let Some(folder_id) = row.get::<_, Option<u32>>(1)? {
+            folder_ids.push(folder_id);
+        }
+    }
+    Ok((file_ids, folder_ids))
+}
+
 // ================= misc =================
 /// 1. id
 /// 2. fileId
@@ -480,5 +760,14 @@ fn tagged_item_mapper(row: &rusqlite::Row) -> Result<models::TaggedItem, rusqlit
 fn tag_mapper(row: &rusqlite::Row) -> Result<models::Tag, rusqlite::Error> {
     let id: u32 = row.get(0)?;
     let title: String = row.get(1)?;
-    Ok(models::Tag { id, title })
+    let parent_id: Option<u32> = row.get(2)?;
+    let color: Option<String> = row.get(3)?;
+    let description: Option<String> = row.get(4)?;
+    Ok(models::Tag {
+        id,
+        title,
+        parent_id,
+        color,
+        description,
+    })
 }
@@ -1,14 +1,18 @@
 use std::backtrace::Backtrace;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use itertools::Itertools;
+use rusqlite::Connection;
 
-use super::{Tag, TagTypes};
+use super::{Tag, TagSort, TagTypes, TagUsage};
 use crate::model::error::file_errors::GetFileError;
 use crate::model::error::tag_errors::{
-    CreateTagError, DeleteTagError, GetTagError, TagRelationError, UpdateTagError,
+    CreateTagError, DeleteTagError, GetTagError, MergeTagError, TagRelationError, UpdateTagError,
+};
+use crate::model::request::tag_requests::UpdateTagRequest;
+use crate::model::response::{
+    TagApi, TagInheritanceApi, TagNamespaceApi, TagTreeApi, TaggedItemApi,
 };
-use crate::model::response::{TagApi, TaggedItemApi};
 use crate::repository::{file_repository, folder_repository, open_connection};
 use crate::service::{file_service, folder_service};
 use crate::tags::repository;
@@ -17,7 +21,41 @@ use crate::tags::repository as tag_repository;
 /// will create a tag, or return the already-existing tag if one with the same name exists
 /// returns the created/existing tag
 pub fn create_tag(name: String) -> Result<TagApi, CreateTagError> {
+    create_tag_with_details(TagApi {
+        title: name,
+        ..Default::default()
+    })
+}
+
+/// will create a tag with the title, parent, color, description, and aliases in `request`,
+/// or return the already-existing tag if one with the same name or an alias matching the name exists.
+/// An already-existing tag is left as-is.
+/// returns the created/existing tag
+pub fn create_tag_with_details(request: TagApi) -> Result<TagApi, CreateTagError> {
+    let Ok(color) = normalize_color(request.color) else {
+        return Err(CreateTagError::InvalidColor);
+    };
+    let description = normalize_description(request.description);
+    let name = request.title;
+    let parent_id = request.parent_id;
     let con = open_connection();
+    if let Some(parent_id) = parent_id {
+        match tag_repository::get_tag(parent_id, &con) {
+            Ok(_) => { /* no op */ }
+            Err(rusqlite::Error::QueryReturnedNoRows) => {
+                con.close().unwrap();
+                return Err(CreateTagError::ParentNotFound);
+            }
+            Err(e) => {
+                log::error!(
+                    "Failed to check if parent tag {parent_id} exists! Error is {e:?}\n{}",
+                    Backtrace::force_capture()
+                );
+                con.close().unwrap();
+                return Err(CreateTagError::DbError);
+            }
+        }
+    }
     let existing_tag: Option<Tag> = match tag_repository::get_tag_by_title(&name, &con) {
         Ok(tags) => tags,
         Err(e) => {
@@ -32,7 +70,29 @@ pub fn create_tag(name: String) -> Result<TagApi, CreateTagError> {
     let tag: Tag = if let Some(t) = existing_tag {
         t
     } else {
-        match tag_repository::create_tag(&name, &con) {
+        let aliases = match check_aliases(None, &name, request.aliases, &con) {
+            Ok(aliases) => aliases,
+            Err(e) => {
+                con.close().unwrap();
+                return Err(match e {
+                    UpdateTagError::AliasAlreadyExists => CreateTagError::AliasAlreadyExists,
+                    _ => CreateTagError::DbError,
+                });
+            }
+        };
+        // the tag and its details are saved together, so a failure can't leave a tag without them
+        let created = con.unchecked_transaction().and_then(|tx| {
+            let mut t = tag_repository::create_tag(&name, parent_id, &tx)?;
+            if color.is_some() || description.is_some() {
+                t.color = color;
+                t.description = description;
+                tag_repository::update_tag(t.clone(), &tx)?;
+            }
+            tag_repository::set_tag_aliases(t.id, &aliases, &tx)?;
+            tx.commit()?;
+            Ok(t)
+        });
+        match created {
             Ok(t) => t,
             Err(e) => {
                 log::error!(
@@ -44,9 +104,15 @@ pub fn create_tag(name: String) -> Result<TagApi, CreateTagError> {
             }
         }
     };
-
+    let res = with_aliases(tag, &con);
     con.close().unwrap();
-    Ok(TagApi::from(tag))
+    res.map_err(|e| {
+        log::error!(
+            "Failed to retrieve aliases for tag {name}! Error is {e:?}\n{}",
+            Backtrace::force_capture()
+        );
+        CreateTagError::DbError
+    })
 }
 
 /// will return the tag with the passed id
@@ -71,20 +137,144 @@ pub fn get_tag(id: u32) -> Result<TagApi, GetTagError> {
             return Err(GetTagError::DbError);
         }
     };
+    let res = with_aliases(tag, &con);
     con.close().unwrap();
-    Ok(TagApi::from(tag))
+    res.map_err(|e| {
+        log::error!(
+            "Could not retrieve aliases for tag with id {id}! Error is {e:?}\n{}",
+            Backtrace::force_capture()
+        );
+        GetTagError::DbError
+    })
 }
 
-/// updates the tag with the passed id to the passed name.
-/// Will fail if a tag already exists with that name
-pub fn update_tag(request: TagApi) -> Result<TagApi, UpdateTagError> {
+/// lists every tag as a tree, where each tag holds the tags nested under it.
+/// Tags at each level are ordered by `sort`, with ties ordered by title.
+///
+/// If `prefix` is passed, only tags whose title or one of whose aliases starts with it (ignoring case) are listed,
+/// and if `namespace` is passed only tags in that namespace (ignoring case) are listed. Listed tags come along with the tags they're nested under
+pub fn get_all_tags(
+    prefix: Option<&str>,
+    namespace: Option<&str>,
+    sort: TagSort,
+) -> Result<Vec<TagTreeApi>, GetTagError> {
+    let con = open_connection();
+    let res = get_tag_usages(&con).and_then(|tags| {
+        tag_repository::get_all_tag_aliases(&con)
+            .map(|aliases| (tags, aliases))
+            .map_err(|e| {
+                log::error!(
+                    "Failed to retrieve all tag aliases! Error is {e:?}\n{}",
+                    Backtrace::force_capture()
+                );
+                GetTagError::DbError
+            })
+    });
+    con.close().unwrap();
+    let (mut tags, mut aliases) = res?;
+    if sort == TagSort::Usage {
+        // tags are already ordered by title, and sorting is stable
+        tags.sort_by_key(|it| std::cmp::Reverse(it.total()));
+    }
+    let mut children: HashMap<Option<u32>, Vec<TagUsage>> = HashMap::new();
+    for tag in tags {
+        children.entry(tag.tag.parent_id).or_default().push(tag);
+    }
+    let prefix = prefix.map(|it| it.trim().to_lowercase());
+    let namespace = namespace.map(|it| it.trim().to_lowercase());
+    let matches = |tag: &Tag, aliases: &[String]| {
+        prefix.as_deref().is_none_or(|prefix| {
+            std::iter::once(&tag.title)
+                .chain(aliases)
+                .any(|it| it.to_lowercase().starts_with(prefix))
+        }) && namespace
+            .as_deref()
+            .is_none_or(|namespace| tag.namespace().as_deref() == Some(namespace))
+    };
+    Ok(build_tag_tree(None, &mut children, &mut aliases, &matches))
+}
+
+/// lists every tag namespace along with how many tags are in it, ordered by name
+pub fn get_tag_namespaces() -> Result<Vec<TagNamespaceApi>, GetTagError> {
+    let con = open_connection();
+    let tags = get_tag_usages(&con);
+    con.close().unwrap();
+    let tags = tags?;
+    let namespaces = tags
+        .iter()
+        .filter_map(|it| it.tag.namespace())
+        .counts()
+        .into_iter()
+        .map(|(name, count)| TagNamespaceApi {
+            name,
+            tag_count: count as u32,
+        })
+        .sorted_by(|a, b| a.name.cmp(&b.name))
+        .collect();
+    Ok(namespaces)
+}
+
+/// retrieves every tag along with how often it's used, ordered by title
+fn get_tag_usages(con: &Connection) -> Result<Vec<TagUsage>, GetTagError> {
+    tag_repository::get_all_tags(con).map_err(|e| {
+        log::error!(
+            "Failed to retrieve all tags! Error is {e:?}\n{}",
+            Backtrace::force_capture()
+        );
+        GetTagError::DbError
+    })
+}
+
+/// builds the tree of tags nested under `parent_id`. Tags and their aliases are removed from `children` and `aliases` as they're added to the tree.
+/// Tags that don't pass `matches` and have no descendants that do are left out
+fn build_tag_tree(
+    parent_id: Option<u32>,
+    children: &mut HashMap<Option<u32>, Vec<TagUsage>>,
+    aliases: &mut HashMap<u32, Vec<String>>,
+    matches: &dyn Fn(&Tag, &[String]) -> bool,
+) -> Vec<TagTreeApi> {
+    let tags = children.remove(&parent_id).unwrap_or_default();
+    tags.into_iter()
+        .filter_map(|usage| {
+            let nested = build_tag_tree(Some(usage.tag.id), children, aliases, matches);
+            let tag_aliases = aliases.remove(&usage.tag.id).unwrap_or_default();
+            let matches = matches(&usage.tag, &tag_aliases);
+            if !matches && nested.is_empty() {
+                return None;
+            }
+            Some(TagTreeApi {
+                id: usage.tag.id,
+                namespace: usage.tag.namespace(),
+                title: usage.tag.title,
+                parent_id: usage.tag.parent_id,
+                color: usage.tag.color,
+                description: usage.tag.description,
+                aliases: tag_aliases,
+                file_count: usage.file_count,
+                folder_count: usage.folder_count,
+                implicit_count: usage.implicit_count,
+                children: nested,
+            })
+        })
+        .collect()
+}
+
+/// updates the tag with the passed id. Fields left out of the request keep their current value.
+/// Will fail if a tag already exists with that name, or if the parent doesn't exist or is nested under the tag
+pub fn update_tag(request: UpdateTagRequest) -> Result<TagApi, UpdateTagError> {
+    let color = match request.color {
+        Some(color) => match normalize_color(Some(color)) {
+            Ok(color) => Some(color),
+            Err(_) => return Err(UpdateTagError::InvalidColor),
+        },
+        None => None,
+    };
     let con: rusqlite::Connection = open_connection();
-    // make sure the tag exists first TODO cleanup - use if let Err pattern since Ok branch is empty
-    match tag_repository::get_tag(request.id.unwrap(), &con) {
-        Ok(_) => { /* no op */ }
+    let existing = match tag_repository::get_tag(request.id, &con) {
+        Ok(t) => t,
         Err(rusqlite::Error::QueryReturnedNoRows) => {
             log::error!(
-                "Could not update tag with id {:?}, because it does not exist!\n{}",
+                "Could not update tag with id {}, because it does not exist!\n{}",
                 request.id,
                 Backtrace::force_capture()
             );
@@ -93,7 +283,7 @@ pub fn update_tag(request: TagApi) -> Result<TagApi, UpdateTagError> {
         }
         Err(e) => {
             log::error!(
-                "Could not update tag with id {:?}! Error is {e}\n{}",
+                "Could not update tag with id {}! Error is {e}\n{}",
                 request.id,
                 Backtrace::force_capture()
             );
@@ -102,19 +292,18 @@ pub fn update_tag(request: TagApi) -> Result<TagApi, UpdateTagError> {
         }
     };
     let new_title = request.title;
-    // now make sure the database doesn't already have a tag with the new name TODO maybe see if can clean up, 2 empty branches is a smell
+    // now make sure the database doesn't already have a tag with the new name
     match tag_repository::get_tag_by_title(&new_title, &con) {
-        Ok(Some(_)) => {
+        Ok(Some(other)) if other.id != request.id => {
             log::error!(
-                "Could not update tag with id {:?} to name {new_title}, because a tag with that name already exists!\n{}",
+                "Could not update tag with id {} to name {new_title}, because a tag with that name already exists!\n{}",
                 request.id,
                 Backtrace::force_capture()
             );
             con.close().unwrap();
             return Err(UpdateTagError::NewNameAlreadyExists);
         }
-        Ok(None) => {}
-        Err(rusqlite::Error::QueryReturnedNoRows) => { /* this is the good route - no op */ }
+        Ok(_) => {}
         Err(e) => {
             log::error!(
                 "Could not search tags by name with value {new_title}! Error is {e:?}\n{}",
@@ -124,28 +313,212 @@ pub fn update_tag(request: TagApi) -> Result<TagApi, UpdateTagError> {
             return Err(UpdateTagError::DbError);
         }
     };
-    // no match, and tag already exists so we're good to go
+    if let Some(Some(parent_id)) = request.parent_id
+        && let Err(e) = check_parent(request.id, parent_id, &con)
+    {
+        con.close().unwrap();
+        return Err(e);
+    }
+    let aliases = match request.aliases {
+        Some(aliases) => aliases,
+        None => match tag_repository::get_tag_aliases(request.id, &con) {
+            Ok(aliases) => aliases,
+            Err(e) => {
+                log::error!(
+                    "Could not retrieve aliases for tag with id {}! Error is {e:?}\n{}",
+                    request.id,
+                    Backtrace::force_capture()
+                );
+                con.close().unwrap();
+                return Err(UpdateTagError::DbError);
+            }
+        },
+    };
+    // existing aliases are checked too, since the new title may be one of them
+    let aliases = match check_aliases(Some(request.id), &new_title, aliases, &con) {
+        Ok(aliases) => aliases,
+        Err(e) => {
+            con.close().unwrap();
+            return Err(e);
+        }
+    };
     let db_tag = Tag {
-        id: request.id.unwrap(),
-        title: new_title.clone(),
+        id: request.id,
+        title: new_title,
+        parent_id: request.parent_id.unwrap_or(existing.parent_id),
+        color: color.unwrap_or(existing.color),
+        description: match request.description {
+            Some(description) => normalize_description(Some(description)),
+            None => existing.description,
+        },
     };
-    match tag_repository::update_tag(db_tag, &con) {
-        Ok(()) => {}
+    let res = con.unchecked_transaction().and_then(|tx| {
+        tag_repository::update_tag(db_tag.clone(), &tx)?;
+        tag_repository::set_tag_aliases(db_tag.id, &aliases, &tx)?;
+        tx.commit()
+    });
+    if let Err(e) = res {
+        log::error!(
+            "Could not update tag with id {}! Error is {e}\n{}",
+            request.id,
+            Backtrace::force_capture()
+        );
+        con.close().unwrap();
+        return Err(UpdateTagError::DbError);
+    }
+    con.close().unwrap();
+    Ok(TagApi {
+        aliases,
+        ..TagApi::from(db_tag)
+    })
+}
+
+/// trims the aliases, leaving out blank ones, duplicates, and ones matching `title`. Aliases are ordered case-insensitively.
+/// Fails if any alias is the title or an alias of a tag other than the one with the id `tag_id`
+fn check_aliases(
+    tag_id: Option<u32>,
+    title: &str,
+    aliases: Vec<String>,
+    con: &Connection,
+) -> Result<Vec<String>, UpdateTagError> {
+    let aliases: Vec<String> = aliases
+        .into_iter()
+        .map(|it| it.trim().to_string())
+        .filter(|it| !it.is_empty() && it.to_lowercase() != title.trim().to_lowercase())
+        .unique_by(|it| it.to_lowercase())
+        .sorted_by_key(|it| it.to_lowercase())
+        .collect();
+    for alias in aliases.iter() {
+        match tag_repository::get_tag_by_title(alias, con) {
+            Ok(Some(existing)) if Some(existing.id) != tag_id => {
+                log::error!(
+                    "Could not use {alias} as an alias, because it's already used by the tag with id {}!\n{}",
+                    existing.id,
+                    Backtrace::force_capture()
+                );
+                return Err(UpdateTagError::AliasAlreadyExists);
+            }
+            Ok(_) => {}
+            Err(e) => {
+                log::error!(
+                    "Could not search tags by name with value {alias}! Error is {e:?}\n{}",
+                    Backtrace::force_capture()
+                );
+                return Err(UpdateTagError::DbError);
+            }
+        }
+    }
+    Ok(aliases)
+}
+
+/// converts the tag to a [TagApi] along with its aliases
+fn with_aliases(tag: Tag, con: &Connection) -> Result<TagApi, rusqlite::Error> {
+    let aliases = tag_repository::get_tag_aliases(tag.id, con)?;
+    Ok(TagApi {
+        aliases,
+        ..TagApi::from(tag)
+    })
+}
+
+/// lowercases hex colors like `#F80` or `#FF8800`, treating a blank color as no color.
+/// Returns `Err` if the color isn't a hex color
+fn normalize_color(color: Option<String>) -> Result<Option<String>, ()> {
+    let Some(color) = color.map(|it| it.trim().to_lowercase()) else {
+        return Ok(None);
+    };
+    if color.is_empty() {
+        return Ok(None);
+    }
+    match color.strip_prefix('#') {
+        Some(hex) if matches!(hex.len(), 3 | 6) && hex.chars().all(|it| it.is_ascii_hexdigit()) => {
+            Ok(Some(color))
+        }
+        _ => Err(()),
+    }
+}
+
+/// trims the description, treating a blank description as no description
+fn normalize_description(description: Option<String>) -> Option<String> {
+    description
+        .map(|it| it.trim().to_string())
+        .filter(|it| !it.is_empty())
+}
+
+/// makes sure the tag with the id `parent_id` exists and that nesting the tag with the id `tag_id` under it won't make a cycle
+fn check_parent(tag_id: u32, parent_id: u32, con: &Connection) -> Result<(), UpdateTagError> {
+    if tag_id == parent_id {
+        return Err(UpdateTagError::CircularParent);
+    }
+    match tag_repository::get_tag(parent_id, con) {
+        Ok(_) => { /* no op */ }
+        Err(rusqlite::Error::QueryReturnedNoRows) => return Err(UpdateTagError::ParentNotFound),
         Err(e) => {
             log::error!(
-                "Could not update tag with id {:?}! Error is {e}\n{}",
-                request.id,
+                "Failed to check if parent tag {parent_id} exists! Error is {e:?}\n{}",
                 Backtrace::force_capture()
             );
-            con.close().unwrap();
             return Err(UpdateTagError::DbError);
         }
-    };
+    }
+    match tag_repository::get_tag_ancestor_ids(parent_id, con) {
+        Ok(ids) if ids.contains(&tag_id) => Err(UpdateTagError::CircularParent),
+        Ok(_) => Ok(()),
+        Err(e) => {
+            log::error!(
+                "Failed to retrieve ancestors of tag {parent_id}! Error is {e:?}\n{}",
+                Backtrace::force_capture()
+            );
+            Err(UpdateTagError::DbError)
+        }
+    }
+}
+
+/// merges the tag with the id `source_id` into the tag with the id `target_id`.
+/// Every file and folder with the source tag gets the target tag instead, tags nested under the source are nested under the target,
+/// and the source is deleted. The source's title and aliases become aliases of the target. Returns the target tag
+pub fn merge_tags(source_id: u32, target_id: u32) -> Result<TagApi, MergeTagError> {
+    if source_id == target_id {
+        return Err(MergeTagError::SameTag);
+    }
+    let con = open_connection();
+    let res = check_merge_tags_exist(source_id, target_id, &con).and_then(|_| {
+        tag_repository::merge_tags(source_id, target_id, &con)
+            .and_then(|_| tag_repository::get_tag(target_id, &con))
+            .and_then(|tag| with_aliases(tag, &con))
+            .map_err(|e| {
+                log::error!(
+                    "Failed to merge tag {source_id} into tag {target_id}! Error is {e:?}\n{}",
+                    Backtrace::force_capture()
+                );
+                MergeTagError::DbError
+            })
+    });
     con.close().unwrap();
-    Ok(TagApi {
-        id: request.id,
-        title: new_title,
-    })
+    res
+}
+
+fn check_merge_tags_exist(
+    source_id: u32,
+    target_id: u32,
+    con: &Connection,
+) -> Result<(), MergeTagError> {
+    for (id, not_found) in [
+        (source_id, MergeTagError::TagNotFound),
+        (target_id, MergeTagError::TargetNotFound),
+    ] {
+        match tag_repository::get_tag(id, con) {
+            Ok(_) => { /* no op */ }
+            Err(rusqlite::Error::QueryReturnedNoRows) => return Err(not_found),
+            Err(e) => {
+                log::error!(
+                    "Could not retrieve tag with id {id}! Error is {e:?}\n{}",
+                    Backtrace::force_capture()
+                );
+                return Err(MergeTagError::DbError);
+            }
+        }
+    }
+    Ok(())
 }
 
 /// deletes the tag with the passed id. Does nothing if that tag doesn't exist
@@ -184,7 +557,7 @@ pub fn delete_tag(id: u32) -> Result<(), DeleteTagError> {
 /// # Parameters
 /// - `file_id`: The ID of the file to update tags for
 /// - `tags`: A vector of tags to set on the file. Tags with an `id` will be linked directly,
-///   tags without an `id` will be created first (or retrieved if they already exist by name)
+///   tags without an `id` will be created first (or retrieved if they already exist by name or alias)
 ///
 /// # Returns
 /// - `Ok(())` if the tags were successfully updated
@@ -250,6 +623,44 @@ pub fn update_file_tags(file_id: u32, tags: Vec<TaggedItemApi>) -> Result<(), Ta
     Ok(())
 }
 
+/// adds the tags with the passed titles to the file as explicit tags, without touching the tags it already has.
+/// Tags that don't exist yet are created, and titles that match an alias resolve to the aliased tag.
+/// Tags the file already has, including ones inherited from a parent folder, are skipped
+pub fn add_tags_to_file(file_id: u32, titles: &[String]) -> Result<(), TagRelationError> {
+    let mut existing_tag_ids: HashSet<u32> = get_tags_on_file(file_id)?
+        .into_iter()
+        .filter_map(|tag| tag.tag_id)
+        .collect();
+    let con = open_connection();
+    for title in titles {
+        let created = match create_tag(title.clone()) {
+            Ok(t) => t,
+            Err(e) => {
+                con.close().unwrap();
+                log::error!(
+                    "Failed to create tag! Error is {e:?}\n{}",
+                    Backtrace::force_capture()
+                );
+                return Err(TagRelationError::DbError);
+            }
+        };
+        let tag_id = created.id.unwrap();
+        if !existing_tag_ids.insert(tag_id) {
+            continue;
+        }
+        if let Err(e) = tag_repository::add_explicit_tag_to_file(file_id, tag_id, &con) {
+            con.close().unwrap();
+            log::error!(
+                "Failed to add tag to file: {e:?}\n{}",
+                Backtrace::force_capture(),
+            );
+            return Err(TagRelationError::DbError);
+        }
+    }
+    con.close().unwrap();
+    Ok(())
+}
+
 /// Updates the tags on a folder by replacing all existing tags with the provided list.
 ///
 /// The tags updated via this function must be explicit tags for that folder.
@@ -265,7 +676,7 @@ pub fn update_file_tags(file_id: u32, tags: Vec<TaggedItemApi>) -> Result<(), Ta
 /// # Parameters
 /// - `folder_id`: The ID of the folder to update tags for
 /// - `tags`: A vector of tags to set on the folder. Tags with an `id` will be linked directly,
-///   tags without an `id` will be created first (or retrieved if they already exist by name).
+///   tags without an `id` will be created first (or retrieved if they already exist by name or alias).
 ///   These tags must be explicit! no checking is done within the function
 ///
 /// # Returns
@@ -417,6 +828,7 @@ pub fn get_tags_on_folder(folder_id: u32) -> Result<Vec<TaggedItemApi>, TagRelat
 /// - All explicit tags on the folder are implied to descendants (if not already present)
 /// - All removed explicit tags have their implications removed from descendants
 /// - Explicit tags on descendants are never overridden
+/// - Descendants that block inheriting a tag don't inherit it, see [repository::remove_blocked_implicit_tags]
 ///
 /// ## Parameters
 /// - `folder_id`: the id of the folder whose tags should be propagated to descendants
@@ -539,6 +951,17 @@ pub fn pass_tags_to_descendants(folder_id: u32) -> Result<(), TagRelationError>
             }
         }
     }
+    // tags were implied without checking if anything under the folder blocks them
+    if let Err(e) =
+        repository::remove_blocked_implicit_tags(&descendant_files, &all_folder_ids, &con)
+    {
+        con.close().unwrap();
+        log::error!(
+            "Failed to remove blocked implicit tags from descendants of folder {folder_id}! Error is {e:?}\n{}",
+            Backtrace::force_capture()
+        );
+        return Err(TagRelationError::DbError);
+    }
     con.close().unwrap();
     Ok(())
 }
@@ -548,6 +971,7 @@ pub fn pass_tags_to_descendants(folder_id: u32) -> Result<(), TagRelationError>
 /// This function retrieves all ancestor folders of a file and implies their
 /// explicit tags to the file. Tags are processed in depth-first order (closest
 /// ancestor first), ensuring that tags from closer ancestors take precedence.
+/// Tags blocked by the file or a folder between it and the ancestor aren't implied.
 ///
 /// ## Parameters
 /// - `file_id`: the ID of the file to imply ancestor tags to
@@ -613,6 +1037,150 @@ pub fn imply_all_ancestor_tags(file_id: u32) -> Result<(), TagRelationError> {
         }
     }
 
+    // tags were implied without checking if the file or its ancestors block them
+    if let Err(e) = repository::remove_blocked_implicit_tags(&[file_id], &[], &con) {
+        con.close().unwrap();
+        log::error!(
+            "Failed to remove blocked implicit tags from file {file_id}! Error is {e:?}\n{}",
+            Backtrace::force_capture()
+        );
+        return Err(TagRelationError::DbError);
+    }
+
     con.close().unwrap();
     Ok(())
 }
+
+/// retrieves the tags the file doesn't inherit from its ancestor folders
+pub fn get_file_tag_inheritance(file_id: u32) -> Result<TagInheritanceApi, TagRelationError> {
+    if !file_service::check_file_exists(file_id) {
+        return Err(TagRelationError::FileNotFound);
+    }
+    let con = open_connection();
+    let blocks = repository::get_inheritance_blocks_for_file(file_id, &con);
+    con.close().unwrap();
+    match blocks {
+        Ok(blocks) => Ok(to_tag_inheritance(blocks)),
+        Err(e) => {
+            log::error!(
+                "Failed to retrieve inheritance blocks for file {file_id}! Error is {e:?}\n{}",
+                Backtrace::force_capture()
+            );
+            Err(TagRelationError::DbError)
+        }
+    }
+}
+
+/// replaces the tags the file doesn't inherit from its ancestor folders, removing the blocked tags it already inherited
+/// and re-inheriting tags that aren't blocked anymore
+pub fn set_file_tag_inheritance(
+    file_id: u32,
+    inheritance: TagInheritanceApi,
+) -> Result<TagInheritanceApi, TagRelationError> {
+    if !file_service::check_file_exists(file_id) {
+        return Err(TagRelationError::FileNotFound);
+    }
+    let tag_ids = get_blocked_tag_ids(&inheritance)?;
+    let con = open_connection();
+    let res =
+        repository::set_inheritance_blocks_for_file(file_id, inheritance.block_all, &tag_ids, &con);
+    con.close().unwrap();
+    if let Err(e) = res {
+        log::error!(
+            "Failed to update inheritance blocks for file {file_id}! Error is {e:?}\n{}",
+            Backtrace::force_capture()
+        );
+        return Err(TagRelationError::DbError);
+    }
+    imply_all_ancestor_tags(file_id)?;
+    get_file_tag_inheritance(file_id)
+}
+
+/// retrieves the tags the folder and everything under it don't inherit from the folder's ancestors
+pub fn get_folder_tag_inheritance(folder_id: u32) -> Result<TagInheritanceApi, TagRelationError> {
+    if !folder_service::folder_exists(Some(folder_id)) {
+        return Err(TagRelationError::FolderNotFound);
+    }
+    let con = open_connection();
+    let blocks = repository::get_inheritance_blocks_for_folder(folder_id, &con);
+    con.close().unwrap();
+    match blocks {
+        Ok(blocks) => Ok(to_tag_inheritance(blocks)),
+        Err(e) => {
+            log::error!(
+                "Failed to retrieve inheritance blocks for folder {folder_id}! Error is {e:?}\n{}",
+                Backtrace::force_capture()
+            );
+            Err(TagRelationError::DbError)
+        }
+    }
+}
+
+/// replaces the tags the folder and everything under it don't inherit from the folder's ancestors,
+/// removing the blocked tags they already inherited and re-inheriting tags that aren't blocked anymore
+pub fn set_folder_tag_inheritance(
+    folder_id: u32,
+    inheritance: TagInheritanceApi,
+) -> Result<TagInheritanceApi, TagRelationError> {
+    // the root folder can't inherit anything
+    if folder_id == 0 || !folder_service::folder_exists(Some(folder_id)) {
+        return Err(TagRelationError::FolderNotFound);
+    }
+    let tag_ids = get_blocked_tag_ids(&inheritance)?;
+    let con = open_connection();
+    let res = repository::set_inheritance_blocks_for_folder(
+        folder_id,
+        inheritance.block_all,
+        &tag_ids,
+        &con,
+    );
+    con.close().unwrap();
+    if let Err(e) = res {
+        log::error!(
+            "Failed to update inheritance blocks for folder {folder_id}! Error is {e:?}\n{}",
+            Backtrace::force_capture()
+        );
+        return Err(TagRelationError::DbError);
+    }
+    pass_tags_to_descendants(folder_id)?;
+    get_folder_tag_inheritance(folder_id)
+}
+
+/// creates the blocked tags that don't exist yet, returning the ids of all of them. No tags are returned if every tag is blocked
+fn get_blocked_tag_ids(inheritance: &TagInheritanceApi) -> Result<Vec<u32>, TagRelationError> {
+    if inheritance.block_all {
+        return Ok(Vec::new());
+    }
+    let mut tag_ids: Vec<u32> = Vec::new();
+    for title in inheritance.blocked_tags.iter().map(|it| it.trim()) {
+        if title.is_empty() {
+            continue;
+        }
+        let tag = create_tag(title.to_string()).map_err(|e| {
+            log::error!(
+                "Failed to create tag! Error is {e:?}\n{}",
+                Backtrace::force_capture()
+            );
+            TagRelationError::DbError
+        })?;
+        let tag_id = tag.id.unwrap();
+        if !tag_ids.contains(&tag_id) {
+            tag_ids.push(tag_id);
+        }
+    }
+    Ok(tag_ids)
+}
+
+fn to_tag_inheritance(blocks: Vec<Option<String>>) -> TagInheritanceApi {
+    if blocks.iter().any(Option::is_none) {
+        TagInheritanceApi {
+            block_all: true,
+            blocked_tags: Vec::new(),
+        }
+    } else {
+        TagInheritanceApi {
+            block_all: false,
+            blocked_tags: blocks.into_iter().flatten().collect(),
+        }
+    }
+}
@@ -37,8 +37,120 @@ mod get_tag_tests {
     }
 }
 
+mod get_all_tags_tests {
+    use super::*;
+    use crate::model::response::TagTreeApi;
+
+    #[test]
+    fn without_creds() {
+        initialize_db().unwrap();
+        let client = client();
+        let res = client.get(uri!("/tags")).dispatch();
+        assert_eq!(res.status(), Status::Unauthorized);
+        cleanup();
+    }
+
+    #[test]
+    fn success() {
+        set_password();
+        create_tag_db_entry("animal");
+        let client = client();
+        let auth = Header::new("Authorization", AUTH);
+        let res = client
+            .post(uri!("/tags"))
+            .header(auth.clone())
+            .body(r#"{"title":"cat","parentId":1}"#)
+            .dispatch();
+        assert_eq!(res.status(), Status::Created);
+        let res = client.get(uri!("/tags")).header(auth).dispatch();
+        assert_eq!(res.status(), Status::Ok);
+        let body: Vec<TagTreeApi> = res.into_json().unwrap();
+        assert_eq!(1, body.len());
+        assert_eq!("animal", body[0].title);
+        assert_eq!("cat", body[0].children[0].title);
+        assert_eq!(Some(1), body[0].children[0].parent_id);
+        cleanup();
+    }
+
+    #[test]
+    fn filters_and_sorts() {
+        set_password();
+        create_tag_db_entry("cat");
+        create_tag_db_entry("car");
+        create_tag_db_entry("dog");
+        create_file_db_entry("a.txt", None);
+        let con = crate::repository::open_connection();
+        crate::tags::repository::add_explicit_tag_to_file(1, 2, &con).unwrap();
+        con.close().unwrap();
+        let client = client();
+        let auth = Header::new("Authorization", AUTH);
+        let res = client
+            .get("/tags?prefix=ca&sort=usage")
+            .header(auth)
+            .dispatch();
+        assert_eq!(res.status(), Status::Ok);
+        let body: Vec<TagTreeApi> = res.into_json().unwrap();
+        let titles: Vec<String> = body.into_iter().map(|it| it.title).collect();
+        assert_eq!(vec!["car", "cat"], titles);
+        cleanup();
+    }
+
+    #[test]
+    fn bad_sort() {
+        set_password();
+        let client = client();
+        let auth = Header::new("Authorization", AUTH);
+        let res = client.get("/tags?sort=color").header(auth).dispatch();
+        assert_eq!(res.status(), Status::BadRequest);
+        cleanup();
+    }
+}
+
+mod get_tag_namespaces_tests {
+    use super::*;
+    use crate::model::response::TagNamespaceApi;
+
+    #[test]
+    fn without_creds() {
+        initialize_db().unwrap();
+        let client = client();
+        let res = client.get(uri!("/tags/namespaces")).dispatch();
+        assert_eq!(res.status(), Status::Unauthorized);
+        cleanup();
+    }
+
+    #[test]
+    fn success() {
+        set_password();
+        create_tag_db_entry("person:alice");
+        create_tag_db_entry("Person:bob");
+        create_tag_db_entry("project:garage");
+        create_tag_db_entry("receipt");
+        let client = client();
+        let auth = Header::new("Authorization", AUTH);
+        let res = client.get(uri!("/tags/namespaces")).header(auth).dispatch();
+        assert_eq!(res.status(), Status::Ok);
+        let body: Vec<TagNamespaceApi> = res.into_json().unwrap();
+        assert_eq!(
+            vec![
+                TagNamespaceApi {
+                    name: "person".to_string(),
+                    tag_count: 2
+                },
+                TagNamespaceApi {
+                    name: "project".to_string(),
+                    tag_count: 1
+                },
+            ],
+            body
+        );
+        cleanup();
+    }
+}
+
 mod create_tag_tests {
     use super::*;
+    use crate::model::response::TagApi;
 
     #[test]
     fn without_creds() {
@@ -65,6 +177,100 @@ mod create_tag_tests {
         assert_eq!(res.status(), Status::Created);
         cleanup();
     }
+
+    #[test]
+    fn parent_not_found() {
+        set_password();
+        let client = client();
+        let auth = Header::new("Authorization", AUTH);
+        let res = client
+            .post(uri!("/tags"))
+            .header(auth)
+            .body(r#"{"title":"new_tag","parentId":5}"#)
+            .dispatch();
+        assert_eq!(res.status(), Status::BadRequest);
+        cleanup();
+    }
+
+    #[test]
+    fn with_details() {
+        set_password();
+        let client = client();
+        let auth = Header::new("Authorization", AUTH);
+        let res = client
+            .post(uri!("/tags"))
+            .header(auth)
+            .body(r##"{"title":"person:alice","color":"#FF8800","description":"my sister"}"##)
+            .dispatch();
+        assert_eq!(res.status(), Status::Created);
+        let body: TagApi = res.into_json().unwrap();
+        assert_eq!(Some("#ff8800".to_string()), body.color);
+        assert_eq!(Some("my sister".to_string()), body.description);
+        assert_eq!(Some("person".to_string()), body.namespace);
+        cleanup();
+    }
+
+    #[test]
+    fn alias_already_exists() {
+        set_password();
+        create_tag_db_entry("picture");
+        let client = client();
+        let auth = Header::new("Authorization", AUTH);
+        let res = client
+            .post(uri!("/tags"))
+            .header(auth)
+            .body(r#"{"title":"image","aliases":["Picture"]}"#)
+            .dispatch();
+        assert_eq!(res.status(), Status::BadRequest);
+        cleanup();
+    }
+
+    #[test]
+    fn missing_fields_are_unchanged() {
+        set_password();
+        create_tag_db_entry("parent");
+        create_tag_db_entry("child");
+        let client = client();
+        let res = client
+            .put(uri!("/tags"))
+            .header(Header::new("Authorization", AUTH))
+            .body(r##"{"id":2,"title":"child","parentId":1,"color":"#123456","aliases":["kid"]}"##)
+            .dispatch();
+        assert_eq!(res.status(), Status::Ok);
+        let res = client
+            .put(uri!("/tags"))
+            .header(Header::new("Authorization", AUTH))
+            .body(r#"{"id":2,"title":"renamed"}"#)
+            .dispatch();
+        assert_eq!(res.status(), Status::Ok);
+        let tag: TagApi = res.into_json().unwrap();
+        assert_eq!(Some(1), tag.parent_id);
+        assert_eq!(Some("#123456".to_string()), tag.color);
+        assert_eq!(vec!["kid"], tag.aliases);
+        let res = client
+            .put(uri!("/tags"))
+            .header(Header::new("Authorization", AUTH))
+            .body(r#"{"id":2,"title":"renamed","parentId":null}"#)
+            .dispatch();
+        let tag: TagApi = res.into_json().unwrap();
+        assert_eq!(None, tag.parent_id);
+        assert_eq!(Some("#123456".to_string()), tag.color);
+        cleanup();
+    }
+
+    #[test]
+    fn invalid_color() {
+        set_password();
+        let client = client();
+        let auth = Header::new("Authorization", AUTH);
+        let res = client
+            .post(uri!("/tags"))
+            .header(auth)
+            .body(r#"{"title":"new_tag","color":"orange"}"#)
+            .dispatch();
+        assert_eq!(res.status(), Status::BadRequest);
+        cleanup();
+    }
 }
 
 mod update_tag_tests {
@@ -126,6 +332,94 @@ mod update_tag_tests {
         assert_eq!(res.status(), Status::BadRequest);
         cleanup();
     }
+
+    #[test]
+    fn circular_parent() {
+        set_password();
+        create_tag_db_entry("tag1");
+        let client = client();
+        let auth = Header::new("Authorization", AUTH);
+        let res = client
+            .put(uri!("/tags"))
+            .header(auth)
+            .body(r#"{"id":1,"title":"tag1","parentId":1}"#)
+            .dispatch();
+        assert_eq!(res.status(), Status::BadRequest);
+        cleanup();
+    }
+
+    #[test]
+    fn invalid_color() {
+        set_password();
+        create_tag_db_entry("tag1");
+        let client = client();
+        let auth = Header::new("Authorization", AUTH);
+        let res = client
+            .put(uri!("/tags"))
+            .header(auth)
+            .body(r##"{"id":1,"title":"tag1","color":"#12345"}"##)
+            .dispatch();
+        assert_eq!(res.status(), Status::BadRequest);
+        cleanup();
+    }
+}
+
+mod merge_tag_tests {
+    use super::*;
+    use crate::model::response::TagApi;
+
+    #[test]
+    fn without_creds() {
+        initialize_db().unwrap();
+        let client = client();
+        let res = client
+            .post(uri!("/tags/1/merge"))
+            .body(r#"{"targetId":2}"#)
+            .dispatch();
+        assert_eq!(res.status(), Status::Unauthorized);
+        cleanup();
+    }
+
+    #[test]
+    fn success() {
+        set_password();
+        create_tag_db_entry("vacation");
+        create_tag_db_entry("holiday");
+        let client = client();
+        let auth = Header::new("Authorization", AUTH);
+        let res = client
+            .post(uri!("/tags/1/merge"))
+            .header(auth.clone())
+            .body(r#"{"targetId":2}"#)
+            .dispatch();
+        assert_eq!(res.status(), Status::Ok);
+        let body: TagApi = res.into_json().unwrap();
+        assert_eq!(Some(2), body.id);
+        let res = client.get(uri!("/tags/1")).header(auth).dispatch();
+        assert_eq!(res.status(), Status::NotFound);
+        cleanup();
+    }
+
+    #[test]
+    fn bad_target() {
+        set_password();
+        create_tag_db_entry("vacation");
+        let client = client();
+        let auth = Header::new("Authorization", AUTH);
+        let res = client
+            .post(uri!("/tags/1/merge"))
+            .header(auth.clone())
+            .body(r#"{"targetId":1}"#)
+            .dispatch();
+        assert_eq!(res.status(), Status::BadRequest);
+        let res = client
+            .post(uri!("/tags/2/merge"))
+            .header(auth)
+            .body(r#"{"targetId":1}"#)
+            .dispatch();
+        assert_eq!(res.status(), Status::NotFound);
+        cleanup();
+    }
 }
 
 mod delete_tag_tests {
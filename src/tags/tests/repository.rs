@@ -8,12 +8,15 @@ mod create_tag_tests {
     fn create_tag() {
         init_db_folder();
         let con = open_connection();
-        let tag = repository::create_tag("test", &con).unwrap();
+        let tag = repository::create_tag("test", None, &con).unwrap();
         con.close().unwrap();
         assert_eq!(
             Tag {
                 id: 1,
                 title: "test".to_string(),
+                parent_id: None,
+                color: None,
+                description: None,
             },
             tag
         );
@@ -31,13 +34,16 @@ mod get_tag_by_title_tests {
     fn get_tag_by_title_found() {
         init_db_folder();
         let con = open_connection();
-        create_tag("test", &con).unwrap();
+        create_tag("test", None, &con).unwrap();
         let found = get_tag_by_title("TeSt", &con).unwrap();
         con.close().unwrap();
         assert_eq!(
             Some(Tag {
                 id: 1,
                 title: "test".to_string(),
+                parent_id: None,
+                color: None,
+                description: None,
             }),
             found
         );
@@ -64,13 +70,16 @@ mod get_tag_by_id_tests {
     fn get_tag_success() {
         init_db_folder();
         let con = open_connection();
-        create_tag("test", &con).unwrap();
+        create_tag("test", None, &con).unwrap();
         let tag = get_tag(1, &con).unwrap();
         con.close().unwrap();
         assert_eq!(
             Tag {
                 id: 1,
                 title: "test".to_string(),
+                parent_id: None,
+                color: None,
+                description: None,
             },
             tag
         );
@@ -88,11 +97,14 @@ mod update_tag_tests {
     fn update_tag_success() {
         init_db_folder();
         let con = open_connection();
-        create_tag("test", &con).unwrap();
+        create_tag("test", None, &con).unwrap();
         update_tag(
             Tag {
                 id: 1,
                 title: "test2".to_string(),
+                parent_id: None,
+                color: None,
+                description: None,
             },
             &con,
         )
@@ -103,6 +115,9 @@ mod update_tag_tests {
             Tag {
                 id: 1,
                 title: "test2".to_string(),
+                parent_id: None,
+                color: None,
+                description: None,
             },
             res
         );
@@ -119,13 +134,269 @@ mod delete_tag_tests {
     fn delete_tag_success() {
         init_db_folder();
         let con = open_connection();
-        create_tag("test", &con).unwrap();
+        create_tag("test", None, &con).unwrap();
         delete_tag(1, &con).unwrap();
         let not_found = get_tag(1, &con);
         con.close().unwrap();
         assert_eq!(Err(rusqlite::Error::QueryReturnedNoRows), not_found);
         cleanup();
     }
+
+    #[test]
+    fn delete_tag_moves_children_to_its_parent() {
+        init_db_folder();
+        let con = open_connection();
+        let animal = create_tag("animal", None, &con).unwrap();
+        let cat = create_tag("cat", Some(animal.id), &con).unwrap();
+        let kitten = create_tag("kitten", Some(cat.id), &con).unwrap();
+        delete_tag(cat.id, &con).unwrap();
+        let kitten = get_tag(kitten.id, &con).unwrap();
+        con.close().unwrap();
+        assert_eq!(Some(animal.id), kitten.parent_id);
+        cleanup();
+    }
+}
+
+mod tag_hierarchy_tests {
+    use crate::repository::open_connection;
+    use crate::tags::repository::{
+        add_explicit_tag_to_folder, create_tag, get_all_tags, get_tag_ancestor_ids,
+    };
+    use crate::tags::{Tag, TagUsage};
+    use crate::test::{
+        cleanup, create_file_db_entry, create_folder_db_entry, create_tag_files,
+        imply_tag_on_folder, init_db_folder,
+    };
+
+    #[test]
+    fn get_tag_ancestor_ids_returns_every_ancestor() {
+        init_db_folder();
+        let con = open_connection();
+        let animal = create_tag("animal", None, &con).unwrap();
+        let cat = create_tag("cat", Some(animal.id), &con).unwrap();
+        let kitten = create_tag("kitten", Some(cat.id), &con).unwrap();
+        let mut ancestors = get_tag_ancestor_ids(kitten.id, &con).unwrap();
+        let root_ancestors = get_tag_ancestor_ids(animal.id, &con).unwrap();
+        con.close().unwrap();
+        ancestors.sort();
+        assert_eq!(vec![animal.id, cat.id], ancestors);
+        assert!(root_ancestors.is_empty());
+        cleanup();
+    }
+
+    #[test]
+    fn get_all_tags_orders_by_title() {
+        init_db_folder();
+        let con = open_connection();
+        create_tag("b", None, &con).unwrap();
+        create_tag("C", Some(1), &con).unwrap();
+        create_tag("a", None, &con).unwrap();
+        let titles: Vec<String> = get_all_tags(&con)
+            .unwrap()
+            .into_iter()
+            .map(|it| it.tag.title)
+            .collect();
+        con.close().unwrap();
+        assert_eq!(vec!["a", "b", "C"], titles);
+        cleanup();
+    }
+
+    #[test]
+    fn get_all_tags_counts_explicit_and_implicit_uses() {
+        init_db_folder();
+        create_file_db_entry("a.txt", None);
+        create_file_db_entry("b.txt", None);
+        create_folder_db_entry("folder", None);
+        create_folder_db_entry("child", Some(1));
+        create_tag_files("tag", vec![1, 2]);
+        let con = open_connection();
+        create_tag("unused", None, &con).unwrap();
+        add_explicit_tag_to_folder(1, 1, &con).unwrap();
+        con.close().unwrap();
+        imply_tag_on_folder(1, 2, 1);
+        let con = open_connection();
+        let tags = get_all_tags(&con).unwrap();
+        con.close().unwrap();
+        assert_eq!(
+            TagUsage {
+                tag: Tag {
+                    id: 1,
+                    title: "tag".to_string(),
+                    parent_id: None,
+                    color: None,
+                    description: None,
+                },
+                file_count: 2,
+                folder_count: 1,
+                implicit_count: 1,
+            },
+            tags[0]
+        );
+        assert_eq!(0, tags[1].total());
+        cleanup();
+    }
+}
+
+mod merge_tags_tests {
+    use crate::repository::open_connection;
+    use crate::tags::repository::{
+        add_explicit_tag_to_folder, create_tag, get_all_tags_for_file, get_all_tags_for_folder,
+        get_tag, get_tag_aliases, merge_tags, set_tag_aliases,
+    };
+    use crate::test::{
+        cleanup, create_file_db_entry, create_folder_db_entry, create_tag_file, create_tag_files,
+        imply_tag_on_file, init_db_folder,
+    };
+
+    #[test]
+    fn moves_items_and_removes_duplicates() {
+        init_db_folder();
+        create_file_db_entry("only vacation", None); // 1
+        create_file_db_entry("both", None); // 2
+        create_file_db_entry("implied holiday", None); // 3
+        create_folder_db_entry("folder", None); // 1
+        create_tag_files("vacation", vec![1, 2, 3]); // 1
+        create_tag_file("holiday", 2); // 2
+        imply_tag_on_file(2, 3, 1);
+        let con = open_connection();
+        add_explicit_tag_to_folder(1, 1, &con).unwrap();
+        merge_tags(1, 2, &con).unwrap();
+        let tags = |id| -> Vec<(u32, Option<u32>)> {
+            get_all_tags_for_file(id, &con)
+                .unwrap()
+                .into_iter()
+                .map(|it| (it.tag_id, it.implicit_from_id))
+                .collect()
+        };
+        assert_eq!(vec![(2, None)], tags(1));
+        assert_eq!(vec![(2, None)], tags(2));
+        // the explicit vacation tag wins over the implied holiday tag
+        assert_eq!(vec![(2, None)], tags(3));
+        assert_eq!(
+            vec![2],
+            get_all_tags_for_folder(1, &con)
+                .unwrap()
+                .into_iter()
+                .map(|it| it.tag_id)
+                .collect::<Vec<u32>>()
+        );
+        assert_eq!(Err(rusqlite::Error::QueryReturnedNoRows), get_tag(1, &con));
+        con.close().unwrap();
+        cleanup();
+    }
+
+    #[test]
+    fn moves_children_to_target() {
+        init_db_folder();
+        let con = open_connection();
+        let vacation = create_tag("vacation", None, &con).unwrap();
+        let beach = create_tag("beach", Some(vacation.id), &con).unwrap();
+        let holiday = create_tag("holiday", None, &con).unwrap();
+        merge_tags(vacation.id, holiday.id, &con).unwrap();
+        let beach = get_tag(beach.id, &con).unwrap();
+        con.close().unwrap();
+        assert_eq!(Some(holiday.id), beach.parent_id);
+        cleanup();
+    }
+
+    #[test]
+    fn lifts_target_nested_under_source() {
+        init_db_folder();
+        let con = open_connection();
+        let root = create_tag("root", None, &con).unwrap();
+        let animal = create_tag("animal", Some(root.id), &con).unwrap();
+        let cat = create_tag("cat", Some(animal.id), &con).unwrap();
+        let kitten = create_tag("kitten", Some(cat.id), &con).unwrap();
+        merge_tags(animal.id, kitten.id, &con).unwrap();
+        let kitten = get_tag(kitten.id, &con).unwrap();
+        let cat = get_tag(cat.id, &con).unwrap();
+        con.close().unwrap();
+        assert_eq!(Some(root.id), kitten.parent_id);
+        assert_eq!(Some(kitten.id), cat.parent_id);
+        cleanup();
+    }
+
+    #[test]
+    fn moves_aliases_to_target() {
+        init_db_folder();
+        let con = open_connection();
+        let pic = create_tag("pic", None, &con).unwrap();
+        let picture = create_tag("picture", None, &con).unwrap();
+        set_tag_aliases(pic.id, &["snapshot".to_string()], &con).unwrap();
+        set_tag_aliases(picture.id, &["photo".to_string()], &con).unwrap();
+        merge_tags(pic.id, picture.id, &con).unwrap();
+        let aliases = get_tag_aliases(picture.id, &con).unwrap();
+        con.close().unwrap();
+        assert_eq!(vec!["photo", "pic", "snapshot"], aliases);
+        cleanup();
+    }
+}
+
+mod tag_aliases_tests {
+    use std::collections::HashMap;
+
+    use crate::repository::open_connection;
+    use crate::tags::repository::{
+        create_tag, delete_tag, get_all_tag_aliases, get_tag_aliases, get_tag_by_title,
+        set_tag_aliases,
+    };
+    use crate::test::{cleanup, init_db_folder};
+
+    #[test]
+    fn set_tag_aliases_replaces_aliases() {
+        init_db_folder();
+        let con = open_connection();
+        let picture = create_tag("picture", None, &con).unwrap();
+        let video = create_tag("video", None, &con).unwrap();
+        set_tag_aliases(picture.id, &["pic".to_string(), "Photo".to_string()], &con).unwrap();
+        set_tag_aliases(video.id, &["clip".to_string()], &con).unwrap();
+        assert_eq!(
+            vec!["Photo", "pic"],
+            get_tag_aliases(picture.id, &con).unwrap()
+        );
+        set_tag_aliases(picture.id, &["snapshot".to_string()], &con).unwrap();
+        let aliases = get_all_tag_aliases(&con).unwrap();
+        con.close().unwrap();
+        assert_eq!(
+            HashMap::from([
+                (picture.id, vec!["snapshot".to_string()]),
+                (video.id, vec!["clip".to_string()]),
+            ]),
+            aliases
+        );
+        cleanup();
+    }
+
+    #[test]
+    fn get_tag_by_title_matches_aliases() {
+        init_db_folder();
+        let con = open_connection();
+        let picture = create_tag("picture", None, &con).unwrap();
+        let pic = create_tag("pic", None, &con).unwrap();
+        set_tag_aliases(picture.id, &["PHOTO".to_string(), "pic".to_string()], &con).unwrap();
+        let photo = get_tag_by_title("photo", &con).unwrap();
+        // a tag's title wins over another tag's alias
+        let found_pic = get_tag_by_title("pic", &con).unwrap();
+        con.close().unwrap();
+        assert_eq!(Some(picture), photo);
+        assert_eq!(Some(pic), found_pic);
+        cleanup();
+    }
+
+    #[test]
+    fn delete_tag_removes_aliases() {
+        init_db_folder();
+        let con = open_connection();
+        let picture = create_tag("picture", None, &con).unwrap();
+        set_tag_aliases(picture.id, &["pic".to_string()], &con).unwrap();
+        delete_tag(picture.id, &con).unwrap();
+        let found = get_tag_by_title("pic", &con).unwrap();
+        let aliases = get_all_tag_aliases(&con).unwrap();
+        con.close().unwrap();
+        assert_eq!(None, found);
+        assert!(aliases.is_empty());
+        cleanup();
+    }
 }
 
 mod get_tag_on_file_tests {
@@ -141,8 +412,8 @@ mod get_tag_on_file_tests {
     fn get_tags_on_file_returns_tags() {
         init_db_folder();
         let con = open_connection();
-        create_tag("test", &con).unwrap();
-        create_tag("test2", &con).unwrap();
+        create_tag("test", None, &con).unwrap();
+        create_tag("test2", None, &con).unwrap();
         create_file(
             &FileRecord {
                 id: None,
@@ -151,6 +422,8 @@ mod get_tag_on_file_tests {
                 create_date: now(),
                 size: 0,
                 file_type: FileTypes::Unknown,
+                upload_date: None,
+                modified_date: None,
             },
             &con,
         )
@@ -194,6 +467,8 @@ mod get_tag_on_file_tests {
                 create_date: now(),
                 size: 0,
                 file_type: FileTypes::Application,
+                upload_date: None,
+                modified_date: None,
             },
             &con,
         )
@@ -218,7 +493,7 @@ mod remove_tag_from_file_tests {
     fn remove_tag_from_file_works() {
         init_db_folder();
         let con = open_connection();
-        create_tag("test", &con).unwrap();
+        create_tag("test", None, &con).unwrap();
         create_file(
             &FileRecord {
                 id: None,
@@ -227,6 +502,8 @@ mod remove_tag_from_file_tests {
                 create_date: now(),
                 size: 0,
                 file_type: FileTypes::Unknown,
+                upload_date: None,
+                modified_date: None,
             },
             &con,
         )
@@ -253,8 +530,8 @@ mod get_tag_on_folder_tests {
     fn get_tags_on_folder_returns_tags() {
         init_db_folder();
         let con = open_connection();
-        create_tag("test", &con).unwrap();
-        create_tag("test2", &con).unwrap();
+        create_tag("test", None, &con).unwrap();
+        create_tag("test2", None, &con).unwrap();
         create_folder(
             &Folder {
                 parent_id: None,
@@ -325,7 +602,7 @@ mod remove_tag_from_folder_tests {
     fn remove_tag_from_folder_works() {
         init_db_folder();
         let con = open_connection();
-        create_tag("test", &con).unwrap();
+        create_tag("test", None, &con).unwrap();
         create_folder(
             &Folder {
                 parent_id: None,
@@ -733,3 +1010,34 @@ mod batch_remove_implicit_tags_tests {
         cleanup();
     }
 }
+
+mod remove_blocked_implicit_tags_tests {
+    use crate::repository::open_connection;
+    use crate::tags::repository::{
+        get_all_tags_for_file, remove_blocked_implicit_tags, set_inheritance_blocks_for_file,
+    };
+    use crate::test::{
+        cleanup, create_file_db_entry, create_folder_db_entry, create_tag_folder,
+        imply_tag_on_file, init_db_folder,
+    };
+
+    #[test]
+    fn only_removes_tags_from_passed_items() {
+        init_db_folder();
+        create_folder_db_entry("folder", None); // 1
+        create_file_db_entry("first", Some(1)); // 1
+        create_file_db_entry("second", Some(1)); // 2
+        create_tag_folder("private", 1); // 1
+        imply_tag_on_file(1, 1, 1);
+        imply_tag_on_file(1, 2, 1);
+        let con = open_connection();
+        set_inheritance_blocks_for_file(1, true, &[], &con).unwrap();
+        set_inheritance_blocks_for_file(2, true, &[], &con).unwrap();
+        remove_blocked_implicit_tags(&[1], &[], &con).unwrap();
+        assert!(get_all_tags_for_file(1, &con).unwrap().is_empty());
+        // the second file wasn't passed, so it's left alone
+        assert_eq!(1, get_all_tags_for_file(2, &con).unwrap().len());
+        con.close().unwrap();
+        cleanup();
+    }
+}
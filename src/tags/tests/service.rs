@@ -23,7 +23,7 @@ mod get_tag_tests {
 
 mod update_tag_tests {
     use crate::model::error::tag_errors::UpdateTagError;
-    use crate::model::response::TagApi;
+    use crate::model::request::tag_requests::UpdateTagRequest;
     use crate::tags::service::{create_tag, get_tag, update_tag};
     use crate::test::{cleanup, init_db_folder};
 
@@ -31,9 +31,10 @@ mod update_tag_tests {
     fn update_tag_works() {
         init_db_folder();
         let tag = create_tag("test_tag".to_string()).unwrap();
-        let updated_tag = update_tag(TagApi {
-            id: tag.id,
+        let updated_tag = update_tag(UpdateTagRequest {
+            id: tag.id.unwrap(),
             title: "new_name".to_string(),
+            ..Default::default()
         })
         .unwrap();
         assert_eq!(String::from("new_name"), updated_tag.title);
@@ -47,9 +48,10 @@ mod update_tag_tests {
     #[test]
     fn update_tag_not_found() {
         init_db_folder();
-        let res = update_tag(TagApi {
-            id: Some(1),
+        let res = update_tag(UpdateTagRequest {
+            id: 1,
             title: "what".to_string(),
+            ..Default::default()
         });
         assert_eq!(UpdateTagError::TagNotFound, res.unwrap_err());
         cleanup();
@@ -60,15 +62,528 @@ mod update_tag_tests {
         init_db_folder();
         create_tag("first".to_string()).unwrap();
         create_tag("second".to_string()).unwrap();
-        let res = update_tag(TagApi {
-            id: Some(2),
+        let res = update_tag(UpdateTagRequest {
+            id: 2,
             title: "FiRsT".to_string(),
+            ..Default::default()
         });
         assert_eq!(UpdateTagError::NewNameAlreadyExists, res.unwrap_err());
         cleanup();
     }
 }
 
+mod tag_hierarchy_tests {
+    use crate::model::error::tag_errors::{CreateTagError, UpdateTagError};
+    use crate::model::request::tag_requests::UpdateTagRequest;
+    use crate::model::response::{TagApi, TagTreeApi};
+    use crate::tags::TagSort;
+    use crate::tags::service::{create_tag, create_tag_with_details, get_all_tags, update_tag};
+    use crate::test::{
+        cleanup, create_file_db_entry, create_tag_file, create_tag_files, init_db_folder,
+    };
+
+    fn nest(id: u32, title: &str, parent_id: Option<u32>) -> Result<TagApi, UpdateTagError> {
+        update_tag(UpdateTagRequest {
+            id,
+            title: title.to_string(),
+            parent_id: Some(parent_id),
+            ..Default::default()
+        })
+    }
+
+    fn create_child_tag(name: &str, parent_id: Option<u32>) -> Result<TagApi, CreateTagError> {
+        create_tag_with_details(TagApi {
+            title: name.to_string(),
+            parent_id,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn create_child_tag_requires_existing_parent() {
+        init_db_folder();
+        let res = create_child_tag("cat", Some(1));
+        assert_eq!(CreateTagError::ParentNotFound, res.unwrap_err());
+        let animal = create_tag("animal".to_string()).unwrap();
+        let cat = create_child_tag("cat", animal.id).unwrap();
+        assert_eq!(animal.id, cat.parent_id);
+        cleanup();
+    }
+
+    #[test]
+    fn update_tag_can_change_only_parent() {
+        init_db_folder();
+        create_tag("animal".to_string()).unwrap();
+        create_tag("cat".to_string()).unwrap();
+        let cat = nest(2, "cat", Some(1)).unwrap();
+        assert_eq!(Some(1), cat.parent_id);
+        cleanup();
+    }
+
+    #[test]
+    fn update_tag_prevents_cycles() {
+        init_db_folder();
+        create_tag("animal".to_string()).unwrap();
+        create_child_tag("cat", Some(1)).unwrap();
+        create_child_tag("kitten", Some(2)).unwrap();
+        assert_eq!(
+            UpdateTagError::CircularParent,
+            nest(1, "animal", Some(1)).unwrap_err()
+        );
+        assert_eq!(
+            UpdateTagError::CircularParent,
+            nest(1, "animal", Some(3)).unwrap_err()
+        );
+        assert_eq!(
+            UpdateTagError::ParentNotFound,
+            nest(1, "animal", Some(99)).unwrap_err()
+        );
+        cleanup();
+    }
+
+    #[test]
+    fn get_all_tags_builds_tree() {
+        init_db_folder();
+        create_tag("animal".to_string()).unwrap();
+        create_child_tag("dog", Some(1)).unwrap();
+        create_child_tag("cat", Some(1)).unwrap();
+        create_tag("receipt".to_string()).unwrap();
+        let tree = get_all_tags(None, None, TagSort::Name).unwrap();
+        let tag = |id, title: &str, parent_id, children| TagTreeApi {
+            id,
+            title: title.to_string(),
+            parent_id,
+            color: None,
+            description: None,
+            namespace: None,
+            aliases: vec![],
+            file_count: 0,
+            folder_count: 0,
+            implicit_count: 0,
+            children,
+        };
+        assert_eq!(
+            vec![
+                tag(
+                    1,
+                    "animal",
+                    None,
+                    vec![
+                        tag(3, "cat", Some(1), vec![]),
+                        tag(2, "dog", Some(1), vec![])
+                    ]
+                ),
+                tag(4, "receipt", None, vec![]),
+            ],
+            tree
+        );
+        cleanup();
+    }
+
+    #[test]
+    fn get_all_tags_sorts_siblings_by_usage() {
+        init_db_folder();
+        create_file_db_entry("a.txt", None);
+        create_file_db_entry("b.txt", None);
+        create_tag_file("animal", 1);
+        create_tag_file("dog", 1);
+        create_tag_files("cat", vec![1, 2]);
+        create_tag("unused".to_string()).unwrap();
+        nest(2, "dog", Some(1)).unwrap();
+        nest(3, "cat", Some(1)).unwrap();
+        let tree = get_all_tags(None, None, TagSort::Usage).unwrap();
+        let titles: Vec<&str> = tree.iter().map(|it| it.title.as_str()).collect();
+        let children: Vec<&str> = tree[0]
+            .children
+            .iter()
+            .map(|it| it.title.as_str())
+            .collect();
+        assert_eq!(vec!["animal", "unused"], titles);
+        assert_eq!(vec!["cat", "dog"], children);
+        assert_eq!(2, tree[0].children[0].file_count);
+        cleanup();
+    }
+
+    #[test]
+    fn get_all_tags_filters_by_prefix_and_keeps_parents() {
+        init_db_folder();
+        create_tag("animal".to_string()).unwrap();
+        create_child_tag("Cat", Some(1)).unwrap();
+        create_child_tag("dog", Some(1)).unwrap();
+        create_tag("car".to_string()).unwrap();
+        create_tag("receipt".to_string()).unwrap();
+        let tree = get_all_tags(Some("CA"), None, TagSort::Name).unwrap();
+        let titles: Vec<&str> = tree.iter().map(|it| it.title.as_str()).collect();
+        assert_eq!(vec!["animal", "car"], titles);
+        assert_eq!(1, tree[0].children.len());
+        assert_eq!("Cat", tree[0].children[0].title);
+        cleanup();
+    }
+}
+
+mod tag_details_tests {
+    use crate::model::error::tag_errors::{CreateTagError, UpdateTagError};
+    use crate::model::request::tag_requests::UpdateTagRequest;
+    use crate::model::response::{TagApi, TagNamespaceApi};
+    use crate::tags::TagSort;
+    use crate::tags::namespace_of;
+    use crate::tags::service::{
+        create_tag, create_tag_with_details, get_all_tags, get_tag, get_tag_namespaces, update_tag,
+    };
+    use crate::test::{cleanup, init_db_folder};
+
+    #[test]
+    fn namespace_of_works() {
+        assert_eq!(Some("person".to_string()), namespace_of("Person:alice"));
+        assert_eq!(
+            Some("project".to_string()),
+            namespace_of(" project : garage")
+        );
+        assert_eq!(Some("a".to_string()), namespace_of("a:b:c"));
+        assert_eq!(None, namespace_of("receipt"));
+        assert_eq!(None, namespace_of(":alice"));
+        assert_eq!(None, namespace_of("person:"));
+    }
+
+    #[test]
+    fn create_tag_with_details_normalizes_color_and_description() {
+        init_db_folder();
+        let tag = create_tag_with_details(TagApi {
+            title: "person:alice".to_string(),
+            color: Some(" #F80 ".to_string()),
+            description: Some("  my sister ".to_string()),
+            ..Default::default()
+        })
+        .unwrap();
+        let expected = TagApi {
+            id: Some(1),
+            title: "person:alice".to_string(),
+            parent_id: None,
+            color: Some("#f80".to_string()),
+            description: Some("my sister".to_string()),
+            namespace: Some("person".to_string()),
+            aliases: vec![],
+        };
+        assert_eq!(expected, tag);
+        assert_eq!(expected, get_tag(1).unwrap());
+        cleanup();
+    }
+
+    #[test]
+    fn create_tag_with_details_rejects_bad_colors() {
+        init_db_folder();
+        for color in ["orange", "#ff88", "ff8800", "#gggggg"] {
+            let res = create_tag_with_details(TagApi {
+                title: "tag".to_string(),
+                color: Some(color.to_string()),
+                ..Default::default()
+            });
+            assert_eq!(CreateTagError::InvalidColor, res.unwrap_err(), "{color}");
+        }
+        cleanup();
+    }
+
+    #[test]
+    fn update_tag_keeps_details_when_missing() {
+        init_db_folder();
+        create_tag("parent".to_string()).unwrap();
+        let created = create_tag_with_details(TagApi {
+            title: "child".to_string(),
+            parent_id: Some(1),
+            color: Some("#123456".to_string()),
+            description: Some("details".to_string()),
+            aliases: vec!["kid".to_string()],
+            ..Default::default()
+        })
+        .unwrap();
+        let updated = update_tag(UpdateTagRequest {
+            id: 2,
+            title: "renamed".to_string(),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(
+            TagApi {
+                title: "renamed".to_string(),
+                ..created
+            },
+            updated
+        );
+        assert_eq!(updated, get_tag(2).unwrap());
+        // null un-nests the tag
+        let updated = update_tag(UpdateTagRequest {
+            id: 2,
+            title: "renamed".to_string(),
+            parent_id: Some(None),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(None, updated.parent_id);
+        assert_eq!(vec!["kid"], updated.aliases);
+        cleanup();
+    }
+
+    #[test]
+    fn update_tag_replaces_color_and_description() {
+        init_db_folder();
+        create_tag_with_details(TagApi {
+            title: "tag".to_string(),
+            color: Some("#123456".to_string()),
+            description: Some("old".to_string()),
+            ..Default::default()
+        })
+        .unwrap();
+        let updated = update_tag(UpdateTagRequest {
+            id: 1,
+            title: "tag".to_string(),
+            color: Some("#ABCDEF".to_string()),
+            description: Some(" ".to_string()),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(Some("#abcdef".to_string()), updated.color);
+        assert_eq!(None, updated.description);
+        assert_eq!(updated, get_tag(1).unwrap());
+        let res = update_tag(UpdateTagRequest {
+            id: 1,
+            title: "tag".to_string(),
+            color: Some("blue".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(UpdateTagError::InvalidColor, res.unwrap_err());
+        cleanup();
+    }
+
+    #[test]
+    fn get_tag_namespaces_counts_tags() {
+        init_db_folder();
+        create_tag("person:alice".to_string()).unwrap();
+        create_tag("PERSON:bob".to_string()).unwrap();
+        create_tag("project:garage".to_string()).unwrap();
+        create_tag("receipt".to_string()).unwrap();
+        let namespaces = get_tag_namespaces().unwrap();
+        assert_eq!(
+            vec![
+                TagNamespaceApi {
+                    name: "person".to_string(),
+                    tag_count: 2
+                },
+                TagNamespaceApi {
+                    name: "project".to_string(),
+                    tag_count: 1
+                },
+            ],
+            namespaces
+        );
+        cleanup();
+    }
+
+    #[test]
+    fn get_all_tags_filters_by_namespace_and_keeps_parents() {
+        init_db_folder();
+        create_tag("people".to_string()).unwrap();
+        create_tag_with_details(TagApi {
+            title: "person:alice".to_string(),
+            parent_id: Some(1),
+            ..Default::default()
+        })
+        .unwrap();
+        create_tag("project:garage".to_string()).unwrap();
+        create_tag("person".to_string()).unwrap();
+        let tree = get_all_tags(None, Some("Person"), TagSort::Name).unwrap();
+        assert_eq!(1, tree.len());
+        assert_eq!("people", tree[0].title);
+        assert_eq!(None, tree[0].namespace);
+        assert_eq!("person:alice", tree[0].children[0].title);
+        assert_eq!(Some("person".to_string()), tree[0].children[0].namespace);
+        cleanup();
+    }
+}
+
+mod tag_aliases_tests {
+    use crate::model::error::tag_errors::{CreateTagError, UpdateTagError};
+    use crate::model::request::tag_requests::UpdateTagRequest;
+    use crate::model::response::{TagApi, TaggedItemApi};
+    use crate::tags::TagSort;
+    use crate::tags::service::{
+        create_tag, create_tag_with_details, get_all_tags, get_tag, get_tags_on_file,
+        get_tags_on_folder, merge_tags, update_file_tags, update_folder_tags, update_tag,
+    };
+    use crate::test::{cleanup, create_file_db_entry, create_folder_db_entry, init_db_folder};
+
+    fn create_picture() -> TagApi {
+        create_tag_with_details(TagApi {
+            title: "picture".to_string(),
+            aliases: vec!["pic".to_string(), "photo".to_string()],
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    fn new_tag(title: &str) -> TaggedItemApi {
+        TaggedItemApi {
+            tag_id: None,
+            title: title.to_string(),
+            implicit_from: None,
+        }
+    }
+
+    #[test]
+    fn create_tag_with_details_cleans_up_aliases() {
+        init_db_folder();
+        let tag = create_tag_with_details(TagApi {
+            title: "picture".to_string(),
+            aliases: vec![
+                " pic ".to_string(),
+                "PIC".to_string(),
+                "".to_string(),
+                "Picture".to_string(),
+                "image".to_string(),
+            ],
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(vec!["image", "pic"], tag.aliases);
+        assert_eq!(tag, get_tag(1).unwrap());
+        cleanup();
+    }
+
+    #[test]
+    fn create_tag_resolves_aliases() {
+        init_db_folder();
+        let picture = create_picture();
+        let tag = create_tag("PHOTO".to_string()).unwrap();
+        assert_eq!(picture, tag);
+        cleanup();
+    }
+
+    #[test]
+    fn aliases_must_be_unused() {
+        init_db_folder();
+        create_picture();
+        create_tag("video".to_string()).unwrap();
+        let res = create_tag_with_details(TagApi {
+            title: "image".to_string(),
+            aliases: vec!["Photo".to_string()],
+            ..Default::default()
+        });
+        assert_eq!(CreateTagError::AliasAlreadyExists, res.unwrap_err());
+        let res = update_tag(UpdateTagRequest {
+            id: 2,
+            title: "video".to_string(),
+            aliases: Some(vec!["picture".to_string()]),
+            ..Default::default()
+        });
+        assert_eq!(UpdateTagError::AliasAlreadyExists, res.unwrap_err());
+        let res = update_tag(UpdateTagRequest {
+            id: 2,
+            title: "pic".to_string(),
+            ..Default::default()
+        });
+        assert_eq!(UpdateTagError::NewNameAlreadyExists, res.unwrap_err());
+        cleanup();
+    }
+
+    #[test]
+    fn update_tag_replaces_aliases() {
+        init_db_folder();
+        create_picture();
+        let updated = update_tag(UpdateTagRequest {
+            id: 1,
+            title: "photo".to_string(),
+            aliases: Some(vec!["photo".to_string(), "picture".to_string()]),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(vec!["picture"], updated.aliases);
+        assert_eq!(updated, get_tag(1).unwrap());
+        cleanup();
+    }
+
+    #[test]
+    fn update_file_tags_resolves_aliases() {
+        init_db_folder();
+        create_picture();
+        create_file_db_entry("beach.png", None);
+        update_file_tags(1, vec![new_tag("pic"), new_tag("Photo")]).unwrap();
+        let tags = get_tags_on_file(1).unwrap();
+        assert_eq!(1, tags.len());
+        assert_eq!(Some(1), tags[0].tag_id);
+        assert_eq!("picture", tags[0].title);
+        cleanup();
+    }
+
+    #[test]
+    fn update_folder_tags_resolves_aliases() {
+        init_db_folder();
+        create_picture();
+        create_folder_db_entry("beach", None);
+        update_folder_tags(1, vec![new_tag("pic"), new_tag("Photo")]).unwrap();
+        let tags = get_tags_on_folder(1).unwrap();
+        assert_eq!(1, tags.len());
+        assert_eq!(Some(1), tags[0].tag_id);
+        assert_eq!("picture", tags[0].title);
+        cleanup();
+    }
+
+    #[test]
+    fn get_all_tags_matches_prefix_on_aliases() {
+        init_db_folder();
+        create_picture();
+        create_tag("video".to_string()).unwrap();
+        let tree = get_all_tags(Some("pho"), None, TagSort::Name).unwrap();
+        assert_eq!(1, tree.len());
+        assert_eq!("picture", tree[0].title);
+        assert_eq!(vec!["photo", "pic"], tree[0].aliases);
+        cleanup();
+    }
+
+    #[test]
+    fn merge_tags_keeps_source_as_alias() {
+        init_db_folder();
+        create_picture();
+        create_tag("snapshot".to_string()).unwrap();
+        let tag = merge_tags(2, 1).unwrap();
+        assert_eq!(vec!["photo", "pic", "snapshot"], tag.aliases);
+        assert_eq!(tag, create_tag("snapshot".to_string()).unwrap());
+        cleanup();
+    }
+}
+
+mod merge_tags_tests {
+    use crate::model::error::tag_errors::MergeTagError;
+    use crate::tags::service::{create_tag, get_tags_on_file, merge_tags};
+    use crate::test::{cleanup, create_file_db_entry, create_tag_file, init_db_folder};
+
+    #[test]
+    fn merge_tags_returns_target() {
+        init_db_folder();
+        create_file_db_entry("a.txt", None);
+        create_tag_file("vacation", 1);
+        create_tag("holiday".to_string()).unwrap();
+        let tag = merge_tags(1, 2).unwrap();
+        assert_eq!(Some(2), tag.id);
+        assert_eq!("holiday", tag.title);
+        let titles: Vec<String> = get_tags_on_file(1)
+            .unwrap()
+            .into_iter()
+            .map(|it| it.title)
+            .collect();
+        assert_eq!(vec!["holiday"], titles);
+        cleanup();
+    }
+
+    #[test]
+    fn merge_tags_checks_tags() {
+        init_db_folder();
+        create_tag("vacation".to_string()).unwrap();
+        assert_eq!(MergeTagError::SameTag, merge_tags(1, 1).unwrap_err());
+        assert_eq!(MergeTagError::TagNotFound, merge_tags(2, 1).unwrap_err());
+        assert_eq!(MergeTagError::TargetNotFound, merge_tags(1, 2).unwrap_err());
+        cleanup();
+    }
+}
+
 mod delete_tag_tests {
     use crate::model::error::tag_errors::GetTagError;
     use crate::tags::service::{create_tag, delete_tag, get_tag};
@@ -105,6 +620,8 @@ mod update_file_tag_test {
             size: 0,
             create_date: now(),
             file_type: FileTypes::Unknown,
+            upload_date: None,
+            modified_date: None,
         }
         .save_to_db();
         update_file_tags(
@@ -150,6 +667,8 @@ mod update_file_tag_test {
             size: 0,
             create_date: now(),
             file_type: FileTypes::Unknown,
+            upload_date: None,
+            modified_date: None,
         }
         .save_to_db();
         update_file_tags(
@@ -185,6 +704,8 @@ mod update_file_tag_test {
             size: 0,
             create_date: now(),
             file_type: FileTypes::Unknown,
+            upload_date: None,
+            modified_date: None,
         }
         .save_to_db();
 
@@ -223,6 +744,8 @@ mod update_file_tag_test {
             size: 0,
             create_date: now(),
             file_type: FileTypes::Unknown,
+            upload_date: None,
+            modified_date: None,
         }
         .save_to_db();
 
@@ -261,6 +784,8 @@ mod update_file_tag_test {
             size: 0,
             create_date: now(),
             file_type: FileTypes::Unknown,
+            upload_date: None,
+            modified_date: None,
         }
         .save_to_db();
 
@@ -301,6 +826,61 @@ mod update_file_tag_test {
     }
 }
 
+mod add_tags_to_file_tests {
+    use crate::model::response::{TagApi, TaggedItemApi};
+    use crate::tags::service::{add_tags_to_file, create_tag_with_details, get_tags_on_file};
+    use crate::test::{
+        cleanup, create_file_db_entry, create_folder_db_entry, create_tag_file, create_tag_folder,
+        imply_tag_on_file, init_db_folder,
+    };
+
+    #[test]
+    fn add_tags_to_file_keeps_existing_tags_and_resolves_aliases() {
+        init_db_folder();
+        create_folder_db_entry("family", None);
+        create_file_db_entry("test.png", Some(1));
+        create_tag_folder("family", 1); // id 1
+        imply_tag_on_file(1, 1, 1);
+        create_tag_file("keep", 1); // id 2
+        create_tag_with_details(TagApi {
+            title: "picture".to_string(),
+            aliases: vec!["pic".to_string()],
+            ..Default::default()
+        })
+        .unwrap(); // id 3
+        let titles = ["family", "keep", "new", "pic", "NEW"].map(String::from);
+        add_tags_to_file(1, &titles).unwrap();
+        let mut tags = get_tags_on_file(1).unwrap();
+        tags.sort_by_key(|it| it.tag_id);
+        assert_eq!(
+            vec![
+                TaggedItemApi {
+                    tag_id: Some(1),
+                    title: "family".to_string(),
+                    implicit_from: Some(1),
+                },
+                TaggedItemApi {
+                    tag_id: Some(2),
+                    title: "keep".to_string(),
+                    implicit_from: None,
+                },
+                TaggedItemApi {
+                    tag_id: Some(3),
+                    title: "picture".to_string(),
+                    implicit_from: None,
+                },
+                TaggedItemApi {
+                    tag_id: Some(4),
+                    title: "new".to_string(),
+                    implicit_from: None,
+                },
+            ],
+            tags
+        );
+        cleanup();
+    }
+}
+
 mod update_folder_tag_test {
     use crate::model::error::tag_errors::TagRelationError;
     use crate::model::repository::Folder;
@@ -986,3 +1566,219 @@ mod imply_all_ancestor_tags_tests {
         cleanup();
     }
 }
+
+mod tag_inheritance_tests {
+    use std::collections::BTreeMap;
+
+    use crate::model::error::tag_errors::TagRelationError;
+    use crate::model::request::folder_requests::UpdateFolderRequest;
+    use crate::model::response::TagInheritanceApi;
+    use crate::repository::open_connection;
+    use crate::service::folder_service;
+    use crate::tags::repository as tag_repository;
+    use crate::tags::service::{
+        create_tag, get_file_tag_inheritance, get_folder_tag_inheritance, get_tags_on_file,
+        get_tags_on_folder, imply_all_ancestor_tags, merge_tags, pass_tags_to_descendants,
+        set_file_tag_inheritance, set_folder_tag_inheritance,
+    };
+    use crate::test::*;
+
+    fn block(tags: Vec<&str>) -> TagInheritanceApi {
+        TagInheritanceApi {
+            block_all: false,
+            blocked_tags: tags.into_iter().map(String::from).collect(),
+        }
+    }
+
+    fn file_tags(file_id: u32) -> Vec<String> {
+        let mut titles: Vec<String> = get_tags_on_file(file_id)
+            .unwrap()
+            .into_iter()
+            .map(|it| it.title)
+            .collect();
+        titles.sort();
+        titles
+    }
+
+    fn folder_tags(folder_id: u32) -> Vec<String> {
+        let mut titles: Vec<String> = get_tags_on_folder(folder_id)
+            .unwrap()
+            .into_iter()
+            .map(|it| it.title)
+            .collect();
+        titles.sort();
+        titles
+    }
+
+    #[test]
+    fn folder_block_stops_inheritance_for_everything_under_it() {
+        init_db_folder();
+        create_folder_db_entry("Family", None); // id 1
+        create_folder_db_entry("Shared", Some(1)); // id 2
+        create_folder_db_entry("Trip", Some(2)); // id 3
+        create_tag_folder("private", 1);
+        create_tag_folder("family", 1);
+        create_tag_folder("shared", 2);
+        create_file_db_entry("a.txt", Some(2)); // id 1
+        create_file_db_entry("b.txt", Some(3)); // id 2
+        pass_tags_to_descendants(1).unwrap();
+        pass_tags_to_descendants(2).unwrap();
+        set_folder_tag_inheritance(2, block(vec!["private"])).unwrap();
+        assert_eq!(vec!["family", "shared"], folder_tags(2));
+        assert_eq!(vec!["family", "shared"], folder_tags(3));
+        assert_eq!(vec!["family", "shared"], file_tags(1));
+        assert_eq!(vec!["family", "shared"], file_tags(2));
+        // newly added tags on the ancestor still respect the block
+        pass_tags_to_descendants(1).unwrap();
+        assert_eq!(vec!["family", "shared"], file_tags(2));
+        assert_eq!(Ok(block(vec!["private"])), get_folder_tag_inheritance(2));
+        cleanup();
+    }
+
+    #[test]
+    fn folder_block_does_not_stop_tags_from_under_it() {
+        init_db_folder();
+        create_folder_db_entry("Family", None); // id 1
+        create_folder_db_entry("Shared", Some(1)); // id 2
+        create_folder_db_entry("Trip", Some(2)); // id 3
+        create_tag_folder("private", 1);
+        create_file_db_entry("a.txt", Some(3));
+        set_folder_tag_inheritance(
+            2,
+            TagInheritanceApi {
+                block_all: true,
+                blocked_tags: vec![],
+            },
+        )
+        .unwrap();
+        // a folder under the blocking folder can still pass the same tag down
+        let con = open_connection();
+        tag_repository::add_explicit_tag_to_folder(3, 1, &con).unwrap();
+        con.close().unwrap();
+        pass_tags_to_descendants(1).unwrap();
+        pass_tags_to_descendants(3).unwrap();
+        assert!(folder_tags(2).is_empty());
+        assert_eq!(vec!["private"], file_tags(1));
+        assert_eq!(Some(3), get_tags_on_file(1).unwrap()[0].implicit_from);
+        cleanup();
+    }
+
+    #[test]
+    fn removing_folder_block_inherits_tags_again() {
+        init_db_folder();
+        create_folder_db_entry("Family", None); // id 1
+        create_folder_db_entry("Shared", Some(1)); // id 2
+        create_tag_folder("private", 1);
+        create_file_db_entry("a.txt", Some(2));
+        set_folder_tag_inheritance(2, block(vec!["private"])).unwrap();
+        pass_tags_to_descendants(1).unwrap();
+        assert!(file_tags(1).is_empty());
+        set_folder_tag_inheritance(2, block(vec![])).unwrap();
+        assert_eq!(vec!["private"], folder_tags(2));
+        assert_eq!(vec!["private"], file_tags(1));
+        cleanup();
+    }
+
+    #[test]
+    fn file_block_stops_inheritance() {
+        init_db_folder();
+        create_folder_db_entry("Family", None); // id 1
+        create_tag_folder("private", 1);
+        create_tag_folder("family", 1);
+        create_file_db_entry("a.txt", Some(1));
+        imply_all_ancestor_tags(1).unwrap();
+        set_file_tag_inheritance(1, block(vec!["private", "PRIVATE"])).unwrap();
+        assert_eq!(vec!["family"], file_tags(1));
+        imply_all_ancestor_tags(1).unwrap();
+        assert_eq!(vec!["family"], file_tags(1));
+        assert_eq!(Ok(block(vec!["private"])), get_file_tag_inheritance(1));
+        set_file_tag_inheritance(
+            1,
+            TagInheritanceApi {
+                block_all: true,
+                blocked_tags: vec!["family".to_string()],
+            },
+        )
+        .unwrap();
+        assert!(file_tags(1).is_empty());
+        set_file_tag_inheritance(1, Default::default()).unwrap();
+        assert_eq!(vec!["family", "private"], file_tags(1));
+        cleanup();
+    }
+
+    #[test]
+    fn explicit_tags_are_not_blocked() {
+        init_db_folder();
+        create_folder_db_entry("Family", None);
+        create_tag_folder("private", 1);
+        create_file_db_entry("a.txt", Some(1));
+        let con = open_connection();
+        tag_repository::add_explicit_tag_to_file(1, 1, &con).unwrap();
+        con.close().unwrap();
+        set_file_tag_inheritance(1, block(vec!["private"])).unwrap();
+        assert_eq!(vec!["private"], file_tags(1));
+        cleanup();
+    }
+
+    #[test]
+    fn moving_folder_honors_blocks() {
+        init_db_folder();
+        create_folder_db_entry("Family", None); // id 1
+        create_folder_db_entry("Shared", None); // id 2
+        create_folder_db_entry("Trip", Some(2)); // id 3
+        create_folder_disk("Family");
+        create_folder_disk("Shared");
+        create_folder_disk("Shared/Trip");
+        create_tag_folder("private", 1);
+        create_file_db_entry("a.txt", Some(3));
+        set_folder_tag_inheritance(2, block(vec!["private"])).unwrap();
+        folder_service::update_folder(&UpdateFolderRequest {
+            id: 2,
+            name: "Shared".to_string(),
+            parent_id: Some(1),
+            tags: vec![],
+            description: None,
+            properties: Some(BTreeMap::new()),
+        })
+        .unwrap();
+        assert!(folder_tags(2).is_empty());
+        assert!(folder_tags(3).is_empty());
+        assert!(file_tags(1).is_empty());
+        cleanup();
+    }
+
+    #[test]
+    fn merging_tags_moves_blocks() {
+        init_db_folder();
+        create_folder_db_entry("Family", None); // id 1
+        create_folder_db_entry("Shared", Some(1)); // id 2
+        create_tag("secret".to_string()).unwrap(); // id 1
+        create_tag_folder("private", 1); // id 2
+        create_file_db_entry("a.txt", Some(2));
+        pass_tags_to_descendants(1).unwrap();
+        set_folder_tag_inheritance(2, block(vec!["secret"])).unwrap();
+        assert_eq!(vec!["private"], file_tags(1));
+        merge_tags(1, 2).unwrap();
+        assert_eq!(Ok(block(vec!["private"])), get_folder_tag_inheritance(2));
+        assert!(file_tags(1).is_empty());
+        cleanup();
+    }
+
+    #[test]
+    fn missing_items_are_not_found() {
+        init_db_folder();
+        assert_eq!(
+            Err(TagRelationError::FileNotFound),
+            get_file_tag_inheritance(1)
+        );
+        assert_eq!(
+            Err(TagRelationError::FolderNotFound),
+            set_folder_tag_inheritance(1, Default::default())
+        );
+        assert_eq!(
+            Err(TagRelationError::FolderNotFound),
+            set_folder_tag_inheritance(0, Default::default())
+        );
+        cleanup();
+    }
+}
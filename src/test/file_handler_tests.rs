@@ -1,10 +1,12 @@
+use std::collections::BTreeMap;
 use std::{fs, vec};
 
 use rocket::http::{Header, Status};
 use rocket::local::blocking::Client;
 use rocket::serde::json::serde_json as serde;
 
-use crate::model::api::FileApi;
+use crate::encryption;
+use crate::model::api::{FileApi, SearchFacets};
 use crate::model::file_types::FileTypes;
 use crate::model::response::BasicMessage;
 use crate::repository::initialize_db;
@@ -326,6 +328,81 @@ Content-Disposition: form-data; name=\"folderId\"\r\n\
     cleanup();
 }
 
+#[test]
+fn upload_file_with_date_modified() {
+    set_password();
+    remove_files();
+    let client = client();
+    let body = "--BOUNDARY\r\n\
+Content-Disposition: form-data; name=\"file\"; filename=\"test.txt\"\r\n\
+Content-Type: text/plain\r\n\
+\r\n\
+aGk=\r\n\
+\r\n\
+--BOUNDARY\r\n\
+Content-Disposition: form-data; name=\"extension\"\r\n\
+\r\n\
+txt\r\n\
+--BOUNDARY\r\n\
+Content-Disposition: form-data; name=\"dateModified\"\r\n\
+\r\n\
+2020-01-15T12:00:00\r\n\
+--BOUNDARY--";
+    let res = client
+        .post(uri!("/files"))
+        .header(Header::new("Authorization", AUTH))
+        .header(Header::new(
+            "Content-Type",
+            "multipart/form-data; boundary=BOUNDARY",
+        ))
+        .body(body)
+        .dispatch();
+    assert_eq!(res.status(), Status::Created);
+    let res_body: FileApi = res.into_json().unwrap();
+    let expected =
+        chrono::NaiveDateTime::parse_from_str("2020-01-15T12:00:00", "%Y-%m-%dT%H:%M:%S").unwrap();
+    assert_eq!(res_body.date_modified, Some(expected));
+    assert!(res_body.date_uploaded.unwrap() > expected);
+    // make sure it was persisted
+    let res: FileApi = client
+        .get(uri!("/files/metadata/1"))
+        .header(Header::new("Authorization", AUTH))
+        .dispatch()
+        .into_json()
+        .unwrap();
+    assert_eq!(res.date_modified, Some(expected));
+    cleanup();
+}
+
+#[test]
+fn upload_file_with_bad_date_modified() {
+    set_password();
+    remove_files();
+    let client = client();
+    let body = "--BOUNDARY\r\n\
+Content-Disposition: form-data; name=\"file\"; filename=\"test.txt\"\r\n\
+Content-Type: text/plain\r\n\
+\r\n\
+aGk=\r\n\
+\r\n\
+--BOUNDARY\r\n\
+Content-Disposition: form-data; name=\"dateModified\"\r\n\
+\r\n\
+yesterday\r\n\
+--BOUNDARY--";
+    let res = client
+        .post(uri!("/files"))
+        .header(Header::new("Authorization", AUTH))
+        .header(Header::new(
+            "Content-Type",
+            "multipart/form-data; boundary=BOUNDARY",
+        ))
+        .body(body)
+        .dispatch();
+    assert_eq!(res.status(), Status::BadRequest);
+    cleanup();
+}
+
 #[test]
 fn get_file_without_creds() {
     initialize_db().unwrap();
@@ -400,7 +477,7 @@ fn search_files_bad_search_query() {
     let body: BasicMessage = res.into_json().unwrap();
     assert_eq!(
         body.message,
-        String::from("Search string, attributes, or tags are required.")
+        String::from("Search string, query, content, attributes, or tags are required.")
     );
     cleanup();
 }
@@ -426,6 +503,197 @@ fn search_files() {
     cleanup();
 }
 
+#[test]
+fn search_files_with_query() {
+    set_password();
+    remove_files();
+    test::create_file_db_entry("cat.png", None);
+    test::create_file_db_entry("dog.png", None);
+    test::create_file_db_entry("notes.txt", None);
+    test::create_tag_file("pets", 1);
+    let client = client();
+    let res = client
+        .get("/files/metadata?q=tag%3Apets%20OR%20(type%3Aimage%20AND%20NOT%20cat)")
+        .header(Header::new("Authorization", AUTH))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let mut ids: Vec<u32> = res
+        .into_json::<Vec<FileApi>>()
+        .unwrap()
+        .into_iter()
+        .map(|it| it.id)
+        .collect();
+    ids.sort();
+    assert_eq!(vec![1, 2], ids);
+    cleanup();
+}
+
+#[test]
+fn search_files_pages_and_sorts_results() {
+    set_password();
+    remove_files();
+    test::create_file_db_entry("a.txt", None);
+    test::create_file_db_entry("b.txt", None);
+    test::create_file_db_entry("c.txt", None);
+    let client = client();
+    let res = client
+        .get("/files/metadata?search=txt&sort=name&order=desc&page=2&pageSize=2")
+        .header(Header::new("Authorization", AUTH))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    assert_eq!(Some("3"), res.headers().get_one("X-Total-Count"));
+    let names: Vec<String> = res
+        .into_json::<Vec<FileApi>>()
+        .unwrap()
+        .into_iter()
+        .map(|it| it.name)
+        .collect();
+    assert_eq!(vec!["a.txt".to_string()], names);
+    cleanup();
+}
+
+#[test]
+fn search_files_ranks_by_relevance() {
+    set_password();
+    remove_files();
+    test::create_file_db_entry("tax receipt 2023.pdf", None);
+    test::create_file_db_entry("receipt 2023.pdf", None);
+    test::create_file_db_entry("receipt 2024.pdf", None);
+    let client = client();
+    let res = client
+        .get("/files/metadata?search=2023%20recipt")
+        .header(Header::new("Authorization", AUTH))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let files: Vec<FileApi> = res.into_json().unwrap();
+    let ranked: Vec<(u32, bool)> = files
+        .iter()
+        .map(|it| (it.id, it.relevance.is_some_and(|score| score > 0)))
+        .collect();
+    assert_eq!(vec![(2, true), (1, true)], ranked);
+    assert!(files[0].relevance > files[1].relevance);
+    cleanup();
+}
+
+#[test]
+fn search_file_facets() {
+    set_password();
+    remove_files();
+    test::create_file_db_entry("beach.png", None);
+    test::create_file_db_entry("beach.mp4", None);
+    test::create_file_db_entry("mountain.png", None);
+    test::create_tag_files("beach", vec![1, 2]);
+    let client = client();
+    let res = client
+        .get("/files/metadata/facets?search=beach&pageSize=1")
+        .header(Header::new("Authorization", AUTH))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    // every matching file is counted, not just the requested page
+    let facets: SearchFacets = res.into_json().unwrap();
+    assert_eq!(
+        BTreeMap::from([(FileTypes::Image, 1), (FileTypes::Video, 1)]),
+        facets.file_types
+    );
+    assert_eq!(BTreeMap::from([("beach".to_string(), 2)]), facets.tags);
+    cleanup();
+}
+
+#[test]
+fn search_file_facets_requires_criteria() {
+    set_password();
+    let client = client();
+    let res = client
+        .get("/files/metadata/facets")
+        .header(Header::new("Authorization", AUTH))
+        .dispatch();
+    assert_eq!(res.status(), Status::BadRequest);
+    cleanup();
+}
+
+#[test]
+fn search_files_in_folder() {
+    set_password();
+    remove_files();
+    test::create_folder_db_entry("top", None);
+    test::create_folder_db_entry("bottom", Some(1));
+    test::create_file_db_entry("invoice 1", None);
+    test::create_file_db_entry("invoice 2", Some(1));
+    test::create_file_db_entry("invoice 3", Some(2));
+    let client = client();
+    let search = |url: &str| -> Vec<u32> {
+        let res = client
+            .get(url)
+            .header(Header::new("Authorization", AUTH))
+            .dispatch();
+        assert_eq!(res.status(), Status::Ok);
+        res.into_json::<Vec<FileApi>>()
+            .unwrap()
+            .into_iter()
+            .map(|it| it.id)
+            .collect()
+    };
+    assert_eq!(vec![2], search("/files/metadata?search=invoice&folderId=1"));
+    assert_eq!(
+        vec![2, 3],
+        search("/files/metadata?search=invoice&folderId=1&recursive=true")
+    );
+    cleanup();
+}
+
+#[test]
+fn search_files_with_bad_sort() {
+    set_password();
+    remove_files();
+    let client = client();
+    let res = client
+        .get("/files/metadata?search=txt&sort=color")
+        .header(Header::new("Authorization", AUTH))
+        .dispatch();
+    assert_eq!(res.status(), Status::BadRequest);
+    cleanup();
+}
+
+#[test]
+fn search_files_with_bad_query() {
+    set_password();
+    remove_files();
+    let client = client();
+    let res = client
+        .get("/files/metadata?q=(tag%3Apets")
+        .header(Header::new("Authorization", AUTH))
+        .dispatch();
+    assert_eq!(res.status(), Status::BadRequest);
+    let body: BasicMessage = res.into_json().unwrap();
+    assert_eq!(
+        body.message,
+        String::from("search query is missing a closing `)`")
+    );
+    cleanup();
+}
+
+#[test]
+fn search_files_with_deeply_nested_query() {
+    set_password();
+    remove_files();
+    let client = client();
+    let res = client
+        .get(format!(
+            "/files/metadata?q={}a{}",
+            "%28".repeat(10_000),
+            "%29".repeat(10_000)
+        ))
+        .header(Header::new("Authorization", AUTH))
+        .dispatch();
+    assert_eq!(res.status(), Status::BadRequest);
+    let body: BasicMessage = res.into_json().unwrap();
+    assert_eq!(
+        body.message,
+        String::from("search query can't be nested more than 64 levels deep")
+    );
+    cleanup();
+}
+
 #[test]
 fn download_file_without_creds() {
     init_db_folder();
@@ -441,6 +709,76 @@ fn download_file_without_creds() {
     cleanup();
 }
 
+#[test]
+fn upload_and_download_encrypted_file() {
+    set_password();
+    remove_files();
+    encryption::set_test_secret(Some("passphrase"));
+    let client = client();
+    let body = "--BOUNDARY\r\n\
+Content-Disposition: form-data; name=\"file\"; filename=\"test.txt\"\r\n\
+Content-Type: text/plain\r\n\
+\r\n\
+hello\r\n\
+--BOUNDARY\r\n\
+Content-Disposition: form-data; name=\"extension\"\r\n\
+\r\n\
+txt\r\n\
+--BOUNDARY\r\n\
+Content-Disposition: form-data; name=\"folderId\"\r\n\
+\r\n\
+0\r\n\
+--BOUNDARY--";
+    let res = client
+        .post("/files")
+        .header(Header::new("Authorization", AUTH))
+        .header(Header::new(
+            "Content-Type",
+            "multipart/form-data; boundary=BOUNDARY",
+        ))
+        .body(body)
+        .dispatch();
+    assert_eq!(res.status(), Status::Created);
+    let created: FileApi = res.into_json().unwrap();
+    assert_eq!(Some(5), created.size);
+    let disk_path = std::path::Path::new(&file_dir()).join("test.txt");
+    assert!(encryption::is_encrypted(&disk_path).unwrap());
+    let res = client
+        .get(format!("/files/{}", created.id))
+        .header(Header::new("Authorization", AUTH))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    assert_eq!(Some("5"), res.headers().get_one("Content-Length"));
+    assert_eq!("hello", res.into_string().unwrap());
+    // the contents are decrypted as they're streamed, so no plain copy is written to the temp directory
+    let temp_files = std::fs::read_dir(crate::temp_dir())
+        .map(|it| it.count())
+        .unwrap_or(0);
+    assert_eq!(0, temp_files);
+    cleanup();
+}
+
+#[test]
+fn download_tampered_encrypted_file_fails() {
+    set_password();
+    remove_files();
+    encryption::set_test_secret(Some("passphrase"));
+    create_file_db_entry("test.txt", None);
+    fs::create_dir_all(file_dir()).unwrap();
+    let disk_path = std::path::Path::new(&file_dir()).join("test.txt");
+    let mut encrypted = encryption::encrypt_contents(b"hello").unwrap();
+    let last = encrypted.len() - 1;
+    encrypted[last] ^= 1;
+    fs::write(&disk_path, encrypted).unwrap();
+    let client = client();
+    let res = client
+        .get(uri!("/files/1"))
+        .header(Header::new("Authorization", AUTH))
+        .dispatch();
+    assert_eq!(res.status(), Status::InternalServerError);
+    cleanup();
+}
+
 #[test]
 fn download_file_not_found() {
     set_password();
@@ -477,6 +815,291 @@ fn download_file() {
     cleanup();
 }
 
+#[test]
+fn download_file_records_access() {
+    set_password();
+    remove_files();
+    test::create_file_db_entry("test.txt", None);
+    create_file_disk("test.txt", "hello");
+    let client = client();
+    for _ in 0..2 {
+        client
+            .get(uri!("/files/1"))
+            .header(Header::new("Authorization", AUTH))
+            .dispatch();
+    }
+    let res = client
+        .get(uri!("/files/metadata/1"))
+        .header(Header::new("Authorization", AUTH))
+        .dispatch();
+    let body: FileApi = res.into_json().unwrap();
+    assert_eq!(2, body.download_count);
+    assert!(body.last_accessed.is_some());
+    cleanup();
+}
+
+#[test]
+fn update_file_contents() {
+    set_password();
+    remove_files();
+    test::create_file_db_entry("test.txt", None);
+    create_file_disk("test.txt", "hello");
+    let client = client();
+    let etag = crate::service::file_service::hash_file_contents(b"hello");
+    let res = client
+        .put(uri!("/files/content/1"))
+        .header(Header::new("Authorization", AUTH))
+        .header(Header::new("If-Match", etag.clone()))
+        .body("goodbye")
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let new_etag = crate::service::file_service::hash_file_contents(b"goodbye");
+    assert_eq!(
+        Some(format!("\"{new_etag}\"").as_str()),
+        res.headers().get_one("ETag")
+    );
+    let body: FileApi = res.into_json().unwrap();
+    assert_eq!(Some(7), body.size);
+    // the old etag is stale now
+    let res = client
+        .put(uri!("/files/content/1"))
+        .header(Header::new("Authorization", AUTH))
+        .header(Header::new("If-Match", etag))
+        .body("clobbered")
+        .dispatch();
+    assert_eq!(res.status(), Status::PreconditionFailed);
+    let res = client
+        .get(uri!("/files/1"))
+        .header(Header::new("Authorization", AUTH))
+        .dispatch();
+    assert_eq!(res.into_string().unwrap(), String::from("goodbye"));
+    cleanup();
+}
+
+#[test]
+fn get_file_returns_etag_for_editable_files() {
+    set_password();
+    remove_files();
+    test::create_file_db_entry("test.txt", None);
+    create_file_disk("test.txt", "hello");
+    test::create_file_db_entry("test.png", None);
+    create_file_disk("test.png", "not really a png");
+    let client = client();
+    let etag = format!(
+        "\"{}\"",
+        crate::service::file_service::hash_file_contents(b"hello")
+    );
+    for uri in ["/files/1", "/files/metadata/1"] {
+        let res = client
+            .get(uri)
+            .header(Header::new("Authorization", AUTH))
+            .dispatch();
+        assert_eq!(res.status(), Status::Ok);
+        assert_eq!(Some(etag.as_str()), res.headers().get_one("ETag"), "{uri}");
+    }
+    // only editable files have an etag
+    for uri in ["/files/2", "/files/metadata/2"] {
+        let res = client
+            .get(uri)
+            .header(Header::new("Authorization", AUTH))
+            .dispatch();
+        assert_eq!(res.status(), Status::Ok);
+        assert_eq!(None, res.headers().get_one("ETag"), "{uri}");
+    }
+    // the returned etag can be used to edit the file
+    let res = client
+        .put(uri!("/files/content/1"))
+        .header(Header::new("Authorization", AUTH))
+        .header(Header::new("If-Match", etag))
+        .body("goodbye")
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    cleanup();
+}
+
+#[test]
+fn update_file_contents_without_if_match() {
+    set_password();
+    remove_files();
+    test::create_file_db_entry("test.txt", None);
+    create_file_disk("test.txt", "hello");
+    let client = client();
+    let res = client
+        .put(uri!("/files/content/1"))
+        .header(Header::new("Authorization", AUTH))
+        .body("goodbye")
+        .dispatch();
+    assert_eq!(res.status(), Status::PreconditionRequired);
+    cleanup();
+}
+
+#[test]
+fn get_recent_files_without_creds() {
+    init_db_folder();
+    remove_files();
+    let client = client();
+    let res = client.get(uri!("/files/recent")).dispatch();
+    assert_eq!(res.status(), Status::Unauthorized);
+    cleanup();
+}
+
+#[test]
+fn get_recent_files() {
+    set_password();
+    remove_files();
+    test::create_file_db_entry("first.txt", None);
+    test::create_file_db_entry("second.txt", None);
+    test::create_file_db_entry("third.txt", None);
+    let client = client();
+    let res = client
+        .get("/files/recent?page=1&pageSize=2")
+        .header(Header::new("Authorization", AUTH))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let body: Vec<FileApi> = res.into_json().unwrap();
+    // all files have the same upload date, so newer ids come first
+    let names: Vec<String> = body.into_iter().map(|it| it.name).collect();
+    assert_eq!(vec!["third.txt", "second.txt"], names);
+    let res = client
+        .get("/files/recent?page=2&pageSize=2")
+        .header(Header::new("Authorization", AUTH))
+        .dispatch();
+    let body: Vec<FileApi> = res.into_json().unwrap();
+    let names: Vec<String> = body.into_iter().map(|it| it.name).collect();
+    assert_eq!(vec!["first.txt"], names);
+    cleanup();
+}
+
+#[test]
+fn get_recent_files_bad_request() {
+    set_password();
+    remove_files();
+    let client = client();
+    let res = client
+        .get("/files/recent?sort=size")
+        .header(Header::new("Authorization", AUTH))
+        .dispatch();
+    assert_eq!(res.status(), Status::BadRequest);
+    let res = client
+        .get("/files/recent?page=0")
+        .header(Header::new("Authorization", AUTH))
+        .dispatch();
+    assert_eq!(res.status(), Status::BadRequest);
+    let body: BasicMessage = res.into_json().unwrap();
+    assert_eq!(body.message, String::from("page must be 1 or greater"));
+    cleanup();
+}
+
+#[test]
+fn get_unprocessed_files_without_creds() {
+    init_db_folder();
+    remove_files();
+    let client = client();
+    let res = client.get(uri!("/files/unprocessed")).dispatch();
+    assert_eq!(res.status(), Status::Unauthorized);
+    cleanup();
+}
+
+#[test]
+fn get_unprocessed_files() {
+    set_password();
+    remove_files();
+    test::create_file_db_entry("tagged.txt", None);
+    test::create_file_db_entry("untagged.txt", None);
+    test::create_file_db_entry("photo.png", None);
+    test::create_tag_file("tag", 1);
+    let client = client();
+    let res = client
+        .get("/files/unprocessed?missing=tags&page=1&pageSize=1")
+        .header(Header::new("Authorization", AUTH))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    assert_eq!(Some("2"), res.headers().get_one("X-Total-Count"));
+    let body: Vec<FileApi> = res.into_json().unwrap();
+    let names: Vec<String> = body.into_iter().map(|it| it.name).collect();
+    assert_eq!(vec!["untagged.txt"], names);
+    // without any previews generated, every image is missing one
+    let res = client
+        .get("/files/unprocessed?missing=preview")
+        .header(Header::new("Authorization", AUTH))
+        .dispatch();
+    let body: Vec<FileApi> = res.into_json().unwrap();
+    let names: Vec<String> = body.into_iter().map(|it| it.name).collect();
+    assert_eq!(vec!["photo.png"], names);
+    cleanup();
+}
+
+#[test]
+fn get_unprocessed_files_bad_request() {
+    set_password();
+    remove_files();
+    let client = client();
+    let res = client
+        .get("/files/unprocessed?missing=thumbnails")
+        .header(Header::new("Authorization", AUTH))
+        .dispatch();
+    assert_eq!(res.status(), Status::BadRequest);
+    let body: BasicMessage = res.into_json().unwrap();
+    assert_eq!(
+        body.message,
+        String::from("Invalid missing value thumbnails. Must be one of tags, exif, preview")
+    );
+    cleanup();
+}
+
+#[test]
+fn favorite_file() {
+    set_password();
+    remove_files();
+    test::create_file_db_entry("starred.txt", None);
+    test::create_file_db_entry("not_starred.txt", None);
+    let client = client();
+    let res = client
+        .put(uri!("/files/favorite/1"))
+        .header(Header::new("Authorization", AUTH))
+        .dispatch();
+    assert_eq!(res.status(), Status::NoContent);
+    let res = client
+        .get(uri!("/files/favorites"))
+        .header(Header::new("Authorization", AUTH))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let body: Vec<FileApi> = res.into_json().unwrap();
+    assert_eq!(1, body.len());
+    assert_eq!(1, body[0].id);
+    assert!(body[0].favorite);
+    let res = client
+        .delete(uri!("/files/favorite/1"))
+        .header(Header::new("Authorization", AUTH))
+        .dispatch();
+    assert_eq!(res.status(), Status::NoContent);
+    let res = client
+        .get(uri!("/files/metadata/1"))
+        .header(Header::new("Authorization", AUTH))
+        .dispatch();
+    let body: FileApi = res.into_json().unwrap();
+    assert!(!body.favorite);
+    cleanup();
+}
+
+#[test]
+fn favorite_file_not_found() {
+    set_password();
+    remove_files();
+    let client = client();
+    let res = client
+        .put(uri!("/files/favorite/1"))
+        .header(Header::new("Authorization", AUTH))
+        .dispatch();
+    assert_eq!(res.status(), Status::NotFound);
+    let body: BasicMessage = res.into_json().unwrap();
+    assert_eq!(
+        body.message,
+        String::from("The file with the passed id could not be found.")
+    );
+    cleanup();
+}
+
 #[test]
 fn delete_file_without_creds() {
     init_db_folder();
@@ -910,3 +1533,32 @@ Content-Disposition: form-data; name=\"folderId\"\r\n\
 
     cleanup();
 }
+
+#[test]
+fn update_file_tag_inheritance() {
+    set_password();
+    let client = client();
+    create_folder_db_entry("Family", None);
+    create_tag_folder("private", 1);
+    create_file_db_entry("test.txt", Some(1));
+    crate::tags::service::pass_tags_to_descendants(1).unwrap();
+    let res = client
+        .put(uri!("/files/inheritance/1"))
+        .header(Header::new("Authorization", AUTH))
+        .body(r#"{"blockAll":true}"#)
+        .dispatch();
+    assert_eq!(Status::Ok, res.status());
+    let body: serde::Value = res.into_json().unwrap();
+    assert_eq!(serde::json!({"blockAll": true, "blockedTags": []}), body);
+    assert!(
+        crate::tags::service::get_tags_on_file(1)
+            .unwrap()
+            .is_empty()
+    );
+    let res = client
+        .get(uri!("/files/inheritance/2"))
+        .header(Header::new("Authorization", AUTH))
+        .dispatch();
+    assert_eq!(Status::NotFound, res.status());
+    cleanup();
+}
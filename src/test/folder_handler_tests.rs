@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -47,6 +48,10 @@ fn get_root_folder() {
         folders: Vec::new(),
         files: Vec::new(),
         tags: Vec::new(),
+        description: None,
+        properties: BTreeMap::new(),
+        favorite: false,
+        saved_searches: Some(Vec::new()),
     };
     let status = res.status();
     let res_json: FolderResponse = res.into_json().unwrap();
@@ -73,6 +78,10 @@ fn get_root_folder_with_0_id() {
         folders: Vec::new(),
         files: Vec::new(),
         tags: Vec::new(),
+        description: None,
+        properties: BTreeMap::new(),
+        favorite: false,
+        saved_searches: Some(Vec::new()),
     };
     let status = res.status();
     let res_json: FolderResponse = res.into_json().unwrap();
@@ -255,6 +264,8 @@ fn update_folder() {
         name: String::from("testRenamed"),
         id: 1,
         tags: Vec::new(),
+        description: None,
+        properties: Some(BTreeMap::new()),
     })
     .unwrap();
     let res = client
@@ -272,6 +283,10 @@ fn update_folder() {
         folders: Vec::new(),
         files: Vec::new(),
         tags: Vec::new(),
+        description: None,
+        properties: BTreeMap::new(),
+        favorite: false,
+        saved_searches: None,
     };
     assert_eq!(body, expected);
     cleanup();
@@ -308,6 +323,8 @@ fn update_folder_new_folder_0_id() {
         name: String::from("testRenamed"),
         id: 2,
         tags: Vec::new(),
+        description: None,
+        properties: Some(BTreeMap::new()),
     })
     .unwrap();
     let res = client
@@ -325,6 +342,10 @@ fn update_folder_new_folder_0_id() {
         folders: Vec::new(),
         files: Vec::new(),
         tags: Vec::new(),
+        description: None,
+        properties: BTreeMap::new(),
+        favorite: false,
+        saved_searches: None,
     };
     assert_eq!(body, expected);
     cleanup();
@@ -350,6 +371,8 @@ fn update_folder_not_found() {
         name: String::from("testRenamed"),
         id: 2,
         tags: Vec::new(),
+        description: None,
+        properties: Some(BTreeMap::new()),
     })
     .unwrap();
     let res = client
@@ -388,6 +411,8 @@ fn update_folder_parent_not_found() {
         name: String::from("testRenamed"),
         id: 1,
         tags: Vec::new(),
+        description: None,
+        properties: Some(BTreeMap::new()),
     })
     .unwrap();
     let res = client
@@ -420,6 +445,8 @@ fn update_folder_already_exists() {
         name: String::from("Test2"),
         id: 1,
         tags: Vec::new(),
+        description: None,
+        properties: Some(BTreeMap::new()),
     })
     .unwrap();
     let res = client
@@ -473,6 +500,8 @@ fn update_folder_folder_already_exists_root() {
         name: String::from("test"),
         id: 1,
         tags: Vec::new(),
+        description: None,
+        properties: Some(BTreeMap::new()),
     })
     .unwrap();
     let res = client
@@ -535,6 +564,8 @@ fn update_folder_folder_already_exists_target_folder() {
         name: String::from("test3"),
         id: 2,
         tags: Vec::new(),
+        description: None,
+        properties: Some(BTreeMap::new()),
     })
     .unwrap();
     let res = client
@@ -565,6 +596,8 @@ fn update_folder_root_not_found() {
         name: String::from("test"),
         id: 0,
         tags: Vec::new(),
+        description: None,
+        properties: Some(BTreeMap::new()),
     })
     .unwrap();
     let res = client
@@ -669,6 +702,8 @@ fn update_folder_to_file_with_same_name_root() {
         name: String::from("file"),
         id: 1,
         tags: Vec::new(),
+        description: None,
+        properties: Some(BTreeMap::new()),
     })
     .unwrap();
     let res = client
@@ -692,7 +727,9 @@ fn update_folder_to_file_with_same_name_root() {
             parent_id: None,
             size: 0,
             create_date: chrono::offset::Local::now().naive_local(),
-            file_type: FileTypes::Unknown
+            file_type: FileTypes::Unknown,
+            upload_date: None,
+            modified_date: None,
         }
     );
     let root_folders = folder_repository::get_child_folders(None, &con).unwrap();
@@ -734,6 +771,8 @@ fn update_folder_to_file_with_same_name_same_folder() {
         name: String::from("file"),
         id: 2,
         tags: Vec::new(),
+        description: None,
+        properties: Some(BTreeMap::new()),
     })
     .unwrap();
     let res = client
@@ -815,6 +854,8 @@ fn update_folder_to_file_with_same_name_different_folder() {
         name: String::from("file"),
         id: 2,
         tags: Vec::new(),
+        description: None,
+        properties: Some(BTreeMap::new()),
     })
     .unwrap();
     let res = client
@@ -880,6 +921,13 @@ fn download_folder_returns_200_for_valid_folder() {
         .header(Header::new("Authorization", AUTH))
         .dispatch();
     assert_eq!(Status::Ok, res.status());
+    let mut archive = tar::Archive::new(std::io::Cursor::new(res.into_bytes().unwrap()));
+    let paths: Vec<PathBuf> = archive
+        .entries()
+        .unwrap()
+        .map(|it| it.unwrap().path().unwrap().to_path_buf())
+        .collect();
+    assert!(paths.contains(&PathBuf::from("top/middle/bottom/test")));
     cleanup();
 }
 
@@ -908,3 +956,147 @@ fn download_folder_returns_404_for_missing_id() {
     assert_eq!(Status::NotFound, res.status());
     cleanup();
 }
+
+#[test]
+fn favorite_folder() {
+    set_password();
+    remove_files();
+    create_folder_db_entry("top", None);
+    create_folder_db_entry("child", Some(1));
+    let client = client();
+    let res = client
+        .put(uri!("/folders/favorite/2"))
+        .header(Header::new("Authorization", AUTH))
+        .dispatch();
+    assert_eq!(Status::NoContent, res.status());
+    let res = client
+        .get(uri!("/folders/favorites"))
+        .header(Header::new("Authorization", AUTH))
+        .dispatch();
+    assert_eq!(Status::Ok, res.status());
+    let body: Vec<FolderResponse> = res.into_json().unwrap();
+    assert_eq!(1, body.len());
+    assert_eq!(2, body[0].id);
+    assert_eq!("top/child", body[0].path);
+    assert!(body[0].favorite);
+    // child folders should also show as starred
+    let res = client
+        .get(uri!("/folders/metadata/1"))
+        .header(Header::new("Authorization", AUTH))
+        .dispatch();
+    let body: FolderResponse = res.into_json().unwrap();
+    assert!(!body.favorite);
+    assert!(body.folders[0].favorite);
+    let res = client
+        .delete(uri!("/folders/favorite/2"))
+        .header(Header::new("Authorization", AUTH))
+        .dispatch();
+    assert_eq!(Status::NoContent, res.status());
+    let res = client
+        .get(uri!("/folders/favorites"))
+        .header(Header::new("Authorization", AUTH))
+        .dispatch();
+    let body: Vec<FolderResponse> = res.into_json().unwrap();
+    assert!(body.is_empty());
+    cleanup();
+}
+
+#[test]
+fn favorite_folder_returns_404_for_missing_id() {
+    set_password();
+    remove_files();
+    let client = client();
+    let res = client
+        .put(uri!("/folders/favorite/12345"))
+        .header(Header::new("Authorization", AUTH))
+        .dispatch();
+    assert_eq!(Status::NotFound, res.status());
+    // root folder can't be starred
+    let res = client
+        .put(uri!("/folders/favorite/0"))
+        .header(Header::new("Authorization", AUTH))
+        .dispatch();
+    assert_eq!(Status::NotFound, res.status());
+    cleanup();
+}
+
+#[test]
+fn search_folders_by_name_and_tag() {
+    set_password();
+    remove_files();
+    create_folder_db_entry("documents", None); // 1
+    create_folder_db_entry("2019 taxes", Some(1)); // 2
+    create_folder_db_entry("2019 photos", None); // 3
+    create_tag_folder("archive", 2);
+    let client = client();
+    let res = client
+        .get("/folders/metadata?search=2019&tags=archive")
+        .header(Header::new("Authorization", AUTH))
+        .dispatch();
+    assert_eq!(Status::Ok, res.status());
+    let body: Vec<FolderResponse> = res.into_json().unwrap();
+    assert_eq!(1, body.len());
+    assert_eq!(2, body[0].id);
+    assert_eq!("2019 taxes", body[0].name);
+    assert_eq!("documents/2019 taxes", body[0].path);
+    assert_eq!("archive", body[0].tags[0].title);
+    cleanup();
+}
+
+#[test]
+fn search_folders_requires_criteria() {
+    set_password();
+    remove_files();
+    let client = client();
+    let res = client
+        .get("/folders/metadata?search=%20")
+        .header(Header::new("Authorization", AUTH))
+        .dispatch();
+    assert_eq!(Status::BadRequest, res.status());
+    cleanup();
+}
+
+#[test]
+fn update_folder_tag_inheritance() {
+    set_password();
+    let client = client();
+    create_folder_db_entry("Family", None); // id 1
+    create_folder_db_entry("Shared", Some(1)); // id 2
+    create_tag_folder("private", 1);
+    create_file_db_entry("test.txt", Some(2));
+    crate::tags::service::pass_tags_to_descendants(1).unwrap();
+    let res = client
+        .put(uri!("/folders/inheritance/2"))
+        .header(Header::new("Authorization", AUTH))
+        .body(r#"{"blockedTags":["private"]}"#)
+        .dispatch();
+    assert_eq!(Status::Ok, res.status());
+    let res = client
+        .get(uri!("/folders/inheritance/2"))
+        .header(Header::new("Authorization", AUTH))
+        .dispatch();
+    let body: serde::Value = res.into_json().unwrap();
+    assert_eq!(
+        serde::json!({"blockAll": false, "blockedTags": ["private"]}),
+        body
+    );
+    assert!(
+        crate::tags::service::get_tags_on_file(1)
+            .unwrap()
+            .is_empty()
+    );
+    cleanup();
+}
+
+#[test]
+fn update_tag_inheritance_for_non_existent_folder() {
+    set_password();
+    let client = client();
+    let res = client
+        .put(uri!("/folders/inheritance/1234"))
+        .header(Header::new("Authorization", AUTH))
+        .body(r#"{"blockAll":true}"#)
+        .dispatch();
+    assert_eq!(Status::NotFound, res.status());
+    cleanup();
+}
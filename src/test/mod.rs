@@ -8,9 +8,11 @@ pub use tests::*;
 #[cfg(test)]
 mod tests {
     use crate::model::api::FileApi;
-    use crate::model::repository::{FileRecord, Folder};
+    use crate::model::repository::{FileRecord, Folder, ItemDetails};
     use crate::previews;
-    use crate::repository::{file_repository, folder_repository, initialize_db, open_connection};
+    use crate::repository::{
+        file_repository, folder_repository, initialize_db, open_connection, property_repository,
+    };
     use crate::service::file_service::{determine_file_type, file_dir};
     use crate::tags::Tag;
     use crate::tags::repository as tag_repository;
@@ -84,6 +86,8 @@ mod tests {
                 size: 0,
                 create_date: now(),
                 file_type,
+                upload_date: None,
+                modified_date: None,
             },
             &connection,
         )
@@ -118,7 +122,9 @@ mod tests {
 
     pub fn create_tag_db_entry(name: &str) -> u32 {
         let connection = open_connection();
-        let id = tag_repository::create_tag(name, &connection).unwrap().id;
+        let id = tag_repository::create_tag(name, None, &connection)
+            .unwrap()
+            .id;
         connection.close().unwrap();
         id
     }
@@ -249,6 +255,8 @@ mod tests {
                 && self.tags == other.tags
                 && self.size == other.size
                 && self.file_type == other.file_type
+                && self.description == other.description
+                && self.properties == other.properties
         }
     }
 
@@ -262,16 +270,24 @@ mod tests {
                 create_date: self.date_created.unwrap_or_default(),
                 size: self.size.unwrap_or_default(),
                 file_type: self.file_type.unwrap_or_default(),
+                upload_date: None,
+                modified_date: None,
             };
             let file_id = file_repository::create_file(&record, &con).unwrap();
             for tag in &mut self.tags {
-                let Tag { id, title: _ } = tag_repository::create_tag(&tag.title, &con).unwrap();
+                let Tag { id, .. } = tag_repository::create_tag(&tag.title, None, &con).unwrap();
                 tag_repository::add_explicit_tag_to_file(file_id, id, &con).unwrap();
                 tag.tag_id = Some(id);
             }
             if let Some(folder_id) = self.folder_id {
                 folder_repository::link_folder_to_file(file_id, folder_id, &con).unwrap();
             }
+            let details = ItemDetails {
+                description: self.description.clone(),
+                properties: self.properties.clone().unwrap_or_default(),
+            };
+            property_repository::set_file_details(file_id, &details, &con).unwrap();
+            let saved = file_repository::get_file(file_id, &con).unwrap();
             con.close().unwrap();
             Self {
                 id: file_id,
@@ -281,6 +297,15 @@ mod tests {
                 size: self.size,
                 date_created: self.date_created,
                 file_type: self.file_type,
+                date_uploaded: saved.upload_date,
+                date_modified: saved.modified_date,
+                description: details.description,
+                properties: Some(details.properties),
+                favorite: false,
+                download_count: 0,
+                last_accessed: None,
+                location: None,
+                relevance: None,
             }
         }
     }
@@ -293,15 +318,10 @@ mod tests {
             if let Some(id) = self.parent_id {
                 folder_repository::link_folder_to_file(file_id, id, &con).unwrap();
             }
+            // dates can be defaulted by the database, so we need to pull the record back out
+            let saved = file_repository::get_file(file_id, &con).unwrap();
             con.close().unwrap();
-            Self {
-                id: Some(file_id),
-                name: self.name.clone(),
-                parent_id: self.parent_id,
-                create_date: self.create_date,
-                size: self.size,
-                file_type: self.file_type,
-            }
+            saved
         }
     }
 }